@@ -0,0 +1,223 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::bail;
+use cdk::amount::SplitTarget;
+use cdk::nuts::CurrencyUnit;
+use cdk::UncheckedUrl;
+use clap::{Parser, Subcommand};
+use iced_cashu_core::config::{
+    self, get_or_create_seed, profiles, set_profile, StorageBackend, DEFAULT_PROFILE,
+};
+use iced_cashu_core::db::unix_time;
+use iced_cashu_core::poll::Backoff;
+use iced_cashu_core::unit::format_amount;
+use iced_cashu_core::{WalletError, WalletService, DEFAULT_MINT};
+use tracing_subscriber::EnvFilter;
+use zeroize::Zeroizing;
+
+/// Seed password read instead of prompting, for scripts
+const PASSWORD_VAR: &str = "CASHU_PASSWORD";
+/// BIP39 passphrase read instead of prompting, for profiles that use one
+const PASSPHRASE_VAR: &str = "CASHU_PASSPHRASE";
+const QUOTE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const MAX_QUOTE_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Use the iced-cashu wallet from the command line
+///
+/// Reads the same seed and databases as the GUI, so both show the same
+/// balance and history.
+#[derive(Debug, Parser)]
+#[command(version, about = "Cashu wallet command line")]
+struct Args {
+    /// Directory the seed, databases and settings are kept in
+    #[arg(long, global = true)]
+    data_dir: Option<PathBuf>,
+    /// Wallet profile to use
+    #[arg(long, global = true, default_value = DEFAULT_PROFILE)]
+    profile: String,
+    /// Mint to use, added to the wallet if it is new
+    #[arg(long, global = true, default_value = DEFAULT_MINT, value_parser = parse_mint)]
+    mint: UncheckedUrl,
+    /// Unit to mint, send and pay in, such as sat or usd
+    #[arg(long, global = true, default_value = "sat", value_parser = parse_unit)]
+    unit: CurrencyUnit,
+    /// Keep wallet data in sqlite, redb or memory instead of the profile's
+    /// configured backend
+    #[arg(long, global = true)]
+    storage: Option<StorageBackend>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Print the balance in each unit and the sat balance of each mint
+    Balance,
+    /// Create an invoice and mint once it is paid
+    Mint { amount: u64 },
+    /// Create a token to send
+    Send { amount: u64 },
+    /// Pay a lightning invoice
+    Pay { invoice: String },
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn")),
+        )
+        .with_writer(std::io::stderr)
+        .init();
+
+    let args = Args::parse();
+    if let Some(dir) = &args.data_dir {
+        config::set_data_dir(dir.clone());
+    }
+    if let Some(backend) = args.storage {
+        config::set_storage_backend(backend);
+    }
+    if !profiles().contains(&args.profile) {
+        bail!("No profile named {}", args.profile);
+    }
+    set_profile(&args.profile);
+
+    let password = password()?;
+    let seed = get_or_create_seed(&password)?;
+    let passphrase = passphrase()?;
+    let service =
+        WalletService::open(&seed, &passphrase, Some(args.mint.clone()), &password).await?;
+
+    match args.command {
+        Command::Balance => balance(&service).await,
+        Command::Mint { amount } => mint(&service, args.mint, amount, args.unit).await,
+        Command::Send { amount } => {
+            let (_, token) = service
+                .create_token(
+                    args.mint,
+                    amount,
+                    args.unit,
+                    SplitTarget::default(),
+                    None,
+                    None,
+                )
+                .await?;
+            println!("{}", token);
+
+            Ok(())
+        }
+        Command::Pay { invoice } => pay(&service, args.mint, invoice, args.unit).await,
+    }
+}
+
+/// Seed password from `CASHU_PASSWORD`, prompting if it is unset
+fn password() -> anyhow::Result<Zeroizing<String>> {
+    match std::env::var(PASSWORD_VAR) {
+        Ok(password) => Ok(Zeroizing::new(password)),
+        Err(_) => Ok(Zeroizing::new(rpassword::prompt_password("Password: ")?)),
+    }
+}
+
+/// BIP39 passphrase from `CASHU_PASSPHRASE`, prompting if it is unset and
+/// the profile uses one
+fn passphrase() -> anyhow::Result<Zeroizing<String>> {
+    match std::env::var(PASSPHRASE_VAR) {
+        Ok(passphrase) => Ok(Zeroizing::new(passphrase)),
+        Err(_) if config::has_passphrase() => {
+            Ok(Zeroizing::new(rpassword::prompt_password("Passphrase: ")?))
+        }
+        Err(_) => Ok(Zeroizing::default()),
+    }
+}
+
+async fn balance(service: &WalletService) -> anyhow::Result<()> {
+    let mut balances: Vec<_> = service.mint_balances().await?.into_iter().collect();
+    balances.sort();
+
+    for (mint_url, amount) in balances {
+        println!("{}: {} sats", mint_url, amount);
+    }
+    let mut units: Vec<_> = service.unit_balances().await?.into_iter().collect();
+    units.sort_by_key(|(unit, _)| *unit != CurrencyUnit::Sat);
+    for (unit, amount) in units {
+        println!("Total: {}", format_amount(amount, &unit));
+    }
+
+    Ok(())
+}
+
+/// Print the invoice of a new mint quote and wait for it to be paid
+async fn mint(
+    service: &WalletService,
+    mint_url: UncheckedUrl,
+    amount: u64,
+    unit: CurrencyUnit,
+) -> anyhow::Result<()> {
+    let pending = service.mint_quote(mint_url, amount, unit).await?;
+    println!("{}", pending.request);
+    eprintln!("Waiting for the invoice to be paid...");
+
+    let mut backoff = Backoff::new(QUOTE_POLL_INTERVAL, MAX_QUOTE_POLL_INTERVAL);
+    loop {
+        let delay = match service
+            .check_mint_quote(pending.mint_url.clone(), pending.quote_id.clone())
+            .await
+        {
+            Ok((_, true)) => break,
+            Ok((_, false)) => backoff.next_delay(),
+            Err(WalletError::RateLimited(retry_after)) => {
+                backoff.retry_after(retry_after.map(Duration::from_secs))
+            }
+            Err(err) => return Err(err.into()),
+        };
+        if pending.expiry <= u64::try_from(unix_time()).unwrap_or(0) {
+            bail!("Invoice expired");
+        }
+        tokio::time::sleep(delay).await;
+    }
+
+    let minted = service
+        .mint(pending.mint_url, pending.quote_id, SplitTarget::default())
+        .await?;
+    println!("Minted {}", format_amount(minted, &pending.unit));
+
+    Ok(())
+}
+
+async fn pay(
+    service: &WalletService,
+    mint_url: UncheckedUrl,
+    invoice: String,
+    unit: CurrencyUnit,
+) -> anyhow::Result<()> {
+    let preview = service.melt_quote(mint_url, invoice, unit).await?;
+    eprintln!(
+        "Paying {}, up to {} in fees",
+        format_amount(preview.amount, &preview.unit),
+        format_amount(preview.fee_reserve + preview.input_fee, &preview.unit)
+    );
+
+    let preimage = service.pay_invoice(preview).await?;
+    match preimage {
+        Some(preimage) => println!("Paid, preimage {}", preimage),
+        None => println!("Paid"),
+    }
+
+    Ok(())
+}
+
+fn parse_mint(input: &str) -> anyhow::Result<UncheckedUrl> {
+    let input = input.trim();
+    reqwest::Url::parse(input)?;
+
+    Ok(UncheckedUrl::from(input))
+}
+
+fn parse_unit(input: &str) -> anyhow::Result<CurrencyUnit> {
+    input
+        .trim()
+        .to_lowercase()
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Unknown unit {}", input))
+}
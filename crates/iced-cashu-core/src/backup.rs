@@ -0,0 +1,64 @@
+use anyhow::bail;
+use cdk::nuts::Proofs;
+use cdk::UncheckedUrl;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::config::{decrypt, encrypt};
+use crate::db::Transaction;
+
+/// Marks a file as an encrypted wallet backup
+const MAGIC: &[u8] = b"ICEDCASHU";
+const VERSION: u32 = 1;
+
+/// Complete wallet state, for restoring without a seed scan
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Backup {
+    pub version: u32,
+    /// BIP39 mnemonic
+    pub seed: String,
+    pub mints: Vec<MintBackup>,
+    pub transactions: Vec<Transaction>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MintBackup {
+    pub mint_url: UncheckedUrl,
+    pub proofs: Proofs,
+}
+
+impl Backup {
+    pub fn new(seed: String, mints: Vec<MintBackup>, transactions: Vec<Transaction>) -> Self {
+        Self {
+            version: VERSION,
+            seed,
+            mints,
+            transactions,
+        }
+    }
+}
+
+/// Serialize `backup` and encrypt it with `password`
+pub fn seal(backup: &Backup, password: &str) -> anyhow::Result<Vec<u8>> {
+    let json = Zeroizing::new(serde_json::to_vec(backup)?);
+
+    let mut out = MAGIC.to_vec();
+    out.extend(encrypt(&json, password)?);
+
+    Ok(out)
+}
+
+/// Decrypt a backup file produced by [`seal`]
+pub fn open(data: &[u8], password: &str) -> anyhow::Result<Backup> {
+    let Some(data) = data.strip_prefix(MAGIC) else {
+        bail!("Not a wallet backup file");
+    };
+
+    let json = decrypt(data, password)?;
+    let backup: Backup = serde_json::from_slice(&json)?;
+    if backup.version > VERSION {
+        bail!("Backup was made by a newer version of the wallet");
+    }
+
+    Ok(backup)
+}
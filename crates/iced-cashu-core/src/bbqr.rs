@@ -0,0 +1,145 @@
+use anyhow::{anyhow, bail};
+
+const HEADER: &str = "B$";
+/// RFC 4648 base32 without padding, which fits the alphanumeric QR mode
+const ENCODING_BASE32: char = '2';
+/// Unicode text
+const FILE_TYPE_TEXT: char = 'U';
+const BASE32: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+/// Parts are numbered with two base36 digits
+const MAX_PARTS: usize = 36 * 36 - 1;
+
+/// Base32 characters in each part, a multiple of 8 so each part decodes on
+/// its own
+pub const PART_SIZE: usize = 400;
+
+pub fn is_part(input: &str) -> bool {
+    input.starts_with(HEADER)
+}
+
+/// `text` as BBQr parts, in order, for text too long for one QR code to be
+/// shown as a series of codes one after the other
+pub fn split(text: &str) -> anyhow::Result<Vec<String>> {
+    let encoded = base32_encode(text.as_bytes());
+    let chunks: Vec<&str> = encoded
+        .as_bytes()
+        .chunks(PART_SIZE)
+        .map(|chunk| std::str::from_utf8(chunk).expect("base32 is ascii"))
+        .collect();
+    if chunks.len() > MAX_PARTS {
+        bail!("Too long for {} QR codes", MAX_PARTS);
+    }
+
+    Ok(chunks
+        .iter()
+        .enumerate()
+        .map(|(index, chunk)| {
+            format!(
+                "{}{}{}{}{}{}",
+                HEADER,
+                ENCODING_BASE32,
+                FILE_TYPE_TEXT,
+                base36(chunks.len()),
+                base36(index),
+                chunk
+            )
+        })
+        .collect())
+}
+
+/// Collects scanned parts, in any order, until the text is complete
+#[derive(Debug, Default)]
+pub struct Joiner {
+    parts: Vec<Option<Vec<u8>>>,
+}
+
+impl Joiner {
+    /// Add a scanned part, returning the text once every part is in
+    ///
+    /// A part of a series of another length starts over.
+    pub fn add(&mut self, part: &str) -> anyhow::Result<Option<String>> {
+        let header = part
+            .get(..8)
+            .filter(|header| header.starts_with(HEADER))
+            .ok_or_else(|| anyhow!("Not a BBQr part"))?;
+        let mut chars = header[2..4].chars();
+        if chars.next() != Some(ENCODING_BASE32) || chars.next() != Some(FILE_TYPE_TEXT) {
+            bail!("Unsupported BBQr encoding {}", &header[2..4]);
+        }
+        let total = usize::from_str_radix(&header[4..6], 36)?;
+        let index = usize::from_str_radix(&header[6..8], 36)?;
+        if index >= total {
+            bail!("BBQr part {} of {} is out of range", index + 1, total);
+        }
+
+        if self.parts.len() != total {
+            self.parts = vec![None; total];
+        }
+        self.parts[index] = Some(base32_decode(&part[8..])?);
+
+        if self.parts.iter().any(Option::is_none) {
+            return Ok(None);
+        }
+        let bytes: Vec<u8> = self.parts.iter().flatten().flatten().copied().collect();
+
+        Ok(Some(String::from_utf8(bytes)?))
+    }
+
+    /// Parts scanned and parts in the series
+    pub fn progress(&self) -> (usize, usize) {
+        (
+            self.parts.iter().filter(|part| part.is_some()).count(),
+            self.parts.len(),
+        )
+    }
+}
+
+fn base36(value: usize) -> String {
+    const DIGITS: &[u8; 36] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+    [value / 36, value % 36]
+        .iter()
+        .map(|digit| DIGITS[*digit] as char)
+        .collect()
+}
+
+fn base32_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::new();
+    let mut acc: u32 = 0;
+    let mut bits = 0;
+    for byte in bytes {
+        acc = (acc << 8) | u32::from(*byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(BASE32[((acc >> bits) & 31) as usize] as char);
+        }
+        acc &= (1 << bits) - 1;
+    }
+    if bits > 0 {
+        encoded.push(BASE32[((acc << (5 - bits)) & 31) as usize] as char);
+    }
+
+    encoded
+}
+
+fn base32_decode(encoded: &str) -> anyhow::Result<Vec<u8>> {
+    let mut bytes = vec![];
+    let mut acc: u32 = 0;
+    let mut bits = 0;
+    for c in encoded.trim_end_matches('=').chars() {
+        let value = BASE32
+            .iter()
+            .position(|digit| *digit as char == c)
+            .ok_or_else(|| anyhow!("Invalid base32 character {:?}", c))?;
+        acc = (acc << 5) | value as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+
+    Ok(bytes)
+}
@@ -0,0 +1,155 @@
+use anyhow::{anyhow, bail};
+use cdk::nuts::CurrencyUnit;
+use cdk::UncheckedUrl;
+use serde_json::json;
+
+use crate::db::unix_time;
+use crate::methods::{self, MeltQuoteResponse};
+
+/// NUT-05 method name mints list when they can pay offers (NUT-25)
+pub const METHOD: &str = "bolt12";
+
+const PREFIX: &str = "lno1";
+const CHARSET: &str = "qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// Offer TLV types, BOLT 12
+const OFFER_CURRENCY: u64 = 6;
+const OFFER_AMOUNT: u64 = 8;
+const OFFER_DESCRIPTION: u64 = 10;
+const OFFER_ABSOLUTE_EXPIRY: u64 = 14;
+const OFFER_ISSUER: u64 = 18;
+
+/// Human readable fields of a BOLT12 offer
+#[derive(Debug, Clone, Default)]
+pub struct OfferDetails {
+    /// Millisats, `None` if the payer picks the amount or it is in `currency`
+    pub amount_msat: Option<u64>,
+    /// ISO 4217 code when the amount is in a fiat currency
+    pub currency: Option<String>,
+    pub description: Option<String>,
+    pub issuer: Option<String>,
+    /// Unix timestamp in seconds
+    pub expires_at: Option<u64>,
+}
+
+pub fn is_offer(input: &str) -> bool {
+    input.trim().to_lowercase().starts_with(PREFIX)
+}
+
+/// Decode `offer`, rejecting malformed and expired offers
+pub fn decode_offer(offer: &str) -> anyhow::Result<OfferDetails> {
+    // Long offers may be split with `+` and whitespace
+    let offer: String = offer
+        .split('+')
+        .map(str::trim)
+        .collect::<String>()
+        .to_lowercase();
+    let data = offer
+        .strip_prefix(PREFIX)
+        .ok_or_else(|| anyhow!("Not a BOLT12 offer"))?;
+
+    let bytes = from_bech32_words(data)?;
+    let mut rest = bytes.as_slice();
+    let mut details = OfferDetails::default();
+    let mut amount = None;
+    while !rest.is_empty() {
+        let kind = read_bigsize(&mut rest)?;
+        let len = read_bigsize(&mut rest)? as usize;
+        if len > rest.len() {
+            bail!("Offer is truncated");
+        }
+        let (value, tail) = rest.split_at(len);
+        rest = tail;
+
+        match kind {
+            OFFER_CURRENCY => details.currency = Some(String::from_utf8(value.to_vec())?),
+            OFFER_AMOUNT => amount = Some(read_tu64(value)?),
+            OFFER_DESCRIPTION => details.description = Some(String::from_utf8(value.to_vec())?),
+            OFFER_ABSOLUTE_EXPIRY => details.expires_at = Some(read_tu64(value)?),
+            OFFER_ISSUER => details.issuer = Some(String::from_utf8(value.to_vec())?),
+            _ => (),
+        }
+    }
+    if details.currency.is_none() {
+        details.amount_msat = amount;
+    }
+
+    if details
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= unix_time() as u64)
+    {
+        bail!("Offer has expired");
+    }
+
+    Ok(details)
+}
+
+/// Ask `mint_url` to quote paying `offer`, `amount_msat` being required for
+/// offers without an amount
+pub(crate) async fn melt_quote(
+    mint_url: &UncheckedUrl,
+    offer: &str,
+    unit: &CurrencyUnit,
+    amount_msat: Option<u64>,
+) -> anyhow::Result<MeltQuoteResponse> {
+    let mut request = json!({ "request": offer, "unit": unit.to_string() });
+    if let Some(amount_msat) = amount_msat {
+        request["options"] = json!({ "amountless": { "amount_msat": amount_msat } });
+    }
+
+    methods::melt_quote(mint_url, METHOD, request).await
+}
+
+/// Bytes of bech32 data without a checksum, as offers are encoded
+fn from_bech32_words(data: &str) -> anyhow::Result<Vec<u8>> {
+    let mut bytes = vec![];
+    let mut acc: u32 = 0;
+    let mut bits = 0;
+    for c in data.chars() {
+        let word = CHARSET
+            .find(c)
+            .ok_or_else(|| anyhow!("Invalid character {:?} in offer", c))?;
+        acc = (acc << 5) | word as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+
+    Ok(bytes)
+}
+
+/// Variable length integer of BOLT 1
+fn read_bigsize(rest: &mut &[u8]) -> anyhow::Result<u64> {
+    let (&first, tail) = rest
+        .split_first()
+        .ok_or_else(|| anyhow!("Offer is truncated"))?;
+    *rest = tail;
+
+    let width = match first {
+        0xfd => 2,
+        0xfe => 4,
+        0xff => 8,
+        value => return Ok(value as u64),
+    };
+    if rest.len() < width {
+        bail!("Offer is truncated");
+    }
+    let (value, tail) = rest.split_at(width);
+    *rest = tail;
+
+    read_tu64(value)
+}
+
+/// Big endian integer with leading zeros left out
+fn read_tu64(value: &[u8]) -> anyhow::Result<u64> {
+    if value.len() > 8 {
+        bail!("Offer number is too long");
+    }
+
+    Ok(value
+        .iter()
+        .fold(0, |acc, byte| (acc << 8) | u64::from(*byte)))
+}
@@ -0,0 +1,495 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{OnceLock, RwLock};
+use std::{fmt, fs};
+
+use anyhow::anyhow;
+use argon2::Argon2;
+use bip39::{Language, Mnemonic};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use zeroize::Zeroizing;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const KEYRING_SERVICE: &str = "iced-cashu";
+const KEYRING_USER: &str = "seed";
+const PIN_FILE: &str = "pin";
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Data dir passed on the command line, overriding the default
+static DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+/// Storage backend passed on the command line, overriding the profile's
+static STORAGE_BACKEND: OnceLock<StorageBackend> = OnceLock::new();
+/// Profile the wallet is running, none for the default one
+static PROFILE: RwLock<Option<String>> = RwLock::new(None);
+
+/// Where the seed is stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SeedBackend {
+    /// Password encrypted `seed.enc` in the data dir
+    #[default]
+    File,
+    /// Platform keyring (Secret Service, Keychain, Windows Credential Manager)
+    Keyring,
+}
+
+impl SeedBackend {
+    pub const ALL: [SeedBackend; 2] = [SeedBackend::File, SeedBackend::Keyring];
+}
+
+impl fmt::Display for SeedBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SeedBackend::File => write!(f, "file"),
+            SeedBackend::Keyring => write!(f, "keyring"),
+        }
+    }
+}
+
+impl FromStr for SeedBackend {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "file" => Ok(SeedBackend::File),
+            "keyring" => Ok(SeedBackend::Keyring),
+            _ => Err(anyhow!("Unknown seed backend: {}", s)),
+        }
+    }
+}
+
+/// Where cdk keeps the wallet's proofs, mints and quotes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StorageBackend {
+    /// `cashu_iced.sqlite` in the profile's directory
+    #[default]
+    Sqlite,
+    /// `cashu_iced.redb` in the profile's directory
+    Redb,
+    /// Nothing written to disk and lost on exit, for testing
+    Memory,
+}
+
+impl fmt::Display for StorageBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageBackend::Sqlite => write!(f, "sqlite"),
+            StorageBackend::Redb => write!(f, "redb"),
+            StorageBackend::Memory => write!(f, "memory"),
+        }
+    }
+}
+
+impl FromStr for StorageBackend {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sqlite" => Ok(StorageBackend::Sqlite),
+            "redb" => Ok(StorageBackend::Redb),
+            "memory" => Ok(StorageBackend::Memory),
+            _ => Err(anyhow!("Unknown storage backend: {}", s)),
+        }
+    }
+}
+
+pub fn generate_mnemonic() -> anyhow::Result<Mnemonic> {
+    Ok(Mnemonic::generate_in(Language::English, 12)?)
+}
+
+pub fn parse_mnemonic(mnemonic: &str) -> anyhow::Result<Mnemonic> {
+    Ok(Mnemonic::parse_in_normalized(Language::English, mnemonic)?)
+}
+
+/// Seed of the current profile, generating and saving a new one under
+/// `password` if there is none yet
+pub fn get_or_create_seed(password: &str) -> anyhow::Result<Mnemonic> {
+    if let Some(seed) = get_seed(password)? {
+        return Ok(seed);
+    }
+
+    let seed = generate_mnemonic()?;
+    save_seed(&seed.to_string(), password)?;
+
+    Ok(seed)
+}
+
+/// Words of `mnemonic` that are not in the English BIP39 word list
+pub fn invalid_words(mnemonic: &str) -> Vec<&str> {
+    mnemonic
+        .split_whitespace()
+        .filter(|word| Language::English.find_word(word).is_none())
+        .collect()
+}
+
+/// Keep the seed, databases and settings in `dir` instead of
+/// `~/.cashu_iced`, must be called before anything is read
+pub fn set_data_dir(dir: PathBuf) {
+    let _ = DATA_DIR.set(dir);
+}
+
+/// Store wallet data in `backend` for this run instead of the profile's
+/// `storage_backend` config, must be called before the wallet is opened
+pub fn set_storage_backend(backend: StorageBackend) {
+    let _ = STORAGE_BACKEND.set(backend);
+}
+
+/// Backend of the current profile's wallet data
+pub fn storage_backend() -> StorageBackend {
+    match STORAGE_BACKEND.get() {
+        Some(backend) => *backend,
+        None => get_config_value("storage_backend").unwrap_or_default(),
+    }
+}
+
+pub fn data_dir() -> PathBuf {
+    if let Some(dir) = DATA_DIR.get() {
+        return dir.clone();
+    }
+
+    let home = home::home_dir().expect("Could not find home directory");
+    let default = home.join(".cashu_iced");
+
+    default
+}
+
+/// Name of the wallet profile in use
+pub fn profile() -> String {
+    PROFILE
+        .read()
+        .expect("Profile lock poisoned")
+        .clone()
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+}
+
+/// Switch to profile `name`, later reads of the seed, databases and wallet
+/// config use its directory
+pub fn set_profile(name: &str) {
+    let profile = (name != DEFAULT_PROFILE).then(|| name.to_string());
+    *PROFILE.write().expect("Profile lock poisoned") = profile;
+}
+
+/// Directory of the current profile's seed, databases and wallet config
+///
+/// The default profile lives in the data dir itself so wallets created before
+/// profiles existed keep working, others under `profiles/<name>/`.
+pub fn wallet_dir() -> PathBuf {
+    match PROFILE.read().expect("Profile lock poisoned").as_deref() {
+        Some(name) => data_dir().join("profiles").join(name),
+        None => data_dir(),
+    }
+}
+
+/// Names of all profiles, the default one first
+pub fn profiles() -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(data_dir().join("profiles"))
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .filter(|entry| entry.path().is_dir())
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .filter(|name| name != DEFAULT_PROFILE)
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names.insert(0, DEFAULT_PROFILE.to_string());
+
+    names
+}
+
+/// Create the directory of a new profile `name`
+pub fn create_profile(name: &str) -> anyhow::Result<()> {
+    let name = name.trim();
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(anyhow!(
+            "Use letters, numbers, '-' and '_' for profile names"
+        ));
+    }
+    if profiles().iter().any(|profile| profile == name) {
+        return Err(anyhow!("Profile {} already exists", name));
+    }
+
+    fs::create_dir_all(data_dir().join("profiles").join(name))?;
+
+    Ok(())
+}
+
+fn derive_key(password: &str, salt: &[u8]) -> anyhow::Result<Zeroizing<[u8; 32]>> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::default()
+        .hash_password_into(password.as_bytes(), salt, key.as_mut())
+        .map_err(|err| anyhow!("Could not derive key: {}", err))?;
+
+    Ok(key)
+}
+
+/// Encrypt `data` with a key derived from `password`
+///
+/// Output is `salt || nonce || ciphertext`.
+pub fn encrypt(data: &[u8], password: &str) -> anyhow::Result<Vec<u8>> {
+    let mut salt = [0u8; SALT_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+
+    let key = derive_key(password, &salt)?;
+    let cipher = XChaCha20Poly1305::new(key.as_ref().into());
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let ciphertext = cipher
+        .encrypt(&nonce, data)
+        .map_err(|_| anyhow!("Could not encrypt"))?;
+
+    let mut out = Vec::with_capacity(SALT_LEN + NONCE_LEN + ciphertext.len());
+    out.extend_from_slice(&salt);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+
+    Ok(out)
+}
+
+/// Decrypt data produced by [`encrypt`]
+pub fn decrypt(data: &[u8], password: &str) -> anyhow::Result<Zeroizing<Vec<u8>>> {
+    if data.len() < SALT_LEN + NONCE_LEN {
+        return Err(anyhow!("Encrypted data is too short"));
+    }
+
+    let (salt, rest) = data.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+
+    let key = derive_key(password, salt)?;
+    let cipher = XChaCha20Poly1305::new(key.as_ref().into());
+
+    let plaintext = cipher
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow!("Incorrect password"))?;
+
+    Ok(Zeroizing::new(plaintext))
+}
+
+/// Read a single config value stored as a file in the profile's directory
+pub fn get_config_value<T: FromStr>(name: &str) -> Option<T> {
+    fs::read_to_string(wallet_dir().join(name))
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+}
+
+pub fn set_config_value<T: fmt::Display>(name: &str, value: T) -> anyhow::Result<()> {
+    fs::create_dir_all(wallet_dir())?;
+    fs::write(wallet_dir().join(name), value.to_string())?;
+
+    Ok(())
+}
+
+pub fn has_pin() -> bool {
+    wallet_dir().join(PIN_FILE).exists()
+}
+
+/// Store a salted hash of the app lock PIN
+pub fn set_pin(pin: &str) -> anyhow::Result<()> {
+    let mut salt = [0u8; SALT_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    let hash = derive_key(pin, &salt)?;
+
+    set_config_value(
+        PIN_FILE,
+        format!("{}:{}", hex::encode(salt), hex::encode(hash.as_slice())),
+    )
+}
+
+pub fn verify_pin(pin: &str) -> bool {
+    let Some(stored) = get_config_value::<String>(PIN_FILE) else {
+        return false;
+    };
+    let Some((salt, hash)) = stored.split_once(':') else {
+        return false;
+    };
+    let (Ok(salt), Ok(hash)) = (hex::decode(salt), hex::decode(hash)) else {
+        return false;
+    };
+
+    derive_key(pin, &salt).is_ok_and(|key| key.as_slice() == hash.as_slice())
+}
+
+pub fn remove_pin() -> anyhow::Result<()> {
+    let path = wallet_dir().join(PIN_FILE);
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+
+    Ok(())
+}
+
+pub fn seed_backend() -> SeedBackend {
+    get_config_value("seed_backend").unwrap_or_default()
+}
+
+/// Whether the profile's seed is used with a BIP39 passphrase, which is
+/// never stored and asked for at every unlock
+pub fn has_passphrase() -> bool {
+    get_config_value("passphrase").unwrap_or_default()
+}
+
+pub fn set_has_passphrase(passphrase: bool) -> anyhow::Result<()> {
+    set_config_value("passphrase", passphrase)
+}
+
+/// Whether the profile's app database is encrypted with the password
+pub fn database_encrypted() -> bool {
+    get_config_value("database_encrypted").unwrap_or_default()
+}
+
+/// Encrypt the app database, or decrypt it, the next time the wallet is
+/// opened
+pub fn set_database_encrypted(encrypted: bool) -> anyhow::Result<()> {
+    set_config_value("database_encrypted", encrypted)
+}
+
+/// Switch seed storage to `backend`, moving the current seed over
+pub fn set_seed_backend(backend: SeedBackend, password: &str) -> anyhow::Result<()> {
+    let seed = get_seed(password)?;
+
+    set_config_value("seed_backend", backend)?;
+
+    if let Some(seed) = seed {
+        save_seed(&seed.to_string(), password)?;
+
+        match backend {
+            SeedBackend::File => {
+                let _ = keyring_entry().and_then(|entry| Ok(entry.delete_password()?));
+            }
+            SeedBackend::Keyring => {
+                // Only drop the file once the keyring actually holds the seed
+                let path = wallet_dir().join("seed.enc");
+                if path.exists() && matches!(get_seed_keyring(), Ok(Some(_))) {
+                    fs::remove_file(path)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn keyring_entry() -> anyhow::Result<keyring::Entry> {
+    // Wallets in other data dirs and profiles get their own entry
+    let mut user = match DATA_DIR.get() {
+        Some(dir) => format!("{}:{}", KEYRING_USER, dir.display()),
+        None => KEYRING_USER.to_string(),
+    };
+    if let Some(profile) = PROFILE.read().expect("Profile lock poisoned").as_deref() {
+        user = format!("{}@{}", user, profile);
+    }
+
+    Ok(keyring::Entry::new(KEYRING_SERVICE, &user)?)
+}
+
+fn get_seed_keyring() -> anyhow::Result<Option<Mnemonic>> {
+    match keyring_entry()?.get_password() {
+        Ok(seed) => {
+            let seed = Zeroizing::new(seed);
+            Ok(Some(Mnemonic::from_str(&seed)?))
+        }
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub fn has_seed() -> bool {
+    wallet_dir().join("seed.enc").exists()
+        || wallet_dir().join("seed.txt").exists()
+        || (seed_backend() == SeedBackend::Keyring && matches!(get_seed_keyring(), Ok(Some(_))))
+}
+
+/// Save the seed to the configured backend
+///
+/// Falls back to the encrypted file when no keyring is available.
+pub fn save_seed(seed: &str, password: &str) -> anyhow::Result<()> {
+    if seed_backend() == SeedBackend::Keyring {
+        match keyring_entry().and_then(|entry| Ok(entry.set_password(seed)?)) {
+            Ok(()) => return Ok(()),
+            Err(err) => {
+                tracing::warn!("Keyring unavailable, using encrypted file: {}", err);
+            }
+        }
+    }
+
+    save_seed_file(seed, password)
+}
+
+fn save_seed_file(seed: &str, password: &str) -> anyhow::Result<()> {
+    fs::create_dir_all(wallet_dir())?;
+
+    let path = wallet_dir().join("seed.enc");
+
+    fs::write(path, encrypt(seed.as_bytes(), password)?)?;
+
+    // Remove a plaintext seed left over from before encryption
+    let legacy_path = wallet_dir().join("seed.txt");
+    if legacy_path.exists() {
+        fs::remove_file(legacy_path)?;
+    }
+
+    Ok(())
+}
+
+/// Load the seed from the configured backend
+///
+/// Falls back to the encrypted file when no keyring is available or it holds
+/// no seed.
+pub fn get_seed(password: &str) -> anyhow::Result<Option<Mnemonic>> {
+    if seed_backend() == SeedBackend::Keyring {
+        match get_seed_keyring() {
+            Ok(Some(seed)) => return Ok(Some(seed)),
+            Ok(None) => (),
+            Err(err) => {
+                tracing::warn!("Keyring unavailable, using encrypted file: {}", err);
+            }
+        }
+    }
+
+    get_seed_file(password)
+}
+
+fn get_seed_file(password: &str) -> anyhow::Result<Option<Mnemonic>> {
+    let path = wallet_dir().join("seed.enc");
+
+    if let Ok(data) = fs::read(path) {
+        let seed = decrypt(&data, password)?;
+        let seed = Zeroizing::new(String::from_utf8(seed.to_vec())?);
+
+        return Ok(Some(Mnemonic::from_str(&seed)?));
+    }
+
+    // Encrypt a plaintext seed written by older versions
+    let legacy_path = wallet_dir().join("seed.txt");
+    match fs::read_to_string(legacy_path).ok() {
+        Some(seed) => {
+            let seed = Zeroizing::new(seed);
+            let mnemonic = Mnemonic::from_str(seed.trim())?;
+            save_seed_file(&mnemonic.to_string(), password)?;
+
+            Ok(Some(mnemonic))
+        }
+        None => Ok(None),
+    }
+}
+
+pub fn is_backed_up() -> bool {
+    wallet_dir().join("backed_up").exists()
+}
+
+pub fn set_backed_up() -> anyhow::Result<()> {
+    fs::create_dir_all(wallet_dir())?;
+
+    let path = wallet_dir().join("backed_up");
+
+    fs::write(path, "")?;
+
+    Ok(())
+}
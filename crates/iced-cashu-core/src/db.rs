@@ -0,0 +1,2424 @@
+use std::collections::HashMap;
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{ErrorKind, Read};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Mutex, Once};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use cdk::nuts::CurrencyUnit;
+use cdk::UncheckedUrl;
+use chrono::{Days, Local, Months, TimeZone};
+use libsqlite3_sys as ffi;
+use serde::{Deserialize, Serialize};
+use sqlx::query::Query;
+use sqlx::sqlite::{
+    SqliteArguments, SqliteConnectOptions, SqliteConnection, SqlitePool, SqliteRow,
+};
+use sqlx::{Connection, Executor, Row, Sqlite};
+
+use crate::mint_info::MintInfo;
+use crate::service::{MeltPreview, PendingMint};
+use crate::token;
+
+/// How long an NWC budget lasts before it renews
+const NWC_BUDGET_PERIOD: i64 = 30 * 24 * 60 * 60;
+/// First bytes of a plain SQLite database, SQLCipher encrypts them too
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+/// SQLCipher keys of databases other crates open, by canonical path
+static FILE_KEYS: Mutex<Vec<(PathBuf, String)>> = Mutex::new(Vec::new());
+static FILE_KEY_HOOK: Once = Once::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransactionKind {
+    Mint,
+    Melt,
+    Send,
+    Receive,
+}
+
+impl fmt::Display for TransactionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransactionKind::Mint => write!(f, "mint"),
+            TransactionKind::Melt => write!(f, "melt"),
+            TransactionKind::Send => write!(f, "send"),
+            TransactionKind::Receive => write!(f, "receive"),
+        }
+    }
+}
+
+impl FromStr for TransactionKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mint" => Ok(TransactionKind::Mint),
+            "melt" => Ok(TransactionKind::Melt),
+            "send" => Ok(TransactionKind::Send),
+            "receive" => Ok(TransactionKind::Receive),
+            _ => Err(anyhow::anyhow!("Unknown transaction kind: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransactionStatus {
+    Pending,
+    Complete,
+    /// Payment did not go through and nothing was spent
+    Failed,
+}
+
+impl TransactionStatus {
+    pub const ALL: [TransactionStatus; 3] = [
+        TransactionStatus::Pending,
+        TransactionStatus::Complete,
+        TransactionStatus::Failed,
+    ];
+}
+
+impl fmt::Display for TransactionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransactionStatus::Pending => write!(f, "pending"),
+            TransactionStatus::Complete => write!(f, "complete"),
+            TransactionStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+impl FromStr for TransactionStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(TransactionStatus::Pending),
+            "complete" => Ok(TransactionStatus::Complete),
+            "failed" => Ok(TransactionStatus::Failed),
+            _ => Err(anyhow::anyhow!("Unknown transaction status: {}", s)),
+        }
+    }
+}
+
+/// Outcome of checking the mint's DLEQ proofs (NUT-12)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DleqStatus {
+    /// Every signature was verified against the mint's keys
+    Verified,
+    /// Mint did not include DLEQ proofs
+    Unverified,
+    /// A DLEQ proof did not match the mint's published keys
+    Invalid,
+}
+
+impl fmt::Display for DleqStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DleqStatus::Verified => write!(f, "verified"),
+            DleqStatus::Unverified => write!(f, "unverified"),
+            DleqStatus::Invalid => write!(f, "invalid"),
+        }
+    }
+}
+
+impl FromStr for DleqStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "verified" => Ok(DleqStatus::Verified),
+            "unverified" => Ok(DleqStatus::Unverified),
+            "invalid" => Ok(DleqStatus::Invalid),
+            _ => Err(anyhow::anyhow!("Unknown DLEQ status: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Transaction {
+    pub id: i64,
+    pub kind: TransactionKind,
+    pub amount: u64,
+    /// Unit `amount` and `fee` are in, sats for transactions from before
+    /// other units were supported
+    #[serde(default)]
+    pub unit: CurrencyUnit,
+    pub mint_url: UncheckedUrl,
+    pub status: TransactionStatus,
+    /// Quote id for mints and melts
+    pub reference: Option<String>,
+    /// Unix timestamp in seconds
+    pub timestamp: i64,
+    /// Signature check of minted or received proofs
+    pub dleq: Option<DleqStatus>,
+    /// Paid in fees on top of `amount`
+    pub fee: u64,
+    /// Lightning fee reserve of a melt quote
+    #[serde(default)]
+    pub fee_reserve: Option<u64>,
+    /// Unused fee reserve the mint returned as NUT-08 change
+    #[serde(default)]
+    pub change: Option<u64>,
+    /// `npub` a sent token was delivered to over nostr
+    #[serde(default)]
+    pub nostr_recipient: Option<String>,
+    #[serde(default)]
+    pub delivery: Option<DeliveryStatus>,
+    /// Free text set by the user, or the invoice description
+    #[serde(default)]
+    pub note: Option<String>,
+    /// Memo of a sent or received token
+    #[serde(default)]
+    pub memo: Option<String>,
+    /// Labels set by the user
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl TryFrom<SqliteRow> for Transaction {
+    type Error = anyhow::Error;
+
+    fn try_from(row: SqliteRow) -> Result<Self, Self::Error> {
+        let kind: String = row.try_get("kind")?;
+        let amount: i64 = row.try_get("amount")?;
+        let unit: String = row.try_get("unit")?;
+        let mint_url: String = row.try_get("mint_url")?;
+        let status: String = row.try_get("status")?;
+        let dleq: Option<String> = row.try_get("dleq")?;
+        let fee: i64 = row.try_get("fee")?;
+        let fee_reserve: Option<i64> = row.try_get("fee_reserve")?;
+        let change: Option<i64> = row.try_get("change_returned")?;
+        let delivery: Option<String> = row.try_get("delivery")?;
+        let tags: String = row.try_get("tags")?;
+
+        Ok(Transaction {
+            id: row.try_get("id")?,
+            kind: kind.parse()?,
+            amount: amount as u64,
+            unit: unit.parse()?,
+            mint_url: UncheckedUrl::from(mint_url),
+            status: status.parse()?,
+            reference: row.try_get("reference")?,
+            timestamp: row.try_get("timestamp")?,
+            dleq: dleq.map(|dleq| dleq.parse()).transpose()?,
+            fee: fee as u64,
+            fee_reserve: fee_reserve.map(|fee_reserve| fee_reserve as u64),
+            change: change.map(|change| change as u64),
+            nostr_recipient: row.try_get("nostr_recipient")?,
+            delivery: delivery.map(|delivery| delivery.parse()).transpose()?,
+            note: row.try_get("note")?,
+            memo: row.try_get("memo")?,
+            tags: parse_tags(&tags),
+        })
+    }
+}
+
+/// Whether a transaction brought funds in or took them out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Incoming,
+    Outgoing,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 2] = [Direction::Incoming, Direction::Outgoing];
+
+    fn kinds(&self) -> [TransactionKind; 2] {
+        match self {
+            Direction::Incoming => [TransactionKind::Mint, TransactionKind::Receive],
+            Direction::Outgoing => [TransactionKind::Melt, TransactionKind::Send],
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Direction::Incoming => write!(f, "Incoming"),
+            Direction::Outgoing => write!(f, "Outgoing"),
+        }
+    }
+}
+
+/// Narrows the history to matching transactions
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HistoryFilter {
+    /// Matched against notes, memos, tags, quote ids and mint urls
+    pub search: String,
+    pub tag: Option<String>,
+    /// Unix timestamp in seconds of the earliest transaction
+    pub from: Option<i64>,
+    /// Unix timestamp in seconds transactions are before
+    pub until: Option<i64>,
+    pub mint_url: Option<UncheckedUrl>,
+    pub direction: Option<Direction>,
+    pub status: Option<TransactionStatus>,
+    pub min_amount: Option<u64>,
+    pub max_amount: Option<u64>,
+}
+
+/// Value bound to a filter condition
+enum FilterValue {
+    Text(String),
+    Integer(i64),
+}
+
+impl HistoryFilter {
+    /// SQL condition of the filter and the values to bind to it, in order
+    fn condition(&self) -> (String, Vec<FilterValue>) {
+        let mut conditions = vec!["1 = 1"];
+        let mut values = vec![];
+
+        let search = self.search.trim();
+        if !search.is_empty() {
+            conditions.push(
+                "(note LIKE ? OR memo LIKE ? OR tags LIKE ? OR reference LIKE ? OR mint_url LIKE ?)",
+            );
+            for _ in 0..5 {
+                values.push(FilterValue::Text(format!("%{}%", search)));
+            }
+        }
+        if let Some(tag) = &self.tag {
+            conditions.push("(',' || tags || ',') LIKE ?");
+            values.push(FilterValue::Text(format!("%,{},%", tag)));
+        }
+        if let Some(from) = self.from {
+            conditions.push("timestamp >= ?");
+            values.push(FilterValue::Integer(from));
+        }
+        if let Some(until) = self.until {
+            conditions.push("timestamp < ?");
+            values.push(FilterValue::Integer(until));
+        }
+        if let Some(mint_url) = &self.mint_url {
+            conditions.push("mint_url = ?");
+            values.push(FilterValue::Text(mint_url.to_string()));
+        }
+        if let Some(direction) = self.direction {
+            conditions.push("kind IN (?, ?)");
+            for kind in direction.kinds() {
+                values.push(FilterValue::Text(kind.to_string()));
+            }
+        }
+        if let Some(status) = self.status {
+            conditions.push("status = ?");
+            values.push(FilterValue::Text(status.to_string()));
+        }
+        if let Some(min_amount) = self.min_amount {
+            conditions.push("amount >= ?");
+            values.push(FilterValue::Integer(min_amount as i64));
+        }
+        if let Some(max_amount) = self.max_amount {
+            conditions.push("amount <= ?");
+            values.push(FilterValue::Integer(max_amount as i64));
+        }
+
+        (conditions.join(" AND "), values)
+    }
+
+    /// `sql` with the filter's values bound to its first placeholders
+    fn query<'q>(
+        &self,
+        sql: &'q str,
+        values: Vec<FilterValue>,
+    ) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+        values
+            .into_iter()
+            .fold(sqlx::query(sql), |query, value| match value {
+                FilterValue::Text(value) => query.bind(value),
+                FilterValue::Integer(value) => query.bind(value),
+            })
+    }
+}
+
+/// Labels typed as a comma separated list, without blanks or duplicates
+pub fn parse_tags(input: &str) -> Vec<String> {
+    let mut tags: Vec<String> = vec![];
+    for tag in input
+        .split(',')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+    {
+        if !tags.iter().any(|existing| existing == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+
+    tags
+}
+
+/// Whether a token sent over nostr reached any relay
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    Delivered,
+    Failed,
+}
+
+impl fmt::Display for DeliveryStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeliveryStatus::Delivered => write!(f, "delivered"),
+            DeliveryStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+impl FromStr for DeliveryStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "delivered" => Ok(DeliveryStatus::Delivered),
+            "failed" => Ok(DeliveryStatus::Failed),
+            _ => Err(anyhow::anyhow!("Unknown delivery status: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SentTokenStatus {
+    /// Not yet redeemed by the recipient
+    Pending,
+    /// Proofs were spent by the recipient
+    Claimed,
+    /// Swapped back into this wallet
+    Reclaimed,
+}
+
+impl fmt::Display for SentTokenStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SentTokenStatus::Pending => write!(f, "pending"),
+            SentTokenStatus::Claimed => write!(f, "claimed"),
+            SentTokenStatus::Reclaimed => write!(f, "reclaimed"),
+        }
+    }
+}
+
+impl FromStr for SentTokenStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(SentTokenStatus::Pending),
+            "claimed" => Ok(SentTokenStatus::Claimed),
+            "reclaimed" => Ok(SentTokenStatus::Reclaimed),
+            _ => Err(anyhow::anyhow!("Unknown sent token status: {}", s)),
+        }
+    }
+}
+
+/// Receive, mint or melt to run again after it failed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RetryOperation {
+    Receive { token: String },
+    Mint { pending: PendingMint },
+    Melt { preview: MeltPreview },
+}
+
+impl RetryOperation {
+    pub fn amount(&self) -> u64 {
+        match self {
+            RetryOperation::Receive { token } => token::parse(token)
+                .map(|token| token::summary(&token).amount)
+                .unwrap_or_default(),
+            RetryOperation::Mint { pending } => pending.amount,
+            RetryOperation::Melt { preview } => preview.amount,
+        }
+    }
+}
+
+impl fmt::Display for RetryOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RetryOperation::Receive { .. } => write!(f, "receive"),
+            RetryOperation::Mint { .. } => write!(f, "mint"),
+            RetryOperation::Melt { .. } => write!(f, "melt"),
+        }
+    }
+}
+
+/// Operation in the retry queue
+#[derive(Debug, Clone)]
+pub struct FailedOperation {
+    pub id: i64,
+    pub operation: RetryOperation,
+    /// Error of the last attempt
+    pub error: String,
+    pub attempts: u32,
+    /// Unix timestamp in seconds of the next automatic retry, `None` once
+    /// the error is not worth retrying on its own
+    pub next_attempt: Option<i64>,
+}
+
+impl TryFrom<SqliteRow> for FailedOperation {
+    type Error = anyhow::Error;
+
+    fn try_from(row: SqliteRow) -> Result<Self, Self::Error> {
+        let operation: String = row.try_get("operation")?;
+        let attempts: i64 = row.try_get("attempts")?;
+
+        Ok(FailedOperation {
+            id: row.try_get("id")?,
+            operation: serde_json::from_str(&operation)?,
+            error: row.try_get("error")?,
+            attempts: attempts as u32,
+            next_attempt: row.try_get("next_attempt")?,
+        })
+    }
+}
+
+/// Melt started but not known to have finished, kept to be reconciled
+/// should the app stop mid-payment
+#[derive(Debug, Clone)]
+pub struct InFlightMelt {
+    pub quote_id: String,
+    pub mint_url: UncheckedUrl,
+    /// NUT-05 method name, e.g. `bolt11`
+    pub method: String,
+    pub unit: CurrencyUnit,
+    pub amount: u64,
+    /// Inputs of melts made without cdk, to take back if the melt failed
+    pub token: Option<String>,
+}
+
+impl TryFrom<SqliteRow> for InFlightMelt {
+    type Error = anyhow::Error;
+
+    fn try_from(row: SqliteRow) -> Result<Self, Self::Error> {
+        let mint_url: String = row.try_get("mint_url")?;
+        let unit: String = row.try_get("unit")?;
+        let amount: i64 = row.try_get("amount")?;
+
+        Ok(InFlightMelt {
+            quote_id: row.try_get("quote_id")?,
+            mint_url: UncheckedUrl::from(mint_url),
+            method: row.try_get("method")?,
+            unit: unit.parse()?,
+            amount: amount as u64,
+            token: row.try_get("token")?,
+        })
+    }
+}
+
+/// Token created by this wallet
+#[derive(Debug, Clone)]
+pub struct SentToken {
+    pub id: i64,
+    pub token: String,
+    pub amount: u64,
+    pub mint_url: UncheckedUrl,
+    pub status: SentTokenStatus,
+    /// Unix timestamp in seconds
+    pub timestamp: i64,
+}
+
+impl TryFrom<SqliteRow> for SentToken {
+    type Error = anyhow::Error;
+
+    fn try_from(row: SqliteRow) -> Result<Self, Self::Error> {
+        let amount: i64 = row.try_get("amount")?;
+        let mint_url: String = row.try_get("mint_url")?;
+        let status: String = row.try_get("status")?;
+
+        Ok(SentToken {
+            id: row.try_get("id")?,
+            token: row.try_get("token")?,
+            amount: amount as u64,
+            mint_url: UncheckedUrl::from(mint_url),
+            status: status.parse()?,
+            timestamp: row.try_get("timestamp")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InboxTokenStatus {
+    /// Waiting for the user to claim or dismiss
+    Pending,
+    Claimed,
+    Dismissed,
+}
+
+impl fmt::Display for InboxTokenStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InboxTokenStatus::Pending => write!(f, "pending"),
+            InboxTokenStatus::Claimed => write!(f, "claimed"),
+            InboxTokenStatus::Dismissed => write!(f, "dismissed"),
+        }
+    }
+}
+
+impl FromStr for InboxTokenStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(InboxTokenStatus::Pending),
+            "claimed" => Ok(InboxTokenStatus::Claimed),
+            "dismissed" => Ok(InboxTokenStatus::Dismissed),
+            _ => Err(anyhow::anyhow!("Unknown inbox token status: {}", s)),
+        }
+    }
+}
+
+/// Token received in a nostr direct message
+#[derive(Debug, Clone)]
+pub struct InboxToken {
+    pub id: i64,
+    /// Id of the DM event, so relays replaying it don't add it twice
+    pub event_id: String,
+    /// `npub` of the sender
+    pub sender: String,
+    pub token: String,
+    pub amount: u64,
+    pub status: InboxTokenStatus,
+    /// Unix timestamp of the DM in seconds
+    pub timestamp: i64,
+}
+
+impl TryFrom<SqliteRow> for InboxToken {
+    type Error = anyhow::Error;
+
+    fn try_from(row: SqliteRow) -> Result<Self, Self::Error> {
+        let amount: i64 = row.try_get("amount")?;
+        let status: String = row.try_get("status")?;
+
+        Ok(InboxToken {
+            id: row.try_get("id")?,
+            event_id: row.try_get("event_id")?,
+            sender: row.try_get("sender")?,
+            token: row.try_get("token")?,
+            amount: amount as u64,
+            status: status.parse()?,
+            timestamp: row.try_get("timestamp")?,
+        })
+    }
+}
+
+impl TryFrom<SqliteRow> for PendingMint {
+    type Error = anyhow::Error;
+
+    fn try_from(row: SqliteRow) -> Result<Self, Self::Error> {
+        let mint_url: String = row.try_get("mint_url")?;
+        let amount: i64 = row.try_get("amount")?;
+        let unit: String = row.try_get("unit")?;
+        let expiry: i64 = row.try_get("expiry")?;
+
+        Ok(PendingMint {
+            quote_id: row.try_get("quote_id")?,
+            mint_url: UncheckedUrl::from(mint_url),
+            request: row.try_get("request")?,
+            amount: amount as u64,
+            unit: unit.parse()?,
+            expiry: expiry as u64,
+            onchain: row.try_get("onchain")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NutzapStatus {
+    /// Being redeemed, so other relays replaying the zap skip it
+    Pending,
+    Redeemed,
+    /// Mint could not be reached, redeemed again when the zap is replayed
+    Unreachable,
+    /// Proofs were already spent or not locked to the wallet
+    Failed,
+}
+
+impl fmt::Display for NutzapStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NutzapStatus::Pending => write!(f, "pending"),
+            NutzapStatus::Redeemed => write!(f, "redeemed"),
+            NutzapStatus::Unreachable => write!(f, "unreachable"),
+            NutzapStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+/// Address book entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Contact {
+    pub id: i64,
+    pub name: String,
+    /// Lightning address or LNURL to pay
+    pub lightning_address: Option<String>,
+    /// Nostr public key to DM tokens to
+    pub npub: Option<String>,
+    /// Hex public key to P2PK lock tokens to
+    pub p2pk_pubkey: Option<String>,
+}
+
+impl fmt::Display for Contact {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl TryFrom<SqliteRow> for Contact {
+    type Error = anyhow::Error;
+
+    fn try_from(row: SqliteRow) -> Result<Self, Self::Error> {
+        Ok(Contact {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            lightning_address: row.try_get("lightning_address")?,
+            npub: row.try_get("npub")?,
+            p2pk_pubkey: row.try_get("p2pk_pubkey")?,
+        })
+    }
+}
+
+/// How often a scheduled payment is due
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Recurrence {
+    #[default]
+    Once,
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl Recurrence {
+    pub const ALL: [Recurrence; 4] = [
+        Recurrence::Once,
+        Recurrence::Daily,
+        Recurrence::Weekly,
+        Recurrence::Monthly,
+    ];
+
+    /// Time due after `time`, in local time so the hour of day stays put
+    /// across DST changes, none for one-off payments
+    pub fn next(self, time: i64) -> Option<i64> {
+        let time = Local.timestamp_opt(time, 0).earliest()?;
+        let next = match self {
+            Recurrence::Once => return None,
+            Recurrence::Daily => time.checked_add_days(Days::new(1)),
+            Recurrence::Weekly => time.checked_add_days(Days::new(7)),
+            Recurrence::Monthly => time.checked_add_months(Months::new(1)),
+        }?;
+
+        Some(next.timestamp())
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Recurrence::Once => write!(f, "once"),
+            Recurrence::Daily => write!(f, "daily"),
+            Recurrence::Weekly => write!(f, "weekly"),
+            Recurrence::Monthly => write!(f, "monthly"),
+        }
+    }
+}
+
+impl FromStr for Recurrence {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "once" => Ok(Recurrence::Once),
+            "daily" => Ok(Recurrence::Daily),
+            "weekly" => Ok(Recurrence::Weekly),
+            "monthly" => Ok(Recurrence::Monthly),
+            _ => Err(anyhow::anyhow!("Unknown recurrence: {}", s)),
+        }
+    }
+}
+
+/// What a scheduled payment does each time it is due
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ScheduledAction {
+    /// Pay a lightning address or LNURL
+    Pay { address: String },
+    /// DM a token to a contact, locked to their P2PK key if they have one
+    Send { contact_id: i64 },
+}
+
+/// Payment the scheduler makes from `next_run` on every `recurrence`
+#[derive(Debug, Clone)]
+pub struct ScheduledPayment {
+    pub id: i64,
+    pub action: ScheduledAction,
+    /// Sats paid each time
+    pub amount: u64,
+    pub recurrence: Recurrence,
+    /// Unix timestamp in seconds the payment is next due
+    pub next_run: i64,
+    /// Error of the last run, none if it succeeded
+    pub last_error: Option<String>,
+}
+
+impl ScheduledPayment {
+    /// First time due after `now`, skipping runs missed while the app was
+    /// closed, none for one-off payments
+    pub fn next_after(&self, now: i64) -> Option<i64> {
+        let mut next = self.recurrence.next(self.next_run)?;
+        while next <= now {
+            next = self.recurrence.next(next)?;
+        }
+
+        Some(next)
+    }
+}
+
+impl TryFrom<SqliteRow> for ScheduledPayment {
+    type Error = anyhow::Error;
+
+    fn try_from(row: SqliteRow) -> Result<Self, Self::Error> {
+        let action: String = row.try_get("action")?;
+        let amount: i64 = row.try_get("amount")?;
+        let recurrence: String = row.try_get("recurrence")?;
+
+        Ok(ScheduledPayment {
+            id: row.try_get("id")?,
+            action: serde_json::from_str(&action)?,
+            amount: amount as u64,
+            recurrence: recurrence.parse()?,
+            next_run: row.try_get("next_run")?,
+            last_error: row.try_get("last_error")?,
+        })
+    }
+}
+
+/// Payment taken in point-of-sale mode, kept in the sales ledger for
+/// receipts and bookkeeping
+#[derive(Debug, Clone)]
+pub struct Sale {
+    pub id: i64,
+    /// Sats paid, tip included
+    pub amount: u64,
+    pub tip: u64,
+    /// Value in `fiat_currency` at the rate of the time of sale
+    pub fiat_amount: Option<f64>,
+    pub fiat_currency: Option<String>,
+    pub note: Option<String>,
+    /// Hex payment hash of the invoice the customer paid
+    pub payment_hash: String,
+    pub mint_url: UncheckedUrl,
+    /// Unix timestamp in seconds
+    pub timestamp: i64,
+}
+
+impl TryFrom<SqliteRow> for Sale {
+    type Error = anyhow::Error;
+
+    fn try_from(row: SqliteRow) -> Result<Self, Self::Error> {
+        let amount: i64 = row.try_get("amount")?;
+        let tip: i64 = row.try_get("tip")?;
+        let mint_url: String = row.try_get("mint_url")?;
+
+        Ok(Sale {
+            id: row.try_get("id")?,
+            amount: amount as u64,
+            tip: tip as u64,
+            fiat_amount: row.try_get("fiat_amount")?,
+            fiat_currency: row.try_get("fiat_currency")?,
+            note: row.try_get("note")?,
+            payment_hash: row.try_get("payment_hash")?,
+            mint_url: UncheckedUrl::from(mint_url),
+            timestamp: row.try_get("created_at")?,
+        })
+    }
+}
+
+/// Nostr Wallet Connect connection of another app
+#[derive(Debug, Clone)]
+pub struct NwcConnection {
+    pub id: i64,
+    pub name: String,
+    pub relay: String,
+    /// Hex secret key of the wallet service side
+    pub service_secret: String,
+    /// Hex secret key handed to the connecting app
+    pub client_secret: String,
+    /// Sats the app may spend per budget period
+    pub budget: u64,
+    pub spent: u64,
+    /// Unix timestamp in seconds
+    pub period_start: i64,
+}
+
+impl NwcConnection {
+    /// Sats left to spend in the current budget period
+    pub fn remaining(&self) -> u64 {
+        if unix_time() - self.period_start >= NWC_BUDGET_PERIOD {
+            return self.budget;
+        }
+
+        self.budget.saturating_sub(self.spent)
+    }
+}
+
+impl TryFrom<SqliteRow> for NwcConnection {
+    type Error = anyhow::Error;
+
+    fn try_from(row: SqliteRow) -> Result<Self, Self::Error> {
+        let budget: i64 = row.try_get("budget")?;
+        let spent: i64 = row.try_get("spent")?;
+
+        Ok(NwcConnection {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            relay: row.try_get("relay")?,
+            service_secret: row.try_get("service_secret")?,
+            client_secret: row.try_get("client_secret")?,
+            budget: budget as u64,
+            spent: spent as u64,
+            period_start: row.try_get("period_start")?,
+        })
+    }
+}
+
+/// App database for records cdk does not keep track of
+#[derive(Debug, Clone)]
+pub struct Database {
+    pool: SqlitePool,
+}
+
+impl Database {
+    /// Open the app database at `path`, encrypted with SQLCipher under
+    /// `password` if `encrypted`
+    ///
+    /// A database written the other way, before encryption was turned on or
+    /// off, is converted first.
+    pub async fn new(path: &str, password: &str, encrypted: bool) -> anyhow::Result<Self> {
+        if is_plaintext(path) == Some(encrypted) {
+            let (from, to) = match encrypted {
+                true => (None, Some(password)),
+                false => (Some(password), None),
+            };
+            convert(path, from, to).await?;
+            tracing::info!("Converted app database, encrypted: {}", encrypted);
+        }
+
+        let options = connect_options(path, encrypted.then_some(password))?;
+        let pool = SqlitePool::connect_with(options).await?;
+
+        sqlx::migrate!("./src/migrations").run(&pool).await?;
+
+        Ok(Self { pool })
+    }
+
+    pub async fn add_transaction(
+        &self,
+        kind: TransactionKind,
+        amount: u64,
+        unit: &CurrencyUnit,
+        mint_url: &UncheckedUrl,
+        status: TransactionStatus,
+        reference: Option<&str>,
+    ) -> anyhow::Result<i64> {
+        let result = sqlx::query(
+            r#"
+INSERT INTO transactions (kind, amount, unit, mint_url, status, reference, timestamp)
+VALUES (?, ?, ?, ?, ?, ?, ?);
+        "#,
+        )
+        .bind(kind.to_string())
+        .bind(amount as i64)
+        .bind(unit.to_string())
+        .bind(mint_url.to_string())
+        .bind(status.to_string())
+        .bind(reference)
+        .bind(unix_time())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Record a completed receive with what was checked of its token,
+    /// returning its id
+    pub async fn record_receive(
+        &self,
+        amount: u64,
+        unit: &CurrencyUnit,
+        mint_url: &UncheckedUrl,
+        dleq: DleqStatus,
+        memo: Option<&str>,
+    ) -> anyhow::Result<i64> {
+        let result = sqlx::query(
+            r#"
+INSERT INTO transactions (kind, amount, unit, mint_url, status, dleq, memo, timestamp)
+VALUES (?, ?, ?, ?, ?, ?, ?, ?);
+        "#,
+        )
+        .bind(TransactionKind::Receive.to_string())
+        .bind(amount as i64)
+        .bind(unit.to_string())
+        .bind(mint_url.to_string())
+        .bind(TransactionStatus::Complete.to_string())
+        .bind(dleq.to_string())
+        .bind(memo)
+        .bind(unix_time())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Insert a transaction from a backup, keeping its timestamp
+    pub async fn import_transaction(&self, transaction: &Transaction) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+INSERT INTO transactions (kind, amount, unit, mint_url, status, reference, timestamp, dleq,
+    fee, fee_reserve, change_returned, nostr_recipient, delivery, note, tags, memo)
+VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?);
+        "#,
+        )
+        .bind(transaction.kind.to_string())
+        .bind(transaction.amount as i64)
+        .bind(transaction.unit.to_string())
+        .bind(transaction.mint_url.to_string())
+        .bind(transaction.status.to_string())
+        .bind(&transaction.reference)
+        .bind(transaction.timestamp)
+        .bind(transaction.dleq.map(|dleq| dleq.to_string()))
+        .bind(transaction.fee as i64)
+        .bind(
+            transaction
+                .fee_reserve
+                .map(|fee_reserve| fee_reserve as i64),
+        )
+        .bind(transaction.change.map(|change| change as i64))
+        .bind(&transaction.nostr_recipient)
+        .bind(transaction.delivery.map(|delivery| delivery.to_string()))
+        .bind(&transaction.note)
+        .bind(transaction.tags.join(","))
+        .bind(&transaction.memo)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn update_transaction_status(
+        &self,
+        reference: &str,
+        status: TransactionStatus,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+UPDATE transactions SET status = ? WHERE reference = ?;
+        "#,
+        )
+        .bind(status.to_string())
+        .bind(reference)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn set_transaction_fee(&self, id: i64, fee: u64) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+UPDATE transactions SET fee = ? WHERE id = ?;
+        "#,
+        )
+        .bind(fee as i64)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Fee reserve of a melt and the change the mint returned from it
+    pub async fn set_melt_change(
+        &self,
+        id: i64,
+        fee_reserve: u64,
+        change: u64,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+UPDATE transactions SET fee_reserve = ?, change_returned = ? WHERE id = ?;
+        "#,
+        )
+        .bind(fee_reserve as i64)
+        .bind(change as i64)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn set_reference_dleq_status(
+        &self,
+        reference: &str,
+        dleq: DleqStatus,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+UPDATE transactions SET dleq = ? WHERE reference = ?;
+        "#,
+        )
+        .bind(dleq.to_string())
+        .bind(reference)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn set_delivery(
+        &self,
+        id: i64,
+        recipient: &str,
+        delivery: DeliveryStatus,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+UPDATE transactions SET nostr_recipient = ?, delivery = ? WHERE id = ?;
+        "#,
+        )
+        .bind(recipient)
+        .bind(delivery.to_string())
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Recipients tokens were sent to over nostr, most recent first
+    pub async fn get_nostr_recipients(&self) -> anyhow::Result<Vec<String>> {
+        let rows = sqlx::query(
+            r#"
+SELECT nostr_recipient FROM transactions
+WHERE nostr_recipient IS NOT NULL
+GROUP BY nostr_recipient
+ORDER BY MAX(timestamp) DESC;
+        "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| Ok(row.try_get("nostr_recipient")?))
+            .collect()
+    }
+
+    /// Newest first
+    pub async fn get_transactions(
+        &self,
+        limit: u32,
+        offset: u32,
+    ) -> anyhow::Result<Vec<Transaction>> {
+        let rows = sqlx::query(
+            r#"
+SELECT * FROM transactions ORDER BY timestamp DESC, id DESC LIMIT ? OFFSET ?;
+        "#,
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(Transaction::try_from).collect()
+    }
+
+    /// Page of the transactions matching `filter`, newest first
+    pub async fn get_filtered_transactions(
+        &self,
+        filter: &HistoryFilter,
+        limit: u32,
+        offset: u32,
+    ) -> anyhow::Result<Vec<Transaction>> {
+        let (condition, values) = filter.condition();
+        let sql = format!(
+            "SELECT * FROM transactions WHERE {} ORDER BY timestamp DESC, id DESC LIMIT ? OFFSET ?;",
+            condition
+        );
+
+        let rows = filter
+            .query(&sql, values)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter().map(Transaction::try_from).collect()
+    }
+
+    pub async fn count_filtered_transactions(&self, filter: &HistoryFilter) -> anyhow::Result<u32> {
+        let (condition, values) = filter.condition();
+        let sql = format!(
+            "SELECT COUNT(*) AS count FROM transactions WHERE {};",
+            condition
+        );
+
+        let count: i64 = filter
+            .query(&sql, values)
+            .fetch_one(&self.pool)
+            .await?
+            .try_get("count")?;
+
+        Ok(count as u32)
+    }
+
+    pub async fn set_transaction_note(
+        &self,
+        id: i64,
+        note: Option<&str>,
+        tags: &[String],
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+UPDATE transactions SET note = ?, tags = ? WHERE id = ?;
+        "#,
+        )
+        .bind(note)
+        .bind(tags.join(","))
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn set_transaction_memo(&self, id: i64, memo: &str) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+UPDATE transactions SET memo = ? WHERE id = ?;
+        "#,
+        )
+        .bind(memo)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Every label in use, sorted
+    pub async fn get_tags(&self) -> anyhow::Result<Vec<String>> {
+        let rows = sqlx::query(
+            r#"
+SELECT DISTINCT tags FROM transactions WHERE tags != '';
+        "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut tags = vec![];
+        for row in rows {
+            let row_tags: String = row.try_get("tags")?;
+            tags.extend(parse_tags(&row_tags));
+        }
+        tags.sort();
+        tags.dedup();
+
+        Ok(tags)
+    }
+
+    pub async fn add_sent_token(
+        &self,
+        token: &str,
+        amount: u64,
+        mint_url: &UncheckedUrl,
+    ) -> anyhow::Result<i64> {
+        let result = sqlx::query(
+            r#"
+INSERT INTO sent_tokens (token, amount, mint_url, status, timestamp)
+VALUES (?, ?, ?, ?, ?);
+        "#,
+        )
+        .bind(token)
+        .bind(amount as i64)
+        .bind(mint_url.to_string())
+        .bind(SentTokenStatus::Pending.to_string())
+        .bind(unix_time())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Newest first
+    pub async fn get_pending_sent_tokens(&self) -> anyhow::Result<Vec<SentToken>> {
+        let rows = sqlx::query(
+            r#"
+SELECT * FROM sent_tokens WHERE status = ? ORDER BY timestamp DESC, id DESC;
+        "#,
+        )
+        .bind(SentTokenStatus::Pending.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(SentToken::try_from).collect()
+    }
+
+    /// Save a token just created from the wallet's proofs together with its
+    /// send transaction, returning the transaction's id
+    ///
+    /// Both are written in one database transaction, the token is never
+    /// recorded without the send or the other way round.
+    pub async fn record_sent_token(
+        &self,
+        token: &str,
+        amount: u64,
+        fee: u64,
+        unit: &CurrencyUnit,
+        mint_url: &UncheckedUrl,
+        memo: Option<&str>,
+    ) -> anyhow::Result<i64> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query(
+            r#"
+INSERT INTO transactions (kind, amount, unit, mint_url, status, fee, memo, timestamp)
+VALUES (?, ?, ?, ?, ?, ?, ?, ?);
+        "#,
+        )
+        .bind(TransactionKind::Send.to_string())
+        .bind(amount as i64)
+        .bind(unit.to_string())
+        .bind(mint_url.to_string())
+        .bind(TransactionStatus::Complete.to_string())
+        .bind(fee as i64)
+        .bind(memo)
+        .bind(unix_time())
+        .execute(&mut tx)
+        .await?;
+        sqlx::query(
+            r#"
+INSERT INTO sent_tokens (token, amount, mint_url, status, timestamp)
+VALUES (?, ?, ?, ?, ?);
+        "#,
+        )
+        .bind(token)
+        .bind(amount as i64)
+        .bind(mint_url.to_string())
+        .bind(SentTokenStatus::Pending.to_string())
+        .bind(unix_time())
+        .execute(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Mark sent token `id` reclaimed and record the receive that reclaimed
+    /// it, in one database transaction
+    pub async fn record_reclaim(
+        &self,
+        id: i64,
+        amount: u64,
+        unit: &CurrencyUnit,
+        mint_url: &UncheckedUrl,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+UPDATE sent_tokens SET status = ? WHERE id = ?;
+        "#,
+        )
+        .bind(SentTokenStatus::Reclaimed.to_string())
+        .bind(id)
+        .execute(&mut tx)
+        .await?;
+        sqlx::query(
+            r#"
+INSERT INTO transactions (kind, amount, unit, mint_url, status, timestamp)
+VALUES (?, ?, ?, ?, ?, ?);
+        "#,
+        )
+        .bind(TransactionKind::Receive.to_string())
+        .bind(amount as i64)
+        .bind(unit.to_string())
+        .bind(mint_url.to_string())
+        .bind(TransactionStatus::Complete.to_string())
+        .bind(unix_time())
+        .execute(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn update_sent_token_status(
+        &self,
+        id: i64,
+        status: SentTokenStatus,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+UPDATE sent_tokens SET status = ? WHERE id = ?;
+        "#,
+        )
+        .bind(status.to_string())
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Store a token received over nostr, `None` if the DM was seen before
+    pub async fn add_inbox_token(
+        &self,
+        event_id: &str,
+        sender: &str,
+        token: &str,
+        amount: u64,
+        timestamp: i64,
+    ) -> anyhow::Result<Option<InboxToken>> {
+        let result = sqlx::query(
+            r#"
+INSERT OR IGNORE INTO inbox_tokens (event_id, sender, token, amount, status, timestamp)
+VALUES (?, ?, ?, ?, ?, ?);
+        "#,
+        )
+        .bind(event_id)
+        .bind(sender)
+        .bind(token)
+        .bind(amount as i64)
+        .bind(InboxTokenStatus::Pending.to_string())
+        .bind(timestamp)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(InboxToken {
+            id: result.last_insert_rowid(),
+            event_id: event_id.to_string(),
+            sender: sender.to_string(),
+            token: token.to_string(),
+            amount,
+            status: InboxTokenStatus::Pending,
+            timestamp,
+        }))
+    }
+
+    pub async fn get_pending_inbox_tokens(&self) -> anyhow::Result<Vec<InboxToken>> {
+        let rows = sqlx::query(
+            r#"
+SELECT * FROM inbox_tokens WHERE status = ? ORDER BY timestamp DESC, id DESC;
+        "#,
+        )
+        .bind(InboxTokenStatus::Pending.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(InboxToken::try_from).collect()
+    }
+
+    pub async fn update_inbox_token_status(
+        &self,
+        id: i64,
+        status: InboxTokenStatus,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+UPDATE inbox_tokens SET status = ? WHERE id = ?;
+        "#,
+        )
+        .bind(status.to_string())
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Record a nutzap and its token before redeeming it, `None` if it was
+    /// seen before and isn't waiting for its mint to come back
+    pub async fn add_nutzap(
+        &self,
+        event_id: &str,
+        sender: &str,
+        mint_url: &UncheckedUrl,
+        token: &str,
+        timestamp: i64,
+    ) -> anyhow::Result<Option<i64>> {
+        let result = sqlx::query(
+            r#"
+INSERT OR IGNORE INTO nutzaps (event_id, sender, mint_url, token, status, timestamp)
+VALUES (?, ?, ?, ?, ?, ?);
+        "#,
+        )
+        .bind(event_id)
+        .bind(sender)
+        .bind(mint_url.to_string())
+        .bind(token)
+        .bind(NutzapStatus::Pending.to_string())
+        .bind(timestamp)
+        .execute(&self.pool)
+        .await?;
+        if result.rows_affected() > 0 {
+            return Ok(Some(result.last_insert_rowid()));
+        }
+
+        let result = sqlx::query(
+            r#"
+UPDATE nutzaps SET status = ? WHERE event_id = ? AND status = ?;
+        "#,
+        )
+        .bind(NutzapStatus::Pending.to_string())
+        .bind(event_id)
+        .bind(NutzapStatus::Unreachable.to_string())
+        .execute(&self.pool)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Ok(None);
+        }
+
+        let row = sqlx::query(
+            r#"
+SELECT id FROM nutzaps WHERE event_id = ?;
+        "#,
+        )
+        .bind(event_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(Some(row.try_get("id")?))
+    }
+
+    pub async fn update_nutzap(
+        &self,
+        id: i64,
+        status: NutzapStatus,
+        amount: u64,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+UPDATE nutzaps SET status = ?, amount = ? WHERE id = ?;
+        "#,
+        )
+        .bind(status.to_string())
+        .bind(amount as i64)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn add_contact(
+        &self,
+        name: &str,
+        lightning_address: Option<&str>,
+        npub: Option<&str>,
+        p2pk_pubkey: Option<&str>,
+    ) -> anyhow::Result<i64> {
+        let result = sqlx::query(
+            r#"
+INSERT INTO contacts (name, lightning_address, npub, p2pk_pubkey)
+VALUES (?, ?, ?, ?);
+        "#,
+        )
+        .bind(name)
+        .bind(lightning_address)
+        .bind(npub)
+        .bind(p2pk_pubkey)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Sorted by name
+    pub async fn get_contacts(&self) -> anyhow::Result<Vec<Contact>> {
+        let rows = sqlx::query(
+            r#"
+SELECT * FROM contacts ORDER BY name COLLATE NOCASE;
+        "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(Contact::try_from).collect()
+    }
+
+    pub async fn delete_contact(&self, id: i64) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+DELETE FROM contacts WHERE id = ?;
+        "#,
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Keep `pending` until it is minted or expires, so quotes survive a
+    /// restart
+    pub async fn add_mint_quote(&self, pending: &PendingMint) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+INSERT OR REPLACE INTO mint_quotes
+(quote_id, mint_url, request, amount, unit, expiry, onchain, created_at)
+VALUES (?, ?, ?, ?, ?, ?, ?, ?);
+        "#,
+        )
+        .bind(&pending.quote_id)
+        .bind(pending.mint_url.to_string())
+        .bind(&pending.request)
+        .bind(pending.amount as i64)
+        .bind(pending.unit.to_string())
+        .bind(pending.expiry.min(i64::MAX as u64) as i64)
+        .bind(pending.onchain)
+        .bind(unix_time())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Newest first
+    pub async fn get_mint_quotes(&self) -> anyhow::Result<Vec<PendingMint>> {
+        let rows = sqlx::query(
+            r#"
+SELECT * FROM mint_quotes ORDER BY created_at DESC;
+        "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(PendingMint::try_from).collect()
+    }
+
+    pub async fn delete_mint_quote(&self, quote_id: &str) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+DELETE FROM mint_quotes WHERE quote_id = ?;
+        "#,
+        )
+        .bind(quote_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Record `melt` as in flight with its pending transaction, returning the
+    /// transaction's id
+    ///
+    /// Both are written in one database transaction so
+    /// [`Self::get_melt_quotes`] always has what a restart needs to settle
+    /// the melt.
+    pub async fn begin_melt(
+        &self,
+        melt: &InFlightMelt,
+        description: Option<&str>,
+    ) -> anyhow::Result<i64> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query(
+            r#"
+INSERT INTO transactions (kind, amount, unit, mint_url, status, reference, note, timestamp)
+VALUES (?, ?, ?, ?, ?, ?, ?, ?);
+        "#,
+        )
+        .bind(TransactionKind::Melt.to_string())
+        .bind(melt.amount as i64)
+        .bind(melt.unit.to_string())
+        .bind(melt.mint_url.to_string())
+        .bind(TransactionStatus::Pending.to_string())
+        .bind(&melt.quote_id)
+        .bind(description)
+        .bind(unix_time())
+        .execute(&mut tx)
+        .await?;
+        sqlx::query(
+            r#"
+INSERT OR REPLACE INTO melt_quotes
+(quote_id, mint_url, method, unit, amount, token, created_at)
+VALUES (?, ?, ?, ?, ?, ?, ?);
+        "#,
+        )
+        .bind(&melt.quote_id)
+        .bind(melt.mint_url.to_string())
+        .bind(&melt.method)
+        .bind(melt.unit.to_string())
+        .bind(melt.amount as i64)
+        .bind(&melt.token)
+        .bind(unix_time())
+        .execute(&mut tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    pub async fn get_melt_quotes(&self) -> anyhow::Result<Vec<InFlightMelt>> {
+        let rows = sqlx::query(
+            r#"
+SELECT * FROM melt_quotes ORDER BY created_at;
+        "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(InFlightMelt::try_from).collect()
+    }
+
+    /// Id of the pending transaction of `quote_id` while the melt is in
+    /// flight
+    pub async fn get_in_flight_melt(&self, quote_id: &str) -> anyhow::Result<Option<i64>> {
+        let row = sqlx::query(
+            r#"
+SELECT transactions.id FROM melt_quotes
+JOIN transactions ON transactions.reference = melt_quotes.quote_id
+WHERE melt_quotes.quote_id = ? AND transactions.kind = ?
+ORDER BY transactions.id DESC LIMIT 1;
+        "#,
+        )
+        .bind(quote_id)
+        .bind(TransactionKind::Melt.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        Ok(Some(row.try_get("id")?))
+    }
+
+    pub async fn delete_melt_quote(&self, quote_id: &str) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+DELETE FROM melt_quotes WHERE quote_id = ?;
+        "#,
+        )
+        .bind(quote_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Cached mint info and the MOTD the user dismissed for it
+    pub async fn get_mint_info(
+        &self,
+        mint_url: &UncheckedUrl,
+    ) -> anyhow::Result<Option<(MintInfo, Option<String>)>> {
+        let row = sqlx::query(
+            r#"
+SELECT info, dismissed_motd FROM mint_info WHERE mint_url = ?;
+        "#,
+        )
+        .bind(mint_url.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let info: String = row.try_get("info")?;
+
+        Ok(Some((
+            serde_json::from_str(&info)?,
+            row.try_get("dismissed_motd")?,
+        )))
+    }
+
+    pub async fn save_mint_info(
+        &self,
+        mint_url: &UncheckedUrl,
+        info: &MintInfo,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+INSERT INTO mint_info (mint_url, info, updated_at)
+VALUES (?, ?, ?)
+ON CONFLICT(mint_url) DO UPDATE SET info = excluded.info, updated_at = excluded.updated_at;
+        "#,
+        )
+        .bind(mint_url.to_string())
+        .bind(serde_json::to_string(info)?)
+        .bind(unix_time())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn dismiss_motd(&self, mint_url: &UncheckedUrl, motd: &str) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+UPDATE mint_info SET dismissed_motd = ? WHERE mint_url = ?;
+        "#,
+        )
+        .bind(motd)
+        .bind(mint_url.to_string())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn add_nwc_connection(
+        &self,
+        name: &str,
+        relay: &str,
+        service_secret: &str,
+        client_secret: &str,
+        budget: u64,
+    ) -> anyhow::Result<NwcConnection> {
+        let now = unix_time();
+        let result = sqlx::query(
+            r#"
+INSERT INTO nwc_connections
+(name, relay, service_secret, client_secret, budget, period_start, created_at)
+VALUES (?, ?, ?, ?, ?, ?, ?);
+        "#,
+        )
+        .bind(name)
+        .bind(relay)
+        .bind(service_secret)
+        .bind(client_secret)
+        .bind(budget as i64)
+        .bind(now)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        self.get_nwc_connection(result.last_insert_rowid())
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("NWC connection was not saved"))
+    }
+
+    pub async fn get_nwc_connection(&self, id: i64) -> anyhow::Result<Option<NwcConnection>> {
+        let row = sqlx::query(
+            r#"
+SELECT * FROM nwc_connections WHERE id = ?;
+        "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(NwcConnection::try_from).transpose()
+    }
+
+    pub async fn get_nwc_connections(&self) -> anyhow::Result<Vec<NwcConnection>> {
+        let rows = sqlx::query(
+            r#"
+SELECT * FROM nwc_connections ORDER BY created_at;
+        "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(NwcConnection::try_from).collect()
+    }
+
+    /// Count `amount` against the connection's budget, starting a new
+    /// period if the current one is over
+    pub async fn add_nwc_spent(&self, id: i64, amount: u64) -> anyhow::Result<()> {
+        let now = unix_time();
+        sqlx::query(
+            r#"
+UPDATE nwc_connections SET
+    spent = CASE WHEN ? - period_start >= ? THEN ? ELSE spent + ? END,
+    period_start = CASE WHEN ? - period_start >= ? THEN ? ELSE period_start END
+WHERE id = ?;
+        "#,
+        )
+        .bind(now)
+        .bind(NWC_BUDGET_PERIOD)
+        .bind(amount as i64)
+        .bind(amount as i64)
+        .bind(now)
+        .bind(NWC_BUDGET_PERIOD)
+        .bind(now)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_nwc_connection(&self, id: i64) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+DELETE FROM nwc_connections WHERE id = ?;
+        "#,
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn add_failed_operation(
+        &self,
+        operation: &RetryOperation,
+        error: &str,
+        next_attempt: Option<i64>,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+INSERT INTO failed_operations
+(kind, operation, error, next_attempt, created_at)
+VALUES (?, ?, ?, ?, ?);
+        "#,
+        )
+        .bind(operation.to_string())
+        .bind(serde_json::to_string(operation)?)
+        .bind(error)
+        .bind(next_attempt)
+        .bind(unix_time())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Oldest first
+    pub async fn get_failed_operations(&self) -> anyhow::Result<Vec<FailedOperation>> {
+        let rows = sqlx::query(
+            r#"
+SELECT * FROM failed_operations ORDER BY created_at;
+        "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(FailedOperation::try_from).collect()
+    }
+
+    /// Record another failed attempt of operation `id`
+    pub async fn update_failed_operation(
+        &self,
+        id: i64,
+        error: &str,
+        next_attempt: Option<i64>,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+UPDATE failed_operations
+SET error = ?, attempts = attempts + 1, next_attempt = ?
+WHERE id = ?;
+        "#,
+        )
+        .bind(error)
+        .bind(next_attempt)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_failed_operation(&self, id: i64) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+DELETE FROM failed_operations WHERE id = ?;
+        "#,
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn add_scheduled_payment(
+        &self,
+        action: &ScheduledAction,
+        amount: u64,
+        recurrence: Recurrence,
+        next_run: i64,
+    ) -> anyhow::Result<i64> {
+        let result = sqlx::query(
+            r#"
+INSERT INTO scheduled_payments
+(action, amount, recurrence, next_run, created_at)
+VALUES (?, ?, ?, ?, ?);
+        "#,
+        )
+        .bind(serde_json::to_string(action)?)
+        .bind(amount as i64)
+        .bind(recurrence.to_string())
+        .bind(next_run)
+        .bind(unix_time())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Soonest due first
+    pub async fn get_scheduled_payments(&self) -> anyhow::Result<Vec<ScheduledPayment>> {
+        let rows = sqlx::query(
+            r#"
+SELECT * FROM scheduled_payments ORDER BY next_run;
+        "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(ScheduledPayment::try_from).collect()
+    }
+
+    /// Move scheduled payment `id` to `next_run`, recording the error of the
+    /// run that just happened
+    pub async fn reschedule_payment(
+        &self,
+        id: i64,
+        next_run: i64,
+        last_error: Option<&str>,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+UPDATE scheduled_payments SET next_run = ?, last_error = ? WHERE id = ?;
+        "#,
+        )
+        .bind(next_run)
+        .bind(last_error)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_scheduled_payment(&self, id: i64) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+DELETE FROM scheduled_payments WHERE id = ?;
+        "#,
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Keep `preimage` to claim tokens locked to its `hash`
+    pub async fn add_preimage(&self, hash: &str, preimage: &str) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+INSERT OR IGNORE INTO htlc_preimages
+(hash, preimage, created_at)
+VALUES (?, ?, ?);
+        "#,
+        )
+        .bind(hash)
+        .bind(preimage)
+        .bind(unix_time())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Preimages held by the wallet, by the hash they unlock
+    pub async fn get_preimages(&self) -> anyhow::Result<HashMap<String, String>> {
+        let rows = sqlx::query(
+            r#"
+SELECT hash, preimage FROM htlc_preimages;
+        "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| Ok((row.try_get("hash")?, row.try_get("preimage")?)))
+            .collect()
+    }
+
+    /// Log `sale` in the sales ledger, its id left out for the new row's
+    pub async fn add_sale(&self, sale: &Sale) -> anyhow::Result<i64> {
+        let result = sqlx::query(
+            r#"
+INSERT INTO sales
+(amount, tip, fiat_amount, fiat_currency, note, payment_hash, mint_url, created_at)
+VALUES (?, ?, ?, ?, ?, ?, ?, ?);
+        "#,
+        )
+        .bind(sale.amount as i64)
+        .bind(sale.tip as i64)
+        .bind(sale.fiat_amount)
+        .bind(&sale.fiat_currency)
+        .bind(&sale.note)
+        .bind(&sale.payment_hash)
+        .bind(sale.mint_url.to_string())
+        .bind(sale.timestamp)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Oldest first, the order of a ledger
+    pub async fn get_sales(&self) -> anyhow::Result<Vec<Sale>> {
+        let rows = sqlx::query(
+            r#"
+SELECT * FROM sales ORDER BY created_at, id;
+        "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(Sale::try_from).collect()
+    }
+
+    pub async fn count_transactions(&self) -> anyhow::Result<u32> {
+        let row = sqlx::query(
+            r#"
+SELECT COUNT(*) AS count FROM transactions;
+        "#,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        let count: i64 = row.try_get("count")?;
+
+        Ok(count as u32)
+    }
+
+    /// Problems `PRAGMA integrity_check` finds, none if the database is sound
+    pub async fn integrity_check(&self) -> anyhow::Result<Vec<String>> {
+        integrity_problems(&self.pool).await
+    }
+
+    /// Rebuild the database, giving the space of deleted rows back
+    pub async fn vacuum(&self) -> anyhow::Result<()> {
+        vacuum(&self.pool).await
+    }
+
+    /// Delete tokens claimed by their recipient or reclaimed, and mint quotes
+    /// that expired, before `before`, returning how many records went
+    ///
+    /// Transactions are kept as the wallet's history.
+    pub async fn prune(&self, before: i64) -> anyhow::Result<u64> {
+        let tokens = sqlx::query(
+            r#"
+DELETE FROM sent_tokens WHERE status IN (?, ?) AND timestamp < ?;
+        "#,
+        )
+        .bind(SentTokenStatus::Claimed.to_string())
+        .bind(SentTokenStatus::Reclaimed.to_string())
+        .bind(before)
+        .execute(&self.pool)
+        .await?;
+        let quotes = sqlx::query(
+            r#"
+DELETE FROM mint_quotes WHERE expiry < ?;
+        "#,
+        )
+        .bind(before)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(tokens.rows_affected() + quotes.rows_affected())
+    }
+}
+
+/// Problems `PRAGMA integrity_check` finds in the SQLite database at `path`,
+/// such as cdk's store, which is plaintext or keyed through [`key_file`]
+pub async fn check_file_integrity(path: &str) -> anyhow::Result<Vec<String>> {
+    let mut connection = SqliteConnection::connect_with(&connect_options(path, None)?).await?;
+    let problems = integrity_problems(&mut connection).await?;
+    connection.close().await?;
+
+    Ok(problems)
+}
+
+/// Rebuild the SQLite database at `path`, plaintext or keyed through
+/// [`key_file`]
+pub async fn vacuum_file(path: &str) -> anyhow::Result<()> {
+    let mut connection = SqliteConnection::connect_with(&connect_options(path, None)?).await?;
+    vacuum(&mut connection).await?;
+    connection.close().await?;
+
+    Ok(())
+}
+
+/// Encrypt the SQLite database at `path` under `password` if `encrypted`,
+/// converting it first if it was written the other way
+///
+/// Meant for cdk's store, which cdk-sqlite opens without a way to pass a
+/// key: every connection opened on `path` from then on, its pool included,
+/// is keyed as it opens.
+pub async fn key_file(path: &Path, password: &str, encrypted: bool) -> anyhow::Result<()> {
+    let canonical = canonical_path(path);
+    // The conversion has to see the database as it is on disk
+    FILE_KEYS
+        .lock()
+        .expect("File keys lock poisoned")
+        .retain(|(keyed, _)| *keyed != canonical);
+
+    let path_str = path.to_string_lossy();
+    if is_plaintext(&path_str) == Some(encrypted) {
+        let (from, to) = match encrypted {
+            true => (None, Some(password)),
+            false => (Some(password), None),
+        };
+        convert(&path_str, from, to).await?;
+        tracing::info!("Converted {}, encrypted: {}", path_str, encrypted);
+    }
+
+    if encrypted {
+        FILE_KEY_HOOK.call_once(|| unsafe {
+            // SQLite declares entry points without arguments, it passes them
+            // those of `apply_file_key` anyway
+            let entry: unsafe extern "C" fn() = std::mem::transmute(
+                apply_file_key
+                    as unsafe extern "C" fn(
+                        *mut ffi::sqlite3,
+                        *mut *mut c_char,
+                        *const c_void,
+                    ) -> c_int,
+            );
+            ffi::sqlite3_auto_extension(Some(entry));
+        });
+        FILE_KEYS
+            .lock()
+            .expect("File keys lock poisoned")
+            .push((canonical, password.to_string()));
+    }
+
+    Ok(())
+}
+
+/// Run by SQLite for every connection it opens, keying those on a database
+/// passed to [`key_file`]
+unsafe extern "C" fn apply_file_key(
+    db: *mut ffi::sqlite3,
+    _error: *mut *mut c_char,
+    _api: *const c_void,
+) -> c_int {
+    let filename = ffi::sqlite3_db_filename(db, b"main\0".as_ptr() as *const c_char);
+    // In-memory and temporary databases
+    if filename.is_null() || *filename == 0 {
+        return ffi::SQLITE_OK;
+    }
+    let path = canonical_path(Path::new(&*CStr::from_ptr(filename).to_string_lossy()));
+
+    let key = FILE_KEYS.lock().ok().and_then(|keys| {
+        keys.iter()
+            .find(|(keyed, _)| *keyed == path)
+            .map(|(_, key)| key.clone())
+    });
+    let Some(statement) =
+        key.and_then(|key| CString::new(format!("PRAGMA key = {};", quote(&key))).ok())
+    else {
+        return ffi::SQLITE_OK;
+    };
+
+    ffi::sqlite3_exec(
+        db,
+        statement.as_ptr(),
+        None,
+        std::ptr::null_mut(),
+        std::ptr::null_mut(),
+    )
+}
+
+/// `path` with its directory resolved, the file itself may not exist yet
+fn canonical_path(path: &Path) -> PathBuf {
+    match (path.parent().map(fs::canonicalize), path.file_name()) {
+        (Some(Ok(dir)), Some(name)) => dir.join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+async fn integrity_problems<'c, E>(executor: E) -> anyhow::Result<Vec<String>>
+where
+    E: Executor<'c, Database = Sqlite>,
+{
+    let rows = sqlx::query("PRAGMA integrity_check;")
+        .fetch_all(executor)
+        .await?;
+
+    Ok(rows
+        .iter()
+        .filter_map(|row| row.try_get::<String, _>(0).ok())
+        .filter(|result| result != "ok")
+        .collect())
+}
+
+async fn vacuum<'c, E>(executor: E) -> anyhow::Result<()>
+where
+    E: Executor<'c, Database = Sqlite>,
+{
+    // Checkpoint too, or the rebuilt pages sit in the WAL file
+    sqlx::query("VACUUM; PRAGMA wal_checkpoint(TRUNCATE);")
+        .execute(executor)
+        .await?;
+
+    Ok(())
+}
+
+fn connect_options(path: &str, key: Option<&str>) -> anyhow::Result<SqliteConnectOptions> {
+    let options = SqliteConnectOptions::from_str(path)?.create_if_missing(true);
+
+    Ok(match key {
+        // sqlx sends the key before any other pragma, as SQLCipher needs
+        Some(key) => options.pragma("key", quote(key)),
+        None => options,
+    })
+}
+
+/// Whether the database at `path` is plain SQLite, none if there is no
+/// database there yet
+fn is_plaintext(path: &str) -> Option<bool> {
+    let mut header = [0u8; SQLITE_HEADER.len()];
+    File::open(path).ok()?.read_exact(&mut header).ok()?;
+
+    Some(header == *SQLITE_HEADER)
+}
+
+/// Rewrite the database at `path` from under key `from` to under key `to`,
+/// no key meaning plaintext
+///
+/// The original is only replaced once the converted copy is complete on
+/// disk, so a crash leaves one or the other in place.
+async fn convert(path: &str, from: Option<&str>, to: Option<&str>) -> anyhow::Result<()> {
+    let converted = format!("{}.converted", path);
+    for file in [
+        &converted,
+        &format!("{}-wal", converted),
+        &format!("{}-shm", converted),
+    ] {
+        remove_if_exists(file)?;
+    }
+
+    // The attached database only exists on the connection that attached it
+    let mut connection = SqliteConnection::connect_with(&connect_options(path, from)?).await?;
+    // Left in the WAL, writes would be lost with it below, or replayed under
+    // the wrong key onto the converted database
+    sqlx::query("PRAGMA wal_checkpoint(TRUNCATE);")
+        .execute(&mut connection)
+        .await?;
+    sqlx::query(&format!(
+        "ATTACH DATABASE {} AS converted KEY {};",
+        quote(&converted),
+        quote(to.unwrap_or_default())
+    ))
+    .execute(&mut connection)
+    .await?;
+    sqlx::query("SELECT sqlcipher_export('converted');")
+        .execute(&mut connection)
+        .await?;
+    sqlx::query("DETACH DATABASE converted;")
+        .execute(&mut connection)
+        .await?;
+    connection.close().await?;
+
+    File::open(&converted)?.sync_all()?;
+    // Already checkpointed, and belonging to the original key
+    remove_if_exists(&format!("{}-wal", path))?;
+    remove_if_exists(&format!("{}-shm", path))?;
+    fs::rename(&converted, path)?;
+    // The rename itself only survives a crash once the directory is synced
+    #[cfg(unix)]
+    if let Some(dir) = Path::new(path)
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+    {
+        File::open(dir)?.sync_all()?;
+    }
+
+    Ok(())
+}
+
+fn remove_if_exists(path: &str) -> anyhow::Result<()> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+        _ => Ok(()),
+    }
+}
+
+/// `value` as an SQL string literal
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+pub fn unix_time() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Datelike, Timelike};
+
+    use super::*;
+
+    fn local(timestamp: i64) -> DateTime<Local> {
+        Local.timestamp_opt(timestamp, 0).unwrap()
+    }
+
+    #[test]
+    fn recurrence_once_is_not_due_again() {
+        assert_eq!(Recurrence::Once.next(0), None);
+    }
+
+    #[test]
+    fn recurrence_keeps_the_local_time_of_day() {
+        let start = Local.with_ymd_and_hms(2024, 3, 28, 9, 30, 0).unwrap();
+
+        for (recurrence, days) in [(Recurrence::Daily, 1), (Recurrence::Weekly, 7)] {
+            let next = local(recurrence.next(start.timestamp()).unwrap());
+
+            assert_eq!(next.date_naive(), start.date_naive() + Days::new(days));
+            assert_eq!((next.hour(), next.minute()), (9, 30));
+        }
+    }
+
+    #[test]
+    fn recurrence_monthly_keeps_the_day_of_month() {
+        let start = Local.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap();
+        let next = local(Recurrence::Monthly.next(start.timestamp()).unwrap());
+
+        assert_eq!((next.month(), next.day(), next.hour()), (2, 15, 12));
+    }
+
+    #[test]
+    fn recurrence_monthly_clamps_to_the_end_of_short_months() {
+        let start = Local.with_ymd_and_hms(2024, 1, 31, 12, 0, 0).unwrap();
+        let next = local(Recurrence::Monthly.next(start.timestamp()).unwrap());
+
+        assert_eq!((next.month(), next.day()), (2, 29));
+    }
+
+    #[test]
+    fn recurrence_round_trips_through_strings() {
+        for recurrence in Recurrence::ALL {
+            assert_eq!(
+                recurrence.to_string().parse::<Recurrence>().unwrap(),
+                recurrence
+            );
+        }
+        assert!("yearly".parse::<Recurrence>().is_err());
+    }
+}
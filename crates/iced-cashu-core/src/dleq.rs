@@ -0,0 +1,34 @@
+use cdk::nuts::{nut12, Proofs};
+use cdk::wallet::Wallet;
+use cdk::UncheckedUrl;
+
+use crate::db::DleqStatus;
+use crate::error::WalletError;
+
+/// Check the NUT-12 DLEQ proofs of `proofs` against the mint's published keys
+pub async fn verify_proofs(
+    wallet: &Wallet,
+    mint_url: &UncheckedUrl,
+    proofs: &Proofs,
+) -> Result<DleqStatus, WalletError> {
+    if proofs.is_empty() {
+        return Ok(DleqStatus::Unverified);
+    }
+
+    let mut status = DleqStatus::Verified;
+
+    for proof in proofs {
+        let keys = wallet.get_keyset_keys(mint_url, proof.keyset_id).await?;
+        let Some(key) = keys.amount_key(proof.amount) else {
+            return Ok(DleqStatus::Invalid);
+        };
+
+        match proof.verify_dleq(key) {
+            Ok(()) => (),
+            Err(nut12::Error::MissingDleqProof) => status = DleqStatus::Unverified,
+            Err(_) => return Ok(DleqStatus::Invalid),
+        }
+    }
+
+    Ok(status)
+}
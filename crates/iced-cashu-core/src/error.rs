@@ -0,0 +1,173 @@
+use thiserror::Error;
+
+use crate::poll::RateLimited;
+
+/// Parts of the messages of network failures that may pass, reqwest's and
+/// the status reasons of overloaded or restarting mints
+const TRANSIENT_ERRORS: [&str; 7] = [
+    "error sending request",
+    "connection",
+    "timed out",
+    "dns error",
+    "bad gateway",
+    "service unavailable",
+    "gateway timeout",
+];
+
+/// Errors surfaced to the user from wallet tasks
+///
+/// Variants carry the rendered source error so they can be cloned into a
+/// frontend's messages.
+#[derive(Debug, Clone, Error)]
+pub enum WalletError {
+    /// Error returned by the cdk wallet
+    #[error("Wallet error: {0}")]
+    Wallet(String),
+    /// Error reading or writing a database
+    #[error("Database error: {0}")]
+    Database(String),
+    /// Error loading or generating the seed
+    #[error("Seed error: {0}")]
+    Seed(String),
+    /// Amount input could not be parsed
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(String),
+    /// Split setting input could not be parsed
+    #[error("Invalid split setting: {0}")]
+    InvalidSplit(String),
+    /// App lock PIN did not match
+    #[error("Incorrect PIN")]
+    IncorrectPin,
+    /// New app lock PIN is too short
+    #[error("PIN must be at least {0} digits")]
+    PinTooShort(usize),
+    /// Seed backup verification failed
+    #[error("Seed words do not match")]
+    SeedMismatch,
+    /// Camera could not be opened or read
+    #[error("Camera error: {0}")]
+    Camera(String),
+    /// LNURL could not be resolved or returned an error
+    #[error("LNURL error: {0}")]
+    Lnurl(String),
+    /// Invoice is malformed or expired
+    #[error("{0}")]
+    InvalidInvoice(String),
+    /// Input is none of the payment strings the wallet understands
+    #[error("Not a token, invoice, LNURL, lightning address or payment request")]
+    UnrecognizedInput,
+    /// Mint quote expired before its invoice was paid
+    #[error("Quote expired before it was paid")]
+    QuoteExpired,
+    /// Mint did not pay out the invoice
+    #[error("Invoice was not paid")]
+    InvoiceNotPaid,
+    /// BOLT12 offer could not be read, quoted or paid
+    #[error("BOLT12 error: {0}")]
+    Bolt12(String),
+    /// Bitcoin address could not be read or the mint could not pay to it
+    #[error("On-chain error: {0}")]
+    Onchain(String),
+    /// Invoice could not be split over several mints or a part failed
+    #[error("Multi-path payment failed: {0}")]
+    MultiPath(String),
+    /// Backup file could not be written or read
+    #[error("Backup error: {0}")]
+    Backup(String),
+    /// Transaction history could not be exported
+    #[error("Export error: {0}")]
+    Export(String),
+    /// Log files could not be read or exported
+    #[error("Log error: {0}")]
+    Logs(String),
+    /// Token file could not be written, read or holds no token
+    #[error("Token file error: {0}")]
+    TokenFile(String),
+    /// Mint info could not be fetched and none was cached
+    #[error("Could not load mint info: {0}")]
+    MintInfo(String),
+    /// Nostr Wallet Connect service failed
+    #[error("Wallet Connect error: {0}")]
+    Nwc(String),
+    /// Local control API could not be served
+    #[error("Control API error: {0}")]
+    Control(String),
+    /// Nostr key or relay could not be used
+    #[error("Nostr error: {0}")]
+    Nostr(String),
+    /// NUT-18 payment request could not be built or read
+    #[error("Payment request error: {0}")]
+    PaymentRequest(String),
+    /// Contact details could not be validated
+    #[error("Invalid contact: {0}")]
+    InvalidContact(String),
+    /// Proxy address could not be parsed
+    #[error("Invalid proxy: {0}")]
+    InvalidProxy(String),
+    /// Profile name is invalid or already taken
+    #[error("Invalid profile: {0}")]
+    InvalidProfile(String),
+    /// Signing key could not be parsed
+    #[error("Invalid signing key: {0}")]
+    SigningKey(String),
+    /// Token is P2PK locked to keys the wallet does not hold
+    #[error("Token is locked to a key this wallet does not hold: {0}")]
+    MissingSigningKey(String),
+    /// Hash lock or preimage could not be parsed
+    #[error("Invalid hash lock: {0}")]
+    Htlc(String),
+    /// Delay before a locked token can be refunded could not be parsed
+    #[error("Invalid refund delay: {0}")]
+    InvalidRefundDelay(String),
+    /// Token is HTLC locked to hashes the wallet has no preimage of
+    #[error("Token is locked to a hash this wallet has no preimage of: {0}")]
+    MissingPreimage(String),
+    /// Mint answered 429, with the seconds it asked to wait if it did
+    #[error("Mint is rate limiting requests")]
+    RateLimited(Option<u64>),
+    /// Mint can't be removed while it holds this amount
+    #[error("Mint still holds {0}")]
+    MintNotEmpty(String),
+    /// Scheduled payment could not be made
+    #[error("Scheduled payment failed: {0}")]
+    Schedule(String),
+}
+
+impl WalletError {
+    /// Whether the error looks like a network failure worth trying again
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::RateLimited(_) => true,
+            Self::Wallet(message) | Self::Onchain(message) | Self::Bolt12(message) => {
+                let message = message.to_lowercase();
+                TRANSIENT_ERRORS
+                    .iter()
+                    .any(|pattern| message.contains(pattern))
+            }
+            _ => false,
+        }
+    }
+
+    /// `err` of a request to a mint, as [`WalletError::RateLimited`] if the
+    /// mint asked to slow down and as `other` otherwise
+    pub(crate) fn from_request(err: anyhow::Error, other: fn(String) -> WalletError) -> Self {
+        match err.downcast_ref::<RateLimited>() {
+            Some(limited) => {
+                Self::RateLimited(limited.retry_after.map(|retry_after| retry_after.as_secs()))
+            }
+            None => other(err.to_string()),
+        }
+    }
+}
+
+impl From<cdk::wallet::error::Error> for WalletError {
+    fn from(err: cdk::wallet::error::Error) -> Self {
+        Self::Wallet(err.to_string())
+    }
+}
+
+impl From<anyhow::Error> for WalletError {
+    fn from(err: anyhow::Error) -> Self {
+        Self::Database(err.to_string())
+    }
+}
@@ -0,0 +1,226 @@
+use std::fmt;
+
+use serde_json::json;
+
+use crate::db::{Sale, Transaction};
+
+/// File format of a history export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 2] = [ExportFormat::Csv, ExportFormat::Json];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportFormat::Csv => write!(f, "CSV"),
+            ExportFormat::Json => write!(f, "JSON"),
+        }
+    }
+}
+
+const CSV_HEADER: [&str; 10] = [
+    "type",
+    "amount",
+    "unit",
+    "fee",
+    "mint",
+    "timestamp",
+    "status",
+    "reference",
+    "memo",
+    "id",
+];
+
+/// `transactions` as a CSV or JSON document, timestamps in RFC 3339 UTC
+pub fn export_transactions(
+    transactions: &[Transaction],
+    format: ExportFormat,
+) -> anyhow::Result<String> {
+    match format {
+        ExportFormat::Csv => {
+            let mut csv = CSV_HEADER.join(",");
+            csv.push('\n');
+            for transaction in transactions {
+                let fields = [
+                    transaction.kind.to_string(),
+                    transaction.amount.to_string(),
+                    transaction.unit.to_string(),
+                    transaction.fee.to_string(),
+                    transaction.mint_url.to_string(),
+                    rfc3339(transaction.timestamp),
+                    transaction.status.to_string(),
+                    transaction.reference.clone().unwrap_or_default(),
+                    transaction.memo.clone().unwrap_or_default(),
+                    transaction.id.to_string(),
+                ];
+                let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+                csv.push_str(&fields.join(","));
+                csv.push('\n');
+            }
+
+            Ok(csv)
+        }
+        ExportFormat::Json => {
+            let entries: Vec<_> = transactions
+                .iter()
+                .map(|transaction| {
+                    json!({
+                        "type": transaction.kind.to_string(),
+                        "amount": transaction.amount,
+                        "unit": transaction.unit.to_string(),
+                        "fee": transaction.fee,
+                        "mint": transaction.mint_url.to_string(),
+                        "timestamp": rfc3339(transaction.timestamp),
+                        "status": transaction.status.to_string(),
+                        "reference": transaction.reference,
+                        "memo": transaction.memo,
+                        "id": transaction.id,
+                    })
+                })
+                .collect();
+
+            Ok(serde_json::to_string_pretty(&entries)?)
+        }
+    }
+}
+
+const SALES_CSV_HEADER: [&str; 9] = [
+    "id",
+    "timestamp",
+    "amount",
+    "tip",
+    "fiat_amount",
+    "fiat_currency",
+    "note",
+    "payment_hash",
+    "mint",
+];
+
+/// Sales ledger as a CSV document, timestamps in RFC 3339 UTC
+pub fn export_sales(sales: &[Sale]) -> String {
+    let mut csv = SALES_CSV_HEADER.join(",");
+    csv.push('\n');
+    for sale in sales {
+        let fields = [
+            sale.id.to_string(),
+            rfc3339(sale.timestamp),
+            sale.amount.to_string(),
+            sale.tip.to_string(),
+            sale.fiat_amount
+                .map(|amount| format!("{:.2}", amount))
+                .unwrap_or_default(),
+            sale.fiat_currency.clone().unwrap_or_default(),
+            sale.note.clone().unwrap_or_default(),
+            sale.payment_hash.clone(),
+            sale.mint_url.to_string(),
+        ];
+        let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
+
+fn rfc3339(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|time| time.to_rfc3339())
+        .unwrap_or_default()
+}
+
+/// Quote `field` if it holds a separator, quote or line break (RFC 4180)
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cdk::UncheckedUrl;
+
+    use super::*;
+
+    fn sale() -> Sale {
+        Sale {
+            id: 7,
+            amount: 1100,
+            tip: 100,
+            fiat_amount: Some(0.5),
+            fiat_currency: Some("USD".to_string()),
+            note: None,
+            payment_hash: "abc123".to_string(),
+            mint_url: UncheckedUrl::from("https://mint.example.com".to_string()),
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn csv_field_quotes_only_when_needed() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn rfc3339_is_utc() {
+        assert_eq!(rfc3339(0), "1970-01-01T00:00:00+00:00");
+    }
+
+    #[test]
+    fn export_without_transactions() {
+        assert_eq!(
+            export_transactions(&[], ExportFormat::Csv).unwrap(),
+            format!("{}\n", CSV_HEADER.join(","))
+        );
+        assert_eq!(export_transactions(&[], ExportFormat::Json).unwrap(), "[]");
+    }
+
+    #[test]
+    fn export_sales_without_sales_is_header_only() {
+        assert_eq!(
+            export_sales(&[]),
+            format!("{}\n", SALES_CSV_HEADER.join(","))
+        );
+    }
+
+    #[test]
+    fn export_sales_writes_a_row_per_sale() {
+        let mut noted = sale();
+        noted.id = 8;
+        noted.fiat_amount = None;
+        noted.fiat_currency = None;
+        noted.note = Some("Coffee, large".to_string());
+
+        let csv = export_sales(&[sale(), noted]);
+        let lines: Vec<_> = csv.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], SALES_CSV_HEADER.join(","));
+        assert_eq!(
+            lines[1],
+            "7,1970-01-01T00:00:00+00:00,1100,100,0.50,USD,,abc123,https://mint.example.com"
+        );
+        assert_eq!(
+            lines[2],
+            "8,1970-01-01T00:00:00+00:00,1100,100,,,\"Coffee, large\",abc123,https://mint.example.com"
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+
+use cdk::nuts::{KeySetInfo, KeysetResponse, Proofs};
+use cdk::UncheckedUrl;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct KeysetsResponse {
+    keysets: Vec<KeysetInfo>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct KeysetInfo {
+    pub id: String,
+    pub unit: String,
+    pub active: bool,
+    /// Parts per thousand of a sat charged for each input, NUT-02
+    #[serde(default)]
+    pub input_fee_ppk: u64,
+}
+
+/// Keysets the mint lists, active or not
+pub async fn fetch_keysets(mint_url: &UncheckedUrl) -> anyhow::Result<Vec<KeysetInfo>> {
+    let response: KeysetsResponse = get_keysets(mint_url).await?;
+
+    Ok(response.keysets)
+}
+
+/// Keysets the mint lists as cdk stores them
+pub async fn fetch_cdk_keysets(mint_url: &UncheckedUrl) -> anyhow::Result<Vec<KeySetInfo>> {
+    let response: KeysetResponse = get_keysets(mint_url).await?;
+
+    Ok(response.keysets)
+}
+
+async fn get_keysets<T: DeserializeOwned>(mint_url: &UncheckedUrl) -> anyhow::Result<T> {
+    Ok(reqwest::get(format!(
+        "{}/v1/keysets",
+        mint_url.to_string().trim_end_matches('/')
+    ))
+    .await?
+    .error_for_status()?
+    .json()
+    .await?)
+}
+
+/// Input fee of each keyset of the mint, keyed by keyset id
+pub async fn fetch_keyset_fees(mint_url: &UncheckedUrl) -> anyhow::Result<HashMap<String, u64>> {
+    Ok(fetch_keysets(mint_url)
+        .await?
+        .into_iter()
+        .map(|keyset| (keyset.id, keyset.input_fee_ppk))
+        .collect())
+}
+
+/// Fee the mint charges for spending `proofs`, rounded up to whole sats
+pub fn input_fee(proofs: &Proofs, fees: &HashMap<String, u64>) -> u64 {
+    let ppk: u64 = proofs
+        .iter()
+        .map(|proof| {
+            fees.get(&proof.keyset_id.to_string())
+                .copied()
+                .unwrap_or_default()
+        })
+        .sum();
+
+    ppk.div_ceil(1000)
+}
+
+/// Input fee of spending enough of `proofs` to cover `amount` plus that fee
+///
+/// Selects largest proofs first, which is what the wallet does when it has
+/// to swap for change.
+pub fn estimate_input_fee(proofs: &Proofs, amount: u64, fees: &HashMap<String, u64>) -> u64 {
+    let mut proofs = proofs.clone();
+    proofs.sort_by(|a, b| b.amount.cmp(&a.amount));
+
+    let mut selected = Proofs::new();
+    let mut total = 0;
+    for proof in proofs {
+        if total >= amount + input_fee(&selected, fees) {
+            break;
+        }
+        total += u64::from(proof.amount);
+        selected.push(proof);
+    }
+
+    input_fee(&selected, fees)
+}
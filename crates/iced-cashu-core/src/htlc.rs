@@ -0,0 +1,187 @@
+use anyhow::{anyhow, bail};
+use bitcoin::hashes::{sha256, Hash};
+use cdk::nuts::nut10::{Kind, Secret};
+use cdk::nuts::nut11::Conditions;
+use cdk::nuts::{PublicKey, SpendingConditions, Token};
+
+use crate::p2pk;
+
+/// Random preimage for a new hash lock, hex encoded
+pub fn generate_preimage() -> String {
+    hex::encode(rand::random::<[u8; 32]>())
+}
+
+/// Hex SHA-256 hash of hex `preimage`, what NUT-14 locks proofs to
+pub fn hash_lock(preimage: &str) -> anyhow::Result<String> {
+    let bytes = hex::decode(preimage.trim())?;
+
+    Ok(sha256::Hash::hash(&bytes).to_string())
+}
+
+/// Parse a hex encoded 32 byte preimage
+pub fn parse_preimage(input: &str) -> anyhow::Result<String> {
+    let input = input.trim().to_lowercase();
+    let bytes = hex::decode(&input).map_err(|err| anyhow!("Invalid preimage: {}", err))?;
+    if bytes.len() != 32 {
+        bail!("Preimage must be 32 bytes, not {}", bytes.len());
+    }
+
+    Ok(input)
+}
+
+/// Parse a hex encoded SHA-256 hash to lock a token to
+pub fn parse_hash(input: &str) -> anyhow::Result<String> {
+    let input = input.trim().to_lowercase();
+    let bytes = hex::decode(&input).map_err(|err| anyhow!("Invalid hash: {}", err))?;
+    if bytes.len() != 32 {
+        bail!("Hash must be 32 bytes, not {}", bytes.len());
+    }
+
+    Ok(input)
+}
+
+/// Conditions locking proofs to the preimage of `hash`, and to a signature
+/// from `pubkey` as well if given, refundable from a locktime to a refund
+/// key if `refund` is given
+pub fn conditions(
+    hash: &str,
+    pubkey: Option<PublicKey>,
+    refund: Option<(u64, PublicKey)>,
+) -> anyhow::Result<SpendingConditions> {
+    let conditions = match refund {
+        Some((locktime, refund_key)) => p2pk::refund_conditions(locktime, refund_key),
+        None => Conditions::default(),
+    };
+
+    Ok(SpendingConditions::HTLCConditions {
+        data: parse_hash(hash)?.parse()?,
+        conditions: Conditions {
+            pubkeys: pubkey.map(|pubkey| vec![pubkey]),
+            ..conditions
+        },
+    })
+}
+
+/// Hashes the HTLC locked proofs of `token` need the preimages of
+pub fn token_hash_locks(token: &Token) -> Vec<String> {
+    let mut hashes = vec![];
+
+    for proof in token
+        .token
+        .iter()
+        .flat_map(|mint_proofs| &mint_proofs.proofs)
+    {
+        let Ok(secret) = Secret::try_from(&proof.secret) else {
+            continue;
+        };
+        if secret.kind != Kind::HTLC {
+            continue;
+        }
+
+        if let Ok(SpendingConditions::HTLCConditions { data, .. }) =
+            SpendingConditions::try_from(secret)
+        {
+            let hash = data.to_string();
+            if !hashes.contains(&hash) {
+                hashes.push(hash);
+            }
+        }
+    }
+
+    hashes
+}
+
+#[cfg(test)]
+mod tests {
+    use cdk::nuts::{nut10, SecretKey};
+    use serde_json::json;
+
+    use super::*;
+
+    const PREIMAGE: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+    /// SHA-256 of [`PREIMAGE`]'s 32 zero bytes
+    const HASH: &str = "66687aadf862bd776c8fc18b8e9f8e20089714856ee233b3902a591d0d5f2925";
+
+    /// Token of one proof per set of `conditions`
+    fn token(conditions: &[SpendingConditions]) -> Token {
+        let c = SecretKey::generate().public_key().to_hex();
+        let proofs: Vec<_> = conditions
+            .iter()
+            .map(|conditions| {
+                let secret = nut10::Secret::from(conditions.clone());
+                json!({
+                    "amount": 1,
+                    "id": "009a1f293253e41e",
+                    "secret": serde_json::to_string(&secret).unwrap(),
+                    "C": c,
+                })
+            })
+            .collect();
+
+        serde_json::from_value(json!({
+            "token": [{ "mint": "https://mint.example.com", "proofs": proofs }],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn hash_lock_is_sha256_of_preimage_bytes() {
+        assert_eq!(hash_lock(PREIMAGE).unwrap(), HASH);
+        assert!(hash_lock("not hex").is_err());
+    }
+
+    #[test]
+    fn generated_preimage_parses() {
+        let preimage = generate_preimage();
+
+        assert_eq!(parse_preimage(&preimage).unwrap(), preimage);
+        assert!(parse_hash(&hash_lock(&preimage).unwrap()).is_ok());
+    }
+
+    #[test]
+    fn parse_preimage_normalizes_hex() {
+        let input = format!("  {}\n", HASH.to_uppercase());
+
+        assert_eq!(parse_preimage(&input).unwrap(), HASH);
+    }
+
+    #[test]
+    fn parse_preimage_rejects_bad_input() {
+        assert!(parse_preimage("zz").is_err());
+        assert!(parse_preimage(&PREIMAGE[2..]).is_err());
+        assert!(parse_preimage(&format!("{}00", PREIMAGE)).is_err());
+    }
+
+    #[test]
+    fn parse_hash_normalizes_hex() {
+        assert_eq!(
+            parse_hash(&format!(" {} ", HASH.to_uppercase())).unwrap(),
+            HASH
+        );
+    }
+
+    #[test]
+    fn parse_hash_rejects_bad_input() {
+        assert!(parse_hash("").is_err());
+        assert!(parse_hash("xyz").is_err());
+        assert!(parse_hash(&HASH[..62]).is_err());
+    }
+
+    #[test]
+    fn token_hash_locks_lists_each_hash_once() {
+        let conditions = conditions(HASH, None, None).unwrap();
+        let pubkey = SecretKey::generate().public_key();
+        let token = token(&[
+            conditions.clone(),
+            conditions,
+            SpendingConditions::new_p2pk(pubkey, None),
+        ]);
+
+        assert_eq!(token_hash_locks(&token), vec![HASH.to_string()]);
+    }
+
+    #[test]
+    fn conditions_reject_invalid_hash() {
+        assert!(conditions("not a hash", None, None).is_err());
+    }
+}
@@ -0,0 +1,66 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail};
+use cdk::lightning_invoice::Bolt11InvoiceDescription;
+use cdk::Bolt11Invoice;
+
+/// Human readable fields of a bolt11 invoice
+#[derive(Debug, Clone)]
+pub struct InvoiceDetails {
+    /// Sats, `None` for amountless invoices
+    pub amount: Option<u64>,
+    pub description: Option<String>,
+    /// Hex encoded node public key
+    pub payee: String,
+    pub payment_hash: String,
+    /// Unix timestamp in seconds
+    pub expires_at: u64,
+}
+
+pub fn description(invoice: &Bolt11Invoice) -> Option<String> {
+    match invoice.description() {
+        Bolt11InvoiceDescription::Direct(description) => Some(description.to_string()),
+        Bolt11InvoiceDescription::Hash(_) => None,
+    }
+}
+
+/// Decode `bolt11`, rejecting malformed and expired invoices
+pub fn decode_invoice(bolt11: &str) -> anyhow::Result<InvoiceDetails> {
+    let invoice = parse(bolt11)?;
+
+    if invoice.is_expired() {
+        bail!("Invoice has expired");
+    }
+
+    Ok(details(&invoice))
+}
+
+/// Decode `bolt11` for display only, expired invoices included
+pub fn inspect_invoice(bolt11: &str) -> anyhow::Result<InvoiceDetails> {
+    Ok(details(&parse(bolt11)?))
+}
+
+fn parse(bolt11: &str) -> anyhow::Result<Bolt11Invoice> {
+    let bolt11 = bolt11.trim();
+    let bolt11 = bolt11
+        .strip_prefix("lightning:")
+        .or_else(|| bolt11.strip_prefix("LIGHTNING:"))
+        .unwrap_or(bolt11);
+
+    Bolt11Invoice::from_str(bolt11).map_err(|err| anyhow!("Invalid invoice: {}", err))
+}
+
+fn details(invoice: &Bolt11Invoice) -> InvoiceDetails {
+    let payee = invoice
+        .payee_pub_key()
+        .copied()
+        .unwrap_or_else(|| invoice.recover_payee_pub_key());
+
+    InvoiceDetails {
+        amount: invoice.amount_milli_satoshis().map(|msat| msat / 1000),
+        description: description(invoice),
+        payee: payee.to_string(),
+        payment_hash: invoice.payment_hash().to_string(),
+        expires_at: (invoice.duration_since_epoch() + invoice.expiry_time()).as_secs(),
+    }
+}
@@ -0,0 +1,32 @@
+//! Wallet operations shared by the iced-cashu frontends
+//!
+//! [`WalletService`] wraps the cdk wallet and the app database so a frontend
+//! only has to render state and forward user input.
+
+pub mod backup;
+pub mod bbqr;
+pub mod bolt12;
+pub mod config;
+pub mod db;
+pub mod dleq;
+pub mod error;
+pub mod export;
+pub mod fees;
+pub mod htlc;
+pub mod invoice;
+pub mod lnurl;
+mod methods;
+pub mod mint_info;
+pub mod onchain;
+pub mod p2pk;
+pub mod poll;
+pub mod service;
+pub mod stats;
+pub mod token;
+pub mod unit;
+
+pub use error::WalletError;
+pub use service::WalletService;
+
+/// Mint a new wallet starts with when none is configured
+pub const DEFAULT_MINT: &str = "https://mint.thesimplekid.dev";
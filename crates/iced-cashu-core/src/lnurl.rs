@@ -0,0 +1,194 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail};
+use bech32::FromBase32;
+use cdk::Bolt11Invoice;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// LUD-06 pay request
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayRequest {
+    pub callback: String,
+    /// Millisatoshis
+    pub min_sendable: u64,
+    /// Millisatoshis
+    pub max_sendable: u64,
+    /// JSON encoded `[[mime, content], ...]`
+    pub metadata: String,
+    pub tag: String,
+}
+
+impl PayRequest {
+    /// The `text/plain` entry of the metadata
+    pub fn description(&self) -> Option<String> {
+        let metadata: Vec<Vec<String>> = serde_json::from_str(&self.metadata).ok()?;
+
+        metadata
+            .into_iter()
+            .find(|entry| entry.first().map(String::as_str) == Some("text/plain"))
+            .and_then(|entry| entry.get(1).cloned())
+    }
+
+    pub fn min_sats(&self) -> u64 {
+        self.min_sendable.div_ceil(1000)
+    }
+
+    pub fn max_sats(&self) -> u64 {
+        self.max_sendable / 1000
+    }
+}
+
+/// LUD-03 withdraw request
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WithdrawRequest {
+    pub callback: String,
+    pub k1: String,
+    /// Millisatoshis
+    pub min_withdrawable: u64,
+    /// Millisatoshis
+    pub max_withdrawable: u64,
+    #[serde(default)]
+    pub default_description: String,
+    pub tag: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum LnurlRequest {
+    Pay(PayRequest),
+    Withdraw(WithdrawRequest),
+}
+
+impl WithdrawRequest {
+    pub fn max_sats(&self) -> u64 {
+        self.max_withdrawable / 1000
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct InvoiceResponse {
+    pr: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct StatusResponse {
+    status: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorResponse {
+    status: String,
+    reason: String,
+}
+
+/// Whether `input` is a bech32 LNURL or a lightning address
+pub fn is_lnurl(input: &str) -> bool {
+    decode(input).is_ok()
+}
+
+/// Resolve an LNURL or lightning address to the URL it points to
+pub fn decode(input: &str) -> anyhow::Result<String> {
+    let input = input.trim();
+    let input = input
+        .strip_prefix("lightning:")
+        .or_else(|| input.strip_prefix("LIGHTNING:"))
+        .unwrap_or(input);
+
+    if input.to_lowercase().starts_with("lnurl") {
+        let (hrp, data, _) = bech32::decode(input)?;
+        if hrp != "lnurl" {
+            bail!("Unexpected LNURL prefix: {}", hrp);
+        }
+
+        return Ok(String::from_utf8(Vec::<u8>::from_base32(&data)?)?);
+    }
+
+    if let Some((name, domain)) = input.split_once('@') {
+        if !name.is_empty() && domain.contains('.') && !domain.contains('/') {
+            return Ok(format!("https://{}/.well-known/lnurlp/{}", domain, name));
+        }
+    }
+
+    Err(anyhow!("Not an LNURL or lightning address"))
+}
+
+/// Parse a response body, surfacing LNURL `ERROR` responses
+fn parse_response<T: for<'de> Deserialize<'de>>(body: &str) -> anyhow::Result<T> {
+    if let Ok(error) = serde_json::from_str::<ErrorResponse>(body) {
+        if error.status == "ERROR" {
+            bail!(error.reason);
+        }
+    }
+
+    Ok(serde_json::from_str(body)?)
+}
+
+async fn resolve(input: &str) -> anyhow::Result<String> {
+    let url = decode(input)?;
+
+    Ok(reqwest::get(url).await?.text().await?)
+}
+
+/// Resolve an LNURL to the pay or withdraw request behind it
+pub async fn resolve_request(input: &str) -> anyhow::Result<LnurlRequest> {
+    let request: Value = parse_response(&resolve(input).await?)?;
+
+    match request["tag"].as_str() {
+        Some("payRequest") => Ok(LnurlRequest::Pay(serde_json::from_value(request)?)),
+        Some("withdrawRequest") => Ok(LnurlRequest::Withdraw(serde_json::from_value(request)?)),
+        Some(tag) => Err(anyhow!("Unsupported LNURL request {}", tag)),
+        None => Err(anyhow!("LNURL response has no tag")),
+    }
+}
+
+/// Ask the service behind a withdraw request to pay `invoice`
+pub async fn submit_withdraw(request: &WithdrawRequest, invoice: &str) -> anyhow::Result<()> {
+    let separator = if request.callback.contains('?') {
+        '&'
+    } else {
+        '?'
+    };
+    let url = format!(
+        "{}{}k1={}&pr={}",
+        request.callback, separator, request.k1, invoice
+    );
+    let body = reqwest::get(url).await?.text().await?;
+
+    let response: StatusResponse = parse_response(&body)?;
+    if response.status != "OK" {
+        bail!("Withdraw failed with status {}", response.status);
+    }
+
+    Ok(())
+}
+
+/// Request an invoice for `amount` sats from a pay request
+pub async fn fetch_invoice(request: &PayRequest, amount: u64) -> anyhow::Result<String> {
+    let msat = amount * 1000;
+    if msat < request.min_sendable || msat > request.max_sendable {
+        bail!(
+            "Amount must be between {} and {} sats",
+            request.min_sats(),
+            request.max_sats()
+        );
+    }
+
+    let separator = if request.callback.contains('?') {
+        '&'
+    } else {
+        '?'
+    };
+    let url = format!("{}{}amount={}", request.callback, separator, msat);
+    let body = reqwest::get(url).await?.text().await?;
+
+    let response: InvoiceResponse = parse_response(&body)?;
+
+    let invoice = Bolt11Invoice::from_str(&response.pr)?;
+    if invoice.amount_milli_satoshis() != Some(msat) {
+        bail!("Invoice amount does not match the requested amount");
+    }
+
+    Ok(response.pr)
+}
@@ -0,0 +1,210 @@
+use std::time::Duration;
+
+use cdk::nuts::{BlindSignature, BlindedMessage, Proofs};
+use cdk::UncheckedUrl;
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Response, StatusCode};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::poll::RateLimited;
+
+/// Method name of lightning invoices, NUT-04 and NUT-05
+pub(crate) const BOLT11: &str = "bolt11";
+
+/// Answer to a mint quote or a check of one
+#[derive(Debug, Deserialize)]
+pub(crate) struct MintQuoteResponse {
+    pub quote: String,
+    /// Where to pay, a Bitcoin address for on-chain quotes
+    pub request: String,
+    #[serde(default)]
+    pub state: Option<String>,
+    /// Set by mints from before quote states
+    #[serde(default)]
+    paid: Option<bool>,
+    /// Unix timestamp in seconds
+    #[serde(default)]
+    pub expiry: Option<u64>,
+}
+
+impl MintQuoteResponse {
+    pub fn is_paid(&self) -> bool {
+        self.state.as_deref() == Some("PAID") || self.paid == Some(true)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct MintResponse {
+    signatures: Vec<BlindSignature>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct MeltQuoteResponse {
+    pub quote: String,
+    pub amount: u64,
+    pub fee_reserve: u64,
+}
+
+/// Answer to a melt or a melt quote check
+#[derive(Debug, Deserialize)]
+pub(crate) struct MeltResponse {
+    #[serde(default)]
+    pub state: Option<String>,
+    /// Set by mints from before quote states
+    #[serde(default)]
+    paid: Option<bool>,
+    #[serde(default)]
+    pub payment_preimage: Option<String>,
+    /// Transaction of an on-chain melt, once broadcast
+    #[serde(default)]
+    pub txid: Option<String>,
+}
+
+impl MeltResponse {
+    pub fn is_paid(&self) -> bool {
+        self.state.as_deref() == Some("PAID") || self.paid == Some(true)
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.state.as_deref() == Some("PENDING")
+    }
+}
+
+/// Ask `mint_url` for a quote to mint against a payment of `method` (NUT-04)
+pub(crate) async fn mint_quote(
+    mint_url: &UncheckedUrl,
+    method: &str,
+    request: Value,
+) -> anyhow::Result<MintQuoteResponse> {
+    Ok(reqwest::Client::new()
+        .post(format!("{}/v1/mint/quote/{}", base(mint_url), method))
+        .json(&request)
+        .send()
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(check_status)?
+        .json()
+        .await?)
+}
+
+/// Current state of mint quote `quote_id`
+pub(crate) async fn mint_quote_state(
+    mint_url: &UncheckedUrl,
+    method: &str,
+    quote_id: &str,
+) -> anyhow::Result<MintQuoteResponse> {
+    Ok(reqwest::get(format!(
+        "{}/v1/mint/quote/{}/{}",
+        base(mint_url),
+        method,
+        quote_id
+    ))
+    .await
+    .map_err(anyhow::Error::from)
+    .and_then(check_status)?
+    .json()
+    .await?)
+}
+
+/// Have the mint sign `outputs` for paid quote `quote_id`
+pub(crate) async fn mint(
+    mint_url: &UncheckedUrl,
+    method: &str,
+    quote_id: &str,
+    outputs: &[BlindedMessage],
+) -> anyhow::Result<Vec<BlindSignature>> {
+    let response: MintResponse = reqwest::Client::new()
+        .post(format!("{}/v1/mint/{}", base(mint_url), method))
+        .json(&json!({ "quote": quote_id, "outputs": outputs }))
+        .send()
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(check_status)?
+        .json()
+        .await?;
+
+    Ok(response.signatures)
+}
+
+/// Ask `mint_url` to quote paying `request` with `method` (NUT-05)
+pub(crate) async fn melt_quote(
+    mint_url: &UncheckedUrl,
+    method: &str,
+    request: Value,
+) -> anyhow::Result<MeltQuoteResponse> {
+    Ok(reqwest::Client::new()
+        .post(format!("{}/v1/melt/quote/{}", base(mint_url), method))
+        .json(&request)
+        .send()
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(check_status)?
+        .json()
+        .await?)
+}
+
+/// Pay quote `quote_id` with `inputs`, asking for no change
+pub(crate) async fn melt(
+    mint_url: &UncheckedUrl,
+    method: &str,
+    quote_id: &str,
+    inputs: &Proofs,
+) -> anyhow::Result<MeltResponse> {
+    Ok(reqwest::Client::new()
+        .post(format!("{}/v1/melt/{}", base(mint_url), method))
+        .json(&json!({ "quote": quote_id, "inputs": inputs }))
+        .send()
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(check_status)?
+        .json()
+        .await?)
+}
+
+/// Current state of melt quote `quote_id`
+pub(crate) async fn melt_quote_state(
+    mint_url: &UncheckedUrl,
+    method: &str,
+    quote_id: &str,
+) -> anyhow::Result<MeltResponse> {
+    Ok(reqwest::get(format!(
+        "{}/v1/melt/quote/{}/{}",
+        base(mint_url),
+        method,
+        quote_id
+    ))
+    .await
+    .map_err(anyhow::Error::from)
+    .and_then(check_status)?
+    .json()
+    .await?)
+}
+
+/// Fail on error statuses, as [`RateLimited`] when the mint asks to slow down
+fn check_status(response: Response) -> anyhow::Result<Response> {
+    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+        // Either seconds or an HTTP date
+        let retry_after = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| match value.trim().parse::<u64>() {
+                Ok(seconds) => Some(Duration::from_secs(seconds)),
+                Err(_) => chrono::DateTime::parse_from_rfc2822(value)
+                    .ok()
+                    .and_then(|date| {
+                        (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+                            .to_std()
+                            .ok()
+                    }),
+            });
+        return Err(RateLimited { retry_after }.into());
+    }
+
+    Ok(response.error_for_status()?)
+}
+
+fn base(mint_url: &UncheckedUrl) -> String {
+    mint_url.to_string().trim_end_matches('/').to_string()
+}
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS transactions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    mint_url TEXT NOT NULL,
+    status TEXT NOT NULL,
+    reference TEXT,
+    timestamp INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS transactions_timestamp_index ON transactions(timestamp);
+CREATE INDEX IF NOT EXISTS transactions_reference_index ON transactions(reference);
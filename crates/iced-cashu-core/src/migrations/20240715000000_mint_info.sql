@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS mint_info (
+    mint_url TEXT PRIMARY KEY,
+    info TEXT NOT NULL,
+    dismissed_motd TEXT,
+    updated_at INTEGER NOT NULL
+);
@@ -0,0 +1 @@
+ALTER TABLE transactions ADD COLUMN fee INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS nwc_connections (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    relay TEXT NOT NULL,
+    service_secret TEXT NOT NULL,
+    client_secret TEXT NOT NULL,
+    budget INTEGER NOT NULL,
+    spent INTEGER NOT NULL DEFAULT 0,
+    period_start INTEGER NOT NULL,
+    created_at INTEGER NOT NULL
+);
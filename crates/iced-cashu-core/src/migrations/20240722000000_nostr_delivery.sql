@@ -0,0 +1,2 @@
+ALTER TABLE transactions ADD COLUMN nostr_recipient TEXT;
+ALTER TABLE transactions ADD COLUMN delivery TEXT;
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS inbox_tokens (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    event_id TEXT NOT NULL UNIQUE,
+    sender TEXT NOT NULL,
+    token TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    status TEXT NOT NULL,
+    timestamp INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS inbox_tokens_status_index ON inbox_tokens(status);
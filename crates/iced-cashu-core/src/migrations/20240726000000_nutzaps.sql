@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS nutzaps (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    event_id TEXT NOT NULL UNIQUE,
+    sender TEXT NOT NULL,
+    mint_url TEXT NOT NULL,
+    token TEXT NOT NULL,
+    amount INTEGER NOT NULL DEFAULT 0,
+    status TEXT NOT NULL,
+    timestamp INTEGER NOT NULL
+);
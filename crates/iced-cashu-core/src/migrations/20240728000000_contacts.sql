@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS contacts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    lightning_address TEXT,
+    npub TEXT,
+    p2pk_pubkey TEXT
+);
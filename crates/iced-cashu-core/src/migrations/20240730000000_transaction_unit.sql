@@ -0,0 +1 @@
+ALTER TABLE transactions ADD COLUMN unit TEXT NOT NULL DEFAULT 'sat';
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS mint_quotes (
+    quote_id TEXT PRIMARY KEY NOT NULL,
+    mint_url TEXT NOT NULL,
+    request TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    unit TEXT NOT NULL DEFAULT 'sat',
+    expiry INTEGER NOT NULL,
+    onchain INTEGER NOT NULL DEFAULT 0,
+    created_at INTEGER NOT NULL
+);
@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS melt_quotes (
+    quote_id TEXT PRIMARY KEY NOT NULL,
+    mint_url TEXT NOT NULL,
+    method TEXT NOT NULL,
+    unit TEXT NOT NULL DEFAULT 'sat',
+    amount INTEGER NOT NULL,
+    token TEXT,
+    created_at INTEGER NOT NULL
+);
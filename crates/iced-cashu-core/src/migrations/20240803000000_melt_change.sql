@@ -0,0 +1,2 @@
+ALTER TABLE transactions ADD COLUMN fee_reserve INTEGER;
+ALTER TABLE transactions ADD COLUMN change_returned INTEGER;
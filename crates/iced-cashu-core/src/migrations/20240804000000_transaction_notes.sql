@@ -0,0 +1,2 @@
+ALTER TABLE transactions ADD COLUMN note TEXT;
+ALTER TABLE transactions ADD COLUMN tags TEXT NOT NULL DEFAULT '';
@@ -0,0 +1,2 @@
+CREATE INDEX IF NOT EXISTS transactions_mint_url_index ON transactions(mint_url, timestamp);
+CREATE INDEX IF NOT EXISTS transactions_kind_index ON transactions(kind, timestamp);
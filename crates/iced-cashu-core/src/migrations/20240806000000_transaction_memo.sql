@@ -0,0 +1 @@
+ALTER TABLE transactions ADD COLUMN memo TEXT;
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS failed_operations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,
+    -- JSON of the operation to run again
+    operation TEXT NOT NULL,
+    error TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 1,
+    -- Unix timestamp of the next automatic retry, none once given up
+    next_attempt INTEGER,
+    created_at INTEGER NOT NULL
+);
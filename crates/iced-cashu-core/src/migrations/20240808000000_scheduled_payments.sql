@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS scheduled_payments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    -- JSON of what to pay
+    action TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    recurrence TEXT NOT NULL,
+    -- Unix timestamp the payment is next due
+    next_run INTEGER NOT NULL,
+    -- Error of the last run, none if it succeeded
+    last_error TEXT,
+    created_at INTEGER NOT NULL
+);
@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS sales (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    -- Sats paid, tip included
+    amount INTEGER NOT NULL,
+    tip INTEGER NOT NULL,
+    -- Fiat value at the rate of the time of sale, none if there was no rate
+    fiat_amount REAL,
+    fiat_currency TEXT,
+    note TEXT,
+    payment_hash TEXT NOT NULL,
+    mint_url TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
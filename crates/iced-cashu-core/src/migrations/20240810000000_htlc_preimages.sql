@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS htlc_preimages (
+    -- Hex SHA-256 hash tokens are locked to
+    hash TEXT PRIMARY KEY,
+    preimage TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
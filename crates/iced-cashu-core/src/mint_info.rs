@@ -0,0 +1,147 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use cdk::UncheckedUrl;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Answers slower than this mark a mint as slow
+const SLOW_RESPONSE: Duration = Duration::from_secs(3);
+/// Mints that take longer than this to answer count as unreachable
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// How a mint answered its last health check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MintHealth {
+    Online,
+    /// Answered, but slowly
+    Slow,
+    /// Did not answer or answered with an error
+    Unreachable,
+}
+
+/// NUT-06 mint information
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MintInfo {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub pubkey: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub description_long: Option<String>,
+    #[serde(default)]
+    pub contact: Vec<Contact>,
+    #[serde(default)]
+    pub motd: Option<String>,
+    #[serde(default)]
+    pub icon_url: Option<String>,
+    /// Terms of service the mint asks its users to accept
+    #[serde(default)]
+    pub tos_url: Option<String>,
+    /// Settings of each supported NUT, keyed by NUT number
+    #[serde(default)]
+    pub nuts: BTreeMap<String, Value>,
+}
+
+/// Contact method, either `[method, info]` or `{"method", "info"}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Contact {
+    Pair(String, String),
+    Object { method: String, info: String },
+}
+
+impl Contact {
+    pub fn method(&self) -> &str {
+        match self {
+            Contact::Pair(method, _) | Contact::Object { method, .. } => method,
+        }
+    }
+
+    pub fn info(&self) -> &str {
+        match self {
+            Contact::Pair(_, info) | Contact::Object { info, .. } => info,
+        }
+    }
+}
+
+impl MintInfo {
+    /// NUT numbers the mint advertises, in numeric order
+    pub fn supported_nuts(&self) -> Vec<u32> {
+        let mut nuts: Vec<u32> = self
+            .nuts
+            .iter()
+            .filter(|(_, settings)| settings["disabled"] != true)
+            .filter_map(|(nut, _)| nut.parse().ok())
+            .collect();
+        nuts.sort_unstable();
+
+        nuts
+    }
+
+    /// Whether the mint issues ecash for payments of `method` (NUT-04)
+    pub fn supports_mint_method(&self, method: &str) -> bool {
+        self.nuts
+            .get("4")
+            .and_then(|settings| settings["methods"].as_array())
+            .is_some_and(|methods| methods.iter().any(|settings| settings["method"] == method))
+    }
+
+    /// Whether the mint pays requests of `method`, e.g. `bolt12` (NUT-05)
+    pub fn supports_melt_method(&self, method: &str) -> bool {
+        self.nuts
+            .get("5")
+            .and_then(|settings| settings["methods"].as_array())
+            .is_some_and(|methods| methods.iter().any(|settings| settings["method"] == method))
+    }
+}
+
+pub async fn fetch_info(mint_url: &UncheckedUrl) -> anyhow::Result<MintInfo> {
+    let info = reqwest::get(format!(
+        "{}/v1/info",
+        mint_url.to_string().trim_end_matches('/')
+    ))
+    .await?
+    .error_for_status()?
+    .json()
+    .await?;
+
+    Ok(info)
+}
+
+/// Ask `mint_url` for its info, rating how it answered
+pub async fn check_health(mint_url: &UncheckedUrl) -> MintHealth {
+    let start = Instant::now();
+    let response = reqwest::Client::new()
+        .get(format!(
+            "{}/v1/info",
+            mint_url.to_string().trim_end_matches('/')
+        ))
+        .timeout(HEALTH_TIMEOUT)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status);
+
+    match response {
+        Err(err) => {
+            tracing::debug!("Health check of {} failed: {}", mint_url, err);
+            MintHealth::Unreachable
+        }
+        Ok(_) if start.elapsed() > SLOW_RESPONSE => MintHealth::Slow,
+        Ok(_) => MintHealth::Online,
+    }
+}
+
+pub async fn fetch_icon(icon_url: &str) -> anyhow::Result<Vec<u8>> {
+    let bytes = reqwest::get(icon_url)
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+
+    Ok(bytes.to_vec())
+}
@@ -0,0 +1,65 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use bitcoin::address::NetworkUnchecked;
+use bitcoin::Address;
+use cdk::nuts::CurrencyUnit;
+use cdk::UncheckedUrl;
+use serde_json::json;
+
+use crate::methods::{self, MeltQuoteResponse, MintQuoteResponse};
+
+/// NUT-05 method name mints list when they can pay to Bitcoin addresses
+pub const METHOD: &str = "onchain";
+
+/// `address` trimmed, if it is a well formed Bitcoin address
+///
+/// The network isn't checked, test mints pay to testnet and signet
+/// addresses.
+pub fn validate_address(address: &str) -> anyhow::Result<String> {
+    let address = address.trim();
+    let address = address
+        .strip_prefix("bitcoin:")
+        .or_else(|| address.strip_prefix("BITCOIN:"))
+        .unwrap_or(address);
+    // Drop BIP21 parameters such as the amount
+    let address = address.split('?').next().unwrap_or_default();
+
+    Address::<NetworkUnchecked>::from_str(address)
+        .map_err(|err| anyhow!("Invalid Bitcoin address: {}", err))?;
+
+    Ok(address.to_string())
+}
+
+/// BIP21 URI asking wallets to pay `amount` sats to `address`
+pub fn payment_uri(address: &str, amount: u64) -> String {
+    format!(
+        "bitcoin:{}?amount={}.{:08}",
+        address,
+        amount / 100_000_000,
+        amount % 100_000_000
+    )
+}
+
+/// Ask `mint_url` for an address to deposit `amount` of `unit` to
+pub(crate) async fn mint_quote(
+    mint_url: &UncheckedUrl,
+    amount: u64,
+    unit: &CurrencyUnit,
+) -> anyhow::Result<MintQuoteResponse> {
+    let request = json!({ "amount": amount, "unit": unit.to_string() });
+
+    methods::mint_quote(mint_url, METHOD, request).await
+}
+
+/// Ask `mint_url` to quote paying `amount` of `unit` to `address`
+pub(crate) async fn melt_quote(
+    mint_url: &UncheckedUrl,
+    address: &str,
+    amount: u64,
+    unit: &CurrencyUnit,
+) -> anyhow::Result<MeltQuoteResponse> {
+    let request = json!({ "request": address, "amount": amount, "unit": unit.to_string() });
+
+    methods::melt_quote(mint_url, METHOD, request).await
+}
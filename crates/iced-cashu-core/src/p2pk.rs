@@ -0,0 +1,264 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail};
+use bech32::FromBase32;
+use bitcoin::bip32::{DerivationPath, ExtendedPrivKey};
+use bitcoin::secp256k1::Secp256k1;
+use bitcoin::Network;
+use cdk::nuts::nut10::{Kind, Secret};
+use cdk::nuts::nut11::Conditions;
+use cdk::nuts::{PublicKey, SecretKey, SpendingConditions, Token};
+
+/// Derivation path of the wallet's P2PK signing key
+const P2PK_DERIVATION_PATH: &str = "m/129372'/10'/0'";
+
+/// Signing key for P2PK locks, derived from the wallet seed
+pub fn derive_signing_key(seed: &[u8]) -> anyhow::Result<SecretKey> {
+    let secp = Secp256k1::new();
+    let xpriv = ExtendedPrivKey::new_master(Network::Bitcoin, seed)?
+        .derive_priv(&secp, &DerivationPath::from_str(P2PK_DERIVATION_PATH)?)?;
+
+    Ok(SecretKey::from_slice(&xpriv.private_key.secret_bytes())?)
+}
+
+/// Parse a hex encoded secret key or a nostr `nsec`
+pub fn parse_secret_key(input: &str) -> anyhow::Result<SecretKey> {
+    let input = input.trim();
+
+    if input.starts_with("nsec") {
+        let (hrp, data, _) = bech32::decode(input)?;
+        if hrp != "nsec" {
+            bail!("Unexpected key prefix: {}", hrp);
+        }
+
+        return Ok(SecretKey::from_slice(&Vec::<u8>::from_base32(&data)?)?);
+    }
+
+    SecretKey::from_hex(input).map_err(|err| anyhow!("Invalid secret key: {}", err))
+}
+
+/// Conditions handing locked proofs back to `refund_key` from `locktime` on
+pub fn refund_conditions(locktime: u64, refund_key: PublicKey) -> Conditions {
+    Conditions {
+        locktime: Some(locktime),
+        refund_keys: Some(vec![refund_key]),
+        ..Default::default()
+    }
+}
+
+/// Conditions locking proofs to `pubkey`, refundable from a locktime to a
+/// refund key if `refund` is given
+pub fn conditions(pubkey: PublicKey, refund: Option<(u64, PublicKey)>) -> SpendingConditions {
+    SpendingConditions::new_p2pk(
+        pubkey,
+        refund.map(|(locktime, refund_key)| refund_conditions(locktime, refund_key)),
+    )
+}
+
+/// Unix time from which the holder of `refund_key` can take back every
+/// proof of `token`, 0 if it can right away and none if some proofs only
+/// the recipient can ever claim
+pub fn reclaimable_at(token: &Token, refund_key: &PublicKey) -> Option<u64> {
+    let mut at = 0;
+
+    for proof in token
+        .token
+        .iter()
+        .flat_map(|mint_proofs| &mint_proofs.proofs)
+    {
+        let Ok(secret) = Secret::try_from(&proof.secret) else {
+            continue;
+        };
+        let conditions = match SpendingConditions::try_from(secret) {
+            Ok(SpendingConditions::P2PKConditions { data, conditions }) => {
+                let signers = conditions.pubkeys.as_deref().unwrap_or_default();
+                if data == *refund_key || signers.contains(refund_key) {
+                    continue;
+                }
+                conditions
+            }
+            Ok(SpendingConditions::HTLCConditions { conditions, .. }) => conditions,
+            Err(_) => return None,
+        };
+
+        let locktime = conditions.locktime?;
+        // Without refund keys anyone can spend the proofs after the locktime
+        if conditions
+            .refund_keys
+            .is_some_and(|refund_keys| !refund_keys.contains(refund_key))
+        {
+            return None;
+        }
+        at = at.max(locktime);
+    }
+
+    Some(at)
+}
+
+/// Sign the locked proofs of `token` that list `key` as a refund key,
+/// returning whether there were any
+pub fn sign_refunds(token: &mut Token, key: &SecretKey) -> anyhow::Result<bool> {
+    let pubkey = key.public_key();
+    let mut signed = false;
+
+    for proof in token
+        .token
+        .iter_mut()
+        .flat_map(|mint_proofs| &mut mint_proofs.proofs)
+    {
+        let Ok(secret) = Secret::try_from(&proof.secret) else {
+            continue;
+        };
+        let refundable = match SpendingConditions::try_from(secret) {
+            Ok(
+                SpendingConditions::P2PKConditions { conditions, .. }
+                | SpendingConditions::HTLCConditions { conditions, .. },
+            ) => conditions
+                .refund_keys
+                .is_some_and(|refund_keys| refund_keys.contains(&pubkey)),
+            Err(_) => false,
+        };
+
+        if refundable {
+            proof.sign_p2pk(key.clone())?;
+            signed = true;
+        }
+    }
+
+    Ok(signed)
+}
+
+/// Public keys any of which can sign for the P2PK locked proofs of `token`
+pub fn token_locks(token: &Token) -> Vec<PublicKey> {
+    let mut pubkeys = vec![];
+
+    for proof in token
+        .token
+        .iter()
+        .flat_map(|mint_proofs| &mint_proofs.proofs)
+    {
+        let Ok(secret) = Secret::try_from(&proof.secret) else {
+            continue;
+        };
+        if secret.kind != Kind::P2PK {
+            continue;
+        }
+
+        if let Ok(SpendingConditions::P2PKConditions { data, conditions }) =
+            SpendingConditions::try_from(secret)
+        {
+            for pubkey in std::iter::once(data).chain(conditions.pubkeys.unwrap_or_default()) {
+                if !pubkeys.contains(&pubkey) {
+                    pubkeys.push(pubkey);
+                }
+            }
+        }
+    }
+
+    pubkeys
+}
+
+#[cfg(test)]
+mod tests {
+    use bech32::{ToBase32, Variant};
+    use cdk::nuts::nut10;
+    use serde_json::json;
+
+    use super::*;
+
+    /// Token of one proof per set of `conditions`
+    fn token(conditions: &[SpendingConditions]) -> Token {
+        let c = SecretKey::generate().public_key().to_hex();
+        let proofs: Vec<_> = conditions
+            .iter()
+            .map(|conditions| {
+                let secret = nut10::Secret::from(conditions.clone());
+                json!({
+                    "amount": 1,
+                    "id": "009a1f293253e41e",
+                    "secret": serde_json::to_string(&secret).unwrap(),
+                    "C": c,
+                })
+            })
+            .collect();
+
+        serde_json::from_value(json!({
+            "token": [{ "mint": "https://mint.example.com", "proofs": proofs }],
+        }))
+        .unwrap()
+    }
+
+    fn pubkey() -> PublicKey {
+        SecretKey::generate().public_key()
+    }
+
+    #[test]
+    fn reclaimable_at_refund_locktime() {
+        let (recipient, refund_key) = (pubkey(), pubkey());
+        let token = token(&[conditions(recipient, Some((100, refund_key)))]);
+
+        assert_eq!(reclaimable_at(&token, &refund_key), Some(100));
+    }
+
+    #[test]
+    fn reclaimable_at_latest_locktime() {
+        let (recipient, refund_key) = (pubkey(), pubkey());
+        let token = token(&[
+            conditions(recipient, Some((300, refund_key))),
+            conditions(recipient, Some((100, refund_key))),
+        ]);
+
+        assert_eq!(reclaimable_at(&token, &refund_key), Some(300));
+    }
+
+    #[test]
+    fn reclaimable_right_away_when_locked_to_own_key() {
+        let key = pubkey();
+        let token = token(&[conditions(key, None)]);
+
+        assert_eq!(reclaimable_at(&token, &key), Some(0));
+    }
+
+    #[test]
+    fn not_reclaimable_without_locktime() {
+        let refund_key = pubkey();
+        let token = token(&[
+            conditions(pubkey(), Some((100, refund_key))),
+            conditions(pubkey(), None),
+        ]);
+
+        assert_eq!(reclaimable_at(&token, &refund_key), None);
+    }
+
+    #[test]
+    fn not_reclaimable_when_refunds_go_to_another_key() {
+        let token = token(&[conditions(pubkey(), Some((100, pubkey())))]);
+
+        assert_eq!(reclaimable_at(&token, &pubkey()), None);
+    }
+
+    #[test]
+    fn reclaimable_by_anyone_after_locktime_without_refund_keys() {
+        let conditions = SpendingConditions::new_p2pk(
+            pubkey(),
+            Some(Conditions {
+                locktime: Some(100),
+                ..Default::default()
+            }),
+        );
+        let token = token(&[conditions]);
+
+        assert_eq!(reclaimable_at(&token, &pubkey()), Some(100));
+    }
+
+    #[test]
+    fn parse_secret_key_hex_and_nsec() {
+        let key = SecretKey::generate();
+        let hex = key.to_secret_hex();
+        let nsec = bech32::encode("nsec", key.secret_bytes().to_base32(), Variant::Bech32).unwrap();
+
+        assert_eq!(parse_secret_key(&hex).unwrap().to_secret_hex(), hex);
+        assert_eq!(parse_secret_key(&nsec).unwrap().to_secret_hex(), hex);
+        assert!(parse_secret_key("nsec1invalid").is_err());
+    }
+}
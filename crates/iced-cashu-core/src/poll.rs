@@ -0,0 +1,144 @@
+use std::time::Duration;
+
+use rand::Rng;
+use thiserror::Error;
+
+/// Most a delay is made longer or shorter at random, as a fraction
+const JITTER: f64 = 0.2;
+/// Wait before the first retry of a failed operation
+const RETRY_BASE: Duration = Duration::from_secs(30);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60 * 60);
+
+/// Delays between checks of a quote, doubling from `base` up to `max`
+///
+/// Delays are jittered so wallets started together don't check in step.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    base: Duration,
+    max: Duration,
+    attempt: u32,
+}
+
+impl Backoff {
+    pub fn new(base: Duration, max: Duration) -> Self {
+        Self {
+            base,
+            max: max.max(base),
+            attempt: 0,
+        }
+    }
+
+    /// Delay before the next check
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = backoff(self.base, self.max, self.attempt);
+        if self.base.saturating_mul(2u32.saturating_pow(self.attempt)) < self.max {
+            self.attempt += 1;
+        }
+
+        delay
+    }
+
+    /// Delay before the next check after the mint asked to wait
+    /// `retry_after`, never shorter than the backoff
+    pub fn retry_after(&mut self, retry_after: Option<Duration>) -> Duration {
+        let delay = self.next_delay();
+        retry_after.map_or(delay, |retry_after| retry_after.max(delay))
+    }
+
+    /// Start over from `base`, for a new quote
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+}
+
+/// Delay before retrying an operation that failed `attempts` times
+pub fn retry_delay(attempts: u32) -> Duration {
+    backoff(RETRY_BASE, MAX_RETRY_DELAY, attempts.saturating_sub(1))
+}
+
+/// `base` doubled `attempt` times, jittered and capped at `max`
+fn backoff(base: Duration, max: Duration, attempt: u32) -> Duration {
+    let delay = base.saturating_mul(2u32.saturating_pow(attempt)).min(max);
+    let jitter = rand::thread_rng().gen_range(1.0 - JITTER..=1.0 + JITTER);
+
+    delay.mul_f64(jitter).min(max)
+}
+
+/// Mint answered 429 Too Many Requests
+#[derive(Debug, Clone, Error)]
+#[error("Mint is rate limiting requests")]
+pub struct RateLimited {
+    /// Wait the mint asked for in `Retry-After`
+    pub retry_after: Option<Duration>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Whether `delay` is `expected` give or take the jitter
+    fn jittered(delay: Duration, expected: Duration) -> bool {
+        delay >= expected.mul_f64(1.0 - JITTER) && delay <= expected.mul_f64(1.0 + JITTER)
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_max() {
+        let mut backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(8));
+
+        for expected in [1, 2, 4] {
+            let delay = backoff.next_delay();
+            assert!(
+                jittered(delay, Duration::from_secs(expected)),
+                "{:?}",
+                delay
+            );
+        }
+        for _ in 0..5 {
+            let delay = backoff.next_delay();
+            assert!(jittered(delay, Duration::from_secs(8)), "{:?}", delay);
+            assert!(delay <= Duration::from_secs(8), "{:?}", delay);
+        }
+    }
+
+    #[test]
+    fn backoff_reset_starts_over() {
+        let mut backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(60));
+        for _ in 0..4 {
+            backoff.next_delay();
+        }
+        backoff.reset();
+
+        assert!(jittered(backoff.next_delay(), Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn backoff_max_is_never_below_base() {
+        let mut backoff = Backoff::new(Duration::from_secs(10), Duration::from_secs(1));
+
+        assert!(jittered(backoff.next_delay(), Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn retry_after_waits_at_least_as_asked() {
+        let mut backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(8));
+
+        assert_eq!(
+            backoff.retry_after(Some(Duration::from_secs(30))),
+            Duration::from_secs(30)
+        );
+        // A shorter wait than the backoff keeps the backoff
+        assert!(jittered(
+            backoff.retry_after(Some(Duration::ZERO)),
+            Duration::from_secs(2)
+        ));
+        assert!(jittered(backoff.retry_after(None), Duration::from_secs(4)));
+    }
+
+    #[test]
+    fn retry_delay_grows_and_is_capped() {
+        assert!(jittered(retry_delay(0), RETRY_BASE));
+        assert!(jittered(retry_delay(1), RETRY_BASE));
+        assert!(jittered(retry_delay(2), RETRY_BASE * 2));
+        assert!(retry_delay(u32::MAX) <= MAX_RETRY_DELAY);
+    }
+}
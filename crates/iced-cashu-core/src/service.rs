@@ -0,0 +1,2306 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use bip39::Mnemonic;
+use cdk::amount::{Amount, SplitTarget};
+use cdk::cdk_database::{self, WalletDatabase, WalletMemoryDatabase};
+use cdk::dhke::construct_proofs;
+use cdk::nuts::{
+    CurrencyUnit, Id, PreMintSecrets, Proofs, PublicKey, SecretKey, SpendingConditions, State,
+    Token,
+};
+use cdk::wallet::Wallet;
+use cdk::{Bolt11Invoice, UncheckedUrl};
+use cdk_redb::WalletRedbDatabase;
+use cdk_sqlite::WalletSQLiteDatabase;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::backup::{self, Backup, MintBackup};
+use crate::config::{database_encrypted, get_seed, storage_backend, wallet_dir, StorageBackend};
+use crate::db::{
+    self, unix_time, Database, DleqStatus, FailedOperation, InFlightMelt, RetryOperation,
+    ScheduledAction, ScheduledPayment, SentToken, SentTokenStatus, TransactionKind,
+    TransactionStatus,
+};
+use crate::error::WalletError;
+use crate::lnurl::{self, LnurlRequest, PayRequest};
+use crate::methods::{self, MeltResponse};
+use crate::{
+    bolt12, dleq, fees, invoice, mint_info, onchain, p2pk, poll, token, unit, DEFAULT_MINT,
+};
+
+/// Multi-path payments
+const MPP_NUT: u32 = 15;
+/// Failed operations are left for the user to retry after this many tries
+const MAX_RETRY_ATTEMPTS: u32 = 10;
+/// Settled sent tokens and expired quotes are pruned after 90 days
+const PRUNE_AFTER: i64 = 90 * 24 * 60 * 60;
+/// Most of a received amount that may go to fees when moving it to the
+/// default mint automatically
+const AUTO_SWAP_MAX_FEE_PERCENT: u64 = 5;
+/// Smallest excess over the threshold the auto-withdraw rule pays out, below
+/// it fees take too large a share
+pub const AUTO_WITHDRAW_MIN_SATS: u64 = 1_000;
+
+/// Stores opened by [`open_localstore`] that have to be shared, redb files
+/// can only be opened once per process and a new memory store is empty
+static SHARED_STORES: Mutex<Vec<(PathBuf, StorageBackend, LocalStore)>> = Mutex::new(Vec::new());
+
+/// cdk's wallet store, whichever [`StorageBackend`] it is kept in
+pub type LocalStore = Arc<dyn WalletDatabase<Err = cdk_database::Error> + Send + Sync>;
+
+/// Mint quote waiting for its invoice to be paid
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingMint {
+    pub quote_id: String,
+    pub mint_url: UncheckedUrl,
+    pub request: String,
+    pub amount: u64,
+    pub unit: CurrencyUnit,
+    /// Unix timestamp in seconds
+    pub expiry: u64,
+    /// `request` is a Bitcoin address to deposit to rather than an invoice
+    pub onchain: bool,
+}
+
+/// Kind of payment request a melt quote pays
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MeltMethod {
+    #[default]
+    Bolt11,
+    /// BOLT12 offer, paid without cdk (NUT-25)
+    Bolt12,
+    /// Bitcoin address, paid without cdk
+    Onchain,
+}
+
+impl MeltMethod {
+    /// Name of the method in the mint's API (NUT-05)
+    pub fn name(&self) -> &'static str {
+        match self {
+            MeltMethod::Bolt11 => methods::BOLT11,
+            MeltMethod::Bolt12 => bolt12::METHOD,
+            MeltMethod::Onchain => onchain::METHOD,
+        }
+    }
+}
+
+/// Melt quote shown for confirmation before paying
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeltPreview {
+    pub quote_id: String,
+    pub mint_url: UncheckedUrl,
+    pub method: MeltMethod,
+    /// Amount and fees are in this unit
+    pub unit: CurrencyUnit,
+    pub amount: u64,
+    pub fee_reserve: u64,
+    /// Estimated fee for the proofs spent on the melt
+    pub input_fee: u64,
+    pub description: Option<String>,
+}
+
+impl MeltPreview {
+    /// Most that can leave the wallet
+    pub fn total(&self) -> u64 {
+        self.amount + self.fee_reserve + self.input_fee
+    }
+}
+
+/// On-chain melt the mint may broadcast some time after it was paid
+#[derive(Debug, Clone)]
+pub struct Withdrawal {
+    pub quote_id: String,
+    pub mint_url: UncheckedUrl,
+    pub amount: u64,
+    /// Quote state the mint last reported
+    pub state: String,
+    pub txid: Option<String>,
+}
+
+impl Withdrawal {
+    pub fn is_broadcast(&self) -> bool {
+        self.state == "PAID" || self.txid.is_some()
+    }
+
+    /// The mint gave up on the withdrawal
+    pub fn is_failed(&self) -> bool {
+        self.state == "UNPAID"
+    }
+}
+
+/// Stored proofs of a mint grouped by keyset and denomination
+#[derive(Debug, Clone)]
+pub struct ProofSummary {
+    pub mint_url: UncheckedUrl,
+    /// Proof count keyed by keyset id and amount
+    pub denominations: BTreeMap<(String, u64), usize>,
+    pub count: usize,
+    /// Input fee of swapping every proof at once
+    pub consolidation_fee: u64,
+}
+
+/// Sats found in each state by a proof state check
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProofStates {
+    /// Spent elsewhere and removed from the wallet
+    pub spent: u64,
+    /// Reserved by an in-flight payment at the mint
+    pub pending: u64,
+}
+
+/// Token a scheduled payment created for a contact, left for the frontend
+/// to DM
+#[derive(Debug, Clone)]
+pub struct ScheduledToken {
+    pub transaction_id: i64,
+    pub contact: String,
+    pub npub: String,
+    pub token: String,
+}
+
+/// The cdk wallet of the current profile together with the app database
+///
+/// Operations record what they did in the transaction history, so every
+/// frontend shows the same history.
+#[derive(Debug, Clone)]
+pub struct WalletService {
+    wallet: Arc<Wallet>,
+    db: Database,
+    /// Public key of the P2PK signing key derived from the seed
+    p2pk: PublicKey,
+    /// The signing key itself, for refunds of tokens it locked
+    signing_key: SecretKey,
+}
+
+impl WalletService {
+    /// Open the wallet of the current profile with `seed` and its BIP39
+    /// `passphrase`, empty for none
+    ///
+    /// `start_mint` is added if the wallet doesn't know it yet, a wallet
+    /// without any mints starts with [`DEFAULT_MINT`]. `password` keys the
+    /// app database and cdk's sqlite store when [`database_encrypted`] is
+    /// set.
+    #[instrument(skip_all, err)]
+    pub async fn open(
+        seed: &Mnemonic,
+        passphrase: &str,
+        start_mint: Option<UncheckedUrl>,
+        password: &str,
+    ) -> Result<Self, WalletError> {
+        if let Some(db_path) = sqlite_store_path() {
+            db::key_file(&db_path, password, database_encrypted()).await?;
+        }
+        let localstore = open_localstore().await?;
+
+        let seed = seed.to_seed_normalized(passphrase);
+        let signing_key =
+            p2pk::derive_signing_key(&seed).map_err(|err| WalletError::Seed(err.to_string()))?;
+        let p2pk = signing_key.public_key();
+
+        let wallet = Wallet::new(localstore, &seed, vec![signing_key.clone()]);
+
+        let balances = wallet.mint_balances().await?;
+        match start_mint {
+            Some(mint_url) if !balances.contains_key(&mint_url) => {
+                wallet.add_mint(mint_url).await?;
+            }
+            None if balances.is_empty() => {
+                wallet.add_mint(UncheckedUrl::from(DEFAULT_MINT)).await?;
+            }
+            _ => (),
+        }
+
+        let app_db_path = wallet_dir().join("./app.sqlite");
+        let db = Database::new(
+            &app_db_path.to_string_lossy(),
+            password,
+            database_encrypted(),
+        )
+        .await?;
+
+        Ok(Self {
+            wallet: Arc::new(wallet),
+            db,
+            p2pk,
+            signing_key,
+        })
+    }
+
+    pub fn wallet(&self) -> &Arc<Wallet> {
+        &self.wallet
+    }
+
+    pub fn db(&self) -> &Database {
+        &self.db
+    }
+
+    pub fn p2pk_pubkey(&self) -> PublicKey {
+        self.p2pk
+    }
+
+    /// Recover the proofs of every known mint from the seed
+    #[instrument(skip_all, err)]
+    pub async fn restore(&self) -> Result<u64, WalletError> {
+        let mut total = 0;
+        for mint_url in self.wallet.mint_balances().await?.into_keys() {
+            let amount = self.wallet.restore(mint_url.clone()).await?;
+            tracing::info!("Restored {} sats from {}", u64::from(amount), mint_url);
+
+            if amount > Amount::ZERO {
+                self.db
+                    .add_transaction(
+                        TransactionKind::Receive,
+                        amount.into(),
+                        &CurrencyUnit::Sat,
+                        &mint_url,
+                        TransactionStatus::Complete,
+                        None,
+                    )
+                    .await?;
+            }
+            total += u64::from(amount);
+        }
+
+        Ok(total)
+    }
+
+    #[instrument(skip_all, fields(mint = %mint_url), err)]
+    pub async fn add_mint(&self, mint_url: UncheckedUrl) -> Result<UncheckedUrl, WalletError> {
+        self.wallet.add_mint(mint_url.clone()).await?;
+
+        Ok(mint_url)
+    }
+
+    /// Forget `mint_url`, refusing while it holds more than `forfeit` sats or
+    /// any amount of another unit
+    ///
+    /// Funds are moved away with [`Self::sweep`] or exported as a token
+    /// first, which can leave fee change too small to move, the amount to
+    /// give up as `forfeit`.
+    #[instrument(skip_all, fields(mint = %mint_url, forfeit), err)]
+    pub async fn remove_mint(
+        &self,
+        mint_url: UncheckedUrl,
+        forfeit: u64,
+    ) -> Result<UncheckedUrl, WalletError> {
+        let units = self
+            .wallet
+            .mint_balances()
+            .await?
+            .remove(&mint_url)
+            .unwrap_or_default();
+        let mut balance = 0;
+        for (currency, amount) in units {
+            let amount = u64::from(amount);
+            if currency == CurrencyUnit::Sat {
+                balance = amount;
+            } else if amount > 0 {
+                // Only sats are moved away before removal
+                let held = unit::format_amount(amount, &currency);
+                return Err(WalletError::MintNotEmpty(held));
+            }
+        }
+        if balance > forfeit {
+            return Err(WalletError::MintNotEmpty(unit::format_amount(
+                balance,
+                &CurrencyUnit::Sat,
+            )));
+        }
+
+        open_localstore()
+            .await?
+            .remove_mint(mint_url.clone())
+            .await
+            .map_err(|err| WalletError::Database(err.to_string()))?;
+        if balance > 0 {
+            tracing::warn!("Gave up {} sats left at {}", balance, mint_url);
+        }
+
+        Ok(mint_url)
+    }
+
+    /// Sat balance across all mints
+    pub async fn check_balance(&self) -> Result<u64, WalletError> {
+        let amount = self.wallet.unit_balance(CurrencyUnit::Sat).await?;
+
+        Ok(amount.into())
+    }
+
+    pub async fn mint_balances(&self) -> Result<HashMap<UncheckedUrl, u64>, WalletError> {
+        let balances = self.wallet.mint_balances().await?;
+
+        Ok(balances
+            .into_iter()
+            .map(|(mint_url, units)| {
+                let amount = units.get(&CurrencyUnit::Sat).copied().unwrap_or_default();
+                (mint_url, amount.into())
+            })
+            .collect())
+    }
+
+    /// Sat balance of a single mint
+    pub async fn mint_balance(&self, mint_url: &UncheckedUrl) -> Result<u64, WalletError> {
+        Ok(self
+            .mint_balances()
+            .await?
+            .get(mint_url)
+            .copied()
+            .unwrap_or_default())
+    }
+
+    /// Mint holding the most sats, with its balance
+    async fn largest_balance(&self) -> Result<Option<(UncheckedUrl, u64)>, WalletError> {
+        Ok(self
+            .mint_balances()
+            .await?
+            .into_iter()
+            .max_by_key(|(_, balance)| *balance))
+    }
+
+    /// Balance in each unit across all mints
+    pub async fn unit_balances(&self) -> Result<HashMap<CurrencyUnit, u64>, WalletError> {
+        let mut totals = HashMap::new();
+        for units in self.wallet.mint_balances().await?.into_values() {
+            for (unit, amount) in units {
+                *totals.entry(unit).or_default() += u64::from(amount);
+            }
+        }
+
+        Ok(totals)
+    }
+
+    /// Units `mint_url` issues, sats first
+    pub async fn mint_units(
+        &self,
+        mint_url: UncheckedUrl,
+    ) -> Result<Vec<CurrencyUnit>, WalletError> {
+        unit::fetch_units(&mint_url)
+            .await
+            .map_err(|err| WalletError::Wallet(err.to_string()))
+    }
+
+    /// Balance of a single mint in `unit`
+    async fn unit_balance(
+        &self,
+        mint_url: &UncheckedUrl,
+        unit: &CurrencyUnit,
+    ) -> Result<u64, WalletError> {
+        Ok(self
+            .wallet
+            .mint_balances()
+            .await?
+            .get(mint_url)
+            .and_then(|units| units.get(unit))
+            .copied()
+            .map(u64::from)
+            .unwrap_or_default())
+    }
+
+    #[instrument(skip_all, fields(mint = %mint_url, amount), err)]
+    pub async fn mint_quote(
+        &self,
+        mint_url: UncheckedUrl,
+        amount: u64,
+        unit: CurrencyUnit,
+    ) -> Result<PendingMint, WalletError> {
+        let quote = self
+            .wallet
+            .mint_quote(mint_url.clone(), unit.clone(), Amount::from(amount))
+            .await?;
+        tracing::info!("Created mint quote {}", quote.id);
+
+        self.db
+            .add_transaction(
+                TransactionKind::Mint,
+                amount,
+                &unit,
+                &mint_url,
+                TransactionStatus::Pending,
+                Some(&quote.id),
+            )
+            .await?;
+
+        let pending = PendingMint {
+            quote_id: quote.id,
+            mint_url,
+            request: quote.request,
+            amount,
+            unit,
+            expiry: quote.expiry,
+            onchain: false,
+        };
+        self.db.add_mint_quote(&pending).await?;
+
+        Ok(pending)
+    }
+
+    /// Ask `mint_url` for an address to deposit `amount` to on-chain
+    #[instrument(skip_all, fields(mint = %mint_url, amount), err)]
+    pub async fn deposit_quote(
+        &self,
+        mint_url: UncheckedUrl,
+        amount: u64,
+        unit: CurrencyUnit,
+    ) -> Result<PendingMint, WalletError> {
+        let onchain_error = |err: anyhow::Error| WalletError::Onchain(err.to_string());
+
+        let info = mint_info::fetch_info(&mint_url)
+            .await
+            .map_err(onchain_error)?;
+        if !info.supports_mint_method(onchain::METHOD) {
+            return Err(WalletError::Onchain(format!(
+                "{} doesn't take on-chain deposits",
+                mint_url
+            )));
+        }
+
+        let quote = onchain::mint_quote(&mint_url, amount, &unit)
+            .await
+            .map_err(onchain_error)?;
+        onchain::validate_address(&quote.request).map_err(onchain_error)?;
+        tracing::info!("Created deposit quote {}", quote.quote);
+
+        self.db
+            .add_transaction(
+                TransactionKind::Mint,
+                amount,
+                &unit,
+                &mint_url,
+                TransactionStatus::Pending,
+                Some(&quote.quote),
+            )
+            .await?;
+
+        let pending = PendingMint {
+            quote_id: quote.quote,
+            mint_url,
+            request: quote.request,
+            amount,
+            unit,
+            // Deposits can take hours to confirm, mints often don't expire them
+            expiry: quote.expiry.unwrap_or(u64::MAX),
+            onchain: true,
+        };
+        self.db.add_mint_quote(&pending).await?;
+
+        Ok(pending)
+    }
+
+    /// Put `operation` in the retry queue if it failed with a transient
+    /// `error`, returning whether it was queued
+    pub async fn queue_retry(
+        &self,
+        operation: RetryOperation,
+        error: &WalletError,
+    ) -> Result<bool, WalletError> {
+        if !error.is_transient() {
+            return Ok(false);
+        }
+        if let RetryOperation::Melt { preview } = &operation {
+            // Settled by `recover_melts` once the mint knows its outcome
+            if self
+                .db
+                .get_in_flight_melt(&preview.quote_id)
+                .await?
+                .is_some()
+            {
+                return Ok(false);
+            }
+        }
+        tracing::info!("Queueing {} to retry after: {}", operation, error);
+        self.db
+            .add_failed_operation(&operation, &error.to_string(), Some(next_retry(1)))
+            .await?;
+
+        Ok(true)
+    }
+
+    /// Operations in the retry queue, oldest first
+    pub async fn failed_operations(&self) -> Result<Vec<FailedOperation>, WalletError> {
+        Ok(self.db.get_failed_operations().await?)
+    }
+
+    /// Run `failed` again, returning the amount received, minted or paid
+    ///
+    /// It leaves the queue once it succeeds, and stops being retried on its
+    /// own after errors that are not transient or too many attempts.
+    #[instrument(skip_all, fields(id = failed.id, operation = %failed.operation), err)]
+    pub async fn retry(
+        &self,
+        failed: FailedOperation,
+        split: SplitTarget,
+    ) -> Result<u64, WalletError> {
+        let result = match failed.operation {
+            RetryOperation::Receive { token } => self.receive(token).await,
+            RetryOperation::Mint { pending } if pending.onchain => {
+                self.mint_deposit(pending, split).await
+            }
+            RetryOperation::Mint { pending } => {
+                self.mint(pending.mint_url, pending.quote_id, split).await
+            }
+            RetryOperation::Melt { preview } => {
+                let amount = preview.amount;
+                // Resumed rather than recorded a second time, so
+                // `recover_melts` only ever sees one row for the quote
+                match self.db.get_in_flight_melt(&preview.quote_id).await? {
+                    Some(id) => self.finish_melt(preview, id).await,
+                    None => self.pay_invoice(preview).await,
+                }
+                .map(|_| amount)
+            }
+        };
+
+        match &result {
+            Ok(_) => self.db.delete_failed_operation(failed.id).await?,
+            Err(err) => {
+                let attempts = failed.attempts + 1;
+                let next_attempt = (err.is_transient() && attempts < MAX_RETRY_ATTEMPTS)
+                    .then(|| next_retry(attempts));
+                self.db
+                    .update_failed_operation(failed.id, &err.to_string(), next_attempt)
+                    .await?;
+            }
+        }
+
+        result
+    }
+
+    pub async fn discard_failed_operation(&self, id: i64) -> Result<(), WalletError> {
+        Ok(self.db.delete_failed_operation(id).await?)
+    }
+
+    /// Mint quotes not minted yet, newest first, including those of earlier
+    /// runs
+    pub async fn mint_quotes(&self) -> Result<Vec<PendingMint>, WalletError> {
+        Ok(self.db.get_mint_quotes().await?)
+    }
+
+    /// Mint `pending` if it was paid in the meantime, returning the amount
+    /// minted
+    ///
+    /// Quotes that expired unpaid are dropped and their transaction marked
+    /// failed.
+    #[instrument(skip_all, fields(mint = %pending.mint_url, quote = %pending.quote_id), err)]
+    pub async fn resume_mint_quote(
+        &self,
+        pending: PendingMint,
+        split: SplitTarget,
+    ) -> Result<Option<u64>, WalletError> {
+        let (mint_url, quote_id) = (pending.mint_url.clone(), pending.quote_id.clone());
+        let (_, paid) = if pending.onchain {
+            self.check_deposit(mint_url, quote_id).await?
+        } else {
+            self.check_mint_quote(mint_url, quote_id).await?
+        };
+
+        if paid {
+            let amount = if pending.onchain {
+                self.mint_deposit(pending, split).await?
+            } else {
+                self.mint(pending.mint_url, pending.quote_id, split).await?
+            };
+            return Ok(Some(amount));
+        }
+
+        if pending.expiry <= unix_time() as u64 {
+            tracing::info!("Mint quote {} expired unpaid", pending.quote_id);
+            self.db.delete_mint_quote(&pending.quote_id).await?;
+            self.db
+                .update_transaction_status(&pending.quote_id, TransactionStatus::Failed)
+                .await?;
+        }
+
+        Ok(None)
+    }
+
+    /// Whether the invoice of quote `quote_id` was paid
+    #[instrument(level = "debug", skip_all, fields(quote = %quote_id), err)]
+    pub async fn check_mint_quote(
+        &self,
+        mint_url: UncheckedUrl,
+        quote_id: String,
+    ) -> Result<(String, bool), WalletError> {
+        // Asked directly so a rate limit's `Retry-After` can be read, cdk
+        // only records the quote once it is paid
+        let paid = methods::mint_quote_state(&mint_url, methods::BOLT11, &quote_id)
+            .await
+            .map_err(|err| WalletError::from_request(err, WalletError::Wallet))?
+            .is_paid();
+        if paid {
+            self.wallet.mint_quote_status(mint_url, &quote_id).await?;
+        }
+
+        Ok((quote_id, paid))
+    }
+
+    /// Whether the deposit of quote `quote_id` confirmed
+    #[instrument(level = "debug", skip_all, fields(quote = %quote_id), err)]
+    pub async fn check_deposit(
+        &self,
+        mint_url: UncheckedUrl,
+        quote_id: String,
+    ) -> Result<(String, bool), WalletError> {
+        let paid = methods::mint_quote_state(&mint_url, onchain::METHOD, &quote_id)
+            .await
+            .map_err(|err| WalletError::from_request(err, WalletError::Onchain))?
+            .is_paid();
+
+        Ok((quote_id, paid))
+    }
+
+    /// Mint the proofs of a confirmed deposit, returning the amount minted
+    ///
+    /// cdk only mints bolt11 quotes, so the outputs are blinded here with
+    /// random secrets. The proofs are swapped into the wallet right away so
+    /// the seed covers them.
+    #[instrument(skip_all, fields(mint = %pending.mint_url, quote = %pending.quote_id), err)]
+    pub async fn mint_deposit(
+        &self,
+        pending: PendingMint,
+        split: SplitTarget,
+    ) -> Result<u64, WalletError> {
+        let onchain_error = |err: anyhow::Error| WalletError::Onchain(err.to_string());
+        let mint_url = pending.mint_url;
+
+        let keyset = fees::fetch_keysets(&mint_url)
+            .await
+            .map_err(onchain_error)?
+            .into_iter()
+            .find(|keyset| keyset.active && keyset.unit == pending.unit.to_string())
+            .ok_or_else(|| {
+                WalletError::Onchain(format!(
+                    "{} has no active {} keyset",
+                    mint_url, pending.unit
+                ))
+            })?;
+        let keyset_id =
+            Id::from_str(&keyset.id).map_err(|err| WalletError::Onchain(err.to_string()))?;
+        let keys = self.wallet.get_keyset_keys(&mint_url, keyset_id).await?;
+
+        let outputs = PreMintSecrets::random(keyset_id, Amount::from(pending.amount), &split)
+            .map_err(|err| WalletError::Onchain(err.to_string()))?;
+        let signatures = methods::mint(
+            &mint_url,
+            onchain::METHOD,
+            &pending.quote_id,
+            &outputs.blinded_messages(),
+        )
+        .await
+        .map_err(onchain_error)?;
+        let proofs = construct_proofs(signatures, outputs.rs(), outputs.secrets(), &keys)
+            .map_err(|err| WalletError::Onchain(err.to_string()))?;
+
+        // The minted proofs are in no store yet, a failed check must not
+        // drop them
+        let dleq = match dleq::verify_proofs(&self.wallet, &mint_url, &proofs).await {
+            Ok(dleq) => dleq,
+            Err(err) => {
+                tracing::warn!("Could not verify DLEQ proofs of deposit: {}", err);
+                DleqStatus::Unverified
+            }
+        };
+        let token = Token::new(mint_url.clone(), proofs, None, Some(pending.unit.clone()))
+            .map_err(|err| WalletError::Onchain(err.to_string()))?
+            .to_string();
+
+        let amount = self.receive_held(&token, pending.amount, &mint_url).await?;
+        tracing::info!(
+            "Minted {} from a deposit",
+            unit::format_amount(amount, &pending.unit)
+        );
+
+        self.db
+            .update_transaction_status(&pending.quote_id, TransactionStatus::Complete)
+            .await?;
+        self.db
+            .set_reference_dleq_status(&pending.quote_id, dleq)
+            .await?;
+        self.db.delete_mint_quote(&pending.quote_id).await?;
+
+        Ok(amount)
+    }
+
+    /// Mint the proofs of a paid quote, returning the amount minted
+    #[instrument(skip_all, fields(mint = %mint_url, quote = %quote_id), err)]
+    pub async fn mint(
+        &self,
+        mint_url: UncheckedUrl,
+        quote_id: String,
+        split: SplitTarget,
+    ) -> Result<u64, WalletError> {
+        let existing: HashSet<_> = self
+            .wallet
+            .get_proofs(mint_url.clone())
+            .await?
+            .unwrap_or_default()
+            .into_iter()
+            .map(|proof| proof.secret)
+            .collect();
+
+        let amount = self
+            .wallet
+            .mint(mint_url.clone(), &quote_id, split, None)
+            .await?;
+        tracing::info!("Minted {} sats", u64::from(amount));
+
+        self.db
+            .update_transaction_status(&quote_id, TransactionStatus::Complete)
+            .await?;
+
+        let minted: Vec<_> = self
+            .wallet
+            .get_proofs(mint_url.clone())
+            .await?
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|proof| !existing.contains(&proof.secret))
+            .collect();
+        // The proofs are already stored, a failed check must not fail the mint
+        let dleq = match dleq::verify_proofs(&self.wallet, &mint_url, &minted).await {
+            Ok(dleq) => dleq,
+            Err(err) => {
+                tracing::warn!("Could not verify DLEQ proofs of minted quote: {}", err);
+                DleqStatus::Unverified
+            }
+        };
+        self.db.set_reference_dleq_status(&quote_id, dleq).await?;
+        self.db.delete_mint_quote(&quote_id).await?;
+
+        Ok(amount.into())
+    }
+
+    /// Claim `token`, returning the amount received
+    pub async fn receive(&self, token: String) -> Result<u64, WalletError> {
+        self.receive_with_preimages(token, vec![]).await
+    }
+
+    /// Claim `token` with `preimages` for its HTLC locked proofs, returning
+    /// the amount received
+    #[instrument(skip_all, err)]
+    pub async fn receive_with_preimages(
+        &self,
+        token: String,
+        preimages: Vec<String>,
+    ) -> Result<u64, WalletError> {
+        let parsed = token::parse(&token).ok();
+        // cdk only understands V3 tokens
+        let token = parsed.as_ref().map(Token::to_string).unwrap_or(token);
+        let mint_url = parsed
+            .as_ref()
+            .and_then(|token| token.token.first().map(|proofs| proofs.mint.clone()))
+            .unwrap_or_default();
+        let unit = parsed.as_ref().map(token::unit).unwrap_or_default();
+
+        // Check the sender's proofs before they are swapped away
+        let mut dleq = DleqStatus::Unverified;
+        for (index, mint_proofs) in parsed.iter().flat_map(|token| &token.token).enumerate() {
+            let status =
+                dleq::verify_proofs(&self.wallet, &mint_proofs.mint, &mint_proofs.proofs).await?;
+            if index == 0 || status != DleqStatus::Verified {
+                dleq = status;
+            }
+            if status == DleqStatus::Invalid {
+                break;
+            }
+        }
+
+        let preimages = (!preimages.is_empty()).then_some(preimages);
+        let amount = self
+            .wallet
+            .receive(&token, &SplitTarget::default(), preimages)
+            .await?;
+        tracing::info!(
+            "Received {} from {}",
+            unit::format_amount(amount.into(), &unit),
+            mint_url
+        );
+
+        // The proofs are in the balance, failing now would only hide it
+        let memo = parsed.as_ref().and_then(|token| token.memo.as_deref());
+        if let Err(err) = self
+            .db
+            .record_receive(amount.into(), &unit, &mint_url, dleq, memo)
+            .await
+        {
+            tracing::warn!("Could not record receive: {}", err);
+        }
+
+        Ok(amount.into())
+    }
+
+    /// Whether the mint of `token` can't be asked for the state of its
+    /// proofs, so a failed claim may still succeed later
+    pub async fn mint_unreachable(&self, token: &str) -> bool {
+        let Ok(token) = token::parse(token) else {
+            return false;
+        };
+
+        for mint_proofs in token.token {
+            if self
+                .wallet
+                .check_proofs_spent(mint_proofs.mint, mint_proofs.proofs)
+                .await
+                .is_err()
+            {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Input fee for spending `amount` from the mint's stored proofs
+    pub async fn estimate_input_fee(
+        &self,
+        mint_url: &UncheckedUrl,
+        amount: u64,
+    ) -> Result<u64, WalletError> {
+        let fees = fees::fetch_keyset_fees(mint_url)
+            .await
+            .map_err(|err| WalletError::Wallet(err.to_string()))?;
+        let proofs = self
+            .wallet
+            .get_proofs(mint_url.clone())
+            .await?
+            .unwrap_or_default();
+
+        Ok(fees::estimate_input_fee(&proofs, amount, &fees))
+    }
+
+    /// Most that can be sent from `mint_url` once the fee for spending every
+    /// proof is taken off
+    pub async fn max_sendable(&self, mint_url: UncheckedUrl) -> Result<u64, WalletError> {
+        let fees = fees::fetch_keyset_fees(&mint_url)
+            .await
+            .map_err(|err| WalletError::Wallet(err.to_string()))?;
+        let proofs = self.wallet.get_proofs(mint_url).await?.unwrap_or_default();
+        let total: u64 = proofs.iter().map(|proof| u64::from(proof.amount)).sum();
+
+        Ok(total.saturating_sub(fees::input_fee(&proofs, &fees)))
+    }
+
+    /// Create a token for `amount` with an optional `memo` to the recipient,
+    /// returning its send transaction and the encoded token
+    #[instrument(skip_all, fields(mint = %mint_url, amount), err)]
+    pub async fn create_token(
+        &self,
+        mint_url: UncheckedUrl,
+        amount: u64,
+        unit: CurrencyUnit,
+        split: SplitTarget,
+        conditions: Option<SpendingConditions>,
+        memo: Option<String>,
+    ) -> Result<(i64, String), WalletError> {
+        let balance = self.unit_balance(&mint_url, &unit).await?;
+
+        let token = self
+            .wallet
+            .send(
+                &mint_url,
+                unit.clone(),
+                Amount::from(amount),
+                memo.clone(),
+                conditions,
+                &split,
+            )
+            .await?;
+
+        // The proofs are out of the balance now, nothing may fail before the
+        // token is saved
+        let spent = match self.unit_balance(&mint_url, &unit).await {
+            Ok(remaining) => balance.saturating_sub(remaining),
+            Err(_) => amount,
+        };
+        tracing::info!("Created token, spent {}", unit::format_amount(spent, &unit));
+
+        match self
+            .db
+            .record_sent_token(
+                &token,
+                amount,
+                spent.saturating_sub(amount),
+                &unit,
+                &mint_url,
+                memo.as_deref(),
+            )
+            .await
+        {
+            Ok(id) => Ok((id, token)),
+            Err(err) => {
+                self.take_back(&token, amount, &mint_url).await;
+                Err(err.into())
+            }
+        }
+    }
+
+    /// Claim back a sent token the recipient has not redeemed, through the
+    /// refund path of proofs locked to them
+    #[instrument(skip_all, fields(id = sent.id), err)]
+    pub async fn reclaim(&self, sent: SentToken) -> Result<u64, WalletError> {
+        let mut encoded = sent.token.clone();
+        let mut unit = CurrencyUnit::default();
+        if let Ok(mut token) = token::parse(&sent.token) {
+            unit = token::unit(&token);
+            if p2pk::sign_refunds(&mut token, &self.signing_key)
+                .map_err(|err| WalletError::SigningKey(err.to_string()))?
+            {
+                encoded = token.to_string();
+            }
+        }
+        let amount = self
+            .wallet
+            .receive(&encoded, &SplitTarget::default(), None)
+            .await?;
+
+        // The proofs are back in the balance, failing now would only hide it
+        if let Err(err) = self
+            .db
+            .record_reclaim(sent.id, amount.into(), &unit, &sent.mint_url)
+            .await
+        {
+            tracing::warn!("Could not record reclaiming token {}: {}", sent.id, err);
+        }
+
+        Ok(amount.into())
+    }
+
+    /// Quote paying `bolt11` with the mint's `unit` balance
+    #[instrument(skip_all, fields(mint = %mint_url, unit = %unit), err)]
+    pub async fn melt_quote(
+        &self,
+        mint_url: UncheckedUrl,
+        bolt11: String,
+        unit: CurrencyUnit,
+    ) -> Result<MeltPreview, WalletError> {
+        self.quote_melt(mint_url, bolt11, unit, None).await
+    }
+
+    /// Quote paying `amount` of `bolt11` from `mint_url`, the rest being paid
+    /// from other mints (NUT-15)
+    #[instrument(skip_all, fields(mint = %mint_url, amount), err)]
+    pub async fn partial_melt_quote(
+        &self,
+        mint_url: UncheckedUrl,
+        bolt11: String,
+        amount: u64,
+    ) -> Result<MeltPreview, WalletError> {
+        self.quote_melt(mint_url, bolt11, CurrencyUnit::Sat, Some(amount))
+            .await
+    }
+
+    /// Quotes splitting `bolt11` over several mints, for invoices no single
+    /// mint's balance covers
+    ///
+    /// Mints supporting multi-path payments are used largest balance first,
+    /// each part leaving room in its mint's balance for the fees.
+    #[instrument(skip_all, err)]
+    pub async fn multi_path_quotes(&self, bolt11: String) -> Result<Vec<MeltPreview>, WalletError> {
+        let amount = Bolt11Invoice::from_str(&bolt11)
+            .ok()
+            .and_then(|invoice| invoice.amount_milli_satoshis())
+            .ok_or_else(|| WalletError::InvalidInvoice("Invoice has no amount".to_string()))?
+            / 1000;
+
+        let mut balances: Vec<(UncheckedUrl, u64)> = self
+            .mint_balances()
+            .await?
+            .into_iter()
+            .filter(|(_, balance)| *balance > 0)
+            .collect();
+        balances.sort_by_key(|(_, balance)| std::cmp::Reverse(*balance));
+
+        let mut legs = vec![];
+        let mut remaining = amount;
+        for (mint_url, balance) in balances {
+            if remaining == 0 {
+                break;
+            }
+            let supported = mint_info::fetch_info(&mint_url)
+                .await
+                .is_ok_and(|info| info.supported_nuts().contains(&MPP_NUT));
+            if !supported {
+                continue;
+            }
+
+            match self
+                .balance_covered_part(&mint_url, &bolt11, remaining, balance)
+                .await
+            {
+                Ok(Some(leg)) => {
+                    remaining = remaining.saturating_sub(leg.amount);
+                    legs.push(leg);
+                }
+                Ok(None) => (),
+                Err(err) => tracing::warn!("Could not quote part at {}: {}", mint_url, err),
+            }
+        }
+
+        if remaining > 0 {
+            return Err(WalletError::MultiPath(format!(
+                "Mints supporting multi-path payments are {} sats short",
+                remaining
+            )));
+        }
+
+        Ok(legs)
+    }
+
+    /// Pay the parts of a multi-path payment together, returning the
+    /// payment preimage if a mint reports one
+    ///
+    /// The receiver only settles once every part has arrived, so when a part
+    /// fails none of them are paid. Their transactions are then marked failed
+    /// and the proofs stay in the wallet.
+    #[instrument(skip_all, fields(parts = legs.len()), err)]
+    pub async fn pay_multi_path(
+        &self,
+        legs: Vec<MeltPreview>,
+    ) -> Result<Option<String>, WalletError> {
+        let results =
+            futures::future::join_all(legs.iter().cloned().map(|leg| self.pay_invoice(leg))).await;
+
+        let mut paid = None;
+        let mut error = None;
+        for (leg, result) in legs.iter().zip(results) {
+            match result {
+                Ok(preimage) => paid = Some(paid.flatten().or(preimage)),
+                Err(err) => {
+                    tracing::warn!("Part paid from {} failed: {}", leg.mint_url, err);
+                    error = Some(err);
+                }
+            }
+        }
+
+        match (paid, error) {
+            (Some(preimage), None) => Ok(preimage),
+            (Some(preimage), Some(_)) => {
+                // Invoice settled, the failed part is left pending to be
+                // checked with its mint
+                tracing::warn!("Invoice paid but a part failed");
+                Ok(preimage)
+            }
+            (None, error) => {
+                for leg in &legs {
+                    self.db
+                        .update_transaction_status(&leg.quote_id, TransactionStatus::Failed)
+                        .await?;
+                }
+                Err(WalletError::MultiPath(
+                    error.map(|err| err.to_string()).unwrap_or_default(),
+                ))
+            }
+        }
+    }
+
+    /// Part of `bolt11` up to `remaining` sats that `mint_url` can pay
+    /// including fees, if any
+    async fn balance_covered_part(
+        &self,
+        mint_url: &UncheckedUrl,
+        bolt11: &str,
+        remaining: u64,
+        balance: u64,
+    ) -> Result<Option<MeltPreview>, WalletError> {
+        let leg = self
+            .partial_melt_quote(mint_url.clone(), bolt11.to_string(), remaining.min(balance))
+            .await?;
+        if leg.total() <= balance {
+            return Ok(Some(leg));
+        }
+
+        // Quote again for less, by what the fees went over
+        let part = (leg.amount + balance).saturating_sub(leg.total());
+        if part == 0 {
+            return Ok(None);
+        }
+        let leg = self
+            .partial_melt_quote(mint_url.clone(), bolt11.to_string(), part)
+            .await?;
+
+        Ok((leg.total() <= balance).then_some(leg))
+    }
+
+    async fn quote_melt(
+        &self,
+        mint_url: UncheckedUrl,
+        bolt11: String,
+        unit: CurrencyUnit,
+        part: Option<u64>,
+    ) -> Result<MeltPreview, WalletError> {
+        let description = Bolt11Invoice::from_str(&bolt11)
+            .ok()
+            .and_then(|invoice| invoice::description(&invoice));
+
+        let quote = self
+            .wallet
+            .melt_quote(
+                mint_url.clone(),
+                unit.clone(),
+                bolt11,
+                // NUT-15 part amounts are in millisats
+                part.map(|amount| Amount::from(amount * 1000)),
+            )
+            .await?;
+        let input_fee = self
+            .estimate_input_fee(&mint_url, u64::from(quote.amount + quote.fee_reserve))
+            .await?;
+
+        Ok(MeltPreview {
+            quote_id: quote.id,
+            mint_url,
+            method: MeltMethod::Bolt11,
+            unit,
+            amount: quote.amount.into(),
+            fee_reserve: quote.fee_reserve.into(),
+            input_fee,
+            description,
+        })
+    }
+
+    /// Pay a melt quote, returning the payment preimage if the mint reports
+    /// one
+    #[instrument(skip_all, fields(mint = %preview.mint_url, quote = %preview.quote_id), err)]
+    pub async fn pay_invoice(&self, preview: MeltPreview) -> Result<Option<String>, WalletError> {
+        match preview.method {
+            MeltMethod::Bolt11 => (),
+            MeltMethod::Bolt12 => {
+                let response = self.melt_external(&preview).await?;
+                if response.is_pending() {
+                    return Err(WalletError::Bolt12("Payment is still pending".to_string()));
+                }
+                return Ok(response.payment_preimage);
+            }
+            MeltMethod::Onchain => return Ok(self.withdraw(preview).await?.txid),
+        }
+
+        // Kept until the melt is known to be paid, see `recover_melts`
+        let id = self
+            .db
+            .begin_melt(
+                &in_flight_melt(&preview, None),
+                preview.description.as_deref(),
+            )
+            .await?;
+
+        self.finish_melt(preview, id).await
+    }
+
+    /// Melt the quote of `preview` through cdk, its in-flight record and
+    /// transaction `id` being already written
+    async fn finish_melt(
+        &self,
+        preview: MeltPreview,
+        id: i64,
+    ) -> Result<Option<String>, WalletError> {
+        // cdk sends blank outputs for the fee reserve with the melt, which
+        // the mint signs for whatever the payment didn't use (NUT-08)
+        let melted = self
+            .wallet
+            .melt(&preview.mint_url, &preview.quote_id, SplitTarget::None)
+            .await?;
+
+        if !melted.paid {
+            return Err(WalletError::InvoiceNotPaid);
+        }
+        tracing::info!(
+            "Paid {}",
+            unit::format_amount(preview.amount, &preview.unit)
+        );
+
+        self.db
+            .update_transaction_status(&preview.quote_id, TransactionStatus::Complete)
+            .await?;
+        self.db.delete_melt_quote(&preview.quote_id).await?;
+
+        // Fee reserve that wasn't needed comes back as change
+        let change: u64 = melted
+            .change
+            .iter()
+            .flatten()
+            .map(|proof| u64::from(proof.amount))
+            .sum();
+        self.db
+            .set_transaction_fee(
+                id,
+                preview
+                    .total()
+                    .saturating_sub(change)
+                    .saturating_sub(preview.amount),
+            )
+            .await?;
+        self.db
+            .set_melt_change(id, preview.fee_reserve, change)
+            .await?;
+        if change > 0 {
+            tracing::info!(
+                "Got back {} of unused fee reserve",
+                unit::format_amount(change, &preview.unit)
+            );
+        }
+
+        Ok(melted.preimage)
+    }
+
+    /// Quote paying BOLT12 `offer` from `mint_url` (NUT-25), `amount` in sats
+    /// being needed for offers without one
+    #[instrument(skip_all, fields(mint = %mint_url), err)]
+    pub async fn offer_quote(
+        &self,
+        mint_url: UncheckedUrl,
+        offer: String,
+        amount: Option<u64>,
+    ) -> Result<MeltPreview, WalletError> {
+        let bolt12_error = |err: anyhow::Error| WalletError::Bolt12(err.to_string());
+
+        let details = bolt12::decode_offer(&offer).map_err(bolt12_error)?;
+        let info = mint_info::fetch_info(&mint_url)
+            .await
+            .map_err(bolt12_error)?;
+        if !info.supports_melt_method(bolt12::METHOD) {
+            return Err(WalletError::Bolt12(format!(
+                "{} can't pay BOLT12 offers",
+                mint_url
+            )));
+        }
+
+        let amount_msat = match (details.amount_msat, &details.currency) {
+            (None, None) => {
+                let amount = amount.ok_or_else(|| {
+                    WalletError::InvalidAmount("Offer has no amount, enter one".to_string())
+                })?;
+                Some(amount * 1000)
+            }
+            _ => None,
+        };
+        let quote = bolt12::melt_quote(&mint_url, &offer, &CurrencyUnit::Sat, amount_msat)
+            .await
+            .map_err(bolt12_error)?;
+        let input_fee = self
+            .estimate_input_fee(&mint_url, quote.amount + quote.fee_reserve)
+            .await?;
+
+        Ok(MeltPreview {
+            quote_id: quote.quote,
+            mint_url,
+            method: MeltMethod::Bolt12,
+            unit: CurrencyUnit::Sat,
+            amount: quote.amount,
+            fee_reserve: quote.fee_reserve,
+            input_fee,
+            description: details.description.or(details.issuer),
+        })
+    }
+
+    /// Quote paying `amount` sats to Bitcoin `address` from `mint_url`
+    #[instrument(skip_all, fields(mint = %mint_url, amount), err)]
+    pub async fn withdraw_quote(
+        &self,
+        mint_url: UncheckedUrl,
+        address: String,
+        amount: u64,
+    ) -> Result<MeltPreview, WalletError> {
+        let onchain_error = |err: anyhow::Error| WalletError::Onchain(err.to_string());
+
+        let address = onchain::validate_address(&address).map_err(onchain_error)?;
+        let info = mint_info::fetch_info(&mint_url)
+            .await
+            .map_err(onchain_error)?;
+        if !info.supports_melt_method(onchain::METHOD) {
+            return Err(WalletError::Onchain(format!(
+                "{} can't pay to Bitcoin addresses",
+                mint_url
+            )));
+        }
+
+        let quote = onchain::melt_quote(&mint_url, &address, amount, &CurrencyUnit::Sat)
+            .await
+            .map_err(onchain_error)?;
+        let input_fee = self
+            .estimate_input_fee(&mint_url, quote.amount + quote.fee_reserve)
+            .await?;
+
+        Ok(MeltPreview {
+            quote_id: quote.quote,
+            mint_url,
+            method: MeltMethod::Onchain,
+            unit: CurrencyUnit::Sat,
+            amount: quote.amount,
+            fee_reserve: quote.fee_reserve,
+            input_fee,
+            description: Some(format!("To {}", address)),
+        })
+    }
+
+    /// Pay an on-chain melt quote, see [`Self::check_withdrawal`] for when
+    /// the mint broadcasts it later
+    #[instrument(skip_all, fields(mint = %preview.mint_url, quote = %preview.quote_id), err)]
+    pub async fn withdraw(&self, preview: MeltPreview) -> Result<Withdrawal, WalletError> {
+        let response = self.melt_external(&preview).await?;
+
+        Ok(Withdrawal {
+            state: quote_state(&response),
+            txid: response.txid,
+            quote_id: preview.quote_id,
+            mint_url: preview.mint_url,
+            amount: preview.amount,
+        })
+    }
+
+    /// Ask the mint whether a withdrawal was broadcast, completing its
+    /// transaction once it was
+    ///
+    /// A withdrawal the mint gave up on is marked failed, its inputs can be
+    /// reclaimed from the sent tokens.
+    #[instrument(level = "debug", skip_all, fields(quote = %withdrawal.quote_id), err)]
+    pub async fn check_withdrawal(
+        &self,
+        mut withdrawal: Withdrawal,
+    ) -> Result<Withdrawal, WalletError> {
+        let response =
+            methods::melt_quote_state(&withdrawal.mint_url, onchain::METHOD, &withdrawal.quote_id)
+                .await
+                .map_err(|err| WalletError::Onchain(err.to_string()))?;
+
+        let status = match response.state.as_deref() {
+            _ if response.is_paid() => Some(TransactionStatus::Complete),
+            Some("UNPAID") => Some(TransactionStatus::Failed),
+            _ => None,
+        };
+        if let Some(status) = status {
+            self.db
+                .update_transaction_status(&withdrawal.quote_id, status)
+                .await?;
+            self.db.delete_melt_quote(&withdrawal.quote_id).await?;
+        }
+
+        withdrawal.state = quote_state(&response);
+        withdrawal.txid = response.txid.or(withdrawal.txid);
+
+        Ok(withdrawal)
+    }
+
+    /// Pay a quote of a method cdk can't melt with proofs of exactly its
+    /// total
+    ///
+    /// The inputs are split off with a send first. No change is asked for,
+    /// the unused fee reserve stays with the mint. Inputs of a payment left
+    /// pending are kept as a sent token, to be reclaimed should it fail.
+    async fn melt_external(&self, preview: &MeltPreview) -> Result<MeltResponse, WalletError> {
+        let balance = self.unit_balance(&preview.mint_url, &preview.unit).await?;
+
+        let token = self
+            .wallet
+            .send(
+                &preview.mint_url,
+                preview.unit.clone(),
+                Amount::from(preview.total()),
+                None,
+                None,
+                &SplitTarget::None,
+            )
+            .await?;
+
+        // The inputs are out of the balance now, nothing may fail before
+        // they are saved with the in flight melt
+        let spent = match self.unit_balance(&preview.mint_url, &preview.unit).await {
+            Ok(remaining) => balance.saturating_sub(remaining),
+            Err(_) => preview.total(),
+        };
+        let started = match token::parse(&token) {
+            Ok(parsed) => self
+                .db
+                .begin_melt(
+                    &in_flight_melt(preview, Some(token.clone())),
+                    preview.description.as_deref(),
+                )
+                .await
+                .map(|id| (id, parsed))
+                .map_err(WalletError::from),
+            Err(err) => Err(WalletError::Wallet(err.to_string())),
+        };
+        let (id, parsed) = match started {
+            Ok(started) => started,
+            Err(err) => {
+                self.take_back(&token, preview.total(), &preview.mint_url)
+                    .await;
+                return Err(err);
+            }
+        };
+        let inputs: Proofs = parsed
+            .token
+            .into_iter()
+            .flat_map(|mint_proofs| mint_proofs.proofs)
+            .collect();
+
+        let response = methods::melt(
+            &preview.mint_url,
+            preview.method.name(),
+            &preview.quote_id,
+            &inputs,
+        )
+        .await;
+        match response {
+            Ok(response) if response.is_paid() || response.is_pending() => {
+                if response.is_paid() {
+                    tracing::info!(
+                        "Paid {} over {}",
+                        unit::format_amount(preview.amount, &preview.unit),
+                        preview.method.name()
+                    );
+                    self.db
+                        .update_transaction_status(&preview.quote_id, TransactionStatus::Complete)
+                        .await?;
+                    self.db.delete_melt_quote(&preview.quote_id).await?;
+                } else {
+                    self.db
+                        .add_sent_token(&token, preview.total(), &preview.mint_url)
+                        .await?;
+                }
+                self.db
+                    .set_transaction_fee(id, spent.saturating_sub(preview.amount))
+                    .await?;
+                self.db.set_melt_change(id, preview.fee_reserve, 0).await?;
+
+                Ok(response)
+            }
+            result => {
+                self.take_back(&token, preview.total(), &preview.mint_url)
+                    .await;
+                self.db
+                    .update_transaction_status(&preview.quote_id, TransactionStatus::Failed)
+                    .await?;
+                self.db.delete_melt_quote(&preview.quote_id).await?;
+
+                Err(match result {
+                    Ok(_) => WalletError::InvoiceNotPaid,
+                    Err(err) => WalletError::Wallet(err.to_string()),
+                })
+            }
+        }
+    }
+
+    /// Settle melts the app stopped in the middle of, returning the amount
+    /// that came back to the wallet
+    ///
+    /// Paid melts get their NUT-08 change through a NUT-09 restore, the
+    /// change outputs being derived from the seed. Inputs of failed melts are
+    /// released. Melts the mint still reports pending are left for the next
+    /// start.
+    #[instrument(skip_all, err)]
+    pub async fn recover_melts(&self) -> Result<u64, WalletError> {
+        let mut recovered = 0;
+
+        for melt in self.db.get_melt_quotes().await? {
+            let response =
+                match methods::melt_quote_state(&melt.mint_url, &melt.method, &melt.quote_id).await
+                {
+                    Ok(response) => response,
+                    Err(err) => {
+                        tracing::warn!("Could not check melt {}: {}", melt.quote_id, err);
+                        continue;
+                    }
+                };
+            if response.is_pending() {
+                continue;
+            }
+
+            let paid = response.is_paid();
+            tracing::info!(
+                "Melt {} interrupted, {}",
+                melt.quote_id,
+                if paid { "paid" } else { "not paid" }
+            );
+            recovered += match (&melt.token, paid) {
+                (None, true) => {
+                    self.release_reserved_proofs(&melt.mint_url).await?;
+                    // The restore reports every proof the mint signed for
+                    // the seed, not only the change
+                    let balance = self.unit_balance(&melt.mint_url, &melt.unit).await?;
+                    self.wallet.restore(melt.mint_url.clone()).await?;
+                    self.unit_balance(&melt.mint_url, &melt.unit)
+                        .await?
+                        .saturating_sub(balance)
+                }
+                (None, false) => self.release_reserved_proofs(&melt.mint_url).await?,
+                (Some(_), true) => 0,
+                (Some(token), false) => match self
+                    .wallet
+                    .receive(token, &SplitTarget::default(), None)
+                    .await
+                {
+                    Ok(amount) => amount.into(),
+                    // Already reclaimed from the sent tokens
+                    Err(err) => {
+                        tracing::warn!("Could not take back melt inputs: {}", err);
+                        0
+                    }
+                },
+            };
+
+            let status = if paid {
+                TransactionStatus::Complete
+            } else {
+                TransactionStatus::Failed
+            };
+            self.db
+                .update_transaction_status(&melt.quote_id, status)
+                .await?;
+            self.db.delete_melt_quote(&melt.quote_id).await?;
+        }
+
+        Ok(recovered)
+    }
+
+    /// Drop the proofs cdk reserved for melts at `mint_url` that the mint
+    /// saw spent, and make those still unspent usable again, returning their
+    /// amount
+    async fn release_reserved_proofs(&self, mint_url: &UncheckedUrl) -> Result<u64, WalletError> {
+        let localstore = open_localstore().await?;
+        let database_error = |err: cdk_database::Error| WalletError::Database(err.to_string());
+
+        let reserved = localstore
+            .get_pending_proofs(mint_url.clone())
+            .await
+            .map_err(database_error)?
+            .unwrap_or_default();
+        if reserved.is_empty() {
+            return Ok(0);
+        }
+
+        let states = self
+            .wallet
+            .check_proofs_spent(mint_url.clone(), reserved.clone())
+            .await?;
+        let mut settled = Proofs::new();
+        let mut unspent = Proofs::new();
+        for (proof, proof_state) in reserved.into_iter().zip(states) {
+            match proof_state.state {
+                State::Spent => settled.push(proof),
+                State::Unspent => {
+                    settled.push(proof.clone());
+                    unspent.push(proof);
+                }
+                // Another melt still holds it
+                _ => (),
+            }
+        }
+
+        localstore
+            .remove_pending_proofs(mint_url.clone(), &settled)
+            .await
+            .map_err(database_error)?;
+        let amount = unspent.iter().map(|proof| u64::from(proof.amount)).sum();
+        if !unspent.is_empty() {
+            localstore
+                .add_proofs(mint_url.clone(), unspent)
+                .await
+                .map_err(database_error)?;
+        }
+
+        Ok(amount)
+    }
+
+    /// Swap all proofs of `mint_url` for as few as possible, returning the
+    /// amount kept
+    #[instrument(skip_all, fields(mint = %mint_url), err)]
+    pub async fn consolidate(&self, mint_url: UncheckedUrl) -> Result<u64, WalletError> {
+        let balance = self.mint_balance(&mint_url).await?;
+        if balance == 0 {
+            return Ok(0);
+        }
+
+        let token = self
+            .wallet
+            .send(
+                &mint_url,
+                CurrencyUnit::Sat,
+                Amount::from(balance),
+                None,
+                None,
+                &SplitTarget::None,
+            )
+            .await?;
+
+        self.receive_held(&token, balance, &mint_url).await
+    }
+
+    /// Sats each mint holds in proofs of keysets it retired, for mints that
+    /// could be reached and hold any
+    pub async fn retired_keyset_balances(&self) -> Result<HashMap<UncheckedUrl, u64>, WalletError> {
+        let mut balances = HashMap::new();
+        for mint_url in self.wallet.mint_balances().await?.into_keys() {
+            let keysets = match fees::fetch_keysets(&mint_url).await {
+                Ok(keysets) => keysets,
+                Err(err) => {
+                    tracing::debug!("Could not fetch keysets of {}: {}", mint_url, err);
+                    continue;
+                }
+            };
+            let retired: HashSet<String> = keysets
+                .into_iter()
+                .filter(|keyset| !keyset.active && keyset.unit == CurrencyUnit::Sat.to_string())
+                .map(|keyset| keyset.id)
+                .collect();
+
+            let amount: u64 = self
+                .wallet
+                .get_proofs(mint_url.clone())
+                .await?
+                .unwrap_or_default()
+                .iter()
+                .filter(|proof| retired.contains(&proof.keyset_id.to_string()))
+                .map(|proof| u64::from(proof.amount))
+                .sum();
+            if amount > 0 {
+                balances.insert(mint_url, amount);
+            }
+        }
+
+        Ok(balances)
+    }
+
+    /// Pick up a keyset rotation at `mint_url` and swap the proofs of
+    /// retired keysets for proofs of the active ones, returning the amount
+    /// swapped
+    ///
+    /// The mint's keysets are stored and the keys of the active ones fetched
+    /// first, so the swap is signed with them.
+    #[instrument(skip_all, fields(mint = %mint_url), err)]
+    pub async fn rotate_keysets(&self, mint_url: UncheckedUrl) -> Result<u64, WalletError> {
+        let localstore = open_localstore().await?;
+        let database_error = |err: cdk_database::Error| WalletError::Database(err.to_string());
+
+        let keysets = fees::fetch_cdk_keysets(&mint_url)
+            .await
+            .map_err(|err| WalletError::Wallet(err.to_string()))?;
+        localstore
+            .add_mint_keysets(mint_url.clone(), keysets.clone())
+            .await
+            .map_err(database_error)?;
+        for keyset in keysets.iter().filter(|keyset| keyset.active) {
+            self.wallet.get_keyset_keys(&mint_url, keyset.id).await?;
+        }
+
+        let retired: HashMap<Id, CurrencyUnit> = keysets
+            .into_iter()
+            .filter(|keyset| !keyset.active)
+            .map(|keyset| (keyset.id, keyset.unit))
+            .collect();
+        let mut proofs_by_unit: HashMap<CurrencyUnit, Proofs> = HashMap::new();
+        for proof in self
+            .wallet
+            .get_proofs(mint_url.clone())
+            .await?
+            .unwrap_or_default()
+        {
+            if let Some(unit) = retired.get(&proof.keyset_id) {
+                proofs_by_unit.entry(unit.clone()).or_default().push(proof);
+            }
+        }
+
+        let mut swapped = 0;
+        for (unit, proofs) in proofs_by_unit {
+            let token = Token::new(mint_url.clone(), proofs.clone(), None, Some(unit))
+                .map_err(|err| WalletError::Wallet(err.to_string()))?
+                .to_string();
+
+            // Held in Sent tokens while the proofs are in no store, receiving
+            // them swaps them so they must not be spent twice
+            let amount: u64 = proofs.iter().map(|proof| u64::from(proof.amount)).sum();
+            let held = self.db.add_sent_token(&token, amount, &mint_url).await?;
+            localstore
+                .remove_proofs(mint_url.clone(), &proofs)
+                .await
+                .map_err(database_error)?;
+            let received = self
+                .wallet
+                .receive(&token, &SplitTarget::default(), None)
+                .await;
+            if let Err(err) = &received {
+                tracing::warn!("Could not swap retired proofs: {}", err);
+                localstore
+                    .add_proofs(mint_url.clone(), proofs)
+                    .await
+                    .map_err(database_error)?;
+            }
+            if let Err(err) = self
+                .db
+                .update_sent_token_status(held, SentTokenStatus::Reclaimed)
+                .await
+            {
+                tracing::warn!("Could not release held token {}: {}", held, err);
+            }
+            swapped += u64::from(received?);
+        }
+        tracing::info!("Swapped {} from retired keysets", swapped);
+
+        Ok(swapped)
+    }
+
+    /// Move `amount` from `from` to `to` over lightning, less the fees,
+    /// returning the amount minted at `to`
+    ///
+    /// Nothing is moved and `None` returned if the fees would be more than
+    /// `max_fee` or leave nothing to mint.
+    #[instrument(skip_all, fields(from = %from, to = %to, amount), err)]
+    pub async fn sweep(
+        &self,
+        from: UncheckedUrl,
+        to: UncheckedUrl,
+        amount: u64,
+        max_fee: u64,
+        split: SplitTarget,
+    ) -> Result<Option<u64>, WalletError> {
+        // Learn the fees from quotes for the whole amount, this probe is not
+        // recorded as it is never paid
+        let probe = self
+            .wallet
+            .mint_quote(to.clone(), CurrencyUnit::Sat, Amount::from(amount))
+            .await?;
+        let probe = self
+            .melt_quote(from.clone(), probe.request, CurrencyUnit::Sat)
+            .await?;
+        let fee = probe.fee_reserve + probe.input_fee;
+        if fee > max_fee || fee >= amount {
+            tracing::info!("Not moving {} sats, fees would be {} sats", amount, fee);
+            return Ok(None);
+        }
+        let swept = amount - fee;
+
+        let pending = self
+            .mint_quote(to.clone(), swept, CurrencyUnit::Sat)
+            .await?;
+        let melt = self
+            .melt_quote(from, pending.request.clone(), CurrencyUnit::Sat)
+            .await?;
+        self.pay_invoice(melt).await?;
+
+        Ok(Some(self.mint(to, pending.quote_id, split).await?))
+    }
+
+    /// Move `amount` just received at `from` to `to` if the fees are low
+    /// enough
+    ///
+    /// The funds stay at `from` when this fails, which is only logged as the
+    /// token itself was claimed.
+    #[instrument(skip_all, fields(from = %from, to = %to, amount))]
+    pub async fn auto_swap(
+        &self,
+        from: UncheckedUrl,
+        to: UncheckedUrl,
+        amount: u64,
+        split: SplitTarget,
+    ) {
+        let max_fee = amount * AUTO_SWAP_MAX_FEE_PERCENT / 100;
+        match self.sweep(from, to, amount, max_fee, split).await {
+            Ok(Some(swept)) => tracing::info!("Moved {} sats to the default mint", swept),
+            Ok(None) => (),
+            Err(err) => tracing::warn!("Could not move received sats: {}", err),
+        }
+    }
+
+    /// Quote paying `amount` sats to the LNURL pay `request` from `mint_url`
+    #[instrument(skip_all, fields(mint = %mint_url, amount), err)]
+    pub async fn lnurl_melt_quote(
+        &self,
+        mint_url: UncheckedUrl,
+        request: &PayRequest,
+        amount: u64,
+    ) -> Result<MeltPreview, WalletError> {
+        let invoice = lnurl::fetch_invoice(request, amount)
+            .await
+            .map_err(|err| WalletError::Lnurl(err.to_string()))?;
+
+        self.melt_quote(mint_url, invoice, CurrencyUnit::Sat).await
+    }
+
+    /// Melt up to `excess` sats to `address` from the mint holding the most,
+    /// returning the amount paid
+    ///
+    /// Fees come out of `excess` so the balance stays at the threshold, an
+    /// excess the fees leave too little of is not paid out.
+    #[instrument(skip_all, fields(excess), err)]
+    pub async fn auto_withdraw(
+        &self,
+        address: &str,
+        excess: u64,
+        description: String,
+    ) -> Result<u64, WalletError> {
+        let Some((mint_url, balance)) = self.largest_balance().await? else {
+            return Ok(0);
+        };
+        let budget = excess.min(balance);
+        let request = resolve_pay_request(address).await?;
+
+        let mut amount = budget.min(request.max_sats());
+        // Fees are only known once quoted, so quote again for what they leave
+        for _ in 0..2 {
+            if amount < AUTO_WITHDRAW_MIN_SATS.max(request.min_sats()) {
+                return Ok(0);
+            }
+            let mut preview = self
+                .lnurl_melt_quote(mint_url.clone(), &request, amount)
+                .await?;
+            if preview.total() <= budget {
+                preview.description = Some(description);
+                self.pay_invoice(preview).await?;
+                tracing::info!("Withdrew {} sats to {}", amount, address);
+                return Ok(amount);
+            }
+            amount = budget.saturating_sub(preview.total() - preview.amount);
+        }
+
+        Ok(0)
+    }
+
+    /// Make the payment of `scheduled` from the mint holding the most,
+    /// returning the token to DM when it goes to a contact
+    ///
+    /// `note` describes the payment to the recipient it is given. Tokens for
+    /// contacts are kept as sent tokens until claimed, so one whose DM failed
+    /// can be reclaimed.
+    #[instrument(skip_all, fields(scheduled = scheduled.id, amount = scheduled.amount), err)]
+    pub async fn run_scheduled_payment(
+        &self,
+        scheduled: &ScheduledPayment,
+        split: SplitTarget,
+        note: impl FnOnce(&str) -> String,
+    ) -> Result<Option<ScheduledToken>, WalletError> {
+        let Some((mint_url, _)) = self.largest_balance().await? else {
+            return Err(WalletError::Schedule("No mint holds any sats".to_string()));
+        };
+
+        match &scheduled.action {
+            ScheduledAction::Pay { address } => {
+                let request = resolve_pay_request(address).await?;
+                let mut preview = self
+                    .lnurl_melt_quote(mint_url, &request, scheduled.amount)
+                    .await?;
+                preview.description = Some(note(address));
+                self.pay_invoice(preview).await?;
+
+                Ok(None)
+            }
+            ScheduledAction::Send { contact_id } => {
+                let contact = self
+                    .db
+                    .get_contacts()
+                    .await?
+                    .into_iter()
+                    .find(|contact| contact.id == *contact_id)
+                    .ok_or_else(|| WalletError::Schedule("The contact was deleted".to_string()))?;
+                let Some(npub) = contact.npub else {
+                    return Err(WalletError::Schedule(format!(
+                        "{} has no npub to send to",
+                        contact.name
+                    )));
+                };
+                let conditions = contact
+                    .p2pk_pubkey
+                    .and_then(|pubkey| PublicKey::from_hex(pubkey).ok())
+                    .map(|pubkey| p2pk::conditions(pubkey, None));
+
+                let (transaction_id, token) = self
+                    .create_token(
+                        mint_url,
+                        scheduled.amount,
+                        CurrencyUnit::Sat,
+                        split,
+                        conditions,
+                        Some(note(&contact.name)),
+                    )
+                    .await?;
+
+                Ok(Some(ScheduledToken {
+                    transaction_id,
+                    contact: contact.name,
+                    npub,
+                    token,
+                }))
+            }
+        }
+    }
+
+    /// Seed, mints, proofs and history sealed under `backup_password`, the
+    /// seed being unlocked with `password`
+    #[instrument(skip_all, err)]
+    pub async fn export_backup(
+        &self,
+        password: &str,
+        backup_password: &str,
+    ) -> Result<Vec<u8>, WalletError> {
+        let seed = get_seed(password)
+            .map_err(|err| WalletError::Seed(err.to_string()))?
+            .ok_or_else(|| WalletError::Seed("No seed found".to_string()))?;
+
+        let mut mints = vec![];
+        for mint_url in self.wallet.mint_balances().await?.into_keys() {
+            let proofs = self
+                .wallet
+                .get_proofs(mint_url.clone())
+                .await?
+                .unwrap_or_default();
+            mints.push(MintBackup { mint_url, proofs });
+        }
+        let transactions = self.db.get_transactions(u32::MAX, 0).await?;
+
+        let backup = Backup::new(seed.to_string(), mints, transactions);
+        backup::seal(&backup, backup_password).map_err(|err| WalletError::Backup(err.to_string()))
+    }
+
+    /// Add the mints, proofs and history of `backup` to the wallet, which
+    /// was opened with its seed
+    #[instrument(skip_all, err)]
+    pub async fn import_backup(&self, backup: Backup) -> Result<(), WalletError> {
+        let localstore = open_localstore().await?;
+
+        for mint in backup.mints {
+            self.wallet.add_mint(mint.mint_url.clone()).await?;
+            if !mint.proofs.is_empty() {
+                localstore
+                    .add_proofs(mint.mint_url, mint.proofs)
+                    .await
+                    .map_err(|err| WalletError::Database(err.to_string()))?;
+            }
+        }
+
+        // Oldest first so ids keep the original order
+        for transaction in backup.transactions.iter().rev() {
+            self.db.import_transaction(transaction).await?;
+        }
+        tracing::info!(
+            "Imported backup of {} transactions",
+            backup.transactions.len()
+        );
+
+        Ok(())
+    }
+
+    /// Ask each mint for the state of the stored proofs and drop the spent
+    /// ones
+    #[instrument(level = "debug", skip_all, err)]
+    pub async fn check_proof_states(&self) -> Result<ProofStates, WalletError> {
+        let localstore = open_localstore().await?;
+        let mut states = ProofStates::default();
+
+        for mint_url in self.wallet.mint_balances().await?.into_keys() {
+            let proofs = self
+                .wallet
+                .get_proofs(mint_url.clone())
+                .await?
+                .unwrap_or_default();
+            if proofs.is_empty() {
+                continue;
+            }
+
+            let proof_states = self
+                .wallet
+                .check_proofs_spent(mint_url.clone(), proofs.clone())
+                .await?;
+
+            let mut spent = Proofs::new();
+            for (proof, proof_state) in proofs.into_iter().zip(proof_states) {
+                match proof_state.state {
+                    State::Spent => {
+                        states.spent += u64::from(proof.amount);
+                        spent.push(proof);
+                    }
+                    State::Pending => states.pending += u64::from(proof.amount),
+                    _ => (),
+                }
+            }
+
+            if !spent.is_empty() {
+                localstore
+                    .remove_proofs(mint_url, &spent)
+                    .await
+                    .map_err(|err| WalletError::Database(err.to_string()))?;
+            }
+        }
+
+        Ok(states)
+    }
+
+    /// Stored proofs of each mint by keyset and denomination, sorted by mint
+    pub async fn proof_summaries(&self) -> Result<Vec<ProofSummary>, WalletError> {
+        let mut summaries = vec![];
+
+        for mint_url in self.wallet.mint_balances().await?.into_keys() {
+            let proofs = self
+                .wallet
+                .get_proofs(mint_url.clone())
+                .await?
+                .unwrap_or_default();
+
+            let mut denominations = BTreeMap::new();
+            for proof in &proofs {
+                *denominations
+                    .entry((proof.keyset_id.to_string(), u64::from(proof.amount)))
+                    .or_default() += 1;
+            }
+
+            let consolidation_fee = match fees::fetch_keyset_fees(&mint_url).await {
+                Ok(fees) => fees::input_fee(&proofs, &fees),
+                Err(err) => {
+                    tracing::warn!("Could not fetch keyset fees for {}: {}", mint_url, err);
+                    0
+                }
+            };
+
+            summaries.push(ProofSummary {
+                mint_url,
+                denominations,
+                count: proofs.len(),
+                consolidation_fee,
+            });
+        }
+        summaries.sort_by(|a, b| a.mint_url.cmp(&b.mint_url));
+
+        Ok(summaries)
+    }
+
+    /// Problems an integrity check finds in the app database and, when it is
+    /// kept in sqlite, cdk's store
+    #[instrument(skip_all, err)]
+    pub async fn check_integrity(&self) -> Result<Vec<String>, WalletError> {
+        let mut problems = self.db.integrity_check().await?;
+        if let Some(path) = sqlite_store_path() {
+            problems.extend(db::check_file_integrity(&path.to_string_lossy()).await?);
+        }
+
+        Ok(problems)
+    }
+
+    /// Drop the proofs cdk reserved that the mints saw spent, returning how
+    /// many went
+    ///
+    /// Mints that can't be reached are skipped.
+    #[instrument(skip_all, err)]
+    pub async fn prune_spent_proofs(&self) -> Result<usize, WalletError> {
+        let localstore = open_localstore().await?;
+        let database_error = |err: cdk_database::Error| WalletError::Database(err.to_string());
+
+        let mut pruned = 0;
+        for mint_url in self.wallet.mint_balances().await?.into_keys() {
+            let reserved = localstore
+                .get_pending_proofs(mint_url.clone())
+                .await
+                .map_err(database_error)?
+                .unwrap_or_default();
+            if reserved.is_empty() {
+                continue;
+            }
+
+            let states = match self
+                .wallet
+                .check_proofs_spent(mint_url.clone(), reserved.clone())
+                .await
+            {
+                Ok(states) => states,
+                Err(err) => {
+                    tracing::warn!("Could not check proofs at {}: {}", mint_url, err);
+                    continue;
+                }
+            };
+            let spent: Proofs = reserved
+                .into_iter()
+                .zip(states)
+                .filter(|(_, proof_state)| matches!(proof_state.state, State::Spent))
+                .map(|(proof, _)| proof)
+                .collect();
+            localstore
+                .remove_pending_proofs(mint_url, &spent)
+                .await
+                .map_err(database_error)?;
+            pruned += spent.len();
+        }
+
+        Ok(pruned)
+    }
+
+    /// Delete settled sent tokens and expired quotes older than
+    /// [`PRUNE_AFTER`], returning how many records went
+    #[instrument(skip_all, err)]
+    pub async fn prune_records(&self) -> Result<u64, WalletError> {
+        let before = unix_time() - PRUNE_AFTER;
+        let mut pruned = self.db.prune(before).await?;
+
+        let localstore = open_localstore().await?;
+        let database_error = |err: cdk_database::Error| WalletError::Database(err.to_string());
+        for quote in localstore.get_mint_quotes().await.map_err(database_error)? {
+            if !quote.paid && (quote.expiry as i64) < before {
+                localstore
+                    .remove_mint_quote(&quote.id)
+                    .await
+                    .map_err(database_error)?;
+                pruned += 1;
+            }
+        }
+
+        Ok(pruned)
+    }
+
+    /// Rebuild the app database and, when it is kept in sqlite, cdk's store
+    #[instrument(skip_all, err)]
+    pub async fn vacuum(&self) -> Result<(), WalletError> {
+        self.db.vacuum().await?;
+        if let Some(path) = sqlite_store_path() {
+            db::vacuum_file(&path.to_string_lossy()).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Receive `token`, whose proofs are in no store, returning the amount
+    ///
+    /// The token is held in Sent tokens until the proofs are back in the
+    /// balance, so a crash or a failed receive leaves it there to reclaim.
+    async fn receive_held(
+        &self,
+        token: &str,
+        amount: u64,
+        mint_url: &UncheckedUrl,
+    ) -> Result<u64, WalletError> {
+        let held = self.db.add_sent_token(token, amount, mint_url).await;
+        if let Err(err) = &held {
+            tracing::warn!("Could not hold token before receiving it: {}", err);
+        }
+
+        let received = self
+            .wallet
+            .receive(token, &SplitTarget::default(), None)
+            .await;
+        match (received, held) {
+            (Ok(received), Ok(id)) => {
+                if let Err(err) = self
+                    .db
+                    .update_sent_token_status(id, SentTokenStatus::Reclaimed)
+                    .await
+                {
+                    tracing::warn!("Could not release held token {}: {}", id, err);
+                }
+                Ok(received.into())
+            }
+            (Ok(received), Err(_)) => Ok(received.into()),
+            (Err(err), Ok(_)) => Err(err.into()),
+            (Err(err), Err(_)) => {
+                // Nowhere else to keep the only copy of these proofs
+                tracing::error!("Could not receive or save token {}: {}", token, err);
+                Err(err.into())
+            }
+        }
+    }
+
+    /// Receive `token` back after what it was created for could not go
+    /// ahead, leaving it in Sent tokens if even that fails
+    async fn take_back(&self, token: &str, amount: u64, mint_url: &UncheckedUrl) {
+        if let Err(err) = self.receive_held(token, amount, mint_url).await {
+            tracing::warn!("Could not take back token: {}", err);
+        }
+    }
+}
+
+/// Pay request behind a lightning address or LNURL
+async fn resolve_pay_request(address: &str) -> Result<PayRequest, WalletError> {
+    match lnurl::resolve_request(address).await {
+        Ok(LnurlRequest::Pay(request)) => Ok(request),
+        Ok(LnurlRequest::Withdraw(_)) => {
+            Err(WalletError::Lnurl(format!("{} can't be paid", address)))
+        }
+        Err(err) => Err(WalletError::Lnurl(err.to_string())),
+    }
+}
+
+fn in_flight_melt(preview: &MeltPreview, token: Option<String>) -> InFlightMelt {
+    InFlightMelt {
+        quote_id: preview.quote_id.clone(),
+        mint_url: preview.mint_url.clone(),
+        method: preview.method.name().to_string(),
+        unit: preview.unit.clone(),
+        amount: preview.amount,
+        token,
+    }
+}
+
+/// Unix timestamp to retry an operation that failed `attempts` times at
+fn next_retry(attempts: u32) -> i64 {
+    unix_time() + poll::retry_delay(attempts).as_secs() as i64
+}
+
+/// State of a melt quote, older mints only saying whether it was paid
+fn quote_state(response: &MeltResponse) -> String {
+    match (&response.state, response.is_paid()) {
+        (Some(state), _) => state.clone(),
+        (None, true) => "PAID".to_string(),
+        (None, false) => "PENDING".to_string(),
+    }
+}
+
+/// cdk's store of the current profile's proofs and mints, in the configured
+/// [`StorageBackend`]
+///
+/// The sqlite store is keyed by [`WalletService::open`] when
+/// [`database_encrypted`] is set, redb has no encryption of its own.
+pub async fn open_localstore() -> Result<LocalStore, WalletError> {
+    let backend = storage_backend();
+    let dir = wallet_dir();
+
+    if let Some(db_path) = sqlite_store_path() {
+        let localstore = WalletSQLiteDatabase::new(&db_path.to_string_lossy())
+            .await
+            .map_err(|err| WalletError::Database(err.to_string()))?;
+        localstore.migrate().await;
+
+        return Ok(Arc::new(localstore));
+    }
+
+    let mut stores = SHARED_STORES.lock().expect("Store lock poisoned");
+    if let Some((_, _, localstore)) = stores
+        .iter()
+        .find(|(opened, opened_backend, _)| *opened == dir && *opened_backend == backend)
+    {
+        return Ok(localstore.clone());
+    }
+
+    let localstore: LocalStore = match backend {
+        StorageBackend::Redb => {
+            let db_path = dir.join("./cashu_iced.redb");
+            Arc::new(
+                WalletRedbDatabase::new(&db_path.to_string_lossy())
+                    .map_err(|err| WalletError::Database(err.to_string()))?,
+            )
+        }
+        _ => Arc::new(WalletMemoryDatabase::default()),
+    };
+    stores.push((dir, backend, localstore.clone()));
+
+    Ok(localstore)
+}
+
+/// Path of cdk's sqlite store, none when another backend is in use
+fn sqlite_store_path() -> Option<PathBuf> {
+    (storage_backend() == StorageBackend::Sqlite).then(|| wallet_dir().join("./cashu_iced.sqlite"))
+}
+
+/// Bytes the current profile's databases take on disk, WAL files included
+pub fn storage_size() -> u64 {
+    let Ok(entries) = fs::read_dir(wallet_dir()) else {
+        return 0;
+    };
+
+    entries
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with("app.sqlite") || name.starts_with("cashu_iced.")
+        })
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
@@ -0,0 +1,77 @@
+use cdk::nuts::CurrencyUnit;
+use chrono::{DateTime, Datelike};
+
+use crate::db::{Transaction, TransactionKind, TransactionStatus};
+
+/// Funds that came in and went out during a calendar month, in UTC
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonthlyTotals {
+    pub year: i32,
+    pub month: u32,
+    pub inflow: u64,
+    /// Sent and paid, fees included
+    pub outflow: u64,
+}
+
+/// Balance right after a transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BalancePoint {
+    /// Unix timestamp in seconds
+    pub timestamp: i64,
+    pub balance: i64,
+}
+
+/// Totals of the completed transactions in one unit, oldest first
+#[derive(Debug, Clone, Default)]
+pub struct Stats {
+    pub months: Vec<MonthlyTotals>,
+    pub balance: Vec<BalancePoint>,
+}
+
+/// Stats of the `unit` transactions in `transactions`
+///
+/// Pending and failed transactions moved no funds and are left out.
+pub fn stats(transactions: &[Transaction], unit: &CurrencyUnit) -> Stats {
+    let mut transactions: Vec<&Transaction> = transactions
+        .iter()
+        .filter(|transaction| {
+            transaction.status == TransactionStatus::Complete && &transaction.unit == unit
+        })
+        .collect();
+    transactions.sort_by_key(|transaction| (transaction.timestamp, transaction.id));
+
+    let mut stats = Stats::default();
+    let mut balance = 0;
+    for transaction in transactions {
+        let Some(time) = DateTime::from_timestamp(transaction.timestamp, 0) else {
+            continue;
+        };
+        let (inflow, outflow) = match transaction.kind {
+            TransactionKind::Mint | TransactionKind::Receive => (transaction.amount, 0),
+            TransactionKind::Melt | TransactionKind::Send => {
+                (0, transaction.amount + transaction.fee)
+            }
+        };
+
+        match stats.months.last_mut() {
+            Some(month) if month.year == time.year() && month.month == time.month() => {
+                month.inflow += inflow;
+                month.outflow += outflow;
+            }
+            _ => stats.months.push(MonthlyTotals {
+                year: time.year(),
+                month: time.month(),
+                inflow,
+                outflow,
+            }),
+        }
+
+        balance += inflow as i64 - outflow as i64;
+        stats.balance.push(BalancePoint {
+            timestamp: transaction.timestamp,
+            balance,
+        });
+    }
+
+    stats
+}
@@ -0,0 +1,400 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail};
+use base64::engine::general_purpose::{URL_SAFE, URL_SAFE_NO_PAD};
+use base64::Engine;
+use cdk::nuts::nut10::{Kind, Secret};
+use cdk::nuts::{CurrencyUnit, SpendingConditions, Token};
+use cdk::UncheckedUrl;
+use serde::{Deserialize, Serialize};
+use serde_bytes::ByteBuf;
+use serde_json::{json, Value};
+
+const V3_PREFIX: &str = "cashuA";
+const V4_PREFIX: &str = "cashuB";
+
+/// Serialization of a cashu token
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TokenFormat {
+    /// JSON `cashuA` token
+    V3,
+    /// Compact CBOR `cashuB` token
+    #[default]
+    V4,
+}
+
+impl TokenFormat {
+    pub const ALL: [TokenFormat; 2] = [TokenFormat::V3, TokenFormat::V4];
+}
+
+impl fmt::Display for TokenFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenFormat::V3 => write!(f, "V3 (cashuA)"),
+            TokenFormat::V4 => write!(f, "V4 (cashuB)"),
+        }
+    }
+}
+
+/// What a token holds, shown before it is claimed
+#[derive(Debug, Clone)]
+pub struct TokenSummary {
+    pub amount: u64,
+    pub unit: CurrencyUnit,
+    pub mints: Vec<UncheckedUrl>,
+    pub memo: Option<String>,
+    pub proofs: usize,
+    /// Distinct NUT-10 conditions of the token's locked proofs
+    pub conditions: Vec<TokenCondition>,
+}
+
+/// Spending condition of some of a token's proofs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenCondition {
+    /// Needs a signature from a locking key, until `locktime` if one is set
+    P2pk { locktime: Option<u64> },
+    /// Needs the preimage of a hash, NUT-14
+    Htlc,
+}
+
+impl fmt::Display for TokenCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenCondition::P2pk { locktime: None } => write!(f, "Locked to a public key"),
+            TokenCondition::P2pk {
+                locktime: Some(locktime),
+            } => {
+                let until = chrono::DateTime::from_timestamp(*locktime as i64, 0)
+                    .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_else(|| locktime.to_string());
+                write!(f, "Locked to a public key until {}", until)
+            }
+            TokenCondition::Htlc => write!(f, "Locked to the preimage of a hash"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TokenV4 {
+    #[serde(rename = "m")]
+    mint_url: String,
+    #[serde(rename = "u")]
+    unit: String,
+    #[serde(rename = "d", default, skip_serializing_if = "Option::is_none")]
+    memo: Option<String>,
+    #[serde(rename = "t")]
+    keysets: Vec<KeysetV4>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct KeysetV4 {
+    #[serde(rename = "i")]
+    keyset_id: ByteBuf,
+    #[serde(rename = "p")]
+    proofs: Vec<ProofV4>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ProofV4 {
+    #[serde(rename = "a")]
+    amount: u64,
+    #[serde(rename = "s")]
+    secret: String,
+    #[serde(rename = "c")]
+    c: ByteBuf,
+    #[serde(rename = "d", default, skip_serializing_if = "Option::is_none")]
+    dleq: Option<DleqV4>,
+    #[serde(rename = "w", default, skip_serializing_if = "Option::is_none")]
+    witness: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DleqV4 {
+    e: ByteBuf,
+    s: ByteBuf,
+    r: ByteBuf,
+}
+
+/// Parse a V3 or V4 token
+pub fn parse(input: &str) -> anyhow::Result<Token> {
+    let input = input.trim();
+
+    if input.starts_with(V3_PREFIX) {
+        return Token::from_str(input).map_err(|err| anyhow!("Invalid token: {}", err));
+    }
+
+    let Some(encoded) = input.strip_prefix(V4_PREFIX) else {
+        bail!("Not a cashu token");
+    };
+
+    let bytes = URL_SAFE_NO_PAD
+        .decode(encoded.trim_end_matches('='))
+        .or_else(|_| URL_SAFE.decode(encoded))?;
+    let token: TokenV4 = ciborium::from_reader(bytes.as_slice())?;
+
+    from_v4(token)
+}
+
+/// Unit of `token`, tokens without one are in sats
+pub fn unit(token: &Token) -> CurrencyUnit {
+    token.unit.clone().unwrap_or_default()
+}
+
+pub fn summary(token: &Token) -> TokenSummary {
+    let proofs: Vec<_> = token
+        .token
+        .iter()
+        .flat_map(|mint_proofs| &mint_proofs.proofs)
+        .collect();
+
+    let mut conditions = vec![];
+    for proof in &proofs {
+        let Ok(secret) = Secret::try_from(&proof.secret) else {
+            continue;
+        };
+        let condition = match secret.kind {
+            Kind::HTLC => TokenCondition::Htlc,
+            Kind::P2PK => match SpendingConditions::try_from(secret) {
+                Ok(SpendingConditions::P2PKConditions { conditions, .. }) => TokenCondition::P2pk {
+                    locktime: conditions.locktime,
+                },
+                _ => TokenCondition::P2pk { locktime: None },
+            },
+        };
+        if !conditions.contains(&condition) {
+            conditions.push(condition);
+        }
+    }
+
+    TokenSummary {
+        amount: proofs.iter().map(|proof| u64::from(proof.amount)).sum(),
+        unit: unit(token),
+        mints: token
+            .token
+            .iter()
+            .map(|mint_proofs| mint_proofs.mint.clone())
+            .collect(),
+        memo: token.memo.clone(),
+        proofs: proofs.len(),
+        conditions,
+    }
+}
+
+/// Encode `token` in `format`
+///
+/// V4 tokens can only hold proofs from a single mint.
+pub fn encode(token: &Token, format: TokenFormat) -> anyhow::Result<String> {
+    match format {
+        TokenFormat::V3 => Ok(token.to_string()),
+        TokenFormat::V4 => {
+            let mut bytes = vec![];
+            ciborium::into_writer(&to_v4(token)?, &mut bytes)?;
+
+            Ok(format!("{}{}", V4_PREFIX, URL_SAFE_NO_PAD.encode(bytes)))
+        }
+    }
+}
+
+fn hex_field(value: &Value, field: &str) -> anyhow::Result<ByteBuf> {
+    let hex = value[field]
+        .as_str()
+        .ok_or_else(|| anyhow!("Missing proof field {}", field))?;
+
+    Ok(ByteBuf::from(hex::decode(hex)?))
+}
+
+fn to_v4(token: &Token) -> anyhow::Result<TokenV4> {
+    let value = serde_json::to_value(token)?;
+
+    let [mint_proofs] = value["token"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+    else {
+        bail!("V4 tokens can only hold proofs from a single mint");
+    };
+
+    let mut keysets: Vec<KeysetV4> = vec![];
+    for proof in mint_proofs["proofs"].as_array().into_iter().flatten() {
+        let keyset_id = hex_field(proof, "id")?;
+
+        let dleq = match proof.get("dleq").filter(|dleq| !dleq.is_null()) {
+            Some(dleq) => Some(DleqV4 {
+                e: hex_field(dleq, "e")?,
+                s: hex_field(dleq, "s")?,
+                r: hex_field(dleq, "r")?,
+            }),
+            None => None,
+        };
+        let witness = match proof.get("witness") {
+            Some(Value::String(witness)) => Some(witness.clone()),
+            Some(Value::Null) | None => None,
+            Some(witness) => Some(witness.to_string()),
+        };
+
+        let proof = ProofV4 {
+            amount: proof["amount"]
+                .as_u64()
+                .ok_or_else(|| anyhow!("Missing proof amount"))?,
+            secret: proof["secret"]
+                .as_str()
+                .ok_or_else(|| anyhow!("Missing proof secret"))?
+                .to_string(),
+            c: hex_field(proof, "C")?,
+            dleq,
+            witness,
+        };
+
+        match keysets
+            .iter_mut()
+            .find(|keyset| keyset.keyset_id == keyset_id)
+        {
+            Some(keyset) => keyset.proofs.push(proof),
+            None => keysets.push(KeysetV4 {
+                keyset_id,
+                proofs: vec![proof],
+            }),
+        }
+    }
+
+    Ok(TokenV4 {
+        mint_url: mint_proofs["mint"].as_str().unwrap_or_default().to_string(),
+        unit: value["unit"].as_str().unwrap_or("sat").to_string(),
+        memo: value["memo"].as_str().map(str::to_string),
+        keysets,
+    })
+}
+
+fn from_v4(token: TokenV4) -> anyhow::Result<Token> {
+    let proofs: Vec<Value> = token
+        .keysets
+        .iter()
+        .flat_map(|keyset| {
+            keyset.proofs.iter().map(|proof| {
+                let mut value = json!({
+                    "amount": proof.amount,
+                    "id": hex::encode(&keyset.keyset_id),
+                    "secret": proof.secret,
+                    "C": hex::encode(&proof.c),
+                });
+                if let Some(dleq) = &proof.dleq {
+                    value["dleq"] = json!({
+                        "e": hex::encode(&dleq.e),
+                        "s": hex::encode(&dleq.s),
+                        "r": hex::encode(&dleq.r),
+                    });
+                }
+                if let Some(witness) = &proof.witness {
+                    value["witness"] = json!(witness);
+                }
+                value
+            })
+        })
+        .collect();
+
+    let value = json!({
+        "token": [{ "mint": token.mint_url, "proofs": proofs }],
+        "unit": token.unit,
+        "memo": token.memo,
+    });
+
+    Ok(serde_json::from_value(value)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use cdk::nuts::SecretKey;
+
+    use super::*;
+
+    /// Token of proofs worth `amounts` from each of `mints`
+    fn token(mints: &[&str], amounts: &[u64]) -> Token {
+        let c = SecretKey::generate().public_key().to_hex();
+        let token: Vec<_> = mints
+            .iter()
+            .map(|mint| {
+                let proofs: Vec<_> = amounts
+                    .iter()
+                    .map(|amount| {
+                        json!({
+                            "amount": amount,
+                            "id": "009a1f293253e41e",
+                            "secret": hex::encode(rand::random::<[u8; 32]>()),
+                            "C": c,
+                        })
+                    })
+                    .collect();
+                json!({ "mint": mint, "proofs": proofs })
+            })
+            .collect();
+
+        serde_json::from_value(json!({
+            "token": token,
+            "unit": "sat",
+            "memo": "Thank you",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn parse_rejects_other_input() {
+        assert!(parse("").is_err());
+        assert!(parse("lnbc1").is_err());
+        assert!(parse("cashuAnot-base64").is_err());
+        assert!(parse("cashuBnot-cbor").is_err());
+    }
+
+    #[test]
+    fn parse_v3_token() {
+        let token = token(&["https://mint.example.com"], &[2, 8]);
+        let encoded = format!("  {}\n", encode(&token, TokenFormat::V3).unwrap());
+
+        assert_eq!(parse(&encoded).unwrap().to_string(), token.to_string());
+    }
+
+    #[test]
+    fn summary_of_token() {
+        let summary = summary(&token(&["https://mint.example.com"], &[2, 8]));
+
+        assert_eq!(summary.amount, 10);
+        assert_eq!(summary.proofs, 2);
+        assert_eq!(summary.unit, CurrencyUnit::Sat);
+        assert_eq!(
+            summary.mints,
+            vec![UncheckedUrl::from("https://mint.example.com".to_string())]
+        );
+        assert_eq!(summary.memo.as_deref(), Some("Thank you"));
+        assert!(summary.conditions.is_empty());
+    }
+
+    #[test]
+    fn v4_token_round_trips() {
+        let token = token(&["https://mint.example.com"], &[1, 4, 16]);
+        let encoded = encode(&token, TokenFormat::V4).unwrap();
+        assert!(encoded.starts_with(V4_PREFIX));
+
+        let parsed = parse(&encoded).unwrap();
+
+        assert_eq!(parsed.to_string(), token.to_string());
+    }
+
+    #[test]
+    fn v4_token_parses_with_padding() {
+        let token = token(&["https://mint.example.com"], &[1]);
+        let encoded = encode(&token, TokenFormat::V4).unwrap();
+        let padding = "=".repeat((4 - (encoded.len() - V4_PREFIX.len()) % 4) % 4);
+
+        let parsed = parse(&format!("{}{}", encoded, padding)).unwrap();
+
+        assert_eq!(parsed.to_string(), token.to_string());
+    }
+
+    #[test]
+    fn v4_token_holds_a_single_mint() {
+        let token = token(&["https://a.example.com", "https://b.example.com"], &[1]);
+
+        assert!(encode(&token, TokenFormat::V4).is_err());
+        assert_eq!(summary(&token).amount, 2);
+    }
+}
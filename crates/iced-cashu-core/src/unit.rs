@@ -0,0 +1,226 @@
+use cdk::nuts::CurrencyUnit;
+use cdk::UncheckedUrl;
+
+use crate::fees;
+
+/// Units counted in cents, shown with two decimals
+const FIAT_UNITS: [&str; 2] = ["usd", "eur"];
+
+/// Spaces accepted between digit groups whatever the separator, as users
+/// type them
+const SPACES: [char; 3] = [' ', '\u{a0}', '\u{202f}'];
+
+/// Separators numbers are written with in a locale, `1,234,567.89` by default
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    /// Between groups of three digits
+    pub group: char,
+    pub decimal: char,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            group: ',',
+            decimal: '.',
+        }
+    }
+}
+
+impl NumberFormat {
+    /// `value` with its digits grouped in threes
+    pub fn integer(&self, value: u64) -> String {
+        let digits = value.to_string();
+        let mut grouped = String::with_capacity(digits.len() * 4 / 3);
+        for (index, digit) in digits.chars().enumerate() {
+            if index > 0 && (digits.len() - index) % 3 == 0 {
+                grouped.push(self.group);
+            }
+            grouped.push(digit);
+        }
+
+        grouped
+    }
+
+    /// `value` rounded to `decimals` places, negative values as zero
+    pub fn decimal(&self, value: f64, decimals: u32) -> String {
+        let scale = 10u64.pow(decimals);
+        let scaled = (value.max(0.0) * scale as f64).round() as u64;
+        if decimals == 0 {
+            return self.integer(scaled);
+        }
+
+        format!(
+            "{}{}{:0width$}",
+            self.integer(scaled / scale),
+            self.decimal,
+            scaled % scale,
+            width = decimals as usize
+        )
+    }
+
+    /// Whole number typed with or without group separators
+    pub fn parse_integer(&self, input: &str) -> Option<u64> {
+        self.strip_groups(input)?.parse().ok()
+    }
+
+    /// Number typed with the locale's decimal separator
+    pub fn parse_decimal(&self, input: &str) -> Option<f64> {
+        let input = self.strip_groups(input)?.replace(self.decimal, ".");
+        let value: f64 = input.parse().ok()?;
+
+        value.is_finite().then_some(value)
+    }
+
+    /// Whole and fractional digits typed, `None` unless they are all digits
+    fn split_decimal<'a>(&self, input: &'a str) -> Option<(&'a str, &'a str)> {
+        let (whole, fraction) = input.split_once(self.decimal).unwrap_or((input, ""));
+        let digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+
+        (digits(whole) && digits(fraction) && !(whole.is_empty() && fraction.is_empty()))
+            .then_some((whole, fraction))
+    }
+
+    /// `input` trimmed and without group separators, `None` if they don't
+    /// separate groups of three digits
+    fn strip_groups(&self, input: &str) -> Option<String> {
+        let input = input.trim();
+        let whole = input.split(self.decimal).next().unwrap_or_default();
+        let is_group = |c: char| c == self.group || SPACES.contains(&c);
+
+        let mut groups = whole.split(is_group);
+        groups.next()?;
+        if !groups.all(|group| group.len() == 3) {
+            return None;
+        }
+
+        Some(input.chars().filter(|c| !is_group(*c)).collect())
+    }
+}
+
+/// `amount` of `unit` for display, e.g. `21 sats` or `1.50 USD`
+pub fn format_amount(amount: u64, unit: &CurrencyUnit) -> String {
+    format_amount_with(amount, unit, &NumberFormat::default())
+}
+
+/// `amount` of `unit` written with the separators of `format`
+pub fn format_amount_with(amount: u64, unit: &CurrencyUnit, format: &NumberFormat) -> String {
+    match unit.to_string().as_str() {
+        "sat" => format!("{} sats", format.integer(amount)),
+        "msat" => format!("{} msats", format.integer(amount)),
+        code if FIAT_UNITS.contains(&code) => {
+            format!(
+                "{}{}{:02} {}",
+                format.integer(amount / 100),
+                format.decimal,
+                amount % 100,
+                code.to_uppercase()
+            )
+        }
+        code => format!("{} {}", format.integer(amount), code),
+    }
+}
+
+/// Amount typed in `unit`, fiat units taking up to two decimals
+pub fn parse_amount(input: &str, unit: &CurrencyUnit) -> Option<u64> {
+    parse_amount_with(input, unit, &NumberFormat::default())
+}
+
+/// Amount typed in `unit` with the separators of `format`
+pub fn parse_amount_with(input: &str, unit: &CurrencyUnit, format: &NumberFormat) -> Option<u64> {
+    if !FIAT_UNITS.contains(&unit.to_string().as_str()) {
+        return format.parse_integer(input);
+    }
+
+    let input = format.strip_groups(input)?;
+    let (whole, cents) = format.split_decimal(&input)?;
+    if cents.len() > 2 {
+        return None;
+    }
+    let cents: u64 = format!("{:0<2}", cents).parse().ok()?;
+    let whole: u64 = match whole {
+        "" => 0,
+        whole => whole.parse().ok()?,
+    };
+
+    whole.checked_mul(100)?.checked_add(cents)
+}
+
+/// Units of the mint's active keysets, sats first
+pub async fn fetch_units(mint_url: &UncheckedUrl) -> anyhow::Result<Vec<CurrencyUnit>> {
+    let mut units: Vec<CurrencyUnit> = vec![];
+    for keyset in fees::fetch_keysets(mint_url).await? {
+        let unit = keyset.unit.parse()?;
+        if keyset.active && !units.contains(&unit) {
+            units.push(unit);
+        }
+    }
+    units.sort_by_key(|unit| *unit != CurrencyUnit::Sat);
+
+    Ok(units)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `1.234.567,89`, as written in much of Europe
+    const COMMA_DECIMAL: NumberFormat = NumberFormat {
+        group: '.',
+        decimal: ',',
+    };
+
+    #[test]
+    fn parse_sats_with_or_without_groups() {
+        assert_eq!(parse_amount("21", &CurrencyUnit::Sat), Some(21));
+        assert_eq!(
+            parse_amount(" 1,234,567 ", &CurrencyUnit::Sat),
+            Some(1_234_567)
+        );
+        assert_eq!(parse_amount("1 234", &CurrencyUnit::Sat), Some(1234));
+    }
+
+    #[test]
+    fn parse_sats_rejects_misplaced_groups_and_decimals() {
+        assert_eq!(parse_amount("1,23", &CurrencyUnit::Sat), None);
+        assert_eq!(parse_amount("1.5", &CurrencyUnit::Sat), None);
+        assert_eq!(parse_amount("", &CurrencyUnit::Sat), None);
+        assert_eq!(parse_amount("-5", &CurrencyUnit::Sat), None);
+    }
+
+    #[test]
+    fn parse_fiat_in_cents() {
+        assert_eq!(parse_amount("1.5", &CurrencyUnit::Usd), Some(150));
+        assert_eq!(parse_amount("1,000.05", &CurrencyUnit::Usd), Some(100_005));
+        assert_eq!(parse_amount(".25", &CurrencyUnit::Usd), Some(25));
+        assert_eq!(parse_amount("3", &CurrencyUnit::Usd), Some(300));
+    }
+
+    #[test]
+    fn parse_fiat_rejects_fractions_of_cents() {
+        assert_eq!(parse_amount("1.505", &CurrencyUnit::Usd), None);
+        assert_eq!(parse_amount(".", &CurrencyUnit::Usd), None);
+    }
+
+    #[test]
+    fn parse_with_locale_separators() {
+        assert_eq!(
+            parse_amount_with("1.234,5", &CurrencyUnit::Usd, &COMMA_DECIMAL),
+            Some(123_450)
+        );
+        assert_eq!(
+            parse_amount_with("1.234", &CurrencyUnit::Sat, &COMMA_DECIMAL),
+            Some(1234)
+        );
+    }
+
+    #[test]
+    fn format_amounts() {
+        assert_eq!(format_amount(1234, &CurrencyUnit::Sat), "1,234 sats");
+        assert_eq!(format_amount(150, &CurrencyUnit::Usd), "1.50 USD");
+        assert_eq!(
+            format_amount_with(123_456, &CurrencyUnit::Usd, &COMMA_DECIMAL),
+            "1.234,56 USD"
+        );
+    }
+}
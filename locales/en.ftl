@@ -0,0 +1,467 @@
+# English messages of the wallet interface, the fallback for every
+# other language
+
+getting-a-quote = Getting a quote...
+paying = Paying...
+creating-token = Creating token...
+minting = Minting...
+receiving = Receiving...
+checking-balance = Checking balance...
+payment-recovered = Payment recovered
+recovered-sats = { $amount } sats came back from interrupted payments
+invoice-paid = Invoice paid
+minted-sats = Minted { $amount } sats
+payment-sent = Payment sent
+paid-sats = Paid { $amount } sats
+token-received = Token received
+moved-sats-to = Moved { $amount } sats to { $swap_target }
+sent-sats-on-chain-in = Sent { $amount } sats on-chain in { $txid }
+sent-sats-on-chain = Sent { $amount } sats on-chain
+withdrawal-sent = Withdrawal sent
+retry-succeeded = Retry succeeded
+retry-finished = Finished the { $operation } of { $amount } sats
+inbox-token-body = { $amount } sats from { $sender }, open the wallet to claim
+payment-request-paid = Payment request paid
+received-sats = Received { $amount } sats
+nutzap-received = Nutzap received
+cashu = Cashu
+back-up-your-wallet = Back up your wallet
+back-up = Back up
+sats = sats
+info = Info
+add-mint = Add Mint
+transfer = Transfer
+withdraw = Withdraw
+balance-sats = { $balance } sats
+send-receive = Send / Receive
+history = History
+settings = Settings
+data-placeholder = Paste a token, invoice, LNURL, lightning address or payment request
+scan = Scan
+open-file = Open file
+claim = Claim
+continue = Continue
+pay = Pay
+unit = Unit:
+amount-to-send = Amount to send
+token-locked-to-contact = Token will be locked to { $name }'s key
+dont-lock = Don't lock
+memo-placeholder = Message to recipient (optional)
+create-token = Create Token
+amount-to-receive = Amount to receive
+create-invoice = Create Invoice
+deposit-on-chain = Deposit on-chain
+request-payment = Request payment
+home = Home
+between-and-sats = Between { $min_sats } and { $max_sats } sats
+amount-sats = Amount (sats)
+amount = Amount: { $amount }
+fee-reserve = Fee reserve: { $fee_reserve }
+input-fee = Input fee: { $input_fee }
+total = Total: { $total }
+mint = Mint: { $mint_url }
+description = Description: { $description }
+confirm = Confirm
+cancel = Cancel
+locked-to-contact = Locked to { $name }'s key
+message = Message: { $memo }
+deposit-to = Deposit { $amount } to
+waiting-for-the-deposit-to-confirm = Waiting for the deposit to confirm...
+expires-in = Expires in { $minutes }:{ $seconds }
+copy = Copy
+format = Format:
+part-of = Part { $part } of { $parts }
+save-as-file = Save as file
+export-as-paper = Export as paper
+token-saved-to = Token saved to { $path }
+recipient-npub = Recipient npub
+recent = Recent
+send-via-nostr = Send via Nostr
+sent = Sent
+delivery-failed = Delivery failed, no relay accepted the message
+starting-camera = Starting camera...
+scanned-parts = Scanned { $scanned } of { $total } parts, keep the code in view
+mint-url = Mint URL
+light-theme = Light theme:
+dark-theme = Dark theme:
+theme = Theme:
+follow-system = Follow system
+interface-scale = Interface scale:
+profile = Profile: { $profile }
+switch-profile = Switch profile
+default-mint = Default mint:
+quote-polling-seconds = Quote polling (seconds):
+up-to = up to
+qr-part-ms = QR part (ms):
+save = Save
+back-up-seed = Back up seed
+debug-log = Debug log
+decode = Decode
+backup-password = Backup password
+export-backup = Export backup
+backup-saved-to = Backup saved to { $path }
+proofs = Proofs
+wallet-connect = Wallet Connect
+contacts = Contacts
+check-proof-states = Check proof states
+proof-states-result = { $spent } sats spent and removed, { $pending } sats pending
+p2pk-public-key = P2PK public key: { $key }
+nostr-identity = Nostr identity: { $npub }
+nostr-key-derived = Derived from the seed words, restored with them
+nostr-key-stored = Imported or generated, not part of the seed words: keep a copy of its secret key
+nostr-secret-key = Nostr secret key (nsec or hex)
+generate-nostr-key = Generate new key
+use-seed-nostr-key = Use key from seed
+show-qr = Show QR
+hide-qr = Hide QR
+seed-storage = Seed storage:
+encrypt-database = Encrypt the wallet database with the password
+encrypt-database-hint = Applied the next time the wallet is unlocked
+use-passphrase = Protect the seed with a passphrase (25th word)
+passphrase = Passphrase
+confirm-passphrase = Confirm passphrase
+passphrase-warning = The same seed words with a different passphrase open a different, empty wallet. The passphrase is never stored: write it down with the words, without it the funds are lost.
+passphrases-differ = The passphrases do not match
+passphrase-unlock-hint = A mistyped passphrase opens a different, empty wallet
+run-maintenance = Run database maintenance
+maintenance-running = Checking, pruning and compacting the databases…
+integrity-check-passed = Integrity check passed
+integrity-check-failed = Integrity check found problems, the databases were left untouched: { $problems }
+spent-proofs-pruned = Pruned { $count } spent proofs
+old-records-pruned = Pruned { $count } settled tokens and expired quotes older than 90 days
+database-vacuumed = Compacted the databases
+database-size-change = Size: { $before } → { $after }
+megabytes = { $size } MB
+fiat-currency = Fiat currency:
+proof-split = Proof split:
+split-denominations = Sats, e.g. 1,2,4,8
+number-of-proofs = Number of proofs
+new-pin = New PIN
+pin = PIN
+change-pin = Change PIN
+set-pin = Set PIN
+remove-pin = Remove PIN
+lock-after = Lock after (minutes, 0 = never):
+pin-threshold = PIN for payments above (sats):
+never = Never
+desktop-notifications = Desktop notifications
+minimize-to-tray-on-close = Minimize to tray on close
+receive-nutzaps = Receive nutzaps (NIP-61)
+auto-swap = Move tokens received at other mints to the default mint
+auto-withdraw = Auto-withdraw
+auto-withdraw-enabled = Withdraw the balance above a threshold to a lightning address, limiting what the mints hold
+auto-withdraw-above = when the balance is above (sats)
+auto-withdrawing = Withdrawing the excess balance...
+auto-withdraw-description = Auto-withdraw to { $address }
+auto-withdrew = Sent { $amount } sats to { $address }
+auto-withdraw-paused = Auto-withdraw paused
+auto-withdraw-paused-because = Auto-withdraw paused: { $err }
+auto-rotate-keysets = Swap funds off keysets a mint retires automatically
+on-retired-keysets = { $amount } sats on retired keysets
+swap = Swap
+nostr-relays = Nostr relays:
+relays-placeholder = wss://relay.example.com, ...
+socks5-proxy = SOCKS5 proxy:
+test-connection = Test connection
+connect-directly-to = Connect directly to { $mint_url }
+reachable-in-ms = { $mint_url }: reachable in { $millis } ms
+proxy-restart-note = Proxy changes apply after restarting the wallet
+rate-source = Rate source:
+seed-backup-note = Write these words down and keep them somewhere safe
+verify = Verify
+seed-verify-note = Enter the requested words from your seed
+word = Word #{ $number }
+history-search = Search notes, labels, quotes
+label = Label
+clear = Clear
+mint-filter = Mint
+direction = Direction
+status = Status
+date-from = From (YYYY-MM-DD)
+date-to = To (YYYY-MM-DD)
+min-amount = Min amount
+max-amount = Max amount
+previous = Previous
+page-of = Page { $page } of { $pages }
+next = Next
+sent-tokens = Sent tokens
+stats = Stats
+export = Export
+history-saved-to = History saved to { $path }
+note = Note
+labels-comma-separated = Labels, comma separated
+back = Back
+waiting-for-sats = Waiting for { $amount } sats
+waiting-for-payment = Waiting for payment
+amount-sats-optional = Amount (sats, optional)
+description-optional = Description (optional)
+request-payment-note = Payments are accepted from your mints and sent over nostr to { $relays }
+create-request = Create request
+amount-in-sats = Amount: { $amount } sats
+input-fee-sats = Input fee: { $fee } sats
+total-sats = Total: { $total } sats
+locked-to = Locked to { $data }
+sent-via-to = Sent via { $kind } to { $target }
+name = Name
+lightning-address = Lightning address
+npub = npub
+p2pk-public-key-hex = P2PK public key (hex)
+add-contact = Add contact
+budget-sats-30-days = Budget (sats / 30 days)
+relay = Relay
+create-connection = Create connection
+nwc-paste-note = Paste this into the app you want to connect:
+export-logs = Export logs
+logs-saved-to = Logs saved to { $path }
+decode-placeholder = Token, invoice, offer, LNURL or payment request
+loading-mint-info = Loading mint info...
+from = From: { $active_mint }
+to = To:
+preview = Preview
+fee-reserve-sats = Fee reserve: { $fee_reserve } sats
+paying-from-the-source-mint = Paying from the source mint...
+minting-at-the-destination-mint = Minting at the destination mint...
+bitcoin-address = Bitcoin address
+network-fee-sats = Network fee: { $fee_reserve } sats
+enter-your-seed-words = Enter your seed words
+new-password = New password
+words = { $word_count } words
+invalid-words = Invalid words: { $words }
+restore = Restore
+or-import-a-backup-file = Or import a backup file
+import-backup = Import backup
+password = Password
+unlock = Unlock
+restore-wallet = Restore Wallet
+welcome-to-iced-cashu = Welcome to Iced Cashu
+choose-password = Choose a password to encrypt your wallet's seed with
+create-a-new-wallet = Create a new wallet
+restore-from-seed-or-backup = Restore from seed or backup
+dismiss = Dismiss
+claim-token-for-sats = Claim token for { $amount } sats?
+pay-this-invoice-for-sats = Pay this invoice for { $amount } sats?
+pay-this-invoice = Pay this invoice?
+pay-this-offer-for-sats = Pay this offer for { $amount } sats?
+pay-this-offer = Pay this offer?
+pay-this-lnurl = Pay this LNURL?
+pay-this-request-for-sats = Pay this request for { $amount } sats?
+pay-this-payment-request = Pay this payment request?
+open = Open
+leave-prompt = Still running, its result will wait in the pending panel
+leave = Leave
+stay = Stay
+type-a-command = Type a command
+wallet-locked = Wallet locked
+enter-your-pin-to-continue = Enter your PIN to continue
+quotes-from-your-mints = Quotes from your mints
+route-quote = { $mint_url }: { $total } sats ({ $fee_reserve } sats fee reserve, { $input_fee } sats input fee){ $warning }
+more-than-its-balance = , more than its balance
+selected = Selected
+use = Use
+multi-path-note = No single mint can pay this, it will be split over your mints
+multi-path-leg = { $mint_url }: { $amount } sats, up to { $fees } sats in fees
+onboarding-backup = Step 1 of 3: Back up your seed
+onboarding-backup-note = Write these words down, they are the only way to recover your funds
+i-wrote-them-down = I wrote them down
+later = Later
+onboarding-mint = Step 2 of 3: Choose a mint
+onboarding-mint-note = The mint holds the ecash backing your balance, only use mints you trust
+add-another-mint-url = Add another mint URL
+add = Add
+use-this-mint = Use this mint
+onboarding-pin = Step 3 of 3: Set a PIN
+onboarding-pin-note = A PIN locks the wallet when idle and can guard payments
+pin-set = PIN set
+finish = Finish
+skip-and-finish = Skip and finish
+profile-label = Profile:
+new-profile = New profile
+create = Create
+lnurl-or-lightning-address = LNURL or lightning address
+payment-request-for-sats = Payment request for { $amount } sats
+payment-request-any-amount = Payment request for any amount, enter it below
+no-completed-transactions-in = No completed transactions in { $unit }
+balance-over-time = Balance over time ({ $unit })
+monthly-totals = Monthly totals
+month-in = in { $inflow }
+month-out = out { $outflow }
+send = Send
+receive = Receive
+paste-from-clipboard = Paste from clipboard
+open-history = Open history
+open-stats = Open stats
+add-mint-action = Add mint
+mint-info = Mint info
+show-seed = Show seed
+switch-mint-to = Switch mint to { $mint_url }
+ready = Ready
+pending-toggle = { $arrow } Pending ({ $count })
+receiving-at = Receiving { $amount } at { $mint_url }
+show = Show
+check = Check
+paying-amount-state = Paying { $amount }: { $state }
+withdrawing-sats-on-chain = Withdrawing { $amount } sats on-chain: { $state }
+local-control-api = Local control API
+port = Port:
+copy-auth-token = Copy auth token
+mint-unreachable = { $mint_url } is unreachable, payments may fail
+token-is-locked-to = Token is locked to:
+key-held = key held
+key-required = key required
+signing-key-hex-or-nsec = Signing key (hex or nsec)
+import-key = Import key
+offline = (offline)
+fee = fee { $fee }
+nostr-delivery = nostr { $delivery } to { $npub }
+details = Details
+invalid-token = Invalid token: { $err }
+proofs-label = Proofs:
+proof-from-keyset = { $amount } from keyset { $keyset_id }{ $dleq }
+with-dleq-proof = , with DLEQ proof
+payment-hash = Payment hash: { $payment_hash }
+invoice-has-expired = Invoice has expired
+lnurl-pointing-to = LNURL pointing to { $url }
+any-amount-in = Any amount in { $unit }
+id = Id: { $id }
+mints-any = Mints: any
+mints = Mints: { $mints }
+single-use = Single use
+locked-with-to = Locked with { $kind } to { $data }
+pay-over-to = Pay over { $kind } to { $target }
+token-for = Token for { $amount }
+token-unit = Unit: { $unit }
+token-proofs = Proofs: { $proofs }
+transaction-status = Status: { $status }
+date = Date: { $timestamp }
+memo = Memo: { $memo }
+quote = Quote: { $reference }
+transaction-fees = Fee reserve: { $fee_reserve }, actual fee: { $fee }, returned: { $returned }
+transaction-fee = Fee: { $fee }
+transaction-nostr = Nostr { $delivery } to { $npub }
+signatures-verified = signatures verified
+signatures-unverified = signatures unverified
+signatures-invalid = signatures invalid!
+custom = Custom
+max = Max
+received-sats-from = Received { $amount } sats from { $sender }
+retrying = retrying...
+retrying-in-s = retrying in { $seconds }s
+not-retrying = not retrying
+failed-of-sats = Failed { $operation } of { $amount } sats, { $status }
+retry-now = Retry now
+discard = Discard
+token-created = Token created
+token-quote-ready = Token quote ready
+payment-quote-ready = Payment quote ready
+reclaim = Reclaim
+delete = Delete
+of-sats-left = { $remaining } of { $budget } sats left
+keyset-proofs = { $keyset_id }  { $amount } sats × { $count }
+proof-count = { $count } proofs
+consolidate-fee-sats = Consolidate (fee { $consolidation_fee } sats)
+version = Version: { $version }
+supported-nuts = Supported NUTs: { $nuts }
+terms-of-service = Terms of service: { $url }
+mint-unreachable-error = Could not reach the mint: { $err }
+no-input-fees = No input fees
+input-fee-ppk = Input fee: up to { $ppk } sat per 1000 proofs spent
+input-fees-unknown = Input fees unknown
+loading-mint = Loading mint...
+keysets = Keysets
+keyset-info = { $keyset_id } ({ $unit }, { $state }), input fee { $ppk } ppk
+keyset-active = active
+keyset-inactive = inactive
+pending-quotes = Pending quotes
+no-pending-quotes = No pending quotes
+pending-quote = { $amount }, quote { $quote_id }
+set-default-mint = Set as default
+swap-away = Move funds away
+remove-mint = Remove mint
+remove-mint-title = Remove { $mint_url }
+mint-still-holds = This mint still holds { $balance } sats. Move them to another mint or export them as a token before removing it.
+move-funds = Move funds
+export-funds-as-token = Export as token
+remove-mint-forfeit = { $balance } sats of fee change are too little to move and are given up on removal
+mint-emptied = The mint is empty and can be removed
+review-mint = Review this mint before using it
+custodian-warning = Ecash is a bearer asset and the mint is its custodian. The mint can refuse to redeem your ecash or disappear at any time, only keep with it what you can afford to lose.
+custodian-acknowledge = I understand the mint holds the funds backing this ecash
+add-mint-and-claim = Add mint and claim
+discover-mints = Discover mints
+discovering-mints = Looking for mints on nostr...
+no-mints-discovered = No mints found on your relays
+mint-discovery-failed = Could not search nostr: { $err }
+mint-rating = { $rating }/5 from { $reviews } reviews
+mint-reviews = { $reviews } reviews
+mint-units = Units: { $units }
+units-unknown = Units unknown
+added = Added
+refresh = Refresh
+claim-and-move-to = Claim and move to { $swap_target }
+amount-in = Amount in { $currency }
+offer-any-amount = Any amount, enter it below
+expires = Expires: { $expires_at }
+any-amount = Any amount
+payee = Payee: { $payee }
+language = Language:
+split-default = Default
+split-denominations-mode = Denominations
+split-target-count = Target count
+tray-receive = Receive
+tray-pay = Pay
+tray-show = Show window
+tray-tooltip = Cashu Wallet: { $balance } sats
+scheduled-payments = Scheduled payments
+no-scheduled-payments = No payments scheduled
+schedule-a-payment = Schedule a payment
+or-send-ecash-to-a-contact = or send ecash to a contact
+first-payment = First payment:
+scheduled-payments-hint = Payments are made while the app runs, keep it in the tray with minimize to tray to have them made with the window closed
+schedule = Schedule
+skip = Skip
+once = Once
+daily = Daily
+weekly = Weekly
+monthly = Monthly
+ecash-to = ecash to { $contact }
+deleted-contact = a deleted contact
+scheduled-payment-summary = { $amount } sats to { $recipient }, { $recurrence }
+last-run-failed = Last run failed: { $err }
+scheduled-payment-to = Scheduled payment to { $recipient }
+scheduled-payment-made = Scheduled payment made
+scheduled-payment-sent = Paid { $amount } sats as scheduled
+scheduled-payment-failed = Scheduled payment failed
+point-of-sale = Point of sale
+pos-amount = { $amount } sats
+tip = Tip
+no-tip = No tip
+charge = Charge
+paid = PAID
+next-customer = Next customer
+pos-today = Today: { $amount } sats from { $sales } sales
+order-note = Order note (optional)
+receipt = Receipt
+receipt-amount = Amount: { $amount } sats
+receipt-tip = Tip included: { $amount } sats
+receipt-fiat = Value: { $amount } { $currency }
+receipt-time = Time: { $time }
+receipt-note = Note: { $note }
+save-pdf = Save as PDF
+receipt-saved-to = Receipt saved to { $path }
+export-sales = Export sales (CSV)
+sales-saved-to = Sales saved to { $path }
+lock-to-hash = Lock to a hash (HTLC)
+hash-hex = SHA-256 hash (hex)
+generate-preimage = Generate preimage
+preimage = Preimage: { $preimage }
+locked-to-hash = Locked to the preimage of { $hash }
+token-is-hash-locked-to = Token is locked to the preimages of:
+preimage-held = preimage held
+preimage-required = preimage required
+preimage-hex = Preimage (hex)
+refundable-after = Refund to me after
+hours-optional = hours (optional)
+refundable-from = Refundable from { $time }
+refund-now-available = Refund now available
+refund-available-body = { $amount } sats can be taken back from a locked token
+locked-to-recipient = Locked to the recipient
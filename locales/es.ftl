@@ -0,0 +1,467 @@
+# Spanish messages of the wallet interface, missing ones fall back to
+# English
+
+getting-a-quote = Obteniendo cotización...
+paying = Pagando...
+creating-token = Creando token...
+minting = Acuñando...
+receiving = Recibiendo...
+checking-balance = Consultando saldo...
+payment-recovered = Pago recuperado
+recovered-sats = { $amount } sats volvieron de pagos interrumpidos
+invoice-paid = Factura pagada
+minted-sats = Acuñados { $amount } sats
+payment-sent = Pago enviado
+paid-sats = Pagados { $amount } sats
+token-received = Token recibido
+moved-sats-to = Movidos { $amount } sats a { $swap_target }
+sent-sats-on-chain-in = Enviados { $amount } sats on-chain en { $txid }
+sent-sats-on-chain = Enviados { $amount } sats on-chain
+withdrawal-sent = Retiro enviado
+retry-succeeded = Reintento completado
+retry-finished = Completada la operación { $operation } de { $amount } sats
+inbox-token-body = { $amount } sats de { $sender }, abre la billetera para reclamarlos
+payment-request-paid = Solicitud de pago pagada
+received-sats = Recibidos { $amount } sats
+nutzap-received = Nutzap recibido
+cashu = Cashu
+back-up-your-wallet = Haz una copia de seguridad de tu billetera
+back-up = Copia de seguridad
+sats = sats
+info = Info
+add-mint = Añadir mint
+transfer = Transferir
+withdraw = Retirar
+balance-sats = { $balance } sats
+send-receive = Enviar / Recibir
+history = Historial
+settings = Ajustes
+data-placeholder = Pega un token, factura, LNURL, dirección lightning o solicitud de pago
+scan = Escanear
+open-file = Abrir archivo
+claim = Reclamar
+continue = Continuar
+pay = Pagar
+unit = Unidad:
+amount-to-send = Cantidad a enviar
+token-locked-to-contact = El token se bloqueará con la clave de { $name }
+dont-lock = No bloquear
+memo-placeholder = Mensaje para el destinatario (opcional)
+create-token = Crear token
+amount-to-receive = Cantidad a recibir
+create-invoice = Crear factura
+deposit-on-chain = Depositar on-chain
+request-payment = Solicitar pago
+home = Inicio
+between-and-sats = Entre { $min_sats } y { $max_sats } sats
+amount-sats = Cantidad (sats)
+amount = Cantidad: { $amount }
+fee-reserve = Reserva para comisiones: { $fee_reserve }
+input-fee = Comisión de entrada: { $input_fee }
+total = Total: { $total }
+mint = Mint: { $mint_url }
+description = Descripción: { $description }
+confirm = Confirmar
+cancel = Cancelar
+locked-to-contact = Bloqueado con la clave de { $name }
+message = Mensaje: { $memo }
+deposit-to = Deposita { $amount } en
+waiting-for-the-deposit-to-confirm = Esperando a que se confirme el depósito...
+expires-in = Caduca en { $minutes }:{ $seconds }
+copy = Copiar
+format = Formato:
+part-of = Parte { $part } de { $parts }
+save-as-file = Guardar como archivo
+export-as-paper = Exportar en papel
+token-saved-to = Token guardado en { $path }
+recipient-npub = npub del destinatario
+recent = Recientes
+send-via-nostr = Enviar por Nostr
+sent = Enviado
+delivery-failed = Falló la entrega, ningún relay aceptó el mensaje
+starting-camera = Iniciando cámara...
+scanned-parts = Escaneadas { $scanned } de { $total } partes, mantén el código a la vista
+mint-url = URL del mint
+light-theme = Tema claro:
+dark-theme = Tema oscuro:
+theme = Tema:
+follow-system = Seguir al sistema
+interface-scale = Escala de la interfaz:
+profile = Perfil: { $profile }
+switch-profile = Cambiar de perfil
+default-mint = Mint predeterminado:
+quote-polling-seconds = Consulta de cotizaciones (segundos):
+up-to = hasta
+qr-part-ms = Parte del QR (ms):
+save = Guardar
+back-up-seed = Copia de la semilla
+debug-log = Registro de depuración
+decode = Decodificar
+backup-password = Contraseña de la copia
+export-backup = Exportar copia
+backup-saved-to = Copia guardada en { $path }
+proofs = Pruebas
+wallet-connect = Wallet Connect
+contacts = Contactos
+check-proof-states = Comprobar pruebas
+proof-states-result = { $spent } sats gastados y eliminados, { $pending } sats pendientes
+p2pk-public-key = Clave pública P2PK: { $key }
+nostr-identity = Identidad de Nostr: { $npub }
+nostr-key-derived = Derivada de las palabras de la semilla, se recupera con ellas
+nostr-key-stored = Importada o generada, no forma parte de la semilla: guarda una copia de su clave secreta
+nostr-secret-key = Clave secreta de Nostr (nsec o hex)
+generate-nostr-key = Generar una clave nueva
+use-seed-nostr-key = Usar la clave de la semilla
+show-qr = Mostrar QR
+hide-qr = Ocultar QR
+seed-storage = Almacenamiento de la semilla:
+encrypt-database = Cifrar la base de datos del monedero con la contraseña
+encrypt-database-hint = Se aplica la próxima vez que se desbloquee el monedero
+use-passphrase = Proteger la semilla con una frase de contraseña (palabra 25)
+passphrase = Frase de contraseña
+confirm-passphrase = Confirmar la frase de contraseña
+passphrase-warning = Las mismas palabras con otra frase de contraseña abren un monedero distinto y vacío. La frase nunca se guarda: anótala junto a las palabras, sin ella se pierden los fondos.
+passphrases-differ = Las frases de contraseña no coinciden
+passphrase-unlock-hint = Una frase de contraseña mal escrita abre un monedero distinto y vacío
+run-maintenance = Ejecutar el mantenimiento de la base de datos
+maintenance-running = Comprobando, depurando y compactando las bases de datos…
+integrity-check-passed = Comprobación de integridad superada
+integrity-check-failed = La comprobación de integridad encontró problemas, las bases de datos no se han tocado: { $problems }
+spent-proofs-pruned = { $count } pruebas gastadas eliminadas
+old-records-pruned = { $count } tokens liquidados y cotizaciones caducadas de hace más de 90 días eliminados
+database-vacuumed = Bases de datos compactadas
+database-size-change = Tamaño: { $before } → { $after }
+megabytes = { $size } MB
+fiat-currency = Moneda fiat:
+proof-split = División de pruebas:
+split-denominations = Sats, p. ej. 1,2,4,8
+number-of-proofs = Número de pruebas
+new-pin = Nuevo PIN
+pin = PIN
+change-pin = Cambiar PIN
+set-pin = Establecer PIN
+remove-pin = Quitar PIN
+lock-after = Bloquear tras (minutos, 0 = nunca):
+pin-threshold = PIN para pagos mayores de (sats):
+never = Nunca
+desktop-notifications = Notificaciones de escritorio
+minimize-to-tray-on-close = Minimizar a la bandeja al cerrar
+receive-nutzaps = Recibir nutzaps (NIP-61)
+auto-swap = Mover los tokens recibidos en otros mints al mint predeterminado
+auto-withdraw = Retiro automático
+auto-withdraw-enabled = Retirar el saldo por encima de un umbral a una dirección lightning, limitando lo que custodian las mints
+auto-withdraw-above = cuando el saldo supere (sats)
+auto-withdrawing = Retirando el saldo sobrante...
+auto-withdraw-description = Retiro automático a { $address }
+auto-withdrew = Enviados { $amount } sats a { $address }
+auto-withdraw-paused = Retiro automático en pausa
+auto-withdraw-paused-because = Retiro automático en pausa: { $err }
+auto-rotate-keysets = Cambiar automáticamente los fondos de keysets que un mint retira
+on-retired-keysets = { $amount } sats en keysets retirados
+swap = Cambiar
+nostr-relays = Relays de Nostr:
+relays-placeholder = wss://relay.example.com, ...
+socks5-proxy = Proxy SOCKS5:
+test-connection = Probar conexión
+connect-directly-to = Conectar directamente a { $mint_url }
+reachable-in-ms = { $mint_url }: accesible en { $millis } ms
+proxy-restart-note = Los cambios del proxy se aplican al reiniciar la billetera
+rate-source = Fuente del tipo de cambio:
+seed-backup-note = Apunta estas palabras y guárdalas en un lugar seguro
+verify = Verificar
+seed-verify-note = Introduce las palabras indicadas de tu semilla
+word = Palabra n.º { $number }
+history-search = Buscar notas, etiquetas, cotizaciones
+label = Etiqueta
+clear = Limpiar
+mint-filter = Mint
+direction = Dirección
+status = Estado
+date-from = Desde (AAAA-MM-DD)
+date-to = Hasta (AAAA-MM-DD)
+min-amount = Cantidad mínima
+max-amount = Cantidad máxima
+previous = Anterior
+page-of = Página { $page } de { $pages }
+next = Siguiente
+sent-tokens = Tokens enviados
+stats = Estadísticas
+export = Exportar
+history-saved-to = Historial guardado en { $path }
+note = Nota
+labels-comma-separated = Etiquetas, separadas por comas
+back = Atrás
+waiting-for-sats = Esperando { $amount } sats
+waiting-for-payment = Esperando el pago
+amount-sats-optional = Cantidad (sats, opcional)
+description-optional = Descripción (opcional)
+request-payment-note = Se aceptan pagos de tus mints, enviados por nostr a { $relays }
+create-request = Crear solicitud
+amount-in-sats = Cantidad: { $amount } sats
+input-fee-sats = Comisión de entrada: { $fee } sats
+total-sats = Total: { $total } sats
+locked-to = Bloqueado con { $data }
+sent-via-to = Enviado por { $kind } a { $target }
+name = Nombre
+lightning-address = Dirección lightning
+npub = npub
+p2pk-public-key-hex = Clave pública P2PK (hex)
+add-contact = Añadir contacto
+budget-sats-30-days = Presupuesto (sats / 30 días)
+relay = Relay
+create-connection = Crear conexión
+nwc-paste-note = Pega esto en la aplicación que quieras conectar:
+export-logs = Exportar registros
+logs-saved-to = Registros guardados en { $path }
+decode-placeholder = Token, factura, oferta, LNURL o solicitud de pago
+loading-mint-info = Cargando información del mint...
+from = Desde: { $active_mint }
+to = A:
+preview = Vista previa
+fee-reserve-sats = Reserva para comisiones: { $fee_reserve } sats
+paying-from-the-source-mint = Pagando desde el mint de origen...
+minting-at-the-destination-mint = Acuñando en el mint de destino...
+bitcoin-address = Dirección de bitcoin
+network-fee-sats = Comisión de red: { $fee_reserve } sats
+enter-your-seed-words = Introduce las palabras de tu semilla
+new-password = Nueva contraseña
+words = { $word_count } palabras
+invalid-words = Palabras no válidas: { $words }
+restore = Restaurar
+or-import-a-backup-file = O importa un archivo de copia
+import-backup = Importar copia
+password = Contraseña
+unlock = Desbloquear
+restore-wallet = Restaurar billetera
+welcome-to-iced-cashu = Bienvenido a Iced Cashu
+choose-password = Elige una contraseña para cifrar la semilla de tu billetera
+create-a-new-wallet = Crear una billetera nueva
+restore-from-seed-or-backup = Restaurar desde semilla o copia
+dismiss = Descartar
+claim-token-for-sats = ¿Reclamar el token de { $amount } sats?
+pay-this-invoice-for-sats = ¿Pagar esta factura de { $amount } sats?
+pay-this-invoice = ¿Pagar esta factura?
+pay-this-offer-for-sats = ¿Pagar esta oferta de { $amount } sats?
+pay-this-offer = ¿Pagar esta oferta?
+pay-this-lnurl = ¿Pagar este LNURL?
+pay-this-request-for-sats = ¿Pagar esta solicitud de { $amount } sats?
+pay-this-payment-request = ¿Pagar esta solicitud de pago?
+open = Abrir
+leave-prompt = Sigue en curso, su resultado esperará en el panel de pendientes
+leave = Salir
+stay = Quedarse
+type-a-command = Escribe un comando
+wallet-locked = Billetera bloqueada
+enter-your-pin-to-continue = Introduce tu PIN para continuar
+quotes-from-your-mints = Cotizaciones de tus mints
+route-quote = { $mint_url }: { $total } sats ({ $fee_reserve } sats de reserva para comisiones, { $input_fee } sats de comisión de entrada){ $warning }
+more-than-its-balance = , más que su saldo
+selected = Seleccionado
+use = Usar
+multi-path-note = Ningún mint puede pagarlo solo, se repartirá entre tus mints
+multi-path-leg = { $mint_url }: { $amount } sats, hasta { $fees } sats en comisiones
+onboarding-backup = Paso 1 de 3: Haz una copia de tu semilla
+onboarding-backup-note = Apunta estas palabras, son la única forma de recuperar tus fondos
+i-wrote-them-down = Ya las apunté
+later = Más tarde
+onboarding-mint = Paso 2 de 3: Elige un mint
+onboarding-mint-note = El mint custodia el ecash que respalda tu saldo, usa solo mints de confianza
+add-another-mint-url = Añadir la URL de otro mint
+add = Añadir
+use-this-mint = Usar este mint
+onboarding-pin = Paso 3 de 3: Establece un PIN
+onboarding-pin-note = Un PIN bloquea la billetera cuando está inactiva y puede proteger los pagos
+pin-set = PIN establecido
+finish = Terminar
+skip-and-finish = Omitir y terminar
+profile-label = Perfil:
+new-profile = Nuevo perfil
+create = Crear
+lnurl-or-lightning-address = LNURL o dirección lightning
+payment-request-for-sats = Solicitud de pago de { $amount } sats
+payment-request-any-amount = Solicitud de pago por cualquier cantidad, introdúcela abajo
+no-completed-transactions-in = No hay transacciones completadas en { $unit }
+balance-over-time = Saldo a lo largo del tiempo ({ $unit })
+monthly-totals = Totales mensuales
+month-in = entrada { $inflow }
+month-out = salida { $outflow }
+send = Enviar
+receive = Recibir
+paste-from-clipboard = Pegar del portapapeles
+open-history = Abrir historial
+open-stats = Abrir estadísticas
+add-mint-action = Añadir mint
+mint-info = Información del mint
+show-seed = Mostrar semilla
+switch-mint-to = Cambiar al mint { $mint_url }
+ready = Listo
+pending-toggle = { $arrow } Pendientes ({ $count })
+receiving-at = Recibiendo { $amount } en { $mint_url }
+show = Mostrar
+check = Comprobar
+paying-amount-state = Pagando { $amount }: { $state }
+withdrawing-sats-on-chain = Retirando { $amount } sats on-chain: { $state }
+local-control-api = API de control local
+port = Puerto:
+copy-auth-token = Copiar token de autenticación
+mint-unreachable = { $mint_url } no responde, los pagos pueden fallar
+token-is-locked-to = El token está bloqueado con:
+key-held = clave disponible
+key-required = clave necesaria
+signing-key-hex-or-nsec = Clave de firma (hex o nsec)
+import-key = Importar clave
+offline = (sin conexión)
+fee = comisión { $fee }
+nostr-delivery = nostr { $delivery } a { $npub }
+details = Detalles
+invalid-token = Token no válido: { $err }
+proofs-label = Pruebas:
+proof-from-keyset = { $amount } del keyset { $keyset_id }{ $dleq }
+with-dleq-proof = , con prueba DLEQ
+payment-hash = Hash del pago: { $payment_hash }
+invoice-has-expired = La factura ha caducado
+lnurl-pointing-to = LNURL que apunta a { $url }
+any-amount-in = Cualquier cantidad en { $unit }
+id = Id: { $id }
+mints-any = Mints: cualquiera
+mints = Mints: { $mints }
+single-use = Un solo uso
+locked-with-to = Bloqueado con { $kind } a { $data }
+pay-over-to = Pagar por { $kind } a { $target }
+token-for = Token de { $amount }
+token-unit = Unidad: { $unit }
+token-proofs = Pruebas: { $proofs }
+transaction-status = Estado: { $status }
+date = Fecha: { $timestamp }
+memo = Nota: { $memo }
+quote = Cotización: { $reference }
+transaction-fees = Reserva para comisiones: { $fee_reserve }, comisión real: { $fee }, devuelto: { $returned }
+transaction-fee = Comisión: { $fee }
+transaction-nostr = Nostr { $delivery } a { $npub }
+signatures-verified = firmas verificadas
+signatures-unverified = firmas sin verificar
+signatures-invalid = ¡firmas no válidas!
+custom = Personalizado
+max = Máx.
+received-sats-from = Recibidos { $amount } sats de { $sender }
+retrying = reintentando...
+retrying-in-s = reintento en { $seconds } s
+not-retrying = sin reintentos
+failed-of-sats = Falló { $operation } de { $amount } sats, { $status }
+retry-now = Reintentar ahora
+discard = Descartar
+token-created = Token creado
+token-quote-ready = Cotización del token lista
+payment-quote-ready = Cotización del pago lista
+reclaim = Recuperar
+delete = Eliminar
+of-sats-left = Quedan { $remaining } de { $budget } sats
+keyset-proofs = { $keyset_id }  { $amount } sats × { $count }
+proof-count = { $count } pruebas
+consolidate-fee-sats = Consolidar (comisión de { $consolidation_fee } sats)
+version = Versión: { $version }
+supported-nuts = NUTs admitidos: { $nuts }
+terms-of-service = Condiciones del servicio: { $url }
+mint-unreachable-error = No se pudo contactar con el mint: { $err }
+no-input-fees = Sin comisiones de entrada
+input-fee-ppk = Comisión de entrada: hasta { $ppk } sat por cada 1000 pruebas gastadas
+input-fees-unknown = Comisiones de entrada desconocidas
+loading-mint = Cargando mint...
+keysets = Keysets
+keyset-info = { $keyset_id } ({ $unit }, { $state }), comisión de entrada { $ppk } ppk
+keyset-active = activo
+keyset-inactive = inactivo
+pending-quotes = Cotizaciones pendientes
+no-pending-quotes = No hay cotizaciones pendientes
+pending-quote = { $amount }, cotización { $quote_id }
+set-default-mint = Usar por defecto
+swap-away = Mover los fondos
+remove-mint = Quitar mint
+remove-mint-title = Quitar { $mint_url }
+mint-still-holds = Este mint aún guarda { $balance } sats. Muévelos a otro mint o expórtalos como token antes de quitarlo.
+move-funds = Mover fondos
+export-funds-as-token = Exportar como token
+remove-mint-forfeit = { $balance } sats de cambio de comisiones son muy pocos para moverlos y se pierden al quitarlo
+mint-emptied = El mint está vacío y se puede quitar
+review-mint = Revisa este mint antes de usarlo
+custodian-warning = El ecash es un activo al portador y el mint es su custodio. El mint puede negarse a canjear tu ecash o desaparecer en cualquier momento, guarda en él solo lo que puedas permitirte perder.
+custodian-acknowledge = Entiendo que el mint custodia los fondos que respaldan este ecash
+add-mint-and-claim = Añadir mint y reclamar
+discover-mints = Descubrir mints
+discovering-mints = Buscando mints en nostr...
+no-mints-discovered = No se encontraron mints en tus relays
+mint-discovery-failed = No se pudo buscar en nostr: { $err }
+mint-rating = { $rating }/5 de { $reviews } reseñas
+mint-reviews = { $reviews } reseñas
+mint-units = Unidades: { $units }
+units-unknown = Unidades desconocidas
+added = Añadido
+refresh = Actualizar
+claim-and-move-to = Reclamar y mover a { $swap_target }
+amount-in = Cantidad en { $currency }
+offer-any-amount = Cualquier cantidad, introdúcela abajo
+expires = Caduca: { $expires_at }
+any-amount = Cualquier cantidad
+payee = Beneficiario: { $payee }
+language = Idioma:
+split-default = Predeterminada
+split-denominations-mode = Denominaciones
+split-target-count = Número objetivo
+tray-receive = Recibir
+tray-pay = Pagar
+tray-show = Mostrar ventana
+tray-tooltip = Cashu Wallet: { $balance } sats
+scheduled-payments = Pagos programados
+no-scheduled-payments = No hay pagos programados
+schedule-a-payment = Programar un pago
+or-send-ecash-to-a-contact = o enviar ecash a un contacto
+first-payment = Primer pago:
+scheduled-payments-hint = Los pagos se hacen mientras la aplicación está abierta, mantenla en la bandeja con minimizar a la bandeja para que se hagan con la ventana cerrada
+schedule = Programar
+skip = Omitir
+once = Una vez
+daily = Cada día
+weekly = Cada semana
+monthly = Cada mes
+ecash-to = ecash a { $contact }
+deleted-contact = un contacto eliminado
+scheduled-payment-summary = { $amount } sats a { $recipient }, { $recurrence }
+last-run-failed = La última ejecución falló: { $err }
+scheduled-payment-to = Pago programado a { $recipient }
+scheduled-payment-made = Pago programado realizado
+scheduled-payment-sent = Pagados { $amount } sats según lo programado
+scheduled-payment-failed = Falló un pago programado
+point-of-sale = Punto de venta
+pos-amount = { $amount } sats
+tip = Propina
+no-tip = Sin propina
+charge = Cobrar
+paid = PAGADO
+next-customer = Siguiente cliente
+pos-today = Hoy: { $amount } sats en { $sales } ventas
+order-note = Nota del pedido (opcional)
+receipt = Recibo
+receipt-amount = Importe: { $amount } sats
+receipt-tip = Propina incluida: { $amount } sats
+receipt-fiat = Valor: { $amount } { $currency }
+receipt-time = Hora: { $time }
+receipt-note = Nota: { $note }
+save-pdf = Guardar como PDF
+receipt-saved-to = Recibo guardado en { $path }
+export-sales = Exportar ventas (CSV)
+sales-saved-to = Ventas guardadas en { $path }
+lock-to-hash = Bloquear con un hash (HTLC)
+hash-hex = Hash SHA-256 (hex)
+generate-preimage = Generar preimagen
+preimage = Preimagen: { $preimage }
+locked-to-hash = Bloqueado con la preimagen de { $hash }
+token-is-hash-locked-to = El token está bloqueado con las preimágenes de:
+preimage-held = preimagen disponible
+preimage-required = preimagen necesaria
+preimage-hex = Preimagen (hex)
+refundable-after = Reembolsarme después de
+hours-optional = horas (opcional)
+refundable-from = Reembolsable desde { $time }
+refund-now-available = Reembolso disponible
+refund-available-body = Se pueden recuperar { $amount } sats de un token bloqueado
+locked-to-recipient = Bloqueado para el destinatario
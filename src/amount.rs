@@ -0,0 +1,95 @@
+use crate::fiat::ExchangeRate;
+use crate::i18n;
+
+/// Preset amounts offered next to amount entries, in sats
+pub const PRESETS: [u64; 3] = [1_000, 5_000, 21_000];
+
+/// Unit an amount is typed in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AmountUnit {
+    #[default]
+    Sat,
+    Fiat,
+}
+
+/// Edit to an amount entry
+#[derive(Debug, Clone)]
+pub enum AmountEvent {
+    Changed(String),
+    ToggleUnit,
+    /// Preset in sats, `None` to clear the entry for a custom amount
+    Preset(Option<u64>),
+    /// Fill in the most that can be spent
+    Max,
+}
+
+/// Amount typed in sats or in the selected fiat currency
+#[derive(Debug, Clone, Default)]
+pub struct AmountInput {
+    pub input: String,
+    pub unit: AmountUnit,
+}
+
+impl AmountInput {
+    /// Value in sats, `None` if the input isn't a number or there is no rate
+    /// to convert fiat with
+    pub fn sats(&self, rate: Option<&ExchangeRate>) -> Option<u64> {
+        let format = i18n::number_format();
+
+        match self.unit {
+            AmountUnit::Sat => format.parse_integer(&self.input),
+            AmountUnit::Fiat => {
+                let fiat = format.parse_decimal(&self.input)?;
+                if fiat < 0.0 {
+                    return None;
+                }
+
+                Some(rate?.fiat_to_sats(fiat))
+            }
+        }
+    }
+
+    pub fn set_sats(&mut self, sats: u64, rate: Option<&ExchangeRate>) {
+        self.input = match (self.unit, rate) {
+            (AmountUnit::Fiat, Some(rate)) => i18n::format_decimal(rate.sats_to_fiat(sats), 2),
+            _ => {
+                self.unit = AmountUnit::Sat;
+                sats.to_string()
+            }
+        };
+    }
+
+    /// Switch between sats and fiat, converting what was typed
+    pub fn toggle_unit(&mut self, rate: Option<&ExchangeRate>) {
+        let sats = self.sats(rate);
+
+        self.unit = match self.unit {
+            AmountUnit::Sat if rate.is_some() => AmountUnit::Fiat,
+            _ => AmountUnit::Sat,
+        };
+
+        match sats {
+            Some(sats) => self.set_sats(sats, rate),
+            None => self.input.clear(),
+        }
+    }
+
+    /// The amount in the other unit, e.g. `≈ 5,000 sats`
+    pub fn conversion(&self, rate: Option<&ExchangeRate>) -> Option<String> {
+        let rate = rate?;
+        let sats = self.sats(Some(rate))?;
+
+        Some(match self.unit {
+            AmountUnit::Sat => format!(
+                "≈ {} {}",
+                i18n::format_decimal(rate.sats_to_fiat(sats), 2),
+                rate.currency
+            ),
+            AmountUnit::Fiat => format!("≈ {} sats", i18n::format_number(sats)),
+        })
+    }
+
+    pub fn clear(&mut self) {
+        self.input.clear();
+    }
+}
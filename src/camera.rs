@@ -0,0 +1,128 @@
+use std::time::Duration;
+
+use futures::SinkExt;
+use iced::widget::image;
+use iced::Subscription;
+use iced_cashu_core::bbqr;
+use nokhwa::pixel_format::RgbFormat;
+use nokhwa::utils::{CameraIndex, RequestedFormat, RequestedFormatType};
+use nokhwa::Camera;
+use tokio::sync::mpsc;
+
+const FRAME_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone)]
+pub enum CameraEvent {
+    /// Latest frame for the preview
+    Frame(image::Handle),
+    /// Contents of a decoded QR code, or of every part of an animated one
+    Scanned(String),
+    /// Parts of an animated QR code scanned so far and in total
+    Progress(usize, usize),
+    Error(String),
+}
+
+/// Capture frames from the default camera until the subscription is dropped
+pub fn scan() -> Subscription<CameraEvent> {
+    struct Scan;
+
+    iced::subscription::channel(
+        std::any::TypeId::of::<Scan>(),
+        10,
+        |mut output| async move {
+            let (sender, mut receiver) = mpsc::channel(2);
+
+            // Capturing blocks, so it runs on its own thread and stops once the
+            // receiver is dropped
+            tokio::task::spawn_blocking(move || capture(sender));
+
+            while let Some(event) = receiver.recv().await {
+                let _ = output.send(event).await;
+            }
+
+            // The camera thread has exited, wait to be dropped
+            std::future::pending().await
+        },
+    )
+}
+
+fn capture(sender: mpsc::Sender<CameraEvent>) {
+    let format = RequestedFormat::new::<RgbFormat>(RequestedFormatType::AbsoluteHighestFrameRate);
+
+    let mut camera = match Camera::new(CameraIndex::Index(0), format)
+        .and_then(|mut camera| camera.open_stream().map(|_| camera))
+    {
+        Ok(camera) => camera,
+        Err(err) => {
+            let _ = sender.blocking_send(CameraEvent::Error(err.to_string()));
+            return;
+        }
+    };
+
+    let mut joiner = bbqr::Joiner::default();
+    loop {
+        let frame = match camera
+            .frame()
+            .and_then(|frame| frame.decode_image::<RgbFormat>())
+        {
+            Ok(frame) => frame,
+            Err(err) => {
+                let _ = sender.blocking_send(CameraEvent::Error(err.to_string()));
+                break;
+            }
+        };
+
+        let (width, height) = frame.dimensions();
+        let rgb = frame.into_raw();
+
+        match decode_qr(width as usize, height as usize, &rgb) {
+            Some(content) if bbqr::is_part(&content) => match joiner.add(&content) {
+                Ok(Some(joined)) => {
+                    let _ = sender.blocking_send(CameraEvent::Scanned(joined));
+                    break;
+                }
+                Ok(None) => {
+                    let (scanned, total) = joiner.progress();
+                    let _ = sender.blocking_send(CameraEvent::Progress(scanned, total));
+                }
+                Err(err) => tracing::debug!("Skipping QR part: {}", err),
+            },
+            Some(content) => {
+                let _ = sender.blocking_send(CameraEvent::Scanned(content));
+                break;
+            }
+            None => (),
+        }
+
+        let rgba: Vec<u8> = rgb
+            .chunks_exact(3)
+            .flat_map(|pixel| [pixel[0], pixel[1], pixel[2], u8::MAX])
+            .collect();
+
+        if sender
+            .blocking_send(CameraEvent::Frame(image::Handle::from_rgba(
+                width, height, rgba,
+            )))
+            .is_err()
+        {
+            break;
+        }
+
+        std::thread::sleep(FRAME_INTERVAL);
+    }
+
+    let _ = camera.stop_stream();
+}
+
+fn decode_qr(width: usize, height: usize, rgb: &[u8]) -> Option<String> {
+    let mut image = rqrr::PreparedImage::prepare_from_greyscale(width, height, |x, y| {
+        let i = (y * width + x) * 3;
+        let luma = 0.299 * rgb[i] as f32 + 0.587 * rgb[i + 1] as f32 + 0.114 * rgb[i + 2] as f32;
+        luma as u8
+    });
+
+    image
+        .detect_grids()
+        .into_iter()
+        .find_map(|grid| grid.decode().ok().map(|(_, content)| content))
+}
@@ -0,0 +1,83 @@
+use iced::widget::canvas::{self, Frame, Geometry, Path, Stroke};
+use iced::{mouse, Point, Rectangle, Renderer, Theme};
+use iced_cashu_core::stats::BalancePoint;
+
+/// Balance over time as a step line, scaled to fill the canvas
+#[derive(Debug)]
+pub struct BalanceChart<'a> {
+    points: &'a [BalancePoint],
+}
+
+impl<'a> BalanceChart<'a> {
+    pub fn new(points: &'a [BalancePoint]) -> Self {
+        Self { points }
+    }
+}
+
+impl<Message> canvas::Program<Message> for BalanceChart<'_> {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let palette = theme.extended_palette();
+        let (width, height) = (bounds.width, bounds.height);
+
+        let (Some(first), Some(last)) = (self.points.first(), self.points.last()) else {
+            return vec![frame.into_geometry()];
+        };
+
+        // Histories that start after funds were already held can dip below 0
+        let lowest = self
+            .points
+            .iter()
+            .map(|point| point.balance)
+            .min()
+            .unwrap_or(0)
+            .min(0);
+        let highest = self
+            .points
+            .iter()
+            .map(|point| point.balance)
+            .max()
+            .unwrap_or(0);
+        let time_span = (last.timestamp - first.timestamp).max(1) as f32;
+        let balance_span = (highest - lowest).max(1) as f32;
+
+        let x = |timestamp: i64| (timestamp - first.timestamp) as f32 / time_span * width;
+        let y = |balance: i64| height - (balance - lowest) as f32 / balance_span * height;
+
+        frame.stroke(
+            &Path::line(Point::new(0.0, y(0)), Point::new(width, y(0))),
+            Stroke::default()
+                .with_color(palette.background.strong.color)
+                .with_width(1.0),
+        );
+
+        let line = Path::new(|builder| {
+            let mut previous = y(0);
+            builder.move_to(Point::new(0.0, previous));
+            for point in self.points {
+                let (x, next) = (x(point.timestamp), y(point.balance));
+                builder.line_to(Point::new(x, previous));
+                builder.line_to(Point::new(x, next));
+                previous = next;
+            }
+            builder.line_to(Point::new(width, previous));
+        });
+        frame.stroke(
+            &line,
+            Stroke::default()
+                .with_color(palette.primary.strong.color)
+                .with_width(2.0),
+        );
+
+        vec![frame.into_geometry()]
+    }
+}
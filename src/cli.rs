@@ -0,0 +1,56 @@
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use cdk::UncheckedUrl;
+use clap::Parser;
+
+use crate::config::{self, StorageBackend};
+use crate::proxy;
+
+static ARGS: OnceLock<Args> = OnceLock::new();
+
+/// Options that override the saved settings for this run
+///
+/// Pointing `--data-dir` at different directories runs isolated wallets
+/// side by side.
+#[derive(Debug, Clone, Default, Parser)]
+#[command(version, about = "Cashu wallet")]
+pub struct Args {
+    /// Directory the seed, databases and settings are kept in
+    #[arg(long)]
+    pub data_dir: Option<PathBuf>,
+    /// SOCKS5 proxy for mint traffic, e.g. 127.0.0.1:9050
+    #[arg(long, value_parser = proxy::parse_proxy)]
+    pub proxy: Option<String>,
+    /// Mint to start with, added to the wallet if it is new
+    #[arg(long, value_parser = parse_mint)]
+    pub mint: Option<UncheckedUrl>,
+    /// Keep wallet data in sqlite, redb or memory instead of the profile's
+    /// configured backend
+    #[arg(long)]
+    pub storage: Option<StorageBackend>,
+}
+
+/// Parse the process arguments, exiting with usage on error
+pub fn init() {
+    let args = Args::parse();
+    if let Some(dir) = &args.data_dir {
+        config::set_data_dir(dir.clone());
+    }
+    if let Some(backend) = args.storage {
+        config::set_storage_backend(backend);
+    }
+
+    let _ = ARGS.set(args);
+}
+
+pub fn args() -> &'static Args {
+    ARGS.get_or_init(Args::default)
+}
+
+fn parse_mint(input: &str) -> anyhow::Result<UncheckedUrl> {
+    let input = input.trim();
+    reqwest::Url::parse(input)?;
+
+    Ok(UncheckedUrl::from(input))
+}
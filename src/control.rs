@@ -0,0 +1,355 @@
+use std::collections::HashMap;
+use std::fs;
+use std::future::IntoFuture;
+use std::io::Write;
+use std::net::{Ipv4Addr, SocketAddr};
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use axum::extract::State;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::{Json, Router};
+use cdk::nuts::CurrencyUnit;
+use cdk::UncheckedUrl;
+use futures::channel::{mpsc, oneshot};
+use futures::{SinkExt, StreamExt};
+use iced::Subscription;
+use rand::RngCore;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::config::wallet_dir;
+use crate::error::WalletError;
+use crate::split::SplitSetting;
+use crate::WalletService;
+
+const TOKEN_FILE: &str = "control_token";
+
+/// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// Wallet operation failed
+const WALLET_ERROR: i64 = -32000;
+
+/// Call from a local app that the wallet has to act on
+#[derive(Debug, Clone)]
+pub struct ControlRequest {
+    id: u64,
+    pub method: ControlMethod,
+}
+
+#[derive(Debug, Clone)]
+pub enum ControlMethod {
+    GetBalance,
+    Send {
+        amount: u64,
+        mint: Option<UncheckedUrl>,
+        memo: Option<String>,
+    },
+    Receive {
+        token: String,
+    },
+    Pay {
+        invoice: String,
+        mint: Option<UncheckedUrl>,
+    },
+}
+
+/// Result of a request, sent back to the server to answer the call
+#[derive(Debug, Clone)]
+pub struct ControlReply {
+    pub request: ControlRequest,
+    pub result: Result<Value, String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum ControlEvent {
+    /// Server is listening, replies go through the sender
+    Ready(mpsc::Sender<ControlReply>),
+    Request(ControlRequest),
+    Error(String),
+}
+
+/// Token callers send as `Authorization: Bearer <token>`, created on first
+/// use
+pub fn auth_token() -> anyhow::Result<String> {
+    let path = wallet_dir().join(TOKEN_FILE);
+    if let Ok(token) = fs::read_to_string(&path) {
+        return Ok(token.trim().to_string());
+    }
+
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let token = hex::encode(bytes);
+
+    fs::create_dir_all(wallet_dir())?;
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    // Only the user running the wallet may read the token
+    #[cfg(unix)]
+    options.mode(0o600);
+    options.open(path)?.write_all(token.as_bytes())?;
+
+    Ok(token)
+}
+
+/// Answer JSON-RPC calls on `127.0.0.1:port`
+pub fn server(port: u16, token: String) -> Subscription<ControlEvent> {
+    iced::subscription::channel(
+        ("control", port, token.clone()),
+        100,
+        move |mut output| async move {
+            if let Err(err) = run(port, token, &mut output).await {
+                tracing::warn!("Control API stopped: {}", err);
+                let _ = output.send(ControlEvent::Error(err.to_string())).await;
+            }
+
+            std::future::pending().await
+        },
+    )
+}
+
+/// Carry out `request`, sending and paying from `mint_url` unless the call
+/// names a mint
+pub async fn handle(
+    service: WalletService,
+    mint_url: UncheckedUrl,
+    split: SplitSetting,
+    request: ControlRequest,
+) -> ControlReply {
+    let result = match request.method.clone() {
+        ControlMethod::GetBalance => {
+            async {
+                let balance = service.check_balance().await?;
+                let mints: HashMap<String, u64> = service
+                    .mint_balances()
+                    .await?
+                    .into_iter()
+                    .map(|(mint_url, amount)| (mint_url.to_string(), amount))
+                    .collect();
+
+                Ok::<_, WalletError>(json!({ "balance": balance, "mints": mints }))
+            }
+            .await
+        }
+        ControlMethod::Send { amount, mint, memo } => service
+            .create_token(
+                mint.unwrap_or(mint_url),
+                amount,
+                CurrencyUnit::Sat,
+                split.target(amount),
+                None,
+                memo,
+            )
+            .await
+            .map(|(_, token)| json!({ "token": token })),
+        ControlMethod::Receive { token } => service
+            .receive(token)
+            .await
+            .map(|amount| json!({ "amount": amount })),
+        ControlMethod::Pay { invoice, mint } => {
+            async {
+                let preview = service
+                    .melt_quote(mint.unwrap_or(mint_url), invoice, CurrencyUnit::Sat)
+                    .await?;
+                let amount = preview.amount;
+                let preimage = service.pay_invoice(preview).await?;
+
+                Ok::<_, WalletError>(json!({ "amount": amount, "preimage": preimage }))
+            }
+            .await
+        }
+    };
+
+    ControlReply {
+        request,
+        result: result.map_err(|err| err.to_string()),
+    }
+}
+
+type Pending = Arc<Mutex<HashMap<u64, oneshot::Sender<Result<Value, String>>>>>;
+
+#[derive(Clone)]
+struct ServerState {
+    token: Arc<str>,
+    output: mpsc::Sender<ControlEvent>,
+    pending: Pending,
+    next_id: Arc<AtomicU64>,
+}
+
+async fn run(
+    port: u16,
+    token: String,
+    output: &mut mpsc::Sender<ControlEvent>,
+) -> anyhow::Result<()> {
+    // Only reachable from this machine
+    let listener =
+        tokio::net::TcpListener::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, port))).await?;
+    tracing::info!("Control API listening on {}", listener.local_addr()?);
+
+    let pending = Pending::default();
+    let state = ServerState {
+        token: token.into(),
+        output: output.clone(),
+        pending: pending.clone(),
+        next_id: Arc::default(),
+    };
+    let app = Router::new().route("/", post(rpc)).with_state(state);
+
+    let (sender, mut replies) = mpsc::channel(100);
+    output.send(ControlEvent::Ready(sender)).await?;
+
+    let server = axum::serve(listener, app).into_future();
+    tokio::pin!(server);
+    loop {
+        tokio::select! {
+            result = &mut server => return Ok(result?),
+            Some(reply) = replies.next() => {
+                let waiting = pending
+                    .lock()
+                    .expect("Control API lock poisoned")
+                    .remove(&reply.request.id);
+                if let Some(waiting) = waiting {
+                    let _ = waiting.send(reply.result);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcCall {
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct SendParams {
+    amount: u64,
+    mint: Option<String>,
+    memo: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReceiveParams {
+    token: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PayParams {
+    invoice: String,
+    mint: Option<String>,
+}
+
+async fn rpc(
+    State(mut state): State<ServerState>,
+    headers: HeaderMap,
+    body: String,
+) -> (StatusCode, Json<Value>) {
+    let authorized = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|token| constant_time_eq(token.as_bytes(), state.token.as_bytes()));
+    if !authorized {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({ "error": "Missing or wrong auth token" })),
+        );
+    }
+
+    let call: RpcCall = match serde_json::from_str(&body) {
+        Ok(call) => call,
+        Err(err) => return (StatusCode::OK, error(Value::Null, PARSE_ERROR, err)),
+    };
+    let method = match parse_method(&call) {
+        Ok(method) => method,
+        Err((code, message)) => return (StatusCode::OK, error(call.id, code, message)),
+    };
+
+    let id = state.next_id.fetch_add(1, Ordering::Relaxed);
+    let (sender, receiver) = oneshot::channel();
+    state
+        .pending
+        .lock()
+        .expect("Control API lock poisoned")
+        .insert(id, sender);
+
+    let request = ControlRequest { id, method };
+    if state
+        .output
+        .send(ControlEvent::Request(request))
+        .await
+        .is_err()
+    {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            error(call.id, WALLET_ERROR, "Wallet is closing"),
+        );
+    }
+
+    match receiver.await {
+        Ok(Ok(result)) => (
+            StatusCode::OK,
+            Json(json!({ "jsonrpc": "2.0", "id": call.id, "result": result })),
+        ),
+        Ok(Err(message)) => (StatusCode::OK, error(call.id, WALLET_ERROR, message)),
+        Err(_) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            error(call.id, WALLET_ERROR, "Wallet is closing"),
+        ),
+    }
+}
+
+fn parse_method(call: &RpcCall) -> Result<ControlMethod, (i64, String)> {
+    let invalid = |err: serde_json::Error| (INVALID_PARAMS, err.to_string());
+
+    match call.method.as_str() {
+        "get_balance" => Ok(ControlMethod::GetBalance),
+        "send" => {
+            let params: SendParams =
+                serde_json::from_value(call.params.clone()).map_err(invalid)?;
+            Ok(ControlMethod::Send {
+                amount: params.amount,
+                mint: params.mint.map(UncheckedUrl::from),
+                memo: params.memo,
+            })
+        }
+        "receive" => {
+            let params: ReceiveParams =
+                serde_json::from_value(call.params.clone()).map_err(invalid)?;
+            Ok(ControlMethod::Receive {
+                token: params.token,
+            })
+        }
+        "pay" => {
+            let params: PayParams = serde_json::from_value(call.params.clone()).map_err(invalid)?;
+            Ok(ControlMethod::Pay {
+                invoice: params.invoice,
+                mint: params.mint.map(UncheckedUrl::from),
+            })
+        }
+        method => Err((METHOD_NOT_FOUND, format!("Unknown method {}", method))),
+    }
+}
+
+/// Whether `a` and `b` are equal, comparing every byte so the time taken
+/// doesn't reveal how much of a guessed token was right
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+fn error(id: Value, code: i64, message: impl ToString) -> Json<Value> {
+    Json(json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message.to_string() },
+    }))
+}
@@ -0,0 +1,130 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use cdk::UncheckedUrl;
+use nostr_sdk::{Alphabet, Event, Filter, Kind, PublicKey, SingleLetterTag};
+
+use crate::nostr;
+
+/// NIP-87 cashu mint announcement, published by the mint's operator
+const MINT_ANNOUNCEMENT_KIND: u16 = 38172;
+/// NIP-87 recommendation of a mint, published by its users
+const RECOMMENDATION_KIND: u16 = 38000;
+/// How long relays get to send what they have
+const DISCOVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Mint announced or recommended on nostr
+#[derive(Debug, Clone)]
+pub struct DiscoveredMint {
+    pub mint_url: UncheckedUrl,
+    /// Units of the mint's active keysets, empty if it could not be reached
+    pub units: Vec<String>,
+    /// Recommendations, one per author
+    pub reviews: usize,
+    /// Mean of the ratings out of 5 given with recommendations
+    pub rating: Option<f32>,
+}
+
+/// Mints announced or recommended on `relays`, most reviewed first
+pub async fn discover(relays: Vec<String>) -> anyhow::Result<Vec<DiscoveredMint>> {
+    let client = nostr::connect(&relays).await?;
+    let filters = vec![
+        Filter::new().kind(Kind::from(MINT_ANNOUNCEMENT_KIND)),
+        Filter::new()
+            .kind(Kind::from(RECOMMENDATION_KIND))
+            .custom_tag(
+                SingleLetterTag::lowercase(Alphabet::K),
+                [MINT_ANNOUNCEMENT_KIND.to_string()],
+            ),
+    ];
+    let events = client.get_events_of(filters, Some(DISCOVERY_TIMEOUT)).await;
+    client.disconnect().await?;
+
+    let mut mints: HashMap<String, Reviews> = HashMap::new();
+    for event in events? {
+        let urls = mint_urls(&event);
+        if event.kind == Kind::from(RECOMMENDATION_KIND) {
+            let rating = rating(&event.content);
+            for url in &urls {
+                mints
+                    .entry(url.clone())
+                    .or_default()
+                    .add(event.pubkey, rating);
+            }
+        } else {
+            for url in urls {
+                mints.entry(url).or_default();
+            }
+        }
+    }
+
+    let mut discovered: Vec<DiscoveredMint> =
+        futures::future::join_all(mints.into_iter().map(|(url, reviews)| async move {
+            let mint_url = UncheckedUrl::from(url);
+            let units = match iced_cashu_core::unit::fetch_units(&mint_url).await {
+                Ok(units) => units.iter().map(ToString::to_string).collect(),
+                Err(err) => {
+                    tracing::debug!("Could not fetch units of {}: {}", mint_url, err);
+                    vec![]
+                }
+            };
+
+            DiscoveredMint {
+                mint_url,
+                units,
+                reviews: reviews.authors.len(),
+                rating: reviews.mean_rating(),
+            }
+        }))
+        .await;
+    discovered.sort_by(|a, b| b.reviews.cmp(&a.reviews));
+
+    Ok(discovered)
+}
+
+/// Recommendations of one mint
+#[derive(Debug, Default)]
+struct Reviews {
+    authors: HashSet<PublicKey>,
+    ratings: Vec<u8>,
+}
+
+impl Reviews {
+    /// Count `author` once, relays may return several of their events
+    fn add(&mut self, author: PublicKey, rating: Option<u8>) {
+        if self.authors.insert(author) {
+            self.ratings.extend(rating);
+        }
+    }
+
+    fn mean_rating(&self) -> Option<f32> {
+        if self.ratings.is_empty() {
+            return None;
+        }
+        let total: u32 = self.ratings.iter().copied().map(u32::from).sum();
+
+        Some(total as f32 / self.ratings.len() as f32)
+    }
+}
+
+/// Normalized `u` tags of an announcement or recommendation
+fn mint_urls(event: &Event) -> Vec<String> {
+    event
+        .tags
+        .iter()
+        .filter_map(|tag| match tag.as_vec() {
+            [kind, url, ..] if kind == "u" && url.starts_with("http") => {
+                Some(url.trim_end_matches('/').to_string())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Rating of a recommendation, which NIP-87 puts at the start of the
+/// review as `[4/5]`
+fn rating(content: &str) -> Option<u8> {
+    let (rating, _) = content.trim_start().strip_prefix('[')?.split_once("/5]")?;
+
+    rating.trim().parse().ok().filter(|rating| *rating <= 5)
+}
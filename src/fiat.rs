@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+
+use crate::db::unix_time;
+
+/// Rates older than this are shown as stale
+const STALE_AFTER_SECS: i64 = 15 * 60;
+const SATS_PER_BTC: f64 = 100_000_000.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FiatCurrency {
+    #[default]
+    Usd,
+    Eur,
+    Gbp,
+    Cad,
+    Chf,
+    Aud,
+    Jpy,
+}
+
+impl FiatCurrency {
+    pub const ALL: [FiatCurrency; 7] = [
+        FiatCurrency::Usd,
+        FiatCurrency::Eur,
+        FiatCurrency::Gbp,
+        FiatCurrency::Cad,
+        FiatCurrency::Chf,
+        FiatCurrency::Aud,
+        FiatCurrency::Jpy,
+    ];
+}
+
+impl fmt::Display for FiatCurrency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FiatCurrency::Usd => write!(f, "USD"),
+            FiatCurrency::Eur => write!(f, "EUR"),
+            FiatCurrency::Gbp => write!(f, "GBP"),
+            FiatCurrency::Cad => write!(f, "CAD"),
+            FiatCurrency::Chf => write!(f, "CHF"),
+            FiatCurrency::Aud => write!(f, "AUD"),
+            FiatCurrency::Jpy => write!(f, "JPY"),
+        }
+    }
+}
+
+impl FromStr for FiatCurrency {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "USD" => Ok(FiatCurrency::Usd),
+            "EUR" => Ok(FiatCurrency::Eur),
+            "GBP" => Ok(FiatCurrency::Gbp),
+            "CAD" => Ok(FiatCurrency::Cad),
+            "CHF" => Ok(FiatCurrency::Chf),
+            "AUD" => Ok(FiatCurrency::Aud),
+            "JPY" => Ok(FiatCurrency::Jpy),
+            _ => Err(anyhow!("Unknown currency: {}", s)),
+        }
+    }
+}
+
+/// Where exchange rates are fetched from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RateSource {
+    #[default]
+    Mempool,
+    Coingecko,
+}
+
+impl RateSource {
+    pub const ALL: [RateSource; 2] = [RateSource::Mempool, RateSource::Coingecko];
+}
+
+impl fmt::Display for RateSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RateSource::Mempool => write!(f, "mempool"),
+            RateSource::Coingecko => write!(f, "coingecko"),
+        }
+    }
+}
+
+impl FromStr for RateSource {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mempool" => Ok(RateSource::Mempool),
+            "coingecko" => Ok(RateSource::Coingecko),
+            _ => Err(anyhow!("Unknown rate source: {}", s)),
+        }
+    }
+}
+
+/// Price of one bitcoin in `currency`
+#[derive(Debug, Clone, Copy)]
+pub struct ExchangeRate {
+    pub currency: FiatCurrency,
+    pub price: f64,
+    /// Unix timestamp in seconds the rate was fetched
+    pub timestamp: i64,
+}
+
+impl ExchangeRate {
+    pub fn sats_to_fiat(&self, sats: u64) -> f64 {
+        sats as f64 * self.price / SATS_PER_BTC
+    }
+
+    pub fn fiat_to_sats(&self, fiat: f64) -> u64 {
+        (fiat * SATS_PER_BTC / self.price).round() as u64
+    }
+
+    pub fn is_stale(&self) -> bool {
+        unix_time() - self.timestamp > STALE_AFTER_SECS
+    }
+}
+
+pub async fn fetch_rate(
+    source: RateSource,
+    currency: FiatCurrency,
+) -> anyhow::Result<ExchangeRate> {
+    let price = match source {
+        RateSource::Mempool => {
+            let prices: HashMap<String, serde_json::Value> =
+                reqwest::get("https://mempool.space/api/v1/prices")
+                    .await?
+                    .json()
+                    .await?;
+
+            prices
+                .get(&currency.to_string())
+                .and_then(|price| price.as_f64())
+        }
+        RateSource::Coingecko => {
+            let code = currency.to_string().to_lowercase();
+            let url = format!(
+                "https://api.coingecko.com/api/v3/simple/price?ids=bitcoin&vs_currencies={}",
+                code
+            );
+            let prices: HashMap<String, HashMap<String, f64>> =
+                reqwest::get(url).await?.json().await?;
+
+            prices
+                .get("bitcoin")
+                .and_then(|prices| prices.get(&code))
+                .copied()
+        }
+    }
+    .ok_or(anyhow!("{} has no {} rate", source, currency))?;
+
+    Ok(ExchangeRate {
+        currency,
+        price,
+        timestamp: unix_time(),
+    })
+}
@@ -0,0 +1,185 @@
+use std::fmt;
+use std::sync::{OnceLock, RwLock};
+
+use cdk::nuts::CurrencyUnit;
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use iced_cashu_core::unit::{self, NumberFormat};
+use serde::{Deserialize, Serialize};
+use unic_langid::LanguageIdentifier;
+
+/// Language the interface is shown in, switched at runtime by
+/// [`set_language`]
+static LANGUAGE: RwLock<Language> = RwLock::new(Language::English);
+static BUNDLES: OnceLock<Vec<(Language, FluentBundle<FluentResource>)>> = OnceLock::new();
+
+/// Translation of `key` in the current language, see `locales/*.ftl`
+///
+/// Arguments are given as `name = value` for the message's `{ $name }`
+/// placeables and can be any `Display` value.
+macro_rules! t {
+    ($key:literal) => {
+        $crate::i18n::translate($key, None)
+    };
+    ($key:literal, $($name:ident = $value:expr),+ $(,)?) => {{
+        let mut args = fluent_bundle::FluentArgs::new();
+        $(args.set(stringify!($name), $value.to_string());)+
+        $crate::i18n::translate($key, Some(&args))
+    }};
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    #[serde(rename = "en")]
+    English,
+    #[serde(rename = "es")]
+    Spanish,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::Spanish];
+
+    /// Language of the OS locale, English if there is no translation for it
+    pub fn system() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|locale| !locale.is_empty())
+            .and_then(|locale| {
+                Self::ALL
+                    .into_iter()
+                    .find(|language| locale.starts_with(language.code()))
+            })
+            .unwrap_or_default()
+    }
+
+    fn code(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Spanish => "es",
+        }
+    }
+
+    /// Separators numbers are written with, `1 234 567,89` in Spanish
+    pub fn number_format(self) -> NumberFormat {
+        match self {
+            Language::English => NumberFormat::default(),
+            Language::Spanish => NumberFormat {
+                group: '\u{a0}',
+                decimal: ',',
+            },
+        }
+    }
+
+    fn messages(self) -> &'static str {
+        match self {
+            Language::English => include_str!("../locales/en.ftl"),
+            Language::Spanish => include_str!("../locales/es.ftl"),
+        }
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Language::English => write!(f, "English"),
+            Language::Spanish => write!(f, "Español"),
+        }
+    }
+}
+
+pub fn language() -> Language {
+    *LANGUAGE.read().expect("Language lock poisoned")
+}
+
+pub fn set_language(language: Language) {
+    *LANGUAGE.write().expect("Language lock poisoned") = language;
+}
+
+pub fn number_format() -> NumberFormat {
+    language().number_format()
+}
+
+/// `amount` of `unit` for display in the current language
+pub fn format_amount(amount: u64, unit: &CurrencyUnit) -> String {
+    unit::format_amount_with(amount, unit, &number_format())
+}
+
+/// Amount typed in `unit` in the current language
+pub fn parse_amount(input: &str, unit: &CurrencyUnit) -> Option<u64> {
+    unit::parse_amount_with(input, unit, &number_format())
+}
+
+/// Whole number such as a sat count with its digits grouped, anything that
+/// isn't one shown as is
+pub fn format_number(value: impl fmt::Display) -> String {
+    let value = value.to_string();
+    match value.parse() {
+        Ok(number) => number_format().integer(number),
+        Err(_) => value,
+    }
+}
+
+/// `value` rounded to `decimals` places in the current language
+pub fn format_decimal(value: f64, decimals: u32) -> String {
+    number_format().decimal(value, decimals)
+}
+
+/// Message `key` in the current language, falling back to English and then
+/// to the key itself for messages that are not translated
+pub fn translate(key: &str, args: Option<&FluentArgs>) -> String {
+    [language(), Language::English]
+        .into_iter()
+        .find_map(|language| format(bundle(language), key, args))
+        .unwrap_or_else(|| key.to_string())
+}
+
+fn format(
+    bundle: &FluentBundle<FluentResource>,
+    key: &str,
+    args: Option<&FluentArgs>,
+) -> Option<String> {
+    let pattern = bundle.get_message(key)?.value()?;
+    let mut errors = vec![];
+    let message = bundle.format_pattern(pattern, args, &mut errors);
+    if !errors.is_empty() {
+        tracing::warn!("Could not format message {}: {:?}", key, errors);
+    }
+
+    Some(message.into_owned())
+}
+
+fn bundle(language: Language) -> &'static FluentBundle<FluentResource> {
+    let bundles = BUNDLES.get_or_init(|| {
+        Language::ALL
+            .into_iter()
+            .map(|language| (language, load(language)))
+            .collect()
+    });
+
+    bundles
+        .iter()
+        .find(|(bundled, _)| *bundled == language)
+        .map(|(_, bundle)| bundle)
+        .expect("Every language is bundled")
+}
+
+fn load(language: Language) -> FluentBundle<FluentResource> {
+    let id: LanguageIdentifier = language.code().parse().expect("Valid language code");
+    let mut bundle = FluentBundle::new_concurrent(vec![id]);
+    // Unicode isolation marks around arguments render as boxes
+    bundle.set_use_isolating(false);
+
+    let resource = FluentResource::try_new(language.messages().to_string()).unwrap_or_else(
+        |(resource, errors)| {
+            tracing::warn!("Errors in {} messages: {:?}", language, errors);
+            resource
+        },
+    );
+    if let Err(errors) = bundle.add_resource(resource) {
+        tracing::warn!("Duplicate {} messages: {:?}", language, errors);
+    }
+
+    bundle
+}
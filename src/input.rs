@@ -0,0 +1,118 @@
+use std::str::FromStr;
+
+use cdk::nuts::Token;
+use cdk::Bolt11Invoice;
+
+use crate::{bolt12, lnurl, payment_request, token};
+
+/// A payment string recognised in user input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DetectedInput {
+    /// Cashu token and its total value in sats
+    Token { token: String, amount: u64 },
+    /// Bolt11 invoice and its amount in sats, if it has one
+    Bolt11 {
+        invoice: String,
+        amount: Option<u64>,
+    },
+    /// BOLT12 offer and its amount in sats, if it has one in sats
+    Bolt12 { offer: String, amount: Option<u64> },
+    /// Bech32 LNURL or lightning address
+    Lnurl(String),
+    /// NUT-18 `creqA` payment request and its amount in sats, if it has one
+    PaymentRequest {
+        request: String,
+        amount: Option<u64>,
+    },
+}
+
+impl DetectedInput {
+    /// The recognised payment string
+    pub fn input(&self) -> &str {
+        match self {
+            DetectedInput::Token { token, .. } => token,
+            DetectedInput::Bolt11 { invoice, .. } => invoice,
+            DetectedInput::Bolt12 { offer, .. } => offer,
+            DetectedInput::Lnurl(lnurl) => lnurl,
+            DetectedInput::PaymentRequest { request, .. } => request,
+        }
+    }
+}
+
+pub fn token_amount(token: &Token) -> u64 {
+    token
+        .token
+        .iter()
+        .flat_map(|mint_proofs| mint_proofs.proofs.iter())
+        .map(|proof| u64::from(proof.amount))
+        .sum()
+}
+
+/// First valid cashu token in free text, such as a chat message
+pub fn find_token(text: &str) -> Option<String> {
+    text.split_whitespace()
+        .map(|word| word.strip_prefix("cashu:").unwrap_or(word))
+        .find(|word| {
+            (word.starts_with("cashuA") || word.starts_with("cashuB")) && token::parse(word).is_ok()
+        })
+        .map(str::to_string)
+}
+
+/// Encoded token in the contents of a token file, either a `.cashu` JSON
+/// file or text holding an encoded token
+pub fn token_from_file(contents: &str) -> Option<String> {
+    let contents = contents.trim();
+    if contents.starts_with('{') {
+        return serde_json::from_str::<Token>(contents)
+            .ok()
+            .map(|token| token.to_string());
+    }
+
+    find_token(contents)
+}
+
+pub fn detect(input: &str) -> Option<DetectedInput> {
+    let input = input.trim();
+    let input = input
+        .strip_prefix("lightning:")
+        .or_else(|| input.strip_prefix("LIGHTNING:"))
+        .unwrap_or(input);
+
+    if input.starts_with("cashuA") || input.starts_with("cashuB") {
+        let token = token::parse(input).ok()?;
+
+        return Some(DetectedInput::Token {
+            token: input.to_string(),
+            amount: token_amount(&token),
+        });
+    }
+
+    if let Ok(request) = payment_request::decode(input) {
+        return Some(DetectedInput::PaymentRequest {
+            request: input.to_string(),
+            amount: request.amount,
+        });
+    }
+
+    if let Ok(invoice) = Bolt11Invoice::from_str(input) {
+        return Some(DetectedInput::Bolt11 {
+            invoice: input.to_string(),
+            amount: invoice.amount_milli_satoshis().map(|msat| msat / 1000),
+        });
+    }
+
+    if bolt12::is_offer(input) {
+        let details = bolt12::decode_offer(input).ok()?;
+
+        return Some(DetectedInput::Bolt12 {
+            offer: input.to_string(),
+            amount: details.amount_msat.map(|msat| msat / 1000),
+        });
+    }
+
+    if lnurl::is_lnurl(input) {
+        return Some(DetectedInput::Lnurl(input.to_string()));
+    }
+
+    None
+}
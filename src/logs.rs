@@ -0,0 +1,102 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, EnvFilter, Layer};
+
+use crate::config::data_dir;
+
+const LOG_PREFIX: &str = "iced-cashu";
+const LOG_SUFFIX: &str = "log";
+/// Days of logs kept before the oldest file is deleted
+const MAX_LOG_FILES: usize = 7;
+/// Filter used unless `RUST_LOG` is set
+const DEFAULT_FILTER: &str = "info,iced_cashu=debug";
+
+/// Flushes the file writer when the process exits
+static GUARD: OnceLock<WorkerGuard> = OnceLock::new();
+
+/// Log to stderr and to a daily rotated file in `logs/` in the data dir
+pub fn init() {
+    let filter =
+        || EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER));
+    let registry = tracing_subscriber::registry().with(fmt::layer().with_filter(filter()));
+
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_PREFIX)
+        .filename_suffix(LOG_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(log_dir());
+
+    match appender {
+        Ok(appender) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            let _ = GUARD.set(guard);
+            registry
+                .with(
+                    fmt::layer()
+                        .with_ansi(false)
+                        .with_writer(writer)
+                        .with_filter(filter()),
+                )
+                .init();
+        }
+        Err(err) => {
+            registry.init();
+            tracing::warn!("Could not open log file: {}", err);
+        }
+    }
+}
+
+pub fn log_dir() -> PathBuf {
+    data_dir().join("logs")
+}
+
+/// Last `count` lines of the current log file
+pub fn recent(count: usize) -> Vec<String> {
+    let Some(path) = log_files().pop() else {
+        return vec![];
+    };
+    let contents = fs::read_to_string(path).unwrap_or_default();
+
+    let lines: Vec<&str> = contents.lines().collect();
+    lines[lines.len().saturating_sub(count)..]
+        .iter()
+        .map(|line| line.to_string())
+        .collect()
+}
+
+/// All kept log files joined, oldest first, for attaching to bug reports
+pub fn collect() -> anyhow::Result<String> {
+    let mut logs = String::new();
+    for path in log_files() {
+        logs.push_str(&fs::read_to_string(path)?);
+    }
+
+    Ok(logs)
+}
+
+/// Log files sorted oldest first, the date in their names sorts them
+fn log_files() -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(log_dir())
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.file_name()
+                        .and_then(|name| name.to_str())
+                        .is_some_and(|name| name.starts_with(LOG_PREFIX))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+
+    files
+}
@@ -1,372 +1,9701 @@
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use cdk::amount::{Amount, SplitTarget};
-use cdk::nuts::CurrencyUnit;
+use amount::{AmountEvent, AmountInput, AmountUnit, PRESETS};
+use bolt12::OfferDetails;
+use camera::CameraEvent;
+use cdk::amount::SplitTarget;
+use cdk::nuts::{CurrencyUnit, PublicKey, State, Token};
 use cdk::wallet::Wallet;
 use cdk::UncheckedUrl;
-use cdk_sqlite::WalletSQLiteDatabase;
-use config::{data_dir, generate_mnemonic, get_seed, save_seed};
-use iced::widget::{button, center, column, qr_code, row, text, text_input};
-use iced::{clipboard, Alignment, Element, Task, Theme};
+use chart::BalanceChart;
+use chrono::TimeZone;
+use config::{
+    create_profile, database_encrypted, get_or_create_seed, get_seed, has_passphrase, has_pin,
+    has_seed, invalid_words, is_backed_up, parse_mnemonic, profile, profiles, remove_pin,
+    save_seed, seed_backend, set_backed_up, set_database_encrypted, set_has_passphrase, set_pin,
+    set_profile, set_seed_backend, verify_pin, SeedBackend,
+};
+use db::{
+    unix_time, Contact, Database, DeliveryStatus, Direction, DleqStatus, FailedOperation,
+    HistoryFilter, InboxToken, InboxTokenStatus, NutzapStatus, NwcConnection, Recurrence,
+    RetryOperation, Sale, ScheduledAction, ScheduledPayment, SentToken, SentTokenStatus,
+    Transaction, TransactionStatus,
+};
+use discovery::DiscoveredMint;
+use error::WalletError;
+use export::ExportFormat;
+use fees::KeysetInfo;
+use fiat::{fetch_rate, ExchangeRate, FiatCurrency, RateSource};
+use i18n::Language;
+use iced::keyboard::{self, key, Key, Modifiers};
+use iced::widget::{
+    button, canvas, center, checkbox, column, container, image, pick_list, progress_bar, qr_code,
+    radio, row, scrollable, slider, text, text_input, Column, Row,
+};
+use iced::{
+    clipboard, widget, window, Alignment, Color, Element, Length, Point, Size, Subscription, Task,
+    Theme,
+};
+use iced_cashu_core::service::{
+    storage_size, MeltPreview, PendingMint, ProofStates, ProofSummary, Withdrawal,
+    AUTO_WITHDRAW_MIN_SATS,
+};
+use iced_cashu_core::{
+    backup, bbqr, bolt12, config, db, error, export, fees, htlc, invoice, lnurl, mint_info,
+    onchain, p2pk, poll, stats, token, WalletService, DEFAULT_MINT,
+};
+use input::{detect, token_amount, token_from_file, DetectedInput};
+use invoice::{decode_invoice, InvoiceDetails};
+use lnurl::{LnurlRequest, PayRequest, WithdrawRequest};
+use mint_info::{MintHealth, MintInfo};
+use nostr::{InboxEvent, IncomingToken};
+use nostr_sdk::Keys;
+use nutzap::{Nutzap, NutzapEvent};
+use nwc::{NwcError, NwcEvent, NwcMethod, NwcReply, NwcRequest, NwcResult};
+use payment_request::{PaymentEvent, PaymentPayload, PaymentRequest};
+use poll::Backoff;
+use pos::{DailyTotals, Pos, TIP_PRESETS};
+use proxy::ProxySettings;
+use settings::{LastView, Settings, WindowState, UI_SCALE_RANGE};
+use split::{SplitMode, SplitSetting};
+use stats::Stats;
+use token::{TokenFormat, TokenSummary};
+use tracing::instrument;
+use tray::{Tray, TrayAction};
+use ws::{QuoteKind, QuoteUpdate};
+use zeroize::Zeroizing;
 
-mod config;
+mod amount;
+mod camera;
+mod chart;
+mod cli;
+#[cfg(feature = "control-api")]
+mod control;
+mod discovery;
+mod fiat;
+#[macro_use]
+mod i18n;
+mod input;
+mod logs;
+mod nostr;
+mod notify;
+mod nutzap;
+mod nwc;
+mod palette;
+mod paper;
+mod payment_request;
+mod pos;
+mod proxy;
+mod settings;
+mod split;
+mod tray;
+mod ws;
 
-const DEFAULT_MINT: &str = "https://mint.thesimplekid.dev";
+const HISTORY_PAGE_SIZE: u32 = 20;
+const RATE_REFRESH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const CLIPBOARD_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const PROOF_STATE_INTERVAL: Duration = Duration::from_secs(10 * 60);
+const SENT_TOKEN_POLL_INTERVAL: Duration = Duration::from_secs(60);
+const MINT_HEALTH_INTERVAL: Duration = Duration::from_secs(2 * 60);
+/// How often mints are checked for retired keysets the wallet holds proofs of
+const KEYSET_CHECK_INTERVAL: Duration = Duration::from_secs(30 * 60);
+const RETRY_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// Seconds until a one-off scheduled payment that failed is tried again
+const SCHEDULE_RETRY_DELAY: i64 = 60 * 60;
+/// How often the OS dark mode preference is checked when following it
+const SYSTEM_THEME_INTERVAL: Duration = Duration::from_secs(5);
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+const MIN_PIN_LENGTH: usize = 4;
+const LOG_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+/// Log lines shown in the Debug view
+const LOG_TAIL_LINES: usize = 200;
+/// Tokens longer than this are shown as an animated QR code
+const ANIMATED_QR_THRESHOLD: usize = 1000;
+/// Ids of the inputs that views and shortcuts focus
+const DATA_INPUT: &str = "data";
+const SEND_AMOUNT_INPUT: &str = "send-amount";
+const RECEIVE_AMOUNT_INPUT: &str = "receive-amount";
+const MINT_URL_INPUT: &str = "mint-url";
+const PIN_INPUT: &str = "pin";
+const PALETTE_INPUT: &str = "palette";
+const POS_AMOUNT_INPUT: &str = "pos-amount";
+const ERROR_COLOR: Color = Color::from_rgb(1.0, 0.33, 0.33);
+const ONLINE_COLOR: Color = Color::from_rgb(0.31, 0.8, 0.4);
+const SLOW_COLOR: Color = Color::from_rgb(0.95, 0.77, 0.2);
 
 pub fn main() -> iced::Result {
+    cli::init();
+    logs::init();
+    proxy::apply();
+    let settings = Settings::load();
+    i18n::set_language(settings.language);
+    if let Some(profile) = settings.profile {
+        if profiles().contains(&profile) {
+            set_profile(&profile);
+        }
+    }
+
     iced::program("Cashu Wallet - Iced", IcedCashu::update, IcedCashu::view)
+        .window_size(settings.window.size())
+        .position(settings.window.position())
         .theme(IcedCashu::theme)
+        .scale_factor(IcedCashu::scale_factor)
+        .subscription(IcedCashu::subscription)
+        .exit_on_close_request(false)
         .run()
 }
 
 #[derive(Default)]
 struct IcedCashu {
-    wallet: Option<Arc<Wallet>>,
+    service: Option<WalletService>,
+    /// Token, invoice, LNURL or payment request typed into the Send/Receive
+    /// view
     data: String,
-    pay_invoice: String,
     invoice: String,
     token: String,
     qr_code: Option<qr_code::Data>,
+    /// Parts of an animated QR code for a token too long for one
+    qr_frames: Vec<qr_code::Data>,
+    /// Part of `qr_frames` being shown
+    qr_frame: usize,
+    /// Last created token, re-encoded when the format changes
+    sent_token: Option<Token>,
+    /// Where the last created token was saved to
+    token_file_path: Option<String>,
+    token_format: TokenFormat,
     view: View,
     balance: u64,
-    receive_amount: String,
-    send_amount: String,
+    receive_amount: AmountInput,
+    send_amount: AmountInput,
     active_mint: UncheckedUrl,
+    mints: Vec<UncheckedUrl>,
+    mint_balances: HashMap<UncheckedUrl, u64>,
+    /// Balance in each unit, other than sats only shown when held
+    unit_balances: HashMap<CurrencyUnit, u64>,
+    /// Units the active mint issues, sats first
+    mint_units: Vec<CurrencyUnit>,
+    /// Unit minted, sent and paid in
+    unit: CurrencyUnit,
+    add_mint_url: String,
+    /// Mints found on nostr, None while searching
+    discovered_mints: Option<Result<Vec<DiscoveredMint>, String>>,
+    /// Info of the active mint
+    mint_info: Option<MintDetails>,
+    /// Mint opened from the mint list, None while loading
+    mint_overview: Option<MintOverview>,
+    /// Mint whose funds have to be moved away before it is removed
+    removal: Option<MintRemoval>,
+    maintenance: Option<Maintenance>,
+    /// Mint waiting to be reviewed before it is first used
+    mint_review: Option<MintReview>,
+    history: Vec<Transaction>,
+    history_page: u32,
+    history_count: u32,
+    /// Sent tokens the recipient has not redeemed yet
+    sent_tokens: Vec<SentToken>,
+    error: Option<WalletError>,
+    backed_up: bool,
+    seed_words: Vec<String>,
+    verify_indices: Vec<usize>,
+    verify_words: Vec<String>,
+    restore_mnemonic: String,
+    /// Seed encryption password, kept for the session
+    password: Zeroizing<String>,
+    /// Whether a new or restored wallet gets a BIP39 passphrase
+    use_passphrase: bool,
+    /// BIP39 passphrase, only kept until the wallet is open
+    passphrase: Zeroizing<String>,
+    passphrase_confirm: Zeroizing<String>,
+    backup_password: Zeroizing<String>,
+    /// Where the last backup was written
+    backup_path: Option<String>,
+    seed_backend: SeedBackend,
+    /// App database encryption as configured, applied when the wallet opens
+    database_encrypted: bool,
+    settings: Settings,
+    default_mint_input: String,
+    polling_interval_input: String,
+    max_polling_input: String,
+    qr_interval_input: String,
+    /// Scale picked on the slider, applied once it is released
+    ui_scale_input: u16,
+    exchange_rate: Option<ExchangeRate>,
+    scanning: bool,
+    camera_frame: Option<image::Handle>,
+    /// Parts of an animated QR code scanned so far and in total
+    scan_progress: Option<(usize, usize)>,
+    /// Last clipboard contents seen, so the same content isn't offered twice
+    last_clipboard: String,
+    clipboard_prompt: Option<DetectedInput>,
+    lnurl_pay: Option<PayRequest>,
+    lnurl_amount: String,
+    melt_preview: Option<MeltPreview>,
+    /// Quotes from every mint with a balance, cheapest first, when there is
+    /// more than one to pick from
+    melt_routes: Vec<MeltPreview>,
+    /// Parts of an invoice paid from several mints together (NUT-15)
+    multi_path: Vec<MeltPreview>,
+    pending_mint: Option<PendingMint>,
+    /// Pending section of the main view is expanded
+    pending_expanded: bool,
+    /// History entry shown in full
+    transaction_detail: Option<Transaction>,
+    history_filter: HistoryFilter,
+    /// Labels used on any transaction
+    history_tags: Vec<String>,
+    note_input: String,
+    /// Labels of the shown transaction as typed, comma separated
+    tags_input: String,
+    /// History date range as typed, `YYYY-MM-DD`
+    history_from: String,
+    history_to: String,
+    history_min: String,
+    history_max: String,
+    export_format: ExportFormat,
+    /// Where the history was last exported to
+    history_export_path: Option<String>,
+    /// Summary of the history in the active unit
+    stats: Option<Stats>,
+    /// Melt currently being paid
+    melting: Option<MeltPreview>,
+    /// Operations started and not finished yet, with how many of each run
+    in_flight: HashMap<Operation, usize>,
+    /// Home was pressed while a flow was unfinished, waiting for the user to
+    /// confirm leaving it
+    leaving: bool,
+    /// Search of the open command palette
+    palette: Option<String>,
+    /// Operations the user went home from, whose results are offered in the
+    /// pending panel instead of opening their view
+    left_running: HashSet<Operation>,
+    /// View of a result that finished after the user left its flow
+    ready: Option<View>,
+    melt_state: Option<String>,
+    /// Quotes whose mint has no websocket, polled over HTTP instead
+    poll_quotes: HashSet<String>,
+    /// Whether the OS prefers dark mode, as last checked
+    system_dark: bool,
+    /// Result of the last health check of each mint
+    mint_health: HashMap<UncheckedUrl, MintHealth>,
+    /// Sats each mint holds on keysets it retired
+    retired_keysets: HashMap<UncheckedUrl, u64>,
+    /// Retry queue of receives, mints and melts that failed
+    failed_operations: Vec<FailedOperation>,
+    /// Failed operations being run again
+    retrying: HashSet<i64>,
+    /// Upcoming scheduled payments, soonest first
+    scheduled_payments: Vec<ScheduledPayment>,
+    /// Scheduled payments being made
+    paying_scheduled: HashSet<i64>,
+    /// Contact a new scheduled payment sends tokens to, instead of paying
+    /// `schedule_address`
+    schedule_contact: Option<Contact>,
+    schedule_address: String,
+    schedule_amount: String,
+    schedule_recurrence: Recurrence,
+    /// Local date and time of the first payment, `YYYY-MM-DD HH:MM`
+    schedule_start: String,
+    pos: Pos,
+    /// Delays between checks of `pending_mint`, set with it
+    poll_backoff: Option<Backoff>,
+    poll_delay: Duration,
+    send_preview: Option<SendPreview>,
+    split_mode: SplitMode,
+    split_input: String,
+    proof_summaries: Vec<ProofSummary>,
+    nwc_connections: Vec<NwcConnection>,
+    /// Channel to the running NWC service for replies
+    nwc_sender: Option<futures::channel::mpsc::Sender<NwcReply>>,
+    /// Channel to the running control API for replies
+    #[cfg(feature = "control-api")]
+    control_sender: Option<futures::channel::mpsc::Sender<control::ControlReply>>,
+    #[cfg(feature = "control-api")]
+    control_token: Option<String>,
+    #[cfg(feature = "control-api")]
+    control_port_input: String,
+    nwc_name: String,
+    nwc_budget: String,
+    nwc_relay: String,
+    /// Connection string of the connection just created
+    nwc_uri: Option<String>,
+    transfer_to: Option<UncheckedUrl>,
+    transfer_amount: String,
+    transfer_preview: Option<TransferPreview>,
+    transfer_step: Option<TransferStep>,
+    withdraw_address: String,
+    withdraw_amount: String,
+    withdraw_preview: Option<MeltPreview>,
+    /// On-chain withdrawal the mint has yet to broadcast
+    withdrawal: Option<Withdrawal>,
+    /// Step of the first run setup, none once it is finished
+    onboarding: Option<OnboardingStep>,
+    /// Result of the last NUT-07 proof state check
+    proof_states: Option<ProofStates>,
+    /// Public key of the P2PK signing key derived from the seed
+    p2pk_pubkey: Option<PublicKey>,
+    /// Public keys of signing keys imported this session
+    imported_pubkeys: Vec<PublicKey>,
+    signing_key_input: Zeroizing<String>,
+    /// HTLC preimages the wallet holds, by the hash they unlock
+    preimages: HashMap<String, String>,
+    /// Preimage typed to claim an HTLC locked token
+    preimage_input: Zeroizing<String>,
+    /// Nostr identity, derived from the seed unless one was imported or
+    /// generated
+    nostr_keys: Option<Keys>,
+    /// Identity derived from the seed, used again when a stored one is removed
+    seed_nostr_keys: Option<Keys>,
+    nostr_key_input: Zeroizing<String>,
+    /// QR code of the identity's npub shown in Settings
+    npub_qr: Option<qr_code::Data>,
+    /// Receipt of the point-of-sale payment just taken, for the customer to
+    /// scan
+    receipt_qr: Option<qr_code::Data>,
+    relays_input: String,
+    /// Send transaction of the token on the Token view
+    sent_transaction: Option<i64>,
+    nostr_recipient: String,
+    /// Recipients tokens were sent to before, most recent first
+    nostr_recipients: Vec<String>,
+    nostr_sending: bool,
+    nostr_delivery: Option<DeliveryStatus>,
+    /// Unclaimed tokens received over nostr DMs
+    inbox: Vec<InboxToken>,
+    contacts: Vec<Contact>,
+    contact_name: String,
+    contact_address: String,
+    contact_npub: String,
+    contact_pubkey: String,
+    /// Contact name and P2PK key the next token is locked to
+    send_lock: Option<(String, PublicKey)>,
+    /// Lock the next token to the preimage of `hash_lock_input` (NUT-14)
+    send_hash_lock: bool,
+    hash_lock_input: String,
+    /// Preimage generated for `hash_lock_input`, kept once the token is sent
+    send_preimage: Option<Zeroizing<String>>,
+    /// Hours after which a locked token can be refunded to this wallet,
+    /// empty for never
+    refund_hours: String,
+    /// Sent tokens whose refund was announced this session
+    refunds_notified: HashSet<i64>,
+    /// Message to the recipient of the next token
+    send_memo: String,
+    request_amount: String,
+    request_description: String,
+    payment_request: Option<OpenRequest>,
+    /// Amount claimed for the last payment request
+    request_received: Option<u64>,
+    request_preview: Option<PaymentRequestPreview>,
+    tray: Option<Tray>,
+    pin_set: bool,
+    locked: bool,
+    /// Sensitive action waiting for the PIN
+    pin_prompt: Option<Box<Message>>,
+    /// PIN was just entered for the action in `pin_prompt`
+    pin_verified: bool,
+    pin_input: Zeroizing<String>,
+    new_pin: Zeroizing<String>,
+    last_activity: Option<Instant>,
+    lock_timeout_input: String,
+    pin_threshold_input: String,
+    auto_withdraw_address_input: String,
+    auto_withdraw_threshold_input: String,
+    /// Why the auto-withdraw rule was last paused
+    auto_withdraw_error: Option<String>,
+    proxy_input: String,
+    /// Round trip to each mint in milliseconds from the last connection test
+    proxy_test: Vec<(UncheckedUrl, Result<u64, String>)>,
+    proxy_testing: bool,
+    /// Name typed in for a new wallet profile
+    new_profile: String,
+    /// Tail of the log file shown in the Debug view
+    log_lines: Vec<String>,
+    /// Where logs were last exported to
+    log_export_path: Option<String>,
+    /// Input pasted into the Decode view
+    decode_input: String,
+}
+
+/// Mint the wallet doesn't know yet, shown for review before it is used
+#[derive(Debug, Clone)]
+struct MintReview {
+    mint_url: UncheckedUrl,
+    /// None while loading, an error if the mint could not be reached
+    details: Option<Result<MintDetails, String>>,
+    /// Highest input fee of the mint's keysets in parts per thousand
+    input_fee_ppk: Option<u64>,
+    /// What to do once the review is acknowledged
+    action: ReviewAction,
+    acknowledged: bool,
+}
+
+#[derive(Debug, Clone)]
+enum ReviewAction {
+    AddMint,
+    /// Receive this token
+    Receive(String),
+    /// Claim the inbox token with this id
+    ClaimInbox(i64),
+}
+
+/// Mint info loaded for display
+#[derive(Debug, Clone)]
+struct MintDetails {
+    mint_url: UncheckedUrl,
+    info: MintInfo,
+    icon: Option<image::Handle>,
+    /// The current MOTD was already dismissed
+    motd_dismissed: bool,
+}
+
+/// Keysets and open quotes of a mint, shown with its balance
+#[derive(Debug, Clone)]
+struct MintOverview {
+    mint_url: UncheckedUrl,
+    /// Error if the mint could not be reached
+    keysets: Result<Vec<KeysetInfo>, String>,
+    /// Mint quotes at this mint not minted yet
+    quotes: Vec<PendingMint>,
+}
+
+/// Mint being emptied to remove it
+#[derive(Debug, Clone)]
+struct MintRemoval {
+    mint_url: UncheckedUrl,
+    /// Mint the funds are moved to
+    target: Option<UncheckedUrl>,
+    /// Funds were moved or exported, what is left is fee change too small
+    /// to move and may be given up
+    migrated: bool,
+}
+
+/// Database maintenance run from Settings
+#[derive(Debug, Clone, Default)]
+struct Maintenance {
+    size_before: u64,
+    /// Set once the last step is done
+    size_after: Option<u64>,
+    /// Steps finished so far, in order
+    steps: Vec<MaintenanceStep>,
+    /// Error that stopped maintenance
+    error: Option<String>,
+}
+
+impl Maintenance {
+    const STEPS: usize = 4;
+
+    /// Whether maintenance is done, failed or stopped at a damaged database
+    fn finished(&self) -> bool {
+        let damaged = self.steps.iter().any(|step| match step {
+            MaintenanceStep::IntegrityChecked(problems) => !problems.is_empty(),
+            _ => false,
+        });
+
+        self.size_after.is_some() || self.error.is_some() || damaged
+    }
+}
+
+/// Finished step of database maintenance with what it found
+#[derive(Debug, Clone)]
+enum MaintenanceStep {
+    /// Problems found, nothing else is touched if there are any
+    IntegrityChecked(Vec<String>),
+    ProofsPruned(usize),
+    RecordsPruned(u64),
+    Vacuumed,
+}
+
+impl std::fmt::Display for MaintenanceStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MaintenanceStep::IntegrityChecked(problems) if problems.is_empty() => {
+                write!(f, "{}", t!("integrity-check-passed"))
+            }
+            MaintenanceStep::IntegrityChecked(problems) => write!(
+                f,
+                "{}",
+                t!("integrity-check-failed", problems = problems.join("; "))
+            ),
+            MaintenanceStep::ProofsPruned(count) => write!(
+                f,
+                "{}",
+                t!("spent-proofs-pruned", count = i18n::format_number(count))
+            ),
+            MaintenanceStep::RecordsPruned(count) => write!(
+                f,
+                "{}",
+                t!("old-records-pruned", count = i18n::format_number(count))
+            ),
+            MaintenanceStep::Vacuumed => write!(f, "{}", t!("database-vacuumed")),
+        }
+    }
+}
+
+/// Quotes for moving funds from one mint to another over lightning
+#[derive(Debug, Clone)]
+struct TransferPreview {
+    /// Melt at the source mint paying the mint quote's invoice
+    melt: MeltPreview,
+    /// Mint quote at the destination mint
+    mint: PendingMint,
+}
+
+/// Steps of the first run setup after the wallet is created
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OnboardingStep {
+    /// Write down and verify the seed words, skipped for restored wallets
+    Backup,
+    /// Pick the mint to start with
+    Mint,
+    /// Optionally set a PIN
+    Pin,
+}
+
+/// Progress of a confirmed transfer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TransferStep {
+    Paying,
+    Minting,
+}
+
+/// Token about to be created, shown for confirmation
+#[derive(Debug, Clone)]
+struct SendPreview {
+    mint_url: UncheckedUrl,
+    amount: u64,
+    unit: CurrencyUnit,
+    /// Contact whose P2PK key the token is locked to
+    lock: Option<(String, PublicKey)>,
+    /// Hash whose preimage the token is locked to
+    hash_lock: Option<String>,
+    /// Preimage of `hash_lock` if the wallet generated it
+    preimage: Option<String>,
+    /// Unix time from which a locked token can be refunded to this wallet
+    locktime: Option<u64>,
+    /// Estimated input fee of the swap for the token's proofs
+    fee: u64,
+    /// Message to the recipient carried in the token
+    memo: Option<String>,
+}
+
+/// Payment to a NUT-18 request waiting for confirmation
+#[derive(Debug, Clone)]
+struct PaymentRequestPreview {
+    request: PaymentRequest,
+    mint_url: UncheckedUrl,
+    amount: u64,
+    /// Estimated input fee of the swap for the payment's proofs
+    fee: u64,
+}
+
+/// NUT-18 request waiting to be paid
+#[derive(Debug, Clone)]
+struct OpenRequest {
+    id: String,
+    encoded: String,
+    amount: Option<u64>,
+    mints: Vec<String>,
+    /// A payment arrived and is being claimed
+    claiming: bool,
 }
 
 #[derive(Debug, Clone, Default)]
 enum View {
     #[default]
     Main,
-    Receive,
-    Pay,
+    SendReceive,
     Invoice,
     Token,
+    AddMint,
+    DiscoverMints,
+    MintInfo,
+    Mint,
+    RemoveMint,
+    Onboarding,
+    MintReview,
+    History,
+    SentTokens,
+    Stats,
+    Transfer,
+    Withdraw,
+    TransactionDetail,
+    Settings,
+    SeedBackup,
+    SeedVerify,
+    Restore,
+    Scan,
+    LnurlPay,
+    ConfirmMelt,
+    ConfirmMultiPath,
+    ConfirmSend,
+    Proofs,
+    Nwc,
+    Contacts,
+    Schedule,
+    /// Point-of-sale mode, charging customers invoices of the active mint
+    Pos,
+    RequestPayment,
+    ConfirmPaymentRequest,
+    Debug,
+    Decode,
+}
+
+/// Wallet operation running in the background, shown as progress in the
+/// views it affects, which disable starting another one meanwhile
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Operation {
+    /// Quoting a payment, token or invoice to confirm
+    Quote,
+    Melt,
+    /// Creating a token or paying a payment request with one
+    Send,
+    /// Minting the tokens of a paid quote
+    Mint,
+    Receive,
+    /// Reloading the balances
+    Balance,
+    /// Melting the balance above the auto-withdraw threshold
+    AutoWithdraw,
+}
+
+impl std::fmt::Display for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Operation::Quote => write!(f, "{}", t!("getting-a-quote")),
+            Operation::Melt => write!(f, "{}", t!("paying")),
+            Operation::Send => write!(f, "{}", t!("creating-token")),
+            Operation::Mint => write!(f, "{}", t!("minting")),
+            Operation::Receive => write!(f, "{}", t!("receiving")),
+            Operation::Balance => write!(f, "{}", t!("checking-balance")),
+            Operation::AutoWithdraw => write!(f, "{}", t!("auto-withdrawing")),
+        }
+    }
+}
+
+/// Global keyboard shortcut
+#[derive(Debug, Clone, Copy)]
+enum Shortcut {
+    /// Ctrl+V outside an input, opening the clipboard's contents
+    Paste,
+    /// Esc
+    Home,
+    /// Ctrl+R
+    Receive,
+    /// Ctrl+S
+    Send,
+    /// Ctrl+K
+    Palette,
+    /// Enter outside an input, confirming the view's action
+    Submit,
+    FocusNext,
+    FocusPrevious,
 }
 
 #[derive(Debug, Clone)]
 enum Message {
     DataChanged(String),
-    ReceiveDataChanged(String),
-    SendDataChanged(String),
-    Pay,
-    PayBolt11Change(String),
+    ReceiveAmount(AmountEvent),
+    SendAmount(AmountEvent),
+    MaxSendable(u64),
+    SendReceive,
+    SubmitInput,
     PayInvoice,
+    MeltQuote(MeltPreview),
+    MeltQuotes(Vec<MeltPreview>),
+    MeltRouteSelected(usize),
+    ConfirmMelt,
+    MultiPathQuotes(Vec<MeltPreview>),
+    ConfirmMultiPath,
+    Melted(u64),
+    MeltFailed(WalletError),
+    QuoteUpdate(QuoteUpdate),
+    LnurlResolved(LnurlRequest),
+    LnurlAmountChanged(String),
+    PayLnurl,
     NewWallet,
-    WalletCreated(Wallet),
-    MintQuote((String, String)),
-    ReceiveEcash,
+    PasswordChanged(String),
+    UsePassphraseToggled(bool),
+    PassphraseChanged(String),
+    PassphraseConfirmChanged(String),
+    RestoreView,
+    RestoreMnemonicChanged(String),
+    RestoreWallet,
+    BackupPasswordChanged(String),
+    ExportBackup,
+    BackupExported(Option<String>),
+    ImportBackup,
+    WalletCreated((WalletService, Keys)),
+    SigningKeyChanged(String),
+    ImportSigningKey,
+    SigningKeyImported(PublicKey),
+    PreimagesLoaded(HashMap<String, String>),
+    PreimageChanged(String),
+    HashLockToggled(bool),
+    HashLockChanged(String),
+    GeneratePreimage,
+    RefundHoursChanged(String),
+    CopyPreimage(String),
+    NostrKeyChanged(String),
+    ImportNostrKey,
+    GenerateNostrKey,
+    UseSeedNostrKey,
+    ToggleNpubQr,
+    CopyNpub,
+    MintQuote(PendingMint),
+    CheckMintQuote,
+    CreateDeposit,
+    TogglePending,
+    MintQuoteStatus((String, bool)),
+    MintQuotesLoaded(Vec<PendingMint>),
+    MintQuoteResumed((PendingMint, Option<u64>)),
+    MeltsRecovered(u64),
+    ShowTransaction(Transaction),
+    HistoryTags(Vec<String>),
+    HistorySearchChanged(String),
+    HistoryTagSelected(String),
+    HistoryMintSelected(UncheckedUrl),
+    HistoryDirectionSelected(Direction),
+    HistoryStatusSelected(TransactionStatus),
+    HistoryFromChanged(String),
+    HistoryToChanged(String),
+    HistoryMinChanged(String),
+    HistoryMaxChanged(String),
+    ClearHistoryFilter,
+    NoteChanged(String),
+    TagsChanged(String),
+    SaveNote,
+    NoteSaved(Transaction),
+    ExportFormatSelected(ExportFormat),
+    ExportHistory,
+    HistoryExported(Option<String>),
+    Tick,
+    CancelMintQuote,
     Receive,
     Minted(u64),
     CheckBalance(u64),
     Balance(u64),
     CreateInvoice,
     CreateToken,
-    TokenCreated(String),
+    SendQuoted(SendPreview),
+    ConfirmSend,
+    TokenCreated((i64, String)),
+    TokenFormatSelected(TokenFormat),
     CopyInvoice,
     CopyToken,
+    Finished(Operation),
+    Shortcut(Shortcut),
+    PaletteChanged(String),
+    PaletteSubmit,
+    /// Command picked from the palette
+    RunCommand(Box<Message>),
+    Pasted(Option<String>),
+    StayOnView,
+    ShowReady,
+    DismissReady,
+    SaveTokenFile,
+    ExportPaper,
+    TokenFileSaved(Option<String>),
+    OpenTokenFile,
+    FileDropped(PathBuf),
+    TokenFileOpened(Option<String>),
+    AddMintView,
+    AddMintUrlChanged(String),
+    DiscoverMints,
+    MintsDiscovered(Result<Vec<DiscoveredMint>, String>),
+    AddDiscoveredMint(UncheckedUrl),
+    AddMint,
+    MintAdded(UncheckedUrl),
+    MintSelected(UncheckedUrl),
+    MintBalances(HashMap<UncheckedUrl, u64>),
+    UnitBalances(HashMap<CurrencyUnit, u64>),
+    MintUnits((UncheckedUrl, Vec<CurrencyUnit>)),
+    UnitSelected(CurrencyUnit),
+    MintInfoView,
+    MintInfoLoaded(MintDetails),
+    MintView(UncheckedUrl),
+    MintOverviewLoaded(MintOverview),
+    SetDefaultMint(UncheckedUrl),
+    /// Open the transfer of this mint's funds to another mint
+    SwapAwayFrom(UncheckedUrl),
+    /// Remove an empty mint or start moving its funds away
+    RemoveMint(UncheckedUrl),
+    RemovalTargetSelected(UncheckedUrl),
+    /// Move the funds of the mint being removed to the target over lightning
+    MigrateFunds,
+    FundsMigrated(u64),
+    /// Export the funds of the mint being removed as a token
+    ExportFunds,
+    FundsExported((i64, String)),
+    /// Remove the mint, giving up what the migration left
+    ConfirmRemoval,
+    MintRemoved(UncheckedUrl),
+    RunMaintenance,
+    MaintenanceProgress(Result<MaintenanceStep, String>),
+    CheckMintHealth,
+    LoadFailedOperations,
+    FailedOperationsLoaded(Vec<FailedOperation>),
+    RetryDue,
+    RetryNow(i64),
+    Retried((i64, Result<u64, WalletError>)),
+    DiscardFailed(i64),
+    MintHealthChecked((UncheckedUrl, MintHealth)),
+    MintReviewLoaded((UncheckedUrl, Result<MintInfo, String>, Option<u64>)),
+    MintReviewAcknowledged(bool),
+    ConfirmMintReview,
+    ClaimAndSwap,
+    TokenSwapped(u64),
+    CancelMintReview,
+    DismissMotd,
+    CheckProofStates,
+    ProofsView,
+    NwcView,
+    NwcNameChanged(String),
+    NwcBudgetChanged(String),
+    NwcRelayChanged(String),
+    CreateNwcConnection,
+    NwcConnectionCreated(NwcConnection),
+    NwcConnectionsLoaded(Vec<NwcConnection>),
+    DeleteNwcConnection(i64),
+    Nwc(NwcEvent),
+    NwcReplied(NwcReply),
+    ProofsLoaded(Vec<ProofSummary>),
+    Consolidate(UncheckedUrl),
+    Consolidated(u64),
+    ProofStatesChecked(ProofStates),
+    HistoryView,
+    HistoryPage(u32),
+    HistoryLoaded((Vec<Transaction>, u32)),
+    SentTokensView,
+    StatsView,
+    StatsLoaded(Stats),
+    TransferView,
+    TransferToSelected(UncheckedUrl),
+    TransferAmountChanged(String),
+    PreviewTransfer,
+    TransferQuoted(TransferPreview),
+    ConfirmTransfer,
+    TransferPaid(TransferPreview),
+    TransferComplete(u64),
+    TransferFailed(WalletError),
+    WithdrawView,
+    WithdrawAddressChanged(String),
+    WithdrawAmountChanged(String),
+    PreviewWithdrawal,
+    WithdrawalQuoted(MeltPreview),
+    ConfirmWithdrawal,
+    WithdrawalUpdated(Withdrawal),
+    CheckWithdrawal,
+    CheckSentTokens,
+    SentTokensLoaded(Vec<SentToken>),
+    Reclaim(i64),
+    Reclaimed(u64),
+    Error(WalletError),
+    DismissError,
+    Settings,
+    ShowSeed,
+    SeedBackendSelected(SeedBackend),
+    DatabaseEncryptionToggled(bool),
+    FiatCurrencySelected(FiatCurrency),
+    SplitModeSelected(SplitMode),
+    SplitInputChanged(String),
+    SaveSplitSetting,
+    RelaysInputChanged(String),
+    SaveRelays,
+    NostrRecipientChanged(String),
+    NostrRecipientsLoaded(Vec<String>),
+    SendNostr,
+    NostrSent(DeliveryStatus),
+    NostrSendFailed(WalletError),
+    Inbox(InboxEvent),
+    InboxTokenAdded(Option<InboxToken>),
+    InboxTokensLoaded(Vec<InboxToken>),
+    ClaimInboxToken(i64),
+    InboxTokenClaimed((i64, u64)),
+    DismissInboxToken(i64),
+    InboxTokenDismissed(i64),
+    NutzapsToggled(bool),
+    AutoSwapToggled(bool),
+    AutoWithdrawToggled(bool),
+    AutoWithdrawAddressChanged(String),
+    AutoWithdrawThresholdChanged(String),
+    SaveAutoWithdraw,
+    AutoWithdrawn(Result<u64, String>),
+    AutoRotateKeysetsToggled(bool),
+    CheckKeysets,
+    KeysetsChecked(HashMap<UncheckedUrl, u64>),
+    /// Swap a mint's proofs of retired keysets for ones of its active keysets
+    RotateKeysets(UncheckedUrl),
+    KeysetsRotated((UncheckedUrl, u64)),
+    LanguageSelected(Language),
+    ThemeSelected(Theme),
+    FollowSystemThemeToggled(bool),
+    LightThemeSelected(Theme),
+    DarkThemeSelected(Theme),
+    UiScaleChanged(u16),
+    SaveUiScale,
+    CheckSystemTheme,
+    SystemThemeChecked(bool),
+    DefaultMintChanged(String),
+    PollingIntervalChanged(String),
+    MaxPollingChanged(String),
+    QuoteRateLimited(Option<u64>),
+    QrIntervalChanged(String),
+    NextQrFrame,
+    SaveGeneralSettings,
+    ProxyInputChanged(String),
+    SaveProxy,
+    ProxyBypassToggled(UncheckedUrl, bool),
+    TestProxy,
+    ProxyTested(Vec<(UncheckedUrl, Result<u64, String>)>),
+    NotificationsToggled(bool),
+    MinimizeToTrayToggled(bool),
+    PinInputChanged(String),
+    Unlock,
+    CancelPin,
+    NewPinChanged(String),
+    SetPin,
+    RemovePin,
+    LockTimeoutChanged(String),
+    PinThresholdChanged(String),
+    SaveLockSettings,
+    Activity,
+    CheckIdle,
+    Tray(TrayAction),
+    WindowCloseRequested(window::Id),
+    WindowStateFetched(Size, Option<Point>),
+    ContactsView,
+    ContactNameChanged(String),
+    ContactAddressChanged(String),
+    ContactNpubChanged(String),
+    ContactPubkeyChanged(String),
+    AddContact,
+    ContactsLoaded(Vec<Contact>),
+    DeleteContact(i64),
+    PayContactSelected(Contact),
+    NostrContactSelected(Contact),
+    ScheduleView,
+    PosView,
+    PosAmountChanged(String),
+    PosTipSelected(u64),
+    PosCharge,
+    PosQuote(PendingMint),
+    PosNoteChanged(String),
+    PosNext,
+    SaleLogged(()),
+    ShowReceipt,
+    CopyReceipt,
+    SaveReceipt,
+    ReceiptSaved(Option<String>),
+    ExportSales,
+    SalesExported(Option<String>),
+    ScheduledPaymentsLoaded(Vec<ScheduledPayment>),
+    ScheduleContactSelected(Contact),
+    ScheduleAddressChanged(String),
+    ScheduleAmountChanged(String),
+    ScheduleRecurrenceSelected(Recurrence),
+    ScheduleStartChanged(String),
+    AddScheduledPayment,
+    SkipScheduledPayment(i64),
+    CancelScheduledPayment(i64),
+    ScheduleDue,
+    ScheduledPaymentMade((i64, Result<u64, WalletError>)),
+    ClearSendLock,
+    SendMemoChanged(String),
+    RequestPaymentView,
+    RequestAmountChanged(String),
+    RequestDescriptionChanged(String),
+    CreatePaymentRequest,
+    PaymentRequestEvent(PaymentEvent),
+    PaymentRequestPaid(u64),
+    PaymentRequestClaimFailed(WalletError),
+    PaymentRequestQuoted(PaymentRequestPreview),
+    ConfirmPaymentRequest,
+    PaymentRequestSent(u64),
+    NutzapInfoPublished(()),
+    Nutzap(NutzapEvent),
+    NutzapRedeemed(Option<u64>),
+    RateSourceSelected(RateSource),
+    FetchRate,
+    Scan,
+    CancelScan,
+    Camera(CameraEvent),
+    CheckClipboard,
+    ClipboardRead(Option<String>),
+    AcceptClipboard,
+    DismissClipboard,
+    RateFetched(Option<ExchangeRate>),
+    VerifySeed,
+    VerifyWordChanged((usize, String)),
+    SubmitSeedVerification,
+    ProfileSelected(String),
+    NewProfileChanged(String),
+    CreateProfile,
+    SwitchProfile,
+    DebugView,
+    DecodeView,
+    DecodeInputChanged(String),
+    RefreshLogs,
+    LogsLoaded(Vec<String>),
+    ExportLogs,
+    LogsExported(Option<String>),
+    Onboarding(OnboardingStep),
+    FinishOnboarding,
+    #[cfg(feature = "control-api")]
+    Control(control::ControlEvent),
+    #[cfg(feature = "control-api")]
+    ControlReplied(control::ControlReply),
+    #[cfg(feature = "control-api")]
+    ControlApiToggled(bool),
+    #[cfg(feature = "control-api")]
+    ControlPortChanged(String),
+    #[cfg(feature = "control-api")]
+    SaveControlPort,
+    #[cfg(feature = "control-api")]
+    CopyControlToken,
     Home,
 }
 
-async fn new_wallet() -> Wallet {
-    let db_path = data_dir().join("./cashu_iced.sqlite");
-    let localstore = WalletSQLiteDatabase::new(&db_path.to_string_lossy())
+/// Open the current profile's wallet along with the nostr identity derived
+/// from its seed
+async fn new_wallet(
+    password: Zeroizing<String>,
+    passphrase: Zeroizing<String>,
+) -> Result<(WalletService, Keys), WalletError> {
+    let seed = get_or_create_seed(&password).map_err(|err| WalletError::Seed(err.to_string()))?;
+    let service =
+        WalletService::open(&seed, &passphrase, Settings::load().start_mint(), &password).await?;
+    let keys = nostr::derive_keys(&seed.to_seed_normalized(&passphrase))
+        .map_err(|err| WalletError::Seed(err.to_string()))?;
+
+    tracing::info!("Opened wallet for profile {}", profile());
+
+    Ok((service, keys))
+}
+
+#[instrument(skip_all, err)]
+async fn restore_wallet(
+    mnemonic: Zeroizing<String>,
+    password: Zeroizing<String>,
+    passphrase: Zeroizing<String>,
+) -> Result<(WalletService, Keys), WalletError> {
+    let mnemonic = parse_mnemonic(&mnemonic).map_err(|err| WalletError::Seed(err.to_string()))?;
+    save_seed(&mnemonic.to_string(), &password)
+        .map_err(|err| WalletError::Seed(err.to_string()))?;
+    // The user already has these words written down
+    set_backed_up().map_err(|err| WalletError::Seed(err.to_string()))?;
+
+    let (service, keys) = new_wallet(password, passphrase).await?;
+    service.restore().await?;
+
+    Ok((service, keys))
+}
+
+#[instrument(skip_all, err)]
+async fn import_signing_key(
+    wallet: Arc<Wallet>,
+    key: Zeroizing<String>,
+) -> Result<PublicKey, WalletError> {
+    let key =
+        p2pk::parse_secret_key(&key).map_err(|err| WalletError::SigningKey(err.to_string()))?;
+    let pubkey = key.public_key();
+
+    wallet.add_p2pk_signing_key(key).await;
+
+    Ok(pubkey)
+}
+
+/// Write seed, mints, proofs and history to an encrypted file picked by the
+/// user
+#[instrument(skip_all, err)]
+async fn export_backup(
+    service: WalletService,
+    password: Zeroizing<String>,
+    backup_password: Zeroizing<String>,
+) -> Result<Option<String>, WalletError> {
+    let data = service.export_backup(&password, &backup_password).await?;
+
+    let Some(file) = rfd::AsyncFileDialog::new()
+        .set_file_name("iced-cashu-backup.bin")
+        .save_file()
+        .await
+    else {
+        return Ok(None);
+    };
+    file.write(&data)
         .await
-        .unwrap();
-    localstore.migrate().await;
+        .map_err(|err| WalletError::Backup(err.to_string()))?;
+
+    Ok(Some(file.path().display().to_string()))
+}
 
-    let seed = match get_seed() {
-        Some(seed) => seed,
-        None => {
-            let seed = generate_mnemonic().unwrap();
+/// Write the whole transaction history in `format` to a file picked by the
+/// user
+async fn export_history(db: Database, format: ExportFormat) -> Result<Option<String>, WalletError> {
+    let transactions = db.get_transactions(u32::MAX, 0).await?;
+    let contents = export::export_transactions(&transactions, format)
+        .map_err(|err| WalletError::Export(err.to_string()))?;
 
-            save_seed(&seed.to_string());
-            seed
-        }
+    let Some(file) = rfd::AsyncFileDialog::new()
+        .set_file_name(format!("iced-cashu-history.{}", format.extension()))
+        .save_file()
+        .await
+    else {
+        return Ok(None);
     };
+    file.write(contents.as_bytes())
+        .await
+        .map_err(|err| WalletError::Export(err.to_string()))?;
+
+    Ok(Some(file.path().display().to_string()))
+}
 
-    let wallet = Wallet::new(Arc::new(localstore), &seed.to_seed_normalized(""), vec![]);
-    wallet
+async fn log_sale(db: Database, sale: Sale) -> Result<(), WalletError> {
+    db.add_sale(&sale).await?;
+
+    Ok(())
 }
 
-async fn mint_quote(wallet: Arc<Wallet>, mint_url: UncheckedUrl, amount: u64) -> (String, String) {
-    let quote = wallet
-        .mint_quote(mint_url, CurrencyUnit::Sat, Amount::from(amount))
+/// Print the receipt of `sale` to a PDF picked by the user
+async fn export_receipt(sale: Sale) -> Result<Option<String>, WalletError> {
+    let pdf = paper::render_receipt_pdf(&pos::receipt_lines(&sale), &pos::receipt_text(&sale))
+        .map_err(|err| WalletError::Export(err.to_string()))?;
+
+    let Some(file) = rfd::AsyncFileDialog::new()
+        .set_file_name(format!("receipt-{}.pdf", sale.timestamp))
+        .add_filter("PDF", &["pdf"])
+        .save_file()
+        .await
+    else {
+        return Ok(None);
+    };
+    file.write(&pdf)
         .await
-        .unwrap();
+        .map_err(|err| WalletError::Export(err.to_string()))?;
 
-    (quote.request, quote.id)
+    Ok(Some(file.path().display().to_string()))
 }
 
-async fn mint(wallet: Arc<Wallet>, mint_url: UncheckedUrl, quote_id: String) -> u64 {
-    let mut paid = false;
+/// Write the sales ledger as CSV to a file picked by the user
+async fn export_sales(db: Database) -> Result<Option<String>, WalletError> {
+    let contents = export::export_sales(&db.get_sales().await?);
 
-    while !paid {
-        paid = wallet
-            .mint_quote_status(mint_url.clone(), &quote_id)
-            .await
-            .unwrap()
-            .paid;
-        tokio::time::sleep(Duration::from_secs(5)).await;
-    }
-    let amount = wallet
-        .mint(mint_url, &quote_id, SplitTarget::default(), None)
+    let Some(file) = rfd::AsyncFileDialog::new()
+        .set_file_name("iced-cashu-sales.csv")
+        .save_file()
+        .await
+    else {
+        return Ok(None);
+    };
+    file.write(contents.as_bytes())
         .await
-        .unwrap();
+        .map_err(|err| WalletError::Export(err.to_string()))?;
 
-    amount.into()
+    Ok(Some(file.path().display().to_string()))
 }
 
-async fn receive(wallet: Arc<Wallet>, token: String) -> u64 {
-    let amount = wallet
-        .receive(&token, &SplitTarget::default(), None)
+/// Write `token` to a file picked by the user, as JSON if it is named
+/// `.cashu` and `encoded` otherwise
+async fn save_token_file(token: Token, encoded: String) -> Result<Option<String>, WalletError> {
+    let Some(file) = rfd::AsyncFileDialog::new()
+        .set_file_name("token.txt")
+        .add_filter("Token", &["txt"])
+        .add_filter("Cashu JSON", &["cashu"])
+        .save_file()
         .await
-        .unwrap();
+    else {
+        return Ok(None);
+    };
+    let contents = if file.path().extension().is_some_and(|ext| ext == "cashu") {
+        serde_json::to_string_pretty(&token)
+            .map_err(|err| WalletError::TokenFile(err.to_string()))?
+    } else {
+        encoded
+    };
+    file.write(contents.as_bytes())
+        .await
+        .map_err(|err| WalletError::TokenFile(err.to_string()))?;
 
-    amount.into()
+    Ok(Some(file.path().display().to_string()))
 }
 
-async fn create_token(wallet: Arc<Wallet>, mint_url: UncheckedUrl, amount: u64) -> String {
-    let quote = wallet
-        .send(
-            &mint_url,
-            CurrencyUnit::Sat,
-            Amount::from(amount),
-            None,
-            None,
-            &SplitTarget::None,
-        )
+/// Print `token` as a paper wallet to a PDF picked by the user
+async fn export_paper(token: Token, encoded: String) -> Result<Option<String>, WalletError> {
+    let pdf = paper::render_pdf(&encoded, &token::summary(&token))
+        .map_err(|err| WalletError::TokenFile(err.to_string()))?;
+
+    let Some(file) = rfd::AsyncFileDialog::new()
+        .set_file_name("cashu-paper.pdf")
+        .add_filter("PDF", &["pdf"])
+        .save_file()
+        .await
+    else {
+        return Ok(None);
+    };
+    file.write(&pdf)
+        .await
+        .map_err(|err| WalletError::TokenFile(err.to_string()))?;
+
+    Ok(Some(file.path().display().to_string()))
+}
+
+/// Encoded token in the file at `path`
+async fn read_token_file(path: PathBuf) -> Result<String, WalletError> {
+    let contents = tokio::fs::read_to_string(&path)
         .await
-        .unwrap();
+        .map_err(|err| WalletError::TokenFile(format!("{}: {}", path.display(), err)))?;
 
-    quote
+    token_from_file(&contents)
+        .ok_or_else(|| WalletError::TokenFile(format!("{} holds no cashu token", path.display())))
 }
 
-async fn pay_invoice(wallet: Arc<Wallet>, mint_url: UncheckedUrl, bolt11: String) -> u64 {
-    let quote = wallet
-        .melt_quote(mint_url.clone(), CurrencyUnit::Sat, bolt11, None)
+/// Write all kept log files to a file picked by the user
+async fn export_logs() -> Result<Option<String>, WalletError> {
+    let contents = logs::collect().map_err(|err| WalletError::Logs(err.to_string()))?;
+
+    let Some(file) = rfd::AsyncFileDialog::new()
+        .set_file_name("iced-cashu-logs.txt")
+        .save_file()
+        .await
+    else {
+        return Ok(None);
+    };
+    file.write(contents.as_bytes())
         .await
-        .unwrap();
+        .map_err(|err| WalletError::Logs(err.to_string()))?;
+
+    Ok(Some(file.path().display().to_string()))
+}
 
-    let paid = wallet
-        .melt(&mint_url, &quote.id, SplitTarget::None)
+/// Restore a wallet from a backup file, storing its seed under `password`
+#[instrument(skip_all, err)]
+async fn import_backup(
+    password: Zeroizing<String>,
+    passphrase: Zeroizing<String>,
+    backup_password: Zeroizing<String>,
+) -> Result<(WalletService, Keys), WalletError> {
+    let file = rfd::AsyncFileDialog::new()
+        .pick_file()
         .await
-        .unwrap();
+        .ok_or_else(|| WalletError::Backup("No backup file selected".to_string()))?;
+    let backup = backup::open(&file.read().await, &backup_password)
+        .map_err(|err| WalletError::Backup(err.to_string()))?;
+
+    save_seed(&backup.seed, &password).map_err(|err| WalletError::Seed(err.to_string()))?;
+    set_backed_up().map_err(|err| WalletError::Seed(err.to_string()))?;
 
-    println!("invoice paid: {}", paid.paid);
+    let (service, keys) = new_wallet(password, passphrase).await?;
+    service.import_backup(backup).await?;
 
-    0
+    Ok((service, keys))
 }
 
-async fn check_balance(wallet: Arc<Wallet>) -> u64 {
-    let amount = wallet.unit_balance(CurrencyUnit::Sat).await.unwrap();
+/// Make the payment of `scheduled`, returning the sats that went out
+///
+/// Tokens for contacts are DMed to them, a token whose DM failed stays in
+/// sent tokens to reclaim.
+async fn run_scheduled_payment(
+    service: WalletService,
+    keys: Option<Keys>,
+    relays: Vec<String>,
+    split: SplitSetting,
+    scheduled: ScheduledPayment,
+) -> Result<u64, WalletError> {
+    let keys = match (&scheduled.action, keys) {
+        (ScheduledAction::Send { .. }, None) => {
+            return Err(WalletError::Schedule(
+                "No nostr identity to send the token with".to_string(),
+            ));
+        }
+        (_, keys) => keys,
+    };
+
+    let sent = service
+        .run_scheduled_payment(&scheduled, split.target(scheduled.amount), |recipient| {
+            t!("scheduled-payment-to", recipient = recipient)
+        })
+        .await?;
+    if let (Some(sent), Some(keys)) = (sent, keys) {
+        let db = service.db().clone();
+        let delivery =
+            send_token_dm(keys, relays, db, sent.transaction_id, sent.npub, sent.token).await?;
+        if delivery == DeliveryStatus::Failed {
+            return Err(WalletError::Schedule(format!(
+                "Token for {} was not delivered, reclaim it from sent tokens",
+                sent.contact
+            )));
+        }
+    }
 
-    amount.into()
+    Ok(scheduled.amount)
 }
 
-impl IcedCashu {
-    fn update(&mut self, message: Message) -> Task<Message> {
-        match message {
-            Message::DataChanged(data) => {
-                self.data = data;
-                Task::none()
-            }
-            Message::ReceiveDataChanged(data) => {
-                if let Ok(data) = data.parse() {
-                    self.receive_amount = data;
-                }
-                Task::none()
-            }
-            Message::SendDataChanged(data) => {
-                if let Ok(data) = data.parse() {
-                    self.send_amount = data;
-                }
-                Task::none()
-            }
-            Message::NewWallet => {
-                self.active_mint = UncheckedUrl::from(DEFAULT_MINT);
-                Task::perform(new_wallet(), Message::WalletCreated)
-            }
-            Message::WalletCreated(wallet) => {
-                self.wallet = Some(Arc::new(wallet));
-                let wallet = self.wallet.clone().unwrap();
-                Task::perform(check_balance(wallet), Message::Balance)
-            }
-            Message::MintQuote((request, quote_id)) => {
-                self.qr_code = qr_code::Data::new(&request).ok();
-                self.invoice = request;
+/// Move `scheduled` on after a run, to its next time or an hour later for a
+/// one-off that failed, dropping a one-off that went through
+async fn reschedule(
+    db: Database,
+    scheduled: ScheduledPayment,
+    error: Option<String>,
+) -> Result<(), WalletError> {
+    let now = unix_time();
+    match (scheduled.next_after(now), &error) {
+        (Some(next_run), _) => {
+            db.reschedule_payment(scheduled.id, next_run, error.as_deref())
+                .await?
+        }
+        (None, Some(_)) => {
+            db.reschedule_payment(scheduled.id, now + SCHEDULE_RETRY_DELAY, error.as_deref())
+                .await?
+        }
+        (None, None) => db.delete_scheduled_payment(scheduled.id).await?,
+    }
 
-                self.view = View::Invoice;
-                let wallet = self.wallet.clone().unwrap();
-                Task::perform(
-                    mint(wallet, self.active_mint.clone(), quote_id),
-                    Message::Minted,
-                )
-            }
-            Message::Minted(_amount) => {
-                self.view = View::Main;
-                let wallet = self.wallet.clone().unwrap();
-                Task::perform(check_balance(wallet), Message::Balance)
-            }
-            Message::ReceiveEcash => {
-                self.view = View::Receive;
-                Task::none()
-            }
-            Message::Receive => {
-                let wallet = self.wallet.clone().unwrap();
-                self.view = View::Main;
-                self.data = "".to_string();
-                Task::perform(receive(wallet, self.data.clone()), Message::CheckBalance)
-            }
-            Message::CreateInvoice => {
-                let wallet = self.wallet.clone().unwrap();
-                let amount: u64 = self.receive_amount.parse().unwrap();
-                Task::perform(
-                    mint_quote(wallet, self.active_mint.clone(), amount),
-                    Message::MintQuote,
-                )
-            }
-            Message::CheckBalance(_amount) => {
-                let wallet = self.wallet.clone().unwrap();
-                Task::perform(check_balance(wallet), Message::Balance)
-            }
-            Message::Balance(amount) => {
-                self.balance = amount;
-                Task::none()
-            }
-            Message::CopyInvoice => {
-                // HACK: Copy isnt working so print to console
-                println!("{}", self.invoice);
-                clipboard::write::<String>(self.invoice.clone());
-                Task::none()
-            }
-            Message::CopyToken => {
-                // HACK: Copy isnt working so print to console
-                println!("{}", self.token);
-                clipboard::write::<String>(self.token.clone());
-                Task::none()
-            }
-            Message::PayBolt11Change(data) => {
-                self.pay_invoice = data;
-                Task::none()
-            }
-            Message::PayInvoice => {
-                let wallet = self.wallet.clone().unwrap();
-                self.view = View::Main;
-                Task::perform(
-                    pay_invoice(wallet, self.active_mint.clone(), self.pay_invoice.clone()),
-                    Message::CheckBalance,
-                )
-            }
-            Message::Pay => {
-                self.view = View::Pay;
-                Task::none()
-            }
-            Message::CreateToken => {
-                let wallet = self.wallet.clone().unwrap();
-                let amount: u64 = self.send_amount.parse().unwrap();
-                Task::perform(
-                    create_token(wallet, self.active_mint.clone(), amount),
-                    Message::TokenCreated,
-                )
-            }
-            Message::TokenCreated(token) => {
-                self.token = token;
-                self.view = View::Token;
-                Task::none()
-            }
-            Message::Home => {
-                let wallet = self.wallet.clone().unwrap();
-                self.data = "".to_string();
-                self.pay_invoice = "".to_string();
-                self.token = "".to_string();
-                self.qr_code = None;
-                self.receive_amount = "".to_string();
-                self.send_amount = "".to_string();
+    Ok(())
+}
 
-                self.view = View::Main;
-                Task::perform(check_balance(wallet), Message::Balance)
+async fn load_scheduled_payments(db: Database) -> Result<Vec<ScheduledPayment>, WalletError> {
+    Ok(db.get_scheduled_payments().await?)
+}
+
+/// Melt quotes for `bolt11` from each of `mints` in parallel, cheapest first
+///
+/// Mints that fail to quote are left out, unless all of them do.
+#[instrument(skip_all, fields(mints = mints.len()), err)]
+async fn melt_quotes(
+    service: WalletService,
+    mints: Vec<UncheckedUrl>,
+    bolt11: String,
+) -> Result<Vec<MeltPreview>, WalletError> {
+    let quotes = futures::future::join_all(
+        mints
+            .into_iter()
+            .map(|mint_url| service.melt_quote(mint_url, bolt11.clone(), CurrencyUnit::Sat)),
+    )
+    .await;
+
+    let mut routes = vec![];
+    let mut error = None;
+    for quote in quotes {
+        match quote {
+            Ok(route) => routes.push(route),
+            Err(err) => {
+                tracing::warn!("Could not get melt quote: {}", err);
+                error = Some(err);
             }
         }
     }
+    if let (true, Some(err)) = (routes.is_empty(), error) {
+        return Err(err);
+    }
+    routes.sort_by_key(MeltPreview::total);
 
-    fn view(&self) -> Element<Message> {
-        let title = text("Cashu").size(70);
+    Ok(routes)
+}
+
+/// Info and highest keyset input fee of a mint for its review
+async fn review_mint(
+    mint_url: UncheckedUrl,
+) -> (UncheckedUrl, Result<MintInfo, String>, Option<u64>) {
+    let info = mint_info::fetch_info(&mint_url)
+        .await
+        .map_err(|err| err.to_string());
+    let input_fee_ppk = match fees::fetch_keyset_fees(&mint_url).await {
+        Ok(fees) => Some(fees.into_values().max().unwrap_or_default()),
+        Err(err) => {
+            tracing::warn!("Could not fetch keyset fees for {}: {}", mint_url, err);
+            None
+        }
+    };
 
-        /*
-                let input = text_input("Type the data of your QR code here...", &self.data)
-                    .on_input(Message::DataChanged)
-                    .size(30)
-                    .padding(15);
+    (mint_url, info, input_fee_ppk)
+}
 
-                let choose_theme = row![
-                    text("Theme:"),
-                    pick_list(Theme::ALL, Some(&self.theme), Message::ThemeChanged,)
-                ]
-                .spacing(10)
-                .align_items(Alignment::Center);
-        */
-        let view = match self.wallet {
-            Some(_) => match &self.view {
-                View::Main => Some(column![center(column![
-                    row![text(self.balance).size(50), text("sats").size(40)],
-                    row![
-                        column![button(text("Receive")).on_press(Message::ReceiveEcash)],
-                        column![button(text("Send")).on_press(Message::Pay)]
-                    ]
-                ])]),
-                View::Receive => Some(column![
-                    row![text_input("Paste your token", &self.data)
-                        .on_input(Message::DataChanged)
-                        .padding(15)],
-                    row![button(text("Claim")).on_press(Message::Receive)],
-                    row![text_input("Amount (sats)", &self.receive_amount)
-                        .on_input(Message::ReceiveDataChanged)],
-                    row![button(text("Create Invoice")).on_press(Message::CreateInvoice)],
-                    center(row![button(text("Home")).on_press(Message::Home)])
-                ]),
-                View::Pay => Some(column![
-                    row![text(self.balance).size(50), text("sats").size(40)],
-                    row![text_input("Paste bolt11 invoice", &self.pay_invoice)
-                        .on_input(Message::PayBolt11Change)
-                        .padding(15)],
-                    row![button(text("Pay Invoice")).on_press(Message::PayInvoice)],
-                    row![text_input("Amount (sats)", &self.send_amount)
-                        .on_input(Message::SendDataChanged)],
-                    row![button(text("Create Token")).on_press(Message::CreateToken)],
-                    center(row![button(text("Home")).on_press(Message::Home)])
-                ]),
-                View::Invoice => Some(column![
-                    row![self
-                        .qr_code
-                        .as_ref()
-                        .map(|data| qr_code(data).cell_size(10))
-                        .unwrap()],
-                    row![button(text("Copy")).on_press(Message::CopyInvoice)],
-                    row![button(text("Home")).on_press(Message::Home)]
-                ]),
-                View::Token => Some(column![
-                    row![text(&self.token)],
-                    row![button(text("Copy")).on_press(Message::CopyToken)],
-                    row![button(text("Home")).on_press(Message::Home)]
-                ]),
-            },
-            None => Some(column![
-                button(text("New Wallet")).on_press(Message::NewWallet)
-            ]),
-        };
+/// Fetch the mint's info, falling back to the cached copy when offline
+async fn load_mint_info(db: Database, mint_url: UncheckedUrl) -> Result<MintDetails, WalletError> {
+    let cached = db.get_mint_info(&mint_url).await?;
 
-        let content = column![title]
-            .push_maybe(view)
-            .width(700)
-            .spacing(20)
-            .align_items(Alignment::Center);
+    let info = match mint_info::fetch_info(&mint_url).await {
+        Ok(info) => {
+            db.save_mint_info(&mint_url, &info).await?;
+            info
+        }
+        Err(err) => match &cached {
+            Some((info, _)) => {
+                tracing::warn!("Could not fetch info for {}: {}", mint_url, err);
+                info.clone()
+            }
+            None => return Err(WalletError::MintInfo(err.to_string())),
+        },
+    };
 
-        center(content).padding(20).into()
-    }
+    let icon = match &info.icon_url {
+        Some(icon_url) => mint_info::fetch_icon(icon_url)
+            .await
+            .ok()
+            .map(image::Handle::from_bytes),
+        None => None,
+    };
+    let dismissed_motd = cached.and_then(|(_, dismissed_motd)| dismissed_motd);
 
-    fn theme(&self) -> Theme {
-        Theme::Dracula
+    Ok(MintDetails {
+        motd_dismissed: info.motd.is_some() && info.motd == dismissed_motd,
+        mint_url,
+        info,
+        icon,
+    })
+}
+
+async fn dismiss_motd(db: Database, mut details: MintDetails) -> Result<MintDetails, WalletError> {
+    if let Some(motd) = &details.info.motd {
+        db.dismiss_motd(&details.mint_url, motd).await?;
     }
+    details.motd_dismissed = true;
+
+    Ok(details)
+}
+
+async fn load_mint_overview(
+    service: WalletService,
+    mint_url: UncheckedUrl,
+) -> Result<MintOverview, WalletError> {
+    let quotes = service
+        .mint_quotes()
+        .await?
+        .into_iter()
+        .filter(|quote| quote.mint_url == mint_url)
+        .collect();
+    let keysets = fees::fetch_keysets(&mint_url)
+        .await
+        .map_err(|err| err.to_string());
+
+    Ok(MintOverview {
+        mint_url,
+        keysets,
+        quotes,
+    })
+}
+
+async fn send_preview(
+    service: WalletService,
+    mint_url: UncheckedUrl,
+    amount: u64,
+    unit: CurrencyUnit,
+    lock: Option<(String, PublicKey)>,
+    hash_lock: Option<(String, Option<String>)>,
+    locktime: Option<u64>,
+    memo: Option<String>,
+) -> Result<SendPreview, WalletError> {
+    let fee = service.estimate_input_fee(&mint_url, amount).await?;
+    let (hash_lock, preimage) = hash_lock.unzip();
+
+    Ok(SendPreview {
+        mint_url,
+        amount,
+        unit,
+        lock,
+        hash_lock,
+        preimage: preimage.flatten(),
+        locktime,
+        fee,
+        memo,
+    })
+}
+
+/// DM `token` to `recipient` and record the delivery on its send transaction
+#[instrument(skip_all, fields(transaction = transaction_id), err)]
+async fn send_token_dm(
+    keys: Keys,
+    relays: Vec<String>,
+    db: Database,
+    transaction_id: i64,
+    recipient: String,
+    token: String,
+) -> Result<DeliveryStatus, WalletError> {
+    let receiver =
+        nostr::parse_pubkey(&recipient).map_err(|err| WalletError::Nostr(err.to_string()))?;
+    let npub = nostr::npub(&receiver);
+
+    let delivery = match nostr::send_dm(&keys, &relays, receiver, &token).await {
+        Ok(_) => DeliveryStatus::Delivered,
+        Err(err) => {
+            tracing::warn!("Could not deliver token to {}: {}", npub, err);
+            DeliveryStatus::Failed
+        }
+    };
+    db.set_delivery(transaction_id, &npub, delivery).await?;
+
+    Ok(delivery)
+}
+
+async fn load_nostr_recipients(db: Database) -> Result<Vec<String>, WalletError> {
+    Ok(db.get_nostr_recipients().await?)
+}
+
+/// Store a token from a DM, `None` if it was already seen
+async fn save_inbox_token(
+    db: Database,
+    incoming: IncomingToken,
+) -> Result<Option<InboxToken>, WalletError> {
+    let amount = token::parse(&incoming.token)
+        .map(|token| token_amount(&token))
+        .unwrap_or_default();
+
+    Ok(db
+        .add_inbox_token(
+            &incoming.event_id,
+            &incoming.sender,
+            &incoming.token,
+            amount,
+            incoming.timestamp,
+        )
+        .await?)
+}
+
+async fn load_inbox_tokens(db: Database) -> Result<Vec<InboxToken>, WalletError> {
+    Ok(db.get_pending_inbox_tokens().await?)
+}
+
+#[instrument(skip_all, fields(id = inbox_token.id), err)]
+async fn claim_inbox_token(
+    service: WalletService,
+    inbox_token: InboxToken,
+) -> Result<(i64, u64), WalletError> {
+    let amount = service.receive(inbox_token.token).await?;
+    service
+        .db()
+        .update_inbox_token_status(inbox_token.id, InboxTokenStatus::Claimed)
+        .await?;
+
+    Ok((inbox_token.id, amount))
+}
+
+#[instrument(skip_all, err)]
+async fn publish_nutzap_info(
+    keys: Keys,
+    relays: Vec<String>,
+    p2pk: PublicKey,
+    mints: Vec<UncheckedUrl>,
+) -> Result<(), WalletError> {
+    nutzap::publish_info(keys, relays, p2pk, mints)
+        .await
+        .map_err(|err| WalletError::Nostr(err.to_string()))
+}
+
+/// Put `operation` in the retry queue if `result` failed with a transient
+/// error
+async fn queue_on_failure<T>(
+    service: &WalletService,
+    operation: RetryOperation,
+    result: Result<T, WalletError>,
+) -> Result<T, WalletError> {
+    if let Err(err) = &result {
+        if let Err(queue_err) = service.queue_retry(operation, err).await {
+            tracing::warn!("Could not queue the operation to retry: {}", queue_err);
+        }
+    }
+
+    result
+}
+
+/// Redeem a nutzap the first time it is seen, or again once its mint is
+/// back, returning the amount received
+#[instrument(skip_all, fields(event = %nutzap.event_id), err)]
+async fn redeem_nutzap(service: WalletService, nutzap: Nutzap) -> Result<Option<u64>, WalletError> {
+    let db = service.db();
+    let Some(id) = db
+        .add_nutzap(
+            &nutzap.event_id,
+            &nutzap.sender,
+            &nutzap.mint_url,
+            &nutzap.token,
+            nutzap.timestamp,
+        )
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    match service.receive(nutzap.token.clone()).await {
+        Ok(amount) => {
+            db.update_nutzap(id, NutzapStatus::Redeemed, amount).await?;
+            Ok(Some(amount))
+        }
+        Err(err) => {
+            tracing::warn!("Could not redeem nutzap {}: {}", nutzap.event_id, err);
+            // Only a mint that turned the proofs down makes the zap a lost cause
+            let status = if service.mint_unreachable(&nutzap.token).await {
+                NutzapStatus::Unreachable
+            } else {
+                NutzapStatus::Failed
+            };
+            db.update_nutzap(id, status, 0).await?;
+            Ok(None)
+        }
+    }
+}
+
+async fn load_preimages(db: Database) -> Result<HashMap<String, String>, WalletError> {
+    Ok(db.get_preimages().await?)
+}
+
+async fn load_contacts(db: Database) -> Result<Vec<Contact>, WalletError> {
+    Ok(db.get_contacts().await?)
+}
+
+/// Validate and store a contact, returning the updated address book
+async fn add_contact(
+    db: Database,
+    name: String,
+    lightning_address: String,
+    npub: String,
+    p2pk_pubkey: String,
+) -> Result<Vec<Contact>, WalletError> {
+    let optional = |value: &str| Some(value.trim().to_string()).filter(|value| !value.is_empty());
+    let lightning_address = optional(&lightning_address);
+    let npub = optional(&npub);
+    let p2pk_pubkey = optional(&p2pk_pubkey);
+
+    if name.trim().is_empty() {
+        return Err(WalletError::InvalidContact("Enter a name".to_string()));
+    }
+    if lightning_address.is_none() && npub.is_none() && p2pk_pubkey.is_none() {
+        return Err(WalletError::InvalidContact(
+            "Enter a lightning address, npub or P2PK key".to_string(),
+        ));
+    }
+    if let Some(address) = &lightning_address {
+        if !lnurl::is_lnurl(address) {
+            return Err(WalletError::InvalidContact(format!(
+                "Not a lightning address or LNURL: {}",
+                address
+            )));
+        }
+    }
+    // Store keys in one canonical form
+    let npub = npub
+        .map(|npub| nostr::parse_pubkey(&npub).map(|pubkey| nostr::npub(&pubkey)))
+        .transpose()
+        .map_err(|err| WalletError::InvalidContact(err.to_string()))?;
+    let p2pk_pubkey = p2pk_pubkey
+        .map(|pubkey| PublicKey::from_hex(&pubkey).map(|pubkey| pubkey.to_hex()))
+        .transpose()
+        .map_err(|err| WalletError::InvalidContact(err.to_string()))?;
+
+    db.add_contact(
+        name.trim(),
+        lightning_address.as_deref(),
+        npub.as_deref(),
+        p2pk_pubkey.as_deref(),
+    )
+    .await?;
+
+    Ok(db.get_contacts().await?)
+}
+
+async fn delete_contact(db: Database, id: i64) -> Result<Vec<Contact>, WalletError> {
+    db.delete_contact(id).await?;
+
+    Ok(db.get_contacts().await?)
+}
+
+/// Pick a mint `request` accepts that can cover `amount`, preferring the
+/// active one
+#[instrument(skip_all, fields(mint = %active_mint, amount), err)]
+async fn payment_request_preview(
+    service: WalletService,
+    active_mint: UncheckedUrl,
+    request: PaymentRequest,
+    amount: u64,
+) -> Result<PaymentRequestPreview, WalletError> {
+    if request.unit.as_deref().is_some_and(|unit| unit != "sat") {
+        return Err(WalletError::PaymentRequest(
+            "Only sat payment requests are supported".to_string(),
+        ));
+    }
+    if request.transport().is_none() {
+        return Err(WalletError::PaymentRequest(
+            "Request has no supported transport".to_string(),
+        ));
+    }
+    request
+        .conditions()
+        .map_err(|err| WalletError::PaymentRequest(err.to_string()))?;
+
+    let balances = service.mint_balances().await?;
+    let accepted = |mint_url: &UncheckedUrl| {
+        let mint_url = mint_url.to_string();
+        request.mints.is_empty()
+            || request
+                .mints
+                .iter()
+                .any(|mint| mint.trim_end_matches('/') == mint_url.trim_end_matches('/'))
+    };
+    let mint_url = std::iter::once(&active_mint)
+        .chain(balances.keys())
+        .find(|mint_url| {
+            accepted(mint_url) && balances.get(*mint_url).copied().unwrap_or_default() >= amount
+        })
+        .cloned()
+        .ok_or_else(|| {
+            WalletError::PaymentRequest("No accepted mint has enough balance".to_string())
+        })?;
+
+    let fee = service.estimate_input_fee(&mint_url, amount).await?;
+
+    Ok(PaymentRequestPreview {
+        request,
+        mint_url,
+        amount,
+        fee,
+    })
+}
+
+/// Send a token answering a payment request over its transport
+///
+/// A token that can't be delivered stays in the sent tokens to be reclaimed.
+#[instrument(skip_all, fields(mint = %preview.mint_url), err)]
+async fn pay_payment_request(
+    service: WalletService,
+    keys: Keys,
+    relays: Vec<String>,
+    preview: PaymentRequestPreview,
+    split: SplitTarget,
+) -> Result<u64, WalletError> {
+    let request_error = |err: anyhow::Error| WalletError::PaymentRequest(err.to_string());
+
+    let conditions = preview.request.conditions().map_err(request_error)?;
+    let transport = preview
+        .request
+        .transport()
+        .ok_or_else(|| WalletError::PaymentRequest("No supported transport".to_string()))?;
+
+    let (id, token) = service
+        .create_token(
+            preview.mint_url,
+            preview.amount,
+            CurrencyUnit::Sat,
+            split,
+            conditions,
+            None,
+        )
+        .await?;
+    let token = token::parse(&token).map_err(request_error)?;
+    let payload = PaymentPayload::new(&preview.request, &token).map_err(request_error)?;
+
+    match payment_request::deliver(&keys, &relays, transport, &payload).await {
+        Ok(Some(npub)) => {
+            service
+                .db()
+                .set_delivery(id, &npub, DeliveryStatus::Delivered)
+                .await?
+        }
+        Ok(None) => (),
+        Err(err) => {
+            return Err(WalletError::PaymentRequest(format!(
+                "Payment was not delivered, reclaim it from sent tokens: {}",
+                err
+            )))
+        }
+    }
+
+    Ok(preview.amount)
+}
+
+async fn dismiss_inbox_token(db: Database, id: i64) -> Result<i64, WalletError> {
+    db.update_inbox_token_status(id, InboxTokenStatus::Dismissed)
+        .await?;
+
+    Ok(id)
+}
+
+/// Mark sent tokens whose proofs were spent as claimed, returning the rest
+#[instrument(level = "debug", skip_all, err)]
+async fn check_sent_tokens(
+    wallet: Arc<Wallet>,
+    db: Database,
+) -> Result<Vec<SentToken>, WalletError> {
+    let mut pending = vec![];
+
+    for sent in db.get_pending_sent_tokens().await? {
+        let Ok(token) = token::parse(&sent.token) else {
+            continue;
+        };
+
+        let mut claimed = true;
+        for mint_proofs in token.token {
+            let states = wallet
+                .check_proofs_spent(mint_proofs.mint, mint_proofs.proofs)
+                .await?;
+            claimed &= states.iter().all(|state| state.state == State::Spent);
+        }
+
+        if claimed {
+            db.update_sent_token_status(sent.id, SentTokenStatus::Claimed)
+                .await?;
+        } else {
+            pending.push(sent);
+        }
+    }
+
+    Ok(pending)
+}
+
+async fn load_nwc_connections(db: Database) -> Result<Vec<NwcConnection>, WalletError> {
+    Ok(db.get_nwc_connections().await?)
+}
+
+#[instrument(skip_all, fields(name = %name), err)]
+async fn create_nwc_connection(
+    db: Database,
+    name: String,
+    relay: String,
+    budget: u64,
+) -> Result<NwcConnection, WalletError> {
+    let (service_secret, client_secret) =
+        nwc::generate_secrets().map_err(|err| WalletError::Nwc(err.to_string()))?;
+
+    Ok(db
+        .add_nwc_connection(&name, &relay, &service_secret, &client_secret, budget)
+        .await?)
+}
+
+async fn delete_nwc_connection(db: Database, id: i64) -> Result<Vec<NwcConnection>, WalletError> {
+    db.delete_nwc_connection(id).await?;
+
+    Ok(db.get_nwc_connections().await?)
+}
+
+/// Pay an invoice for a connected app within its budget
+#[instrument(skip_all, fields(mint = %mint_url))]
+async fn nwc_pay(
+    service: WalletService,
+    mint_url: UncheckedUrl,
+    request: NwcRequest,
+    invoice: String,
+) -> NwcReply {
+    let result = async {
+        let other = |err: WalletError| NwcError::Other(err.to_string());
+
+        let db = service.db();
+        let connection = db
+            .get_nwc_connection(request.connection_id)
+            .await
+            .map_err(|err| other(err.into()))?
+            .ok_or_else(|| NwcError::Other("Connection was removed".to_string()))?;
+
+        let preview = service
+            .melt_quote(mint_url, invoice, CurrencyUnit::Sat)
+            .await
+            .map_err(other)?;
+        if preview.total() > connection.remaining() {
+            return Err(NwcError::QuotaExceeded);
+        }
+        if preview.total()
+            > service
+                .mint_balance(&preview.mint_url)
+                .await
+                .map_err(other)?
+        {
+            return Err(NwcError::InsufficientBalance);
+        }
+
+        let preimage = service.pay_invoice(preview.clone()).await.map_err(other)?;
+        db.add_nwc_spent(connection.id, preview.total())
+            .await
+            .map_err(|err| other(err.into()))?;
+
+        Ok(NwcResult::Paid {
+            preimage: preimage.unwrap_or_default(),
+        })
+    }
+    .await;
+
+    NwcReply { request, result }
+}
+
+/// Quote a transfer by paying a mint quote at `to` with a melt from `from`
+#[instrument(skip_all, fields(from = %from, to = %to, amount), err)]
+async fn transfer_quote(
+    service: WalletService,
+    from: UncheckedUrl,
+    to: UncheckedUrl,
+    amount: u64,
+) -> Result<TransferPreview, WalletError> {
+    let mint = service.mint_quote(to, amount, CurrencyUnit::Sat).await?;
+    let melt = service
+        .melt_quote(from, mint.request.clone(), CurrencyUnit::Sat)
+        .await?;
+
+    Ok(TransferPreview { melt, mint })
+}
+
+/// Pull the maximum allowed amount from an LNURL-withdraw into a mint quote
+#[instrument(skip_all, fields(mint = %mint_url), err)]
+async fn withdraw_lnurl(
+    service: WalletService,
+    mint_url: UncheckedUrl,
+    request: WithdrawRequest,
+) -> Result<PendingMint, WalletError> {
+    let pending = service
+        .mint_quote(mint_url, request.max_sats(), CurrencyUnit::Sat)
+        .await?;
+
+    lnurl::submit_withdraw(&request, &pending.request)
+        .await
+        .map_err(|err| WalletError::Lnurl(err.to_string()))?;
+
+    Ok(pending)
+}
+
+#[instrument(skip_all, err)]
+async fn resolve_lnurl(input: String) -> Result<LnurlRequest, WalletError> {
+    lnurl::resolve_request(&input)
+        .await
+        .map_err(|err| WalletError::Lnurl(err.to_string()))
+}
+
+async fn load_history(
+    db: Database,
+    page: u32,
+    filter: HistoryFilter,
+) -> Result<(Vec<Transaction>, u32), WalletError> {
+    let transactions = db
+        .get_filtered_transactions(&filter, HISTORY_PAGE_SIZE, page * HISTORY_PAGE_SIZE)
+        .await?;
+    let count = db.count_filtered_transactions(&filter).await?;
+
+    Ok((transactions, count))
+}
+
+/// Start of day `input` in UTC, typed as `YYYY-MM-DD`
+fn parse_date(input: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::NaiveDate::parse_from_str(input.trim(), "%Y-%m-%d")
+        .ok()?
+        .and_hms_opt(0, 0, 0)
+        .map(|time| time.and_utc())
+}
+
+/// Store the note and labels of `transaction`, returning it updated
+async fn save_note(
+    db: Database,
+    mut transaction: Transaction,
+    note: String,
+    tags: String,
+) -> Result<Transaction, WalletError> {
+    let note = Some(note.trim().to_string()).filter(|note| !note.is_empty());
+    let tags = db::parse_tags(&tags);
+    db.set_transaction_note(transaction.id, note.as_deref(), &tags)
+        .await?;
+
+    transaction.note = note;
+    transaction.tags = tags;
+    Ok(transaction)
+}
+
+async fn exchange_rate(source: RateSource, currency: FiatCurrency) -> Option<ExchangeRate> {
+    match fetch_rate(source, currency).await {
+        Ok(rate) => Some(rate),
+        Err(err) => {
+            tracing::warn!("Could not fetch exchange rate: {}", err);
+            None
+        }
+    }
+}
+
+/// Map a task result to `on_success` or [`Message::Error`]
+/// `bytes` as megabytes for display
+fn format_size(bytes: u64) -> String {
+    t!(
+        "megabytes",
+        size = i18n::format_decimal(bytes as f64 / 1_000_000.0, 2)
+    )
+}
+
+/// Run a maintenance step, reporting it as progress
+fn maintenance_step(
+    step: impl Future<Output = Result<MaintenanceStep, WalletError>> + Send + 'static,
+) -> Task<Message> {
+    Task::perform(step, |result| {
+        Message::MaintenanceProgress(result.map_err(|err| err.to_string()))
+    })
+}
+
+fn or_error<T>(
+    on_success: impl Fn(T) -> Message + Send + 'static,
+) -> impl Fn(Result<T, WalletError>) -> Message + Send + 'static {
+    move |result| match result {
+        Ok(value) => on_success(value),
+        Err(err) => Message::Error(err),
+    }
+}
+
+impl IcedCashu {
+    fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::DataChanged(data) => {
+                self.data = data;
+                Task::none()
+            }
+            Message::ReceiveAmount(event) => {
+                let rate = self.current_rate();
+                match event {
+                    AmountEvent::Changed(input) => self.receive_amount.input = input,
+                    AmountEvent::ToggleUnit => self.receive_amount.toggle_unit(rate.as_ref()),
+                    AmountEvent::Preset(Some(sats)) => {
+                        self.receive_amount.set_sats(sats, rate.as_ref())
+                    }
+                    AmountEvent::Preset(None) => self.receive_amount.clear(),
+                    AmountEvent::Max => (),
+                }
+                Task::none()
+            }
+            Message::SendAmount(event) => {
+                let rate = self.current_rate();
+                match event {
+                    AmountEvent::Changed(input) => self.send_amount.input = input,
+                    AmountEvent::ToggleUnit => self.send_amount.toggle_unit(rate.as_ref()),
+                    AmountEvent::Preset(Some(sats)) => {
+                        self.send_amount.set_sats(sats, rate.as_ref())
+                    }
+                    AmountEvent::Preset(None) => self.send_amount.clear(),
+                    AmountEvent::Max => {
+                        let service = self.service();
+                        let mint_url = self.active_mint.clone();
+                        return Task::perform(
+                            async move { service.max_sendable(mint_url).await },
+                            or_error(Message::MaxSendable),
+                        );
+                    }
+                }
+                Task::none()
+            }
+            Message::MaxSendable(sats) => {
+                let rate = self.current_rate();
+                self.send_amount.set_sats(sats, rate.as_ref());
+                Task::none()
+            }
+            Message::NewWallet => {
+                if !has_seed() {
+                    if let Err(err) = set_has_passphrase(self.use_passphrase) {
+                        self.error = Some(WalletError::Seed(err.to_string()));
+                        return Task::none();
+                    }
+                    self.onboarding = Some(OnboardingStep::Backup);
+                }
+                self.active_mint = Settings::load()
+                    .start_mint()
+                    .unwrap_or_else(|| UncheckedUrl::from(DEFAULT_MINT));
+                Task::perform(
+                    new_wallet(self.password.clone(), self.passphrase.clone()),
+                    or_error(Message::WalletCreated),
+                )
+            }
+            Message::PasswordChanged(password) => {
+                self.password = Zeroizing::new(password);
+                Task::none()
+            }
+            Message::UsePassphraseToggled(use_passphrase) => {
+                self.use_passphrase = use_passphrase;
+                if !use_passphrase {
+                    self.passphrase = Zeroizing::default();
+                    self.passphrase_confirm = Zeroizing::default();
+                }
+                Task::none()
+            }
+            Message::PassphraseChanged(passphrase) => {
+                self.passphrase = Zeroizing::new(passphrase);
+                Task::none()
+            }
+            Message::PassphraseConfirmChanged(passphrase) => {
+                self.passphrase_confirm = Zeroizing::new(passphrase);
+                Task::none()
+            }
+            Message::RestoreView => {
+                self.view = View::Restore;
+                self.database_encrypted = database_encrypted();
+                self.use_passphrase = false;
+                self.passphrase = Zeroizing::default();
+                self.passphrase_confirm = Zeroizing::default();
+                Task::none()
+            }
+            Message::RestoreMnemonicChanged(mnemonic) => {
+                self.restore_mnemonic = mnemonic;
+                Task::none()
+            }
+            Message::RestoreWallet => {
+                if let Err(err) = set_has_passphrase(self.use_passphrase) {
+                    self.error = Some(WalletError::Seed(err.to_string()));
+                    return Task::none();
+                }
+                let mnemonic = Zeroizing::new(std::mem::take(&mut self.restore_mnemonic));
+                self.view = View::Main;
+                // The words are already written down
+                self.onboarding = Some(OnboardingStep::Mint);
+                Task::perform(
+                    restore_wallet(mnemonic, self.password.clone(), self.passphrase.clone()),
+                    or_error(Message::WalletCreated),
+                )
+            }
+            Message::BackupPasswordChanged(password) => {
+                self.backup_password = Zeroizing::new(password);
+                Task::none()
+            }
+            Message::ExportBackup => {
+                let backup_password = std::mem::take(&mut self.backup_password);
+                Task::perform(
+                    export_backup(self.service(), self.password.clone(), backup_password),
+                    or_error(Message::BackupExported),
+                )
+            }
+            Message::BackupExported(path) => {
+                self.backup_path = path;
+                Task::none()
+            }
+            Message::ImportBackup => {
+                if let Err(err) = set_has_passphrase(self.use_passphrase) {
+                    self.error = Some(WalletError::Seed(err.to_string()));
+                    return Task::none();
+                }
+                let backup_password = std::mem::take(&mut self.backup_password);
+                self.view = View::Main;
+                self.onboarding = Some(OnboardingStep::Mint);
+                Task::perform(
+                    import_backup(
+                        self.password.clone(),
+                        self.passphrase.clone(),
+                        backup_password,
+                    ),
+                    or_error(Message::WalletCreated),
+                )
+            }
+            Message::WalletCreated((service, nostr_keys)) => {
+                self.p2pk_pubkey = Some(service.p2pk_pubkey());
+                self.service = Some(service);
+                self.seed_nostr_keys = Some(nostr_keys.clone());
+                let nostr_keys = match nostr::stored_keys(&self.password) {
+                    Ok(stored) => stored.unwrap_or(nostr_keys),
+                    Err(err) => {
+                        self.error = Some(WalletError::Nostr(err.to_string()));
+                        nostr_keys
+                    }
+                };
+                let identity = self.set_nostr_keys(nostr_keys);
+                self.passphrase = Zeroizing::default();
+                self.passphrase_confirm = Zeroizing::default();
+                self.settings = Settings::load();
+                if let Some(mint_url) = self.settings.start_mint() {
+                    self.active_mint = mint_url;
+                }
+                self.default_mint_input = self
+                    .settings
+                    .default_mint
+                    .as_ref()
+                    .map(|mint_url| mint_url.to_string())
+                    .unwrap_or_default();
+                self.polling_interval_input = self.settings.polling_interval.to_string();
+                self.max_polling_input = self.settings.max_polling_interval.to_string();
+                self.qr_interval_input = self.settings.qr_frame_interval.to_string();
+                self.ui_scale_input = self.settings.ui_scale;
+                self.relays_input = self.settings.nostr_relays.join(", ");
+                #[cfg(feature = "control-api")]
+                {
+                    self.control_port_input = self.settings.control_port.to_string();
+                    if self.settings.control_api {
+                        self.load_control_token();
+                    }
+                }
+                self.pin_set = has_pin();
+                self.proxy_input = self.settings.proxy.clone().unwrap_or_default();
+                self.locked = self.pin_set;
+                self.last_activity = Some(Instant::now());
+                self.lock_timeout_input = self.settings.lock_timeout.to_string();
+                self.pos.totals = DailyTotals::load();
+                self.pin_threshold_input = self
+                    .settings
+                    .pin_threshold
+                    .map(|threshold| threshold.to_string())
+                    .unwrap_or_default();
+                self.auto_withdraw_address_input = self
+                    .settings
+                    .auto_withdraw_address
+                    .clone()
+                    .unwrap_or_default();
+                self.auto_withdraw_threshold_input =
+                    self.settings.auto_withdraw_threshold.to_string();
+                if self.tray.is_none() {
+                    match tray::spawn() {
+                        Ok(tray) => self.tray = Some(tray),
+                        Err(err) => tracing::warn!("Could not start tray icon: {}", err),
+                    }
+                }
+                self.backed_up = is_backed_up();
+                self.split_mode = self.settings.split_target.mode();
+                self.split_input = self.settings.split_target.input();
+                let (service, melts) = (self.service(), self.service());
+                let tasks = Task::batch([
+                    identity,
+                    self.check_balances(),
+                    Task::done(Message::FetchRate),
+                    Task::done(Message::CheckProofStates),
+                    Task::done(Message::CheckMintHealth),
+                    Task::done(Message::CheckKeysets),
+                    Task::done(Message::CheckSystemTheme),
+                    Task::done(Message::LoadFailedOperations),
+                    Task::perform(
+                        load_nwc_connections(self.db()),
+                        or_error(Message::NwcConnectionsLoaded),
+                    ),
+                    Task::perform(
+                        load_nostr_recipients(self.db()),
+                        or_error(Message::NostrRecipientsLoaded),
+                    ),
+                    Task::perform(
+                        load_inbox_tokens(self.db()),
+                        or_error(Message::InboxTokensLoaded),
+                    ),
+                    Task::perform(load_contacts(self.db()), or_error(Message::ContactsLoaded)),
+                    Task::perform(
+                        load_preimages(self.db()),
+                        or_error(Message::PreimagesLoaded),
+                    ),
+                    Task::perform(
+                        load_scheduled_payments(self.db()),
+                        or_error(Message::ScheduledPaymentsLoaded),
+                    ),
+                    Task::perform(
+                        async move { service.mint_quotes().await },
+                        or_error(Message::MintQuotesLoaded),
+                    ),
+                    Task::perform(
+                        async move { melts.recover_melts().await },
+                        or_error(Message::MeltsRecovered),
+                    ),
+                ]);
+
+                match self.onboarding {
+                    Some(step) => Task::batch([tasks, self.update(Message::Onboarding(step))]),
+                    None => Task::batch([tasks, self.reopen(self.settings.last_view)]),
+                }
+            }
+            Message::MintQuote(pending) => {
+                // Deposit addresses carry the amount when it is in sats
+                let qr_data = if pending.onchain && pending.unit == CurrencyUnit::Sat {
+                    onchain::payment_uri(&pending.request, pending.amount)
+                } else {
+                    pending.request.clone()
+                };
+                self.qr_code = qr_code::Data::new(qr_data).ok();
+                self.invoice = pending.request.clone();
+                self.pending_mint = Some(pending);
+                self.reset_polling();
+
+                // Left invoices wait in the pending panel with the others
+                if !self.left_running.contains(&Operation::Quote) {
+                    self.view = View::Invoice;
+                }
+                Task::none()
+            }
+            Message::CheckMintQuote => {
+                let Some(pending) = &self.pending_mint else {
+                    return Task::none();
+                };
+                let service = self.service();
+                let (mint_url, quote_id) = (pending.mint_url.clone(), pending.quote_id.clone());
+                let onchain = pending.onchain;
+                if let Some(backoff) = &mut self.poll_backoff {
+                    self.poll_delay = backoff.next_delay();
+                }
+                Task::perform(
+                    async move {
+                        if onchain {
+                            service.check_deposit(mint_url, quote_id).await
+                        } else {
+                            service.check_mint_quote(mint_url, quote_id).await
+                        }
+                    },
+                    |result| match result {
+                        Ok(status) => Message::MintQuoteStatus(status),
+                        Err(WalletError::RateLimited(retry_after)) => {
+                            Message::QuoteRateLimited(retry_after)
+                        }
+                        Err(err) => Message::Error(err),
+                    },
+                )
+            }
+            Message::QuoteRateLimited(retry_after) => {
+                tracing::warn!("Mint is rate limiting quote checks");
+                if let Some(backoff) = &mut self.poll_backoff {
+                    self.poll_delay = backoff.retry_after(retry_after.map(Duration::from_secs));
+                }
+                Task::none()
+            }
+            Message::MintQuoteStatus((quote_id, paid)) => {
+                // Ignore late responses for a quote that was cancelled or
+                // already minted
+                if !paid
+                    || self.pending_mint.as_ref().map(|pending| &pending.quote_id)
+                        != Some(&quote_id)
+                {
+                    return Task::none();
+                }
+
+                let pending = self.pending_mint.take().unwrap();
+                let log_sale = if self.pos.is_charging(&quote_id) {
+                    let sale = self.pos_sale(&pending);
+                    self.pos.paid(sale.clone());
+                    self.qr_code = None;
+                    self.invoice = "".to_string();
+                    Task::perform(log_sale(self.db(), sale), or_error(Message::SaleLogged))
+                } else {
+                    Task::none()
+                };
+                let service = self.service();
+                let split = self.settings.split_target.target(pending.amount);
+                let task = Task::perform(
+                    async move {
+                        let result = if pending.onchain {
+                            service.mint_deposit(pending.clone(), split).await
+                        } else {
+                            service
+                                .mint(pending.mint_url.clone(), pending.quote_id.clone(), split)
+                                .await
+                        };
+                        queue_on_failure(&service, RetryOperation::Mint { pending }, result).await
+                    },
+                    or_error(Message::Minted),
+                );
+                Task::batch([log_sale, self.track(Operation::Mint, task)])
+            }
+            Message::MintQuotesLoaded(quotes) => {
+                // Quotes of an earlier run may have been paid while the app
+                // was closed
+                Task::batch(quotes.into_iter().map(|pending| {
+                    let service = self.service();
+                    let split = self.settings.split_target.target(pending.amount);
+                    Task::perform(
+                        async move {
+                            let minted = service.resume_mint_quote(pending.clone(), split).await?;
+                            Ok::<_, WalletError>((pending, minted))
+                        },
+                        or_error(Message::MintQuoteResumed),
+                    )
+                }))
+            }
+            Message::MintQuoteResumed((pending, minted)) => match minted {
+                Some(amount) => self.update(Message::Minted(amount)),
+                None => {
+                    // Keep watching one of the quotes still open
+                    if self.pending_mint.is_none() && pending.expiry > unix_time() as u64 {
+                        self.pending_mint = Some(pending);
+                        self.reset_polling();
+                    }
+                    Task::none()
+                }
+            },
+            Message::MeltsRecovered(amount) => {
+                if amount == 0 {
+                    return Task::none();
+                }
+                self.notify(
+                    &t!("payment-recovered"),
+                    &t!("recovered-sats", amount = i18n::format_number(amount)),
+                );
+                self.check_balances()
+            }
+            Message::Tick => {
+                let expired = self
+                    .pending_mint
+                    .as_ref()
+                    .is_some_and(|pending| pending.expiry <= unix_time() as u64);
+
+                if expired {
+                    self.error = Some(WalletError::QuoteExpired);
+                    return self.update(Message::CancelMintQuote);
+                }
+                if self.pos.paid_screen_done() {
+                    self.pos.next_sale();
+                }
+                Task::none()
+            }
+            Message::CancelMintQuote => {
+                self.pending_mint = None;
+                // The till stays open for the next customer
+                if matches!(self.view, View::Pos) {
+                    self.pos.charging = None;
+                    self.qr_code = None;
+                    return Task::none();
+                }
+                self.update(Message::Home)
+            }
+            Message::Minted(amount) => {
+                self.notify(
+                    &t!("invoice-paid"),
+                    &t!("minted-sats", amount = i18n::format_number(amount)),
+                );
+                // The quote may have been paid while the user went elsewhere
+                if matches!(self.view, View::Invoice) {
+                    self.view = View::Main;
+                }
+                let service = self.service();
+                Task::perform(
+                    async move { service.check_balance().await },
+                    or_error(Message::Balance),
+                )
+            }
+            Message::SendReceive => {
+                self.view = View::SendReceive;
+                text_input::focus(text_input::Id::new(DATA_INPUT))
+            }
+            Message::SubmitInput => match detect(&self.data) {
+                Some(DetectedInput::Token { .. }) => self.update(Message::Receive),
+                Some(DetectedInput::Bolt11 { .. } | DetectedInput::PaymentRequest { .. }) => {
+                    self.update(Message::PayInvoice)
+                }
+                Some(DetectedInput::Bolt12 { offer, amount }) => {
+                    // Amount is only needed for offers without one
+                    let amount = match amount {
+                        Some(_) => None,
+                        None => self.send_amount.sats(self.current_rate().as_ref()),
+                    };
+                    let service = self.service();
+                    let mint_url = self.active_mint.clone();
+                    Task::perform(
+                        async move { service.offer_quote(mint_url, offer, amount).await },
+                        or_error(Message::MeltQuote),
+                    )
+                }
+                Some(DetectedInput::Lnurl(lnurl)) => {
+                    Task::perform(resolve_lnurl(lnurl), or_error(Message::LnurlResolved))
+                }
+                None => {
+                    self.error = Some(WalletError::UnrecognizedInput);
+                    Task::none()
+                }
+            },
+            Message::Receive => {
+                if let Some(locks) = self.missing_signing_key() {
+                    self.error = Some(WalletError::MissingSigningKey(locks));
+                    return Task::none();
+                }
+                if let Some(hashes) = self.missing_preimage() {
+                    self.error = Some(WalletError::MissingPreimage(hashes));
+                    return Task::none();
+                }
+
+                let token = std::mem::take(&mut self.data);
+                if let Some(mint_url) = self.unknown_mint(&token) {
+                    return self.review_mint(mint_url, ReviewAction::Receive(token));
+                }
+                self.receive(token)
+            }
+            Message::SigningKeyChanged(key) => {
+                self.signing_key_input = Zeroizing::new(key);
+                Task::none()
+            }
+            Message::ImportSigningKey => {
+                let wallet = self.wallet();
+                let key = std::mem::take(&mut self.signing_key_input);
+                Task::perform(
+                    import_signing_key(wallet, key),
+                    or_error(Message::SigningKeyImported),
+                )
+            }
+            Message::SigningKeyImported(pubkey) => {
+                if !self.imported_pubkeys.contains(&pubkey) {
+                    self.imported_pubkeys.push(pubkey);
+                }
+                Task::none()
+            }
+            Message::PreimagesLoaded(preimages) => {
+                self.preimages = preimages;
+                Task::none()
+            }
+            Message::PreimageChanged(preimage) => {
+                self.preimage_input = Zeroizing::new(preimage);
+                Task::none()
+            }
+            Message::HashLockToggled(enabled) => {
+                self.send_hash_lock = enabled;
+                Task::none()
+            }
+            Message::HashLockChanged(hash) => {
+                self.hash_lock_input = hash;
+                // A hash from the other party has a preimage only they know
+                self.send_preimage = None;
+                Task::none()
+            }
+            Message::GeneratePreimage => {
+                let preimage = htlc::generate_preimage();
+                match htlc::hash_lock(&preimage) {
+                    Ok(hash) => {
+                        self.hash_lock_input = hash;
+                        self.send_preimage = Some(Zeroizing::new(preimage));
+                    }
+                    Err(err) => self.error = Some(WalletError::Htlc(err.to_string())),
+                }
+                Task::none()
+            }
+            Message::CopyPreimage(preimage) => clipboard::write::<String>(preimage),
+            Message::RefundHoursChanged(hours) => {
+                self.refund_hours = hours;
+                Task::none()
+            }
+            Message::NostrKeyChanged(key) => {
+                self.nostr_key_input = Zeroizing::new(key);
+                Task::none()
+            }
+            Message::ImportNostrKey => {
+                if self.needs_pin(None, Message::ImportNostrKey) {
+                    return Task::none();
+                }
+                let key = std::mem::take(&mut self.nostr_key_input);
+                match nostr::parse_keys(&key) {
+                    Ok(keys) => self.store_nostr_keys(keys),
+                    Err(err) => {
+                        self.error = Some(WalletError::Nostr(err.to_string()));
+                        Task::none()
+                    }
+                }
+            }
+            Message::GenerateNostrKey => {
+                if self.needs_pin(None, Message::GenerateNostrKey) {
+                    return Task::none();
+                }
+                self.store_nostr_keys(Keys::generate())
+            }
+            Message::UseSeedNostrKey => {
+                if self.needs_pin(None, Message::UseSeedNostrKey) {
+                    return Task::none();
+                }
+                if let Err(err) = nostr::remove_stored_keys() {
+                    self.error = Some(WalletError::Nostr(err.to_string()));
+                    return Task::none();
+                }
+                match self.seed_nostr_keys.clone() {
+                    Some(keys) => self.set_nostr_keys(keys),
+                    None => Task::none(),
+                }
+            }
+            Message::ToggleNpubQr => {
+                self.npub_qr = match (&self.npub_qr, &self.nostr_keys) {
+                    (None, Some(keys)) => {
+                        qr_code::Data::new(format!("nostr:{}", nostr::npub(&keys.public_key())))
+                            .ok()
+                    }
+                    _ => None,
+                };
+                Task::none()
+            }
+            Message::CopyNpub => match &self.nostr_keys {
+                Some(keys) => clipboard::write::<String>(nostr::npub(&keys.public_key())),
+                None => Task::none(),
+            },
+            Message::CreateInvoice => {
+                if self.busy(&[Operation::Quote]) {
+                    return Task::none();
+                }
+                let Some(amount) = self.unit_amount(&self.receive_amount) else {
+                    self.error = Some(WalletError::InvalidAmount(
+                        self.receive_amount.input.clone(),
+                    ));
+                    return Task::none();
+                };
+                let service = self.service();
+                let mint_url = self.active_mint.clone();
+                let unit = self.unit.clone();
+                let task = Task::perform(
+                    async move { service.mint_quote(mint_url, amount, unit).await },
+                    or_error(Message::MintQuote),
+                );
+                self.track(Operation::Quote, task)
+            }
+            Message::CreateDeposit => {
+                if self.busy(&[Operation::Quote]) {
+                    return Task::none();
+                }
+                let Some(amount) = self.unit_amount(&self.receive_amount) else {
+                    self.error = Some(WalletError::InvalidAmount(
+                        self.receive_amount.input.clone(),
+                    ));
+                    return Task::none();
+                };
+                let service = self.service();
+                let mint_url = self.active_mint.clone();
+                let unit = self.unit.clone();
+                let task = Task::perform(
+                    async move { service.deposit_quote(mint_url, amount, unit).await },
+                    or_error(Message::MintQuote),
+                );
+                self.track(Operation::Quote, task)
+            }
+            Message::TogglePending => {
+                self.pending_expanded = !self.pending_expanded;
+                Task::none()
+            }
+            Message::CheckBalance(_amount) => self.check_balances(),
+            Message::CheckProofStates => {
+                let service = self.service();
+                Task::perform(
+                    async move { service.check_proof_states().await },
+                    or_error(Message::ProofStatesChecked),
+                )
+            }
+            Message::RunMaintenance => {
+                self.maintenance = Some(Maintenance {
+                    size_before: storage_size(),
+                    ..Maintenance::default()
+                });
+                let service = self.service();
+                maintenance_step(async move {
+                    let problems = service.check_integrity().await?;
+                    Ok(MaintenanceStep::IntegrityChecked(problems))
+                })
+            }
+            Message::MaintenanceProgress(result) => {
+                // The wallet may have been closed while a step ran
+                let (Some(service), Some(maintenance)) =
+                    (self.service.clone(), self.maintenance.as_mut())
+                else {
+                    return Task::none();
+                };
+                let step = match result {
+                    Ok(step) => step,
+                    Err(err) => {
+                        maintenance.error = Some(err);
+                        return Task::none();
+                    }
+                };
+
+                let next = match &step {
+                    // Leave a damaged database as it is
+                    MaintenanceStep::IntegrityChecked(problems) if !problems.is_empty() => {
+                        Task::none()
+                    }
+                    MaintenanceStep::IntegrityChecked(_) => maintenance_step(async move {
+                        let pruned = service.prune_spent_proofs().await?;
+                        Ok(MaintenanceStep::ProofsPruned(pruned))
+                    }),
+                    MaintenanceStep::ProofsPruned(_) => maintenance_step(async move {
+                        let pruned = service.prune_records().await?;
+                        Ok(MaintenanceStep::RecordsPruned(pruned))
+                    }),
+                    MaintenanceStep::RecordsPruned(_) => maintenance_step(async move {
+                        service.vacuum().await?;
+                        Ok(MaintenanceStep::Vacuumed)
+                    }),
+                    MaintenanceStep::Vacuumed => {
+                        maintenance.size_after = Some(storage_size());
+                        Task::none()
+                    }
+                };
+                maintenance.steps.push(step);
+
+                next
+            }
+            Message::NwcView => {
+                self.view = View::Nwc;
+                if self.nwc_relay.is_empty() {
+                    self.nwc_relay = self
+                        .settings
+                        .nostr_relays
+                        .first()
+                        .cloned()
+                        .unwrap_or_default();
+                }
+                Task::none()
+            }
+            Message::NwcNameChanged(name) => {
+                self.nwc_name = name;
+                Task::none()
+            }
+            Message::NwcBudgetChanged(budget) => {
+                self.nwc_budget = budget;
+                Task::none()
+            }
+            Message::NwcRelayChanged(relay) => {
+                self.nwc_relay = relay;
+                Task::none()
+            }
+            Message::CreateNwcConnection => {
+                let budget: u64 = match self.nwc_budget.parse() {
+                    Ok(budget) => budget,
+                    Err(_) => {
+                        self.error = Some(WalletError::InvalidAmount(self.nwc_budget.clone()));
+                        return Task::none();
+                    }
+                };
+                let db = self.db();
+                Task::perform(
+                    create_nwc_connection(
+                        db,
+                        std::mem::take(&mut self.nwc_name),
+                        self.nwc_relay.clone(),
+                        budget,
+                    ),
+                    or_error(Message::NwcConnectionCreated),
+                )
+            }
+            Message::NwcConnectionCreated(connection) => {
+                self.nwc_budget = "".to_string();
+                match nwc::connection_uri(&connection) {
+                    Ok(uri) => {
+                        self.qr_code = qr_code::Data::new(&uri).ok();
+                        self.nwc_uri = Some(uri);
+                    }
+                    Err(err) => self.error = Some(WalletError::Nwc(err.to_string())),
+                }
+                self.nwc_connections.push(connection);
+                Task::none()
+            }
+            Message::NwcConnectionsLoaded(connections) => {
+                self.nwc_connections = connections;
+                Task::none()
+            }
+            Message::DeleteNwcConnection(id) => {
+                let db = self.db();
+                Task::perform(
+                    delete_nwc_connection(db, id),
+                    or_error(Message::NwcConnectionsLoaded),
+                )
+            }
+            Message::Nwc(event) => match event {
+                NwcEvent::Ready(sender) => {
+                    self.nwc_sender = Some(sender);
+                    Task::none()
+                }
+                NwcEvent::Request(request) => match request.method.clone() {
+                    NwcMethod::GetBalance => {
+                        let balance = self.balance;
+                        self.update(Message::NwcReplied(NwcReply {
+                            request,
+                            result: Ok(NwcResult::Balance(balance)),
+                        }))
+                    }
+                    NwcMethod::PayInvoice { invoice } => Task::perform(
+                        nwc_pay(self.service(), self.active_mint.clone(), request, invoice),
+                        Message::NwcReplied,
+                    ),
+                },
+                NwcEvent::Error(err) => {
+                    self.nwc_sender = None;
+                    self.error = Some(WalletError::Nwc(err));
+                    Task::none()
+                }
+            },
+            Message::NwcReplied(reply) => {
+                let paid = matches!(reply.result, Ok(NwcResult::Paid { .. }));
+                if let Some(sender) = &mut self.nwc_sender {
+                    if let Err(err) = sender.try_send(reply) {
+                        tracing::warn!("Could not send NWC reply: {}", err);
+                    }
+                }
+
+                if !paid {
+                    return Task::none();
+                }
+                let db = self.db();
+                Task::batch([
+                    self.update(Message::CheckBalance(0)),
+                    Task::perform(
+                        load_nwc_connections(db),
+                        or_error(Message::NwcConnectionsLoaded),
+                    ),
+                ])
+            }
+            #[cfg(feature = "control-api")]
+            Message::Control(event) => match event {
+                control::ControlEvent::Ready(sender) => {
+                    self.control_sender = Some(sender);
+                    Task::none()
+                }
+                control::ControlEvent::Request(request) => {
+                    if self.locked {
+                        return self.update(Message::ControlReplied(control::ControlReply {
+                            request,
+                            result: Err("Wallet is locked".to_string()),
+                        }));
+                    }
+                    Task::perform(
+                        control::handle(
+                            self.service(),
+                            self.active_mint.clone(),
+                            self.settings.split_target.clone(),
+                            request,
+                        ),
+                        Message::ControlReplied,
+                    )
+                }
+                control::ControlEvent::Error(err) => {
+                    self.control_sender = None;
+                    self.error = Some(WalletError::Control(err));
+                    Task::none()
+                }
+            },
+            #[cfg(feature = "control-api")]
+            Message::ControlReplied(reply) => {
+                let changed = reply.result.is_ok()
+                    && !matches!(reply.request.method, control::ControlMethod::GetBalance);
+                if let Some(sender) = &mut self.control_sender {
+                    if let Err(err) = sender.try_send(reply) {
+                        tracing::warn!("Could not send control API reply: {}", err);
+                    }
+                }
+
+                match changed {
+                    true => self.update(Message::CheckBalance(0)),
+                    false => Task::none(),
+                }
+            }
+            #[cfg(feature = "control-api")]
+            Message::ControlApiToggled(enabled) => {
+                self.settings.control_api = enabled;
+                self.save_settings();
+                match enabled {
+                    true => self.load_control_token(),
+                    false => self.control_sender = None,
+                }
+                Task::none()
+            }
+            #[cfg(feature = "control-api")]
+            Message::ControlPortChanged(input) => {
+                self.control_port_input = input;
+                Task::none()
+            }
+            #[cfg(feature = "control-api")]
+            Message::SaveControlPort => {
+                match self.control_port_input.trim().parse() {
+                    Ok(port) if port > 0 => {
+                        self.settings.control_port = port;
+                        self.save_settings();
+                    }
+                    _ => {
+                        self.error = Some(WalletError::Control(format!(
+                            "Invalid port {}",
+                            self.control_port_input
+                        )))
+                    }
+                }
+                Task::none()
+            }
+            #[cfg(feature = "control-api")]
+            Message::CopyControlToken => {
+                if let Some(token) = &self.control_token {
+                    clipboard::write::<String>(token.clone())
+                } else {
+                    Task::none()
+                }
+            }
+            Message::ProofsView => {
+                self.view = View::Proofs;
+                let service = self.service();
+                Task::perform(
+                    async move { service.proof_summaries().await },
+                    or_error(Message::ProofsLoaded),
+                )
+            }
+            Message::ProofsLoaded(summaries) => {
+                self.proof_summaries = summaries;
+                Task::none()
+            }
+            Message::Consolidate(mint_url) => {
+                let service = self.service();
+                Task::perform(
+                    async move { service.consolidate(mint_url).await },
+                    or_error(Message::Consolidated),
+                )
+            }
+            Message::Consolidated(amount) => {
+                let service = self.service();
+                Task::batch([
+                    self.update(Message::CheckBalance(amount)),
+                    Task::perform(
+                        async move { service.proof_summaries().await },
+                        or_error(Message::ProofsLoaded),
+                    ),
+                ])
+            }
+            Message::ProofStatesChecked(states) => {
+                self.proof_states = Some(states);
+                self.update(Message::CheckBalance(0))
+            }
+            Message::Balance(amount) => {
+                self.balance = amount;
+                if let Some(tray) = &self.tray {
+                    tray.set_balance(amount);
+                }
+                self.check_auto_withdraw()
+            }
+            Message::CopyInvoice => {
+                tracing::debug!("Copied invoice");
+                clipboard::write::<String>(self.invoice.clone());
+                Task::none()
+            }
+            Message::CopyToken => {
+                tracing::debug!("Copied token");
+                clipboard::write::<String>(self.token.clone());
+                Task::none()
+            }
+            Message::SaveTokenFile => {
+                let Some(sent_token) = self.sent_token.clone() else {
+                    return Task::none();
+                };
+                Task::perform(
+                    save_token_file(sent_token, self.token.clone()),
+                    or_error(Message::TokenFileSaved),
+                )
+            }
+            Message::ExportPaper => {
+                let Some(sent_token) = self.sent_token.clone() else {
+                    return Task::none();
+                };
+                Task::perform(
+                    export_paper(sent_token, self.token.clone()),
+                    or_error(Message::TokenFileSaved),
+                )
+            }
+            Message::TokenFileSaved(path) => {
+                self.token_file_path = path;
+                Task::none()
+            }
+            Message::OpenTokenFile => Task::perform(
+                async {
+                    match rfd::AsyncFileDialog::new()
+                        .add_filter("Cashu token", &["cashu", "txt"])
+                        .pick_file()
+                        .await
+                    {
+                        Some(file) => read_token_file(file.path().to_path_buf()).await.map(Some),
+                        None => Ok(None),
+                    }
+                },
+                or_error(Message::TokenFileOpened),
+            ),
+            Message::FileDropped(path) => {
+                if self.locked {
+                    return Task::none();
+                }
+                Task::perform(
+                    async move { read_token_file(path).await.map(Some) },
+                    or_error(Message::TokenFileOpened),
+                )
+            }
+            Message::TokenFileOpened(token) => {
+                if let Some(token) = token {
+                    self.data = token;
+                    self.view = View::SendReceive;
+                }
+                Task::none()
+            }
+            Message::PayInvoice => {
+                if self.busy(&[Operation::Quote]) {
+                    return Task::none();
+                }
+                let task = self.quote_payment();
+                self.track(Operation::Quote, task)
+            }
+            Message::MeltQuote(preview) => {
+                self.melt_preview = Some(preview);
+                self.melt_routes.clear();
+                self.show_result(Operation::Quote, View::ConfirmMelt);
+                Task::none()
+            }
+            Message::MeltQuotes(routes) => {
+                // Cheapest route the mint's balance covers
+                let Some(cheapest) = routes
+                    .iter()
+                    .position(|route| route.total() <= self.mint_balance(&route.mint_url))
+                else {
+                    // Split it over the mints instead
+                    let service = self.service();
+                    let bolt11 = self.data.clone();
+                    let task = Task::perform(
+                        async move { service.multi_path_quotes(bolt11).await },
+                        or_error(Message::MultiPathQuotes),
+                    );
+                    return self.track(Operation::Quote, task);
+                };
+                self.melt_preview = routes.get(cheapest).cloned();
+                self.melt_routes = routes;
+                self.show_result(Operation::Quote, View::ConfirmMelt);
+                Task::none()
+            }
+            Message::MeltRouteSelected(index) => {
+                if let Some(route) = self.melt_routes.get(index) {
+                    self.melt_preview = Some(route.clone());
+                }
+                Task::none()
+            }
+            Message::ConfirmMelt => {
+                if self.busy(&[Operation::Melt]) {
+                    return Task::none();
+                }
+                let amount = self
+                    .melt_preview
+                    .as_ref()
+                    .and_then(|preview| Self::pin_amount(preview.total(), &preview.unit));
+                if self.needs_pin(amount, Message::ConfirmMelt) {
+                    return Task::none();
+                }
+                let Some(preview) = self.melt_preview.take() else {
+                    return Task::none();
+                };
+                self.melt_routes.clear();
+                let service = self.service();
+                self.view = View::Main;
+                self.data = "".to_string();
+                self.melting = Some(preview.clone());
+                self.melt_state = None;
+                let amount = preview.amount;
+                let task = Task::perform(
+                    async move {
+                        let result = service.pay_invoice(preview.clone()).await;
+                        queue_on_failure(&service, RetryOperation::Melt { preview }, result).await
+                    },
+                    move |result| match result {
+                        Ok(_) => Message::Melted(amount),
+                        Err(err) => Message::MeltFailed(err),
+                    },
+                );
+                self.track(Operation::Melt, task)
+            }
+            Message::MultiPathQuotes(legs) => {
+                self.multi_path = legs;
+                self.show_result(Operation::Quote, View::ConfirmMultiPath);
+                Task::none()
+            }
+            Message::ConfirmMultiPath => {
+                if self.busy(&[Operation::Melt]) {
+                    return Task::none();
+                }
+                let total = self.multi_path.iter().map(MeltPreview::total).sum();
+                if self.needs_pin(Some(total), Message::ConfirmMultiPath) {
+                    return Task::none();
+                }
+                let legs = std::mem::take(&mut self.multi_path);
+                if legs.is_empty() {
+                    return Task::none();
+                }
+                let service = self.service();
+                self.view = View::Main;
+                self.data = "".to_string();
+                let amount = legs.iter().map(|leg| leg.amount).sum();
+                let task = Task::perform(
+                    async move { service.pay_multi_path(legs).await },
+                    move |result| match result {
+                        Ok(_) => Message::Melted(amount),
+                        Err(err) => Message::MeltFailed(err),
+                    },
+                );
+                self.track(Operation::Melt, task)
+            }
+            Message::Melted(amount) => {
+                self.notify(
+                    &t!("payment-sent"),
+                    &t!("paid-sats", amount = i18n::format_number(amount)),
+                );
+                self.melting = None;
+                self.melt_state = None;
+                self.update(Message::CheckBalance(amount))
+            }
+            Message::MeltFailed(err) => {
+                self.melting = None;
+                self.melt_state = None;
+                self.update(Message::Error(err))
+            }
+            Message::QuoteUpdate(update) => match update {
+                QuoteUpdate::State { quote_id, state } => {
+                    if self
+                        .melting
+                        .as_ref()
+                        .is_some_and(|melting| melting.quote_id == quote_id)
+                    {
+                        self.melt_state = Some(state);
+                        return Task::none();
+                    }
+
+                    self.update(Message::MintQuoteStatus((quote_id, state == "PAID")))
+                }
+                QuoteUpdate::Unsupported { quote_id } => {
+                    self.poll_quotes.insert(quote_id);
+                    Task::none()
+                }
+            },
+            Message::LnurlResolved(LnurlRequest::Withdraw(request)) => {
+                self.data = "".to_string();
+                Task::perform(
+                    withdraw_lnurl(self.service(), self.active_mint.clone(), request),
+                    or_error(Message::MintQuote),
+                )
+            }
+            Message::LnurlResolved(LnurlRequest::Pay(request)) => {
+                if request.min_sendable == request.max_sendable {
+                    self.lnurl_amount = request.min_sats().to_string();
+                }
+                self.lnurl_pay = Some(request);
+                self.view = View::LnurlPay;
+                Task::none()
+            }
+            Message::LnurlAmountChanged(amount) => {
+                self.lnurl_amount = amount;
+                Task::none()
+            }
+            Message::PayLnurl => {
+                let Some(request) = self.lnurl_pay.take() else {
+                    return Task::none();
+                };
+                let amount: u64 = match self.lnurl_amount.parse() {
+                    Ok(amount) => amount,
+                    Err(_) => {
+                        self.error = Some(WalletError::InvalidAmount(self.lnurl_amount.clone()));
+                        self.lnurl_pay = Some(request);
+                        return Task::none();
+                    }
+                };
+
+                self.lnurl_amount = "".to_string();
+                let service = self.service();
+                let mint_url = self.active_mint.clone();
+                Task::perform(
+                    async move { service.lnurl_melt_quote(mint_url, &request, amount).await },
+                    or_error(Message::MeltQuote),
+                )
+            }
+            Message::CreateToken => {
+                if self.busy(&[Operation::Quote]) {
+                    return Task::none();
+                }
+                let Some(amount) = self.unit_amount(&self.send_amount) else {
+                    self.error = Some(WalletError::InvalidAmount(self.send_amount.input.clone()));
+                    return Task::none();
+                };
+                let hash_lock = match self.send_hash_lock {
+                    true => match htlc::parse_hash(&self.hash_lock_input) {
+                        Ok(hash) => Some((
+                            hash,
+                            self.send_preimage
+                                .as_ref()
+                                .map(|preimage| preimage.to_string()),
+                        )),
+                        Err(err) => {
+                            self.error = Some(WalletError::Htlc(err.to_string()));
+                            return Task::none();
+                        }
+                    },
+                    false => None,
+                };
+                let locked = self.send_lock.is_some() || hash_lock.is_some();
+                let locktime = match self.refund_hours.trim() {
+                    hours if !locked || hours.is_empty() => None,
+                    hours => match hours.parse::<u64>() {
+                        Ok(hours) if hours > 0 => Some(unix_time() as u64 + hours * 60 * 60),
+                        _ => {
+                            self.error =
+                                Some(WalletError::InvalidRefundDelay(self.refund_hours.clone()));
+                            return Task::none();
+                        }
+                    },
+                };
+                let task = Task::perform(
+                    send_preview(
+                        self.service(),
+                        self.active_mint.clone(),
+                        amount,
+                        self.unit.clone(),
+                        self.send_lock.clone(),
+                        hash_lock,
+                        locktime,
+                        Some(self.send_memo.trim().to_string()).filter(|memo| !memo.is_empty()),
+                    ),
+                    or_error(Message::SendQuoted),
+                );
+                self.track(Operation::Quote, task)
+            }
+            Message::Finished(operation) => {
+                if let Some(count) = self.in_flight.get_mut(&operation) {
+                    *count -= 1;
+                    if *count == 0 {
+                        self.in_flight.remove(&operation);
+                        self.left_running.remove(&operation);
+                    }
+                }
+                Task::none()
+            }
+            Message::Shortcut(shortcut) => match shortcut {
+                Shortcut::Paste => clipboard::read().map(Message::Pasted),
+                Shortcut::Home if self.palette.is_some() => {
+                    self.palette = None;
+                    Task::none()
+                }
+                Shortcut::Home if self.leaving => self.update(Message::StayOnView),
+                Shortcut::Home => self.update(Message::Home),
+                Shortcut::Receive => {
+                    self.view = View::SendReceive;
+                    text_input::focus(text_input::Id::new(RECEIVE_AMOUNT_INPUT))
+                }
+                Shortcut::Send => {
+                    self.view = View::SendReceive;
+                    text_input::focus(text_input::Id::new(SEND_AMOUNT_INPUT))
+                }
+                Shortcut::Submit => match self.view {
+                    View::SendReceive if !self.data.is_empty() => self.update(Message::SubmitInput),
+                    View::ConfirmMelt => self.update(Message::ConfirmMelt),
+                    View::ConfirmMultiPath => self.update(Message::ConfirmMultiPath),
+                    View::ConfirmSend => self.update(Message::ConfirmSend),
+                    View::ConfirmPaymentRequest => self.update(Message::ConfirmPaymentRequest),
+                    _ => Task::none(),
+                },
+                Shortcut::Palette => {
+                    self.palette = Some(String::new());
+                    text_input::focus(text_input::Id::new(PALETTE_INPUT))
+                }
+                Shortcut::FocusNext => widget::focus_next(),
+                Shortcut::FocusPrevious => widget::focus_previous(),
+            },
+            Message::PaletteChanged(query) => {
+                self.palette = Some(query);
+                Task::none()
+            }
+            Message::PaletteSubmit => {
+                let query = self.palette.clone().unwrap_or_default();
+                match palette::search(&query, self.commands()).into_iter().next() {
+                    Some((_, message)) => self.update(Message::RunCommand(Box::new(message))),
+                    None => Task::none(),
+                }
+            }
+            Message::RunCommand(message) => {
+                self.palette = None;
+                self.update(*message)
+            }
+            Message::Pasted(contents) => {
+                let Some(contents) = contents.filter(|contents| !contents.trim().is_empty()) else {
+                    return Task::none();
+                };
+                self.data = contents.trim().to_string();
+                self.view = View::SendReceive;
+                text_input::focus(text_input::Id::new(DATA_INPUT))
+            }
+            Message::StayOnView => {
+                self.leaving = false;
+                Task::none()
+            }
+            Message::ShowReady => {
+                if let Some(view) = self.ready.take() {
+                    self.view = view;
+                }
+                Task::none()
+            }
+            Message::DismissReady => {
+                self.ready = None;
+                self.update(Message::Home)
+            }
+            Message::SendQuoted(preview) => {
+                self.send_preview = Some(preview);
+                self.show_result(Operation::Quote, View::ConfirmSend);
+                Task::none()
+            }
+            Message::ConfirmSend => {
+                if self.busy(&[Operation::Send]) {
+                    return Task::none();
+                }
+                let amount = self.send_preview.as_ref().and_then(|preview| {
+                    Self::pin_amount(preview.amount + preview.fee, &preview.unit)
+                });
+                if self.needs_pin(amount, Message::ConfirmSend) {
+                    return Task::none();
+                }
+                let Some(preview) = self.send_preview.take() else {
+                    return Task::none();
+                };
+                let service = self.service();
+                let split = self.settings.split_target.target(preview.amount);
+                let preimage = preview.hash_lock.clone().zip(preview.preimage);
+                // Refunds go to the key derived from the seed, which a restored
+                // wallet still holds
+                let refund = preview.locktime.zip(self.p2pk_pubkey);
+                let pubkey = preview.lock.map(|(_, pubkey)| pubkey);
+                let conditions = match preview.hash_lock {
+                    Some(hash) => match htlc::conditions(&hash, pubkey, refund) {
+                        Ok(conditions) => Some(conditions),
+                        Err(err) => {
+                            self.error = Some(WalletError::Htlc(err.to_string()));
+                            return Task::none();
+                        }
+                    },
+                    None => pubkey.map(|pubkey| p2pk::conditions(pubkey, refund)),
+                };
+                if let Some((hash, preimage)) = &preimage {
+                    self.preimages.insert(hash.clone(), preimage.clone());
+                }
+                let task = Task::perform(
+                    async move {
+                        // Keep the preimage before the token is locked to it
+                        if let Some((hash, preimage)) = preimage {
+                            service.db().add_preimage(&hash, &preimage).await?;
+                        }
+                        service
+                            .create_token(
+                                preview.mint_url,
+                                preview.amount,
+                                preview.unit,
+                                split,
+                                conditions,
+                                preview.memo,
+                            )
+                            .await
+                    },
+                    or_error(Message::TokenCreated),
+                );
+                self.track(Operation::Send, task)
+            }
+            Message::TokenCreated((transaction_id, token)) => {
+                self.sent_transaction = Some(transaction_id);
+                self.token_file_path = None;
+                self.send_memo = "".to_string();
+                self.send_hash_lock = false;
+                self.hash_lock_input = "".to_string();
+                self.send_preimage = None;
+                self.refund_hours = "".to_string();
+                self.nostr_delivery = None;
+                self.sent_token = token::parse(&token).ok();
+                self.token = token;
+                self.encode_token();
+                self.show_result(Operation::Send, View::Token);
+                Task::none()
+            }
+            Message::TokenFormatSelected(format) => {
+                self.token_format = format;
+                self.encode_token();
+                Task::none()
+            }
+            Message::AddMintView => {
+                self.view = View::AddMint;
+                text_input::focus(text_input::Id::new(MINT_URL_INPUT))
+            }
+            Message::AddMintUrlChanged(data) => {
+                self.add_mint_url = data;
+                Task::none()
+            }
+            Message::DiscoverMints => {
+                self.view = View::DiscoverMints;
+                self.discovered_mints = None;
+                let relays = self.settings.nostr_relays.clone();
+                Task::perform(
+                    async move {
+                        discovery::discover(relays)
+                            .await
+                            .map_err(|err| err.to_string())
+                    },
+                    Message::MintsDiscovered,
+                )
+            }
+            Message::MintsDiscovered(discovered) => {
+                if let Err(err) = &discovered {
+                    tracing::warn!("Could not discover mints: {}", err);
+                }
+                self.discovered_mints = Some(discovered);
+                Task::none()
+            }
+            Message::AddDiscoveredMint(mint_url) => {
+                self.add_mint_url = mint_url.to_string();
+                self.update(Message::AddMint)
+            }
+            Message::AddMint => {
+                let mint_url = UncheckedUrl::from(self.add_mint_url.trim());
+                if !self.mints.contains(&mint_url) && self.mint_review.is_none() {
+                    return self.review_mint(mint_url, ReviewAction::AddMint);
+                }
+                let service = self.service();
+                Task::perform(
+                    async move { service.add_mint(mint_url).await },
+                    or_error(Message::MintAdded),
+                )
+            }
+            Message::MintAdded(mint_url) => {
+                self.active_mint = mint_url;
+                self.add_mint_url = "".to_string();
+                self.view = match self.onboarding {
+                    Some(_) => View::Onboarding,
+                    None => View::Main,
+                };
+                Task::batch([
+                    self.check_balances(),
+                    self.load_mint_info(),
+                    Task::done(Message::CheckMintHealth),
+                ])
+            }
+            Message::MintReviewLoaded((mint_url, info, input_fee_ppk)) => {
+                let Some(review) = &mut self.mint_review else {
+                    return Task::none();
+                };
+                if review.mint_url != mint_url {
+                    return Task::none();
+                }
+                review.details = Some(info.map(|info| MintDetails {
+                    mint_url,
+                    info,
+                    icon: None,
+                    motd_dismissed: true,
+                }));
+                review.input_fee_ppk = input_fee_ppk;
+                Task::none()
+            }
+            Message::MintReviewAcknowledged(acknowledged) => {
+                if let Some(review) = &mut self.mint_review {
+                    review.acknowledged = acknowledged;
+                }
+                Task::none()
+            }
+            Message::ConfirmMintReview => {
+                let Some(review) = self
+                    .mint_review
+                    .clone()
+                    .filter(|review| review.acknowledged)
+                else {
+                    return Task::none();
+                };
+                let task = match review.action {
+                    ReviewAction::AddMint => self.update(Message::AddMint),
+                    ReviewAction::Receive(token) => self.receive(token),
+                    ReviewAction::ClaimInbox(id) => {
+                        // Known once claimed, the review is not asked for again
+                        self.mints.push(review.mint_url);
+                        self.view = View::Main;
+                        self.update(Message::ClaimInboxToken(id))
+                    }
+                };
+                self.mint_review = None;
+                task
+            }
+            Message::ClaimAndSwap => {
+                let Some(review) = self.mint_review.take().filter(|review| review.acknowledged)
+                else {
+                    return Task::none();
+                };
+                let ReviewAction::Receive(token) = review.action else {
+                    return Task::none();
+                };
+
+                self.view = View::Main;
+                let service = self.service();
+                let to = self.swap_target();
+                let split = self.settings.split_target.clone();
+                Task::perform(
+                    async move {
+                        let amount = service.receive(token).await?;
+                        service
+                            .sweep(review.mint_url, to, amount, amount, split.target(amount))
+                            .await?
+                            .ok_or_else(|| {
+                                WalletError::InvalidAmount(format!(
+                                    "{} sats do not cover the fees",
+                                    amount
+                                ))
+                            })
+                    },
+                    or_error(Message::TokenSwapped),
+                )
+            }
+            Message::TokenSwapped(amount) => {
+                self.notify(
+                    &t!("token-received"),
+                    &t!(
+                        "moved-sats-to",
+                        amount = i18n::format_number(amount),
+                        swap_target = self.swap_target()
+                    ),
+                );
+                self.update(Message::CheckBalance(amount))
+            }
+            Message::CancelMintReview => {
+                let Some(review) = self.mint_review.take() else {
+                    return Task::none();
+                };
+                self.view = match (self.onboarding, review.action) {
+                    (Some(_), _) => View::Onboarding,
+                    (None, ReviewAction::AddMint) => View::AddMint,
+                    (None, ReviewAction::Receive(token)) => {
+                        self.data = token;
+                        View::SendReceive
+                    }
+                    (None, ReviewAction::ClaimInbox(_)) => View::Main,
+                };
+                Task::none()
+            }
+            Message::MintSelected(mint_url) => {
+                self.active_mint = mint_url;
+                self.load_mint_info()
+            }
+            Message::MintInfoView => {
+                self.view = View::MintInfo;
+                Task::none()
+            }
+            Message::MintView(mint_url) => {
+                self.view = View::Mint;
+                self.mint_overview = None;
+                Task::perform(
+                    load_mint_overview(self.service(), mint_url),
+                    or_error(Message::MintOverviewLoaded),
+                )
+            }
+            Message::MintOverviewLoaded(overview) => {
+                self.mint_overview = Some(overview);
+                Task::none()
+            }
+            Message::SetDefaultMint(mint_url) => {
+                self.default_mint_input = mint_url.to_string();
+                self.settings.default_mint = Some(mint_url);
+                self.save_settings();
+                Task::none()
+            }
+            Message::SwapAwayFrom(mint_url) => {
+                self.active_mint = mint_url;
+                Task::batch([self.load_mint_info(), self.update(Message::TransferView)])
+            }
+            Message::RemoveMint(mint_url) => {
+                if self.mint_balances.get(&mint_url).copied().unwrap_or(0) == 0 {
+                    let service = self.service();
+                    return Task::perform(
+                        async move { service.remove_mint(mint_url, 0).await },
+                        or_error(Message::MintRemoved),
+                    );
+                }
+
+                // Keep the progress of an earlier attempt at the same mint
+                if self.removal.as_ref().map(|removal| &removal.mint_url) != Some(&mint_url) {
+                    self.removal = Some(MintRemoval {
+                        target: self.mints.iter().find(|other| **other != mint_url).cloned(),
+                        mint_url,
+                        migrated: false,
+                    });
+                }
+                self.view = View::RemoveMint;
+                Task::none()
+            }
+            Message::RemovalTargetSelected(mint_url) => {
+                if let Some(removal) = &mut self.removal {
+                    removal.target = Some(mint_url);
+                }
+                Task::none()
+            }
+            Message::MigrateFunds => {
+                let Some(removal) = &self.removal else {
+                    return Task::none();
+                };
+                let Some(to) = removal.target.clone() else {
+                    return Task::none();
+                };
+                let from = removal.mint_url.clone();
+                let amount = self.mint_balances.get(&from).copied().unwrap_or(0);
+                if self.needs_pin(Some(amount), Message::MigrateFunds) {
+                    return Task::none();
+                }
+
+                let service = self.service();
+                let split = self.settings.split_target.target(amount);
+                let task = Task::perform(
+                    async move {
+                        service
+                            .sweep(from, to, amount, amount, split)
+                            .await?
+                            .ok_or_else(|| {
+                                WalletError::InvalidAmount(format!(
+                                    "{} sats do not cover the fees",
+                                    amount
+                                ))
+                            })
+                    },
+                    or_error(Message::FundsMigrated),
+                );
+                self.track(Operation::Melt, task)
+            }
+            Message::FundsMigrated(amount) => {
+                if let Some(removal) = &mut self.removal {
+                    removal.migrated = true;
+                }
+                self.update(Message::CheckBalance(amount))
+            }
+            Message::ExportFunds => {
+                let Some(removal) = &self.removal else {
+                    return Task::none();
+                };
+                let mint_url = removal.mint_url.clone();
+                let balance = self.mint_balances.get(&mint_url).copied().unwrap_or(0);
+                if self.needs_pin(Some(balance), Message::ExportFunds) {
+                    return Task::none();
+                }
+
+                let service = self.service();
+                let split = self.settings.split_target.target(balance);
+                let task = Task::perform(
+                    async move {
+                        // Less the input fee of spending every proof
+                        let amount = service.max_sendable(mint_url.clone()).await?;
+                        service
+                            .create_token(mint_url, amount, CurrencyUnit::Sat, split, None, None)
+                            .await
+                    },
+                    or_error(Message::FundsExported),
+                );
+                self.track(Operation::Send, task)
+            }
+            Message::FundsExported(created) => {
+                if let Some(removal) = &mut self.removal {
+                    removal.migrated = true;
+                }
+                Task::batch([
+                    self.update(Message::TokenCreated(created)),
+                    self.check_balances(),
+                ])
+            }
+            Message::ConfirmRemoval => {
+                let Some(removal) = self.removal.as_ref().filter(|removal| removal.migrated) else {
+                    return Task::none();
+                };
+                let mint_url = removal.mint_url.clone();
+                let forfeit = self.mint_balances.get(&mint_url).copied().unwrap_or(0);
+                let service = self.service();
+                Task::perform(
+                    async move { service.remove_mint(mint_url, forfeit).await },
+                    or_error(Message::MintRemoved),
+                )
+            }
+            Message::MintRemoved(mint_url) => {
+                tracing::info!("Removed mint {}", mint_url);
+                if self.settings.default_mint.as_ref() == Some(&mint_url) {
+                    self.settings.default_mint = None;
+                    self.default_mint_input.clear();
+                    self.save_settings();
+                }
+                self.mint_overview = None;
+                self.removal = None;
+                self.view = View::Main;
+                self.check_balances()
+            }
+            Message::CheckMintHealth => Task::batch(self.mints.iter().cloned().map(|mint_url| {
+                Task::perform(
+                    async move {
+                        let health = mint_info::check_health(&mint_url).await;
+                        (mint_url, health)
+                    },
+                    Message::MintHealthChecked,
+                )
+            })),
+            Message::MintHealthChecked((mint_url, health)) => {
+                if health == MintHealth::Unreachable {
+                    tracing::warn!("Mint {} is unreachable", mint_url);
+                }
+                self.mint_health.insert(mint_url, health);
+                Task::none()
+            }
+            Message::MintInfoLoaded(details) => {
+                // Ignore info for a mint that is no longer active
+                if details.mint_url == self.active_mint {
+                    self.mint_info = Some(details);
+                }
+                Task::none()
+            }
+            Message::DismissMotd => {
+                let Some(details) = self.mint_info.clone() else {
+                    return Task::none();
+                };
+                let db = self.db();
+                Task::perform(dismiss_motd(db, details), or_error(Message::MintInfoLoaded))
+            }
+            Message::MintBalances(balances) => {
+                let mut mints: Vec<UncheckedUrl> = balances.keys().cloned().collect();
+                mints.sort();
+
+                if !mints.contains(&self.active_mint) {
+                    if let Some(mint_url) = mints.first() {
+                        self.active_mint = mint_url.clone();
+                    }
+                }
+
+                let mints_changed = self.mints != mints;
+                self.mints = mints;
+                self.mint_balances = balances;
+
+                let publish = if mints_changed {
+                    self.publish_nutzap_info()
+                } else {
+                    Task::none()
+                };
+                if self.mint_info.as_ref().map(|details| &details.mint_url)
+                    != Some(&self.active_mint)
+                {
+                    return Task::batch([publish, self.load_mint_info()]);
+                }
+                publish
+            }
+            Message::UnitBalances(balances) => {
+                self.unit_balances = balances;
+                Task::none()
+            }
+            Message::MintUnits((mint_url, units)) => {
+                if mint_url != self.active_mint {
+                    return Task::none();
+                }
+                if !units.contains(&self.unit) {
+                    self.unit = CurrencyUnit::Sat;
+                }
+                self.mint_units = units;
+                Task::none()
+            }
+            Message::UnitSelected(unit) => {
+                self.unit = unit;
+                self.receive_amount = AmountInput::default();
+                self.send_amount = AmountInput::default();
+                Task::none()
+            }
+            Message::HistoryView => {
+                self.view = View::History;
+                self.history_page = 0;
+                let db = self.db();
+                Task::batch([
+                    self.load_history(),
+                    Task::perform(
+                        async move { db.get_tags().await },
+                        or_error(Message::HistoryTags),
+                    ),
+                ])
+            }
+            Message::HistoryTags(tags) => {
+                self.history_tags = tags;
+                Task::none()
+            }
+            Message::HistorySearchChanged(search) => {
+                self.history_filter.search = search;
+                self.history_page = 0;
+                self.load_history()
+            }
+            Message::HistoryTagSelected(tag) => {
+                self.history_filter.tag = Some(tag);
+                self.history_page = 0;
+                self.load_history()
+            }
+            Message::HistoryMintSelected(mint_url) => {
+                self.history_filter.mint_url = Some(mint_url);
+                self.history_page = 0;
+                self.load_history()
+            }
+            Message::HistoryDirectionSelected(direction) => {
+                self.history_filter.direction = Some(direction);
+                self.history_page = 0;
+                self.load_history()
+            }
+            Message::HistoryStatusSelected(status) => {
+                self.history_filter.status = Some(status);
+                self.history_page = 0;
+                self.load_history()
+            }
+            // Bounds that don't parse leave that side open
+            Message::HistoryFromChanged(input) => {
+                self.history_filter.from = parse_date(&input).map(|date| date.timestamp());
+                self.history_from = input;
+                self.history_page = 0;
+                self.load_history()
+            }
+            Message::HistoryToChanged(input) => {
+                self.history_filter.until =
+                    parse_date(&input).map(|date| (date + chrono::Duration::days(1)).timestamp());
+                self.history_to = input;
+                self.history_page = 0;
+                self.load_history()
+            }
+            Message::HistoryMinChanged(input) => {
+                self.history_filter.min_amount = i18n::number_format().parse_integer(&input);
+                self.history_min = input;
+                self.history_page = 0;
+                self.load_history()
+            }
+            Message::HistoryMaxChanged(input) => {
+                self.history_filter.max_amount = i18n::number_format().parse_integer(&input);
+                self.history_max = input;
+                self.history_page = 0;
+                self.load_history()
+            }
+            Message::ClearHistoryFilter => {
+                self.history_filter = HistoryFilter::default();
+                self.history_from = "".to_string();
+                self.history_to = "".to_string();
+                self.history_min = "".to_string();
+                self.history_max = "".to_string();
+                self.history_page = 0;
+                self.load_history()
+            }
+            Message::NoteChanged(note) => {
+                self.note_input = note;
+                Task::none()
+            }
+            Message::TagsChanged(tags) => {
+                self.tags_input = tags;
+                Task::none()
+            }
+            Message::SaveNote => {
+                let Some(transaction) = self.transaction_detail.clone() else {
+                    return Task::none();
+                };
+                Task::perform(
+                    save_note(
+                        self.db(),
+                        transaction,
+                        self.note_input.clone(),
+                        self.tags_input.clone(),
+                    ),
+                    or_error(Message::NoteSaved),
+                )
+            }
+            Message::NoteSaved(transaction) => {
+                self.tags_input = transaction.tags.join(", ");
+                for tag in &transaction.tags {
+                    if !self.history_tags.contains(tag) {
+                        self.history_tags.push(tag.clone());
+                    }
+                }
+                self.history_tags.sort();
+                if let Some(entry) = self
+                    .history
+                    .iter_mut()
+                    .find(|entry| entry.id == transaction.id)
+                {
+                    *entry = transaction.clone();
+                }
+                self.transaction_detail = Some(transaction);
+                Task::none()
+            }
+            Message::ExportFormatSelected(format) => {
+                self.export_format = format;
+                Task::none()
+            }
+            Message::ExportHistory => Task::perform(
+                export_history(self.db(), self.export_format),
+                or_error(Message::HistoryExported),
+            ),
+            Message::HistoryExported(path) => {
+                self.history_export_path = path;
+                Task::none()
+            }
+            Message::ShowTransaction(transaction) => {
+                self.note_input = transaction.note.clone().unwrap_or_default();
+                self.tags_input = transaction.tags.join(", ");
+                self.transaction_detail = Some(transaction);
+                self.view = View::TransactionDetail;
+                Task::none()
+            }
+            Message::HistoryPage(page) => {
+                self.history_page = page;
+                self.load_history()
+            }
+            Message::TransferView => {
+                self.view = View::Transfer;
+                self.transfer_to = self
+                    .mints
+                    .iter()
+                    .find(|mint_url| **mint_url != self.active_mint)
+                    .cloned();
+                Task::none()
+            }
+            Message::TransferToSelected(mint_url) => {
+                self.transfer_to = Some(mint_url);
+                self.transfer_preview = None;
+                Task::none()
+            }
+            Message::TransferAmountChanged(amount) => {
+                self.transfer_amount = amount;
+                self.transfer_preview = None;
+                Task::none()
+            }
+            Message::PreviewTransfer => {
+                let Some(to) = self.transfer_to.clone() else {
+                    return Task::none();
+                };
+                let amount: u64 = match self.transfer_amount.parse() {
+                    Ok(amount) => amount,
+                    Err(_) => {
+                        self.error = Some(WalletError::InvalidAmount(self.transfer_amount.clone()));
+                        return Task::none();
+                    }
+                };
+                Task::perform(
+                    transfer_quote(self.service(), self.active_mint.clone(), to, amount),
+                    or_error(Message::TransferQuoted),
+                )
+            }
+            Message::TransferQuoted(preview) => {
+                self.transfer_preview = Some(preview);
+                Task::none()
+            }
+            Message::ConfirmTransfer => {
+                let amount = self
+                    .transfer_preview
+                    .as_ref()
+                    .map(|preview| preview.melt.total());
+                if self.needs_pin(amount, Message::ConfirmTransfer) {
+                    return Task::none();
+                }
+                let Some(preview) = self.transfer_preview.take() else {
+                    return Task::none();
+                };
+                let service = self.service();
+                let melt = preview.melt.clone();
+                self.transfer_step = Some(TransferStep::Paying);
+                Task::perform(
+                    async move { service.pay_invoice(melt).await },
+                    move |result| match result {
+                        Ok(_) => Message::TransferPaid(preview.clone()),
+                        Err(err) => Message::TransferFailed(err),
+                    },
+                )
+            }
+            Message::TransferPaid(preview) => {
+                let service = self.service();
+                let split = self.settings.split_target.target(preview.mint.amount);
+                self.transfer_step = Some(TransferStep::Minting);
+                Task::perform(
+                    async move {
+                        service
+                            .mint(preview.mint.mint_url, preview.mint.quote_id, split)
+                            .await
+                    },
+                    |result| match result {
+                        Ok(amount) => Message::TransferComplete(amount),
+                        Err(err) => Message::TransferFailed(err),
+                    },
+                )
+            }
+            Message::TransferComplete(amount) => {
+                self.transfer_step = None;
+                self.transfer_amount = "".to_string();
+                self.view = View::Main;
+                self.update(Message::CheckBalance(amount))
+            }
+            Message::TransferFailed(err) => {
+                self.transfer_step = None;
+                self.update(Message::Error(err))
+            }
+            Message::WithdrawView => {
+                self.view = View::Withdraw;
+                Task::none()
+            }
+            Message::WithdrawAddressChanged(address) => {
+                self.withdraw_address = address;
+                self.withdraw_preview = None;
+                Task::none()
+            }
+            Message::WithdrawAmountChanged(amount) => {
+                self.withdraw_amount = amount;
+                self.withdraw_preview = None;
+                Task::none()
+            }
+            Message::PreviewWithdrawal => {
+                let amount: u64 = match self.withdraw_amount.parse() {
+                    Ok(amount) => amount,
+                    Err(_) => {
+                        self.error = Some(WalletError::InvalidAmount(self.withdraw_amount.clone()));
+                        return Task::none();
+                    }
+                };
+                let service = self.service();
+                let mint_url = self.active_mint.clone();
+                let address = self.withdraw_address.clone();
+                Task::perform(
+                    async move { service.withdraw_quote(mint_url, address, amount).await },
+                    or_error(Message::WithdrawalQuoted),
+                )
+            }
+            Message::WithdrawalQuoted(preview) => {
+                self.withdraw_preview = Some(preview);
+                Task::none()
+            }
+            Message::ConfirmWithdrawal => {
+                let amount = self.withdraw_preview.as_ref().map(MeltPreview::total);
+                if self.needs_pin(amount, Message::ConfirmWithdrawal) {
+                    return Task::none();
+                }
+                let Some(preview) = self.withdraw_preview.take() else {
+                    return Task::none();
+                };
+                let service = self.service();
+                self.withdraw_address = "".to_string();
+                self.withdraw_amount = "".to_string();
+                self.view = View::Main;
+                Task::perform(
+                    async move { service.withdraw(preview).await },
+                    or_error(Message::WithdrawalUpdated),
+                )
+            }
+            Message::WithdrawalUpdated(withdrawal) => {
+                if withdrawal.is_broadcast() {
+                    let body = match &withdrawal.txid {
+                        Some(txid) => t!(
+                            "sent-sats-on-chain-in",
+                            amount = i18n::format_number(withdrawal.amount),
+                            txid = txid
+                        ),
+                        None => t!(
+                            "sent-sats-on-chain",
+                            amount = i18n::format_number(withdrawal.amount)
+                        ),
+                    };
+                    self.notify(&t!("withdrawal-sent"), &body);
+                    self.withdrawal = None;
+                    return self.check_balances();
+                }
+                if withdrawal.is_failed() {
+                    self.withdrawal = None;
+                    return self.update(Message::Error(WalletError::Onchain(
+                        "The mint did not send the withdrawal, reclaim it from sent tokens"
+                            .to_string(),
+                    )));
+                }
+
+                // Balances only change when the withdrawal is made
+                match self.withdrawal.replace(withdrawal) {
+                    Some(_) => Task::none(),
+                    None => self.check_balances(),
+                }
+            }
+            Message::CheckWithdrawal => {
+                let Some(withdrawal) = self.withdrawal.clone() else {
+                    return Task::none();
+                };
+                let service = self.service();
+                Task::perform(
+                    async move { service.check_withdrawal(withdrawal).await },
+                    or_error(Message::WithdrawalUpdated),
+                )
+            }
+            Message::SentTokensView => {
+                self.view = View::SentTokens;
+                self.update(Message::CheckSentTokens)
+            }
+            Message::StatsView => {
+                self.view = View::Stats;
+                let db = self.db();
+                let unit = self.unit.clone();
+                Task::perform(
+                    async move {
+                        let transactions = db.get_transactions(u32::MAX, 0).await?;
+                        Ok::<_, WalletError>(stats::stats(&transactions, &unit))
+                    },
+                    or_error(Message::StatsLoaded),
+                )
+            }
+            Message::StatsLoaded(stats) => {
+                self.stats = Some(stats);
+                Task::none()
+            }
+            Message::CheckSentTokens => {
+                let wallet = self.wallet();
+                let db = self.db();
+                Task::perform(
+                    check_sent_tokens(wallet, db),
+                    or_error(Message::SentTokensLoaded),
+                )
+            }
+            Message::SentTokensLoaded(sent_tokens) => {
+                self.sent_tokens = sent_tokens;
+                let now = unix_time() as u64;
+                let refundable: Vec<(i64, u64)> = self
+                    .sent_tokens
+                    .iter()
+                    .filter(|sent| !self.refunds_notified.contains(&sent.id))
+                    .filter(|sent| {
+                        self.reclaimable_at(sent)
+                            .is_some_and(|at| at > 0 && at <= now)
+                    })
+                    .map(|sent| (sent.id, sent.amount))
+                    .collect();
+                for (id, amount) in refundable {
+                    self.refunds_notified.insert(id);
+                    self.notify(
+                        &t!("refund-now-available"),
+                        &t!(
+                            "refund-available-body",
+                            amount = i18n::format_number(amount)
+                        ),
+                    );
+                }
+                Task::none()
+            }
+            Message::Reclaim(id) => {
+                let Some(index) = self.sent_tokens.iter().position(|sent| sent.id == id) else {
+                    return Task::none();
+                };
+                let sent = self.sent_tokens.remove(index);
+                let service = self.service();
+                Task::perform(
+                    async move { service.reclaim(sent).await },
+                    or_error(Message::Reclaimed),
+                )
+            }
+            Message::Reclaimed(amount) => self.update(Message::CheckBalance(amount)),
+            Message::HistoryLoaded((transactions, count)) => {
+                self.history = transactions;
+                self.history_count = count;
+                Task::none()
+            }
+            Message::Error(err) => {
+                tracing::error!("{}", err);
+                // Receives, mints and melts failing this way were queued
+                let queued = err.is_transient();
+                self.error = Some(err);
+                if queued {
+                    return self.update(Message::LoadFailedOperations);
+                }
+                Task::none()
+            }
+            Message::LoadFailedOperations => {
+                let service = self.service();
+                Task::perform(
+                    async move { service.failed_operations().await },
+                    or_error(Message::FailedOperationsLoaded),
+                )
+            }
+            Message::FailedOperationsLoaded(failed_operations) => {
+                self.failed_operations = failed_operations;
+                Task::none()
+            }
+            Message::RetryDue => {
+                let now = unix_time();
+                let due: Vec<FailedOperation> = self
+                    .failed_operations
+                    .iter()
+                    .filter(|failed| {
+                        failed.next_attempt.is_some_and(|next| next <= now)
+                            && !self.retrying.contains(&failed.id)
+                    })
+                    .cloned()
+                    .collect();
+                Task::batch(due.into_iter().map(|failed| self.retry(failed)))
+            }
+            Message::RetryNow(id) => {
+                let Some(failed) = self
+                    .failed_operations
+                    .iter()
+                    .find(|failed| failed.id == id)
+                    .cloned()
+                else {
+                    return Task::none();
+                };
+                if self.retrying.contains(&id) {
+                    return Task::none();
+                }
+                self.retry(failed)
+            }
+            Message::Retried((id, result)) => {
+                self.retrying.remove(&id);
+                let reload = self.update(Message::LoadFailedOperations);
+                match result {
+                    Ok(amount) => {
+                        if let Some(failed) =
+                            self.failed_operations.iter().find(|failed| failed.id == id)
+                        {
+                            self.notify(
+                                &t!("retry-succeeded"),
+                                &t!(
+                                    "retry-finished",
+                                    operation = failed.operation,
+                                    amount = i18n::format_number(amount)
+                                ),
+                            );
+                        }
+                        Task::batch([reload, self.check_balances()])
+                    }
+                    Err(err) => {
+                        tracing::warn!("Retry of failed operation {} failed: {}", id, err);
+                        reload
+                    }
+                }
+            }
+            Message::DiscardFailed(id) => {
+                let service = self.service();
+                Task::perform(
+                    async move { service.discard_failed_operation(id).await },
+                    or_error(|()| Message::LoadFailedOperations),
+                )
+            }
+            Message::DismissError => {
+                self.error = None;
+                Task::none()
+            }
+            Message::Settings => {
+                self.seed_backend = seed_backend();
+                self.database_encrypted = database_encrypted();
+                self.view = View::Settings;
+                Task::none()
+            }
+            Message::SplitModeSelected(mode) => {
+                self.split_mode = mode;
+                if mode == SplitMode::Default {
+                    self.split_input = "".to_string();
+                    return self.update(Message::SaveSplitSetting);
+                }
+                Task::none()
+            }
+            Message::SplitInputChanged(input) => {
+                self.split_input = input;
+                Task::none()
+            }
+            Message::SaveSplitSetting => {
+                match SplitSetting::from_input(self.split_mode, &self.split_input) {
+                    Ok(setting) => {
+                        self.settings.split_target = setting;
+                        self.save_settings();
+                    }
+                    Err(err) => self.error = Some(WalletError::InvalidSplit(err.to_string())),
+                }
+                Task::none()
+            }
+            Message::RelaysInputChanged(input) => {
+                self.relays_input = input;
+                Task::none()
+            }
+            Message::SaveRelays => {
+                let relays = nostr::parse_relays(&self.relays_input);
+                if relays.is_empty() {
+                    self.error = Some(WalletError::Nostr("Enter at least one relay".to_string()));
+                    return Task::none();
+                }
+                self.relays_input = relays.join(", ");
+                self.settings.nostr_relays = relays;
+                self.save_settings();
+                self.publish_nutzap_info()
+            }
+            Message::NostrRecipientChanged(recipient) => {
+                self.nostr_recipient = recipient;
+                Task::none()
+            }
+            Message::NostrRecipientsLoaded(recipients) => {
+                self.nostr_recipients = recipients;
+                Task::none()
+            }
+            Message::SendNostr => {
+                let (Some(keys), Some(transaction_id)) =
+                    (self.nostr_keys.clone(), self.sent_transaction)
+                else {
+                    return Task::none();
+                };
+                let db = self.db();
+                self.nostr_sending = true;
+                Task::perform(
+                    send_token_dm(
+                        keys,
+                        self.settings.nostr_relays.clone(),
+                        db,
+                        transaction_id,
+                        self.nostr_recipient.clone(),
+                        self.token.clone(),
+                    ),
+                    |result| match result {
+                        Ok(delivery) => Message::NostrSent(delivery),
+                        Err(err) => Message::NostrSendFailed(err),
+                    },
+                )
+            }
+            Message::NostrSent(delivery) => {
+                self.nostr_sending = false;
+                self.nostr_delivery = Some(delivery);
+                let db = self.db();
+                Task::perform(
+                    load_nostr_recipients(db),
+                    or_error(Message::NostrRecipientsLoaded),
+                )
+            }
+            Message::NostrSendFailed(err) => {
+                self.nostr_sending = false;
+                self.error = Some(err);
+                Task::none()
+            }
+            Message::Inbox(InboxEvent::Token(incoming)) => {
+                let db = self.db();
+                Task::perform(
+                    save_inbox_token(db, incoming),
+                    or_error(Message::InboxTokenAdded),
+                )
+            }
+            Message::Inbox(InboxEvent::Error(err)) => {
+                self.error = Some(WalletError::Nostr(err));
+                Task::none()
+            }
+            Message::InboxTokenAdded(inbox_token) => {
+                if let Some(inbox_token) = inbox_token {
+                    self.notify(
+                        &t!("token-received"),
+                        &t!(
+                            "inbox-token-body",
+                            amount = i18n::format_number(inbox_token.amount),
+                            sender = inbox_token.sender
+                        ),
+                    );
+                    self.inbox.insert(0, inbox_token);
+                }
+                Task::none()
+            }
+            Message::InboxTokensLoaded(inbox) => {
+                self.inbox = inbox;
+                Task::none()
+            }
+            Message::ClaimInboxToken(id) => {
+                let Some(inbox_token) = self.inbox.iter().find(|t| t.id == id).cloned() else {
+                    return Task::none();
+                };
+                if let Some(mint_url) = self.unknown_mint(&inbox_token.token) {
+                    return self.review_mint(mint_url, ReviewAction::ClaimInbox(id));
+                }
+                Task::perform(
+                    claim_inbox_token(self.service(), inbox_token),
+                    or_error(Message::InboxTokenClaimed),
+                )
+            }
+            Message::InboxTokenClaimed((id, amount)) => {
+                self.inbox.retain(|inbox_token| inbox_token.id != id);
+                self.update(Message::CheckBalance(amount))
+            }
+            Message::DismissInboxToken(id) => {
+                let db = self.db();
+                Task::perform(
+                    dismiss_inbox_token(db, id),
+                    or_error(Message::InboxTokenDismissed),
+                )
+            }
+            Message::InboxTokenDismissed(id) => {
+                self.inbox.retain(|inbox_token| inbox_token.id != id);
+                Task::none()
+            }
+            Message::ContactsView => {
+                self.view = View::Contacts;
+                Task::none()
+            }
+            Message::ContactNameChanged(name) => {
+                self.contact_name = name;
+                Task::none()
+            }
+            Message::ContactAddressChanged(address) => {
+                self.contact_address = address;
+                Task::none()
+            }
+            Message::ContactNpubChanged(npub) => {
+                self.contact_npub = npub;
+                Task::none()
+            }
+            Message::ContactPubkeyChanged(pubkey) => {
+                self.contact_pubkey = pubkey;
+                Task::none()
+            }
+            Message::AddContact => {
+                let db = self.db();
+                Task::perform(
+                    add_contact(
+                        db,
+                        self.contact_name.clone(),
+                        self.contact_address.clone(),
+                        self.contact_npub.clone(),
+                        self.contact_pubkey.clone(),
+                    ),
+                    or_error(Message::ContactsLoaded),
+                )
+            }
+            Message::ContactsLoaded(contacts) => {
+                self.contacts = contacts;
+                self.contact_name = "".to_string();
+                self.contact_address = "".to_string();
+                self.contact_npub = "".to_string();
+                self.contact_pubkey = "".to_string();
+                Task::none()
+            }
+            Message::DeleteContact(id) => {
+                let db = self.db();
+                Task::perform(delete_contact(db, id), or_error(Message::ContactsLoaded))
+            }
+            Message::PayContactSelected(contact) => {
+                if let Some(address) = contact.lightning_address {
+                    self.data = address;
+                }
+                self.send_lock = contact
+                    .p2pk_pubkey
+                    .and_then(|pubkey| PublicKey::from_hex(pubkey).ok())
+                    .map(|pubkey| (contact.name, pubkey));
+                Task::none()
+            }
+            Message::NostrContactSelected(contact) => {
+                if let Some(npub) = contact.npub {
+                    self.nostr_recipient = npub;
+                }
+                Task::none()
+            }
+            Message::PosView => {
+                self.view = View::Pos;
+                Task::none()
+            }
+            Message::PosAmountChanged(amount) => {
+                self.pos.amount = amount;
+                Task::none()
+            }
+            Message::PosTipSelected(tip) => {
+                self.pos.tip = tip;
+                Task::none()
+            }
+            Message::PosCharge => {
+                if self.busy(&[Operation::Quote]) || self.pos.charging.is_some() {
+                    return Task::none();
+                }
+                let Some(amount) = i18n::parse_amount(&self.pos.amount, &CurrencyUnit::Sat)
+                    .filter(|amount| *amount > 0)
+                else {
+                    self.error = Some(WalletError::InvalidAmount(self.pos.amount.clone()));
+                    return Task::none();
+                };
+                let service = self.service();
+                let mint_url = self.active_mint.clone();
+                let total = self.pos.total(amount);
+                let task = Task::perform(
+                    async move { service.mint_quote(mint_url, total, CurrencyUnit::Sat).await },
+                    or_error(Message::PosQuote),
+                );
+                self.track(Operation::Quote, task)
+            }
+            Message::PosQuote(pending) => {
+                self.pos.charging = Some(pending.quote_id.clone());
+                self.qr_code = qr_code::Data::new(pending.request.clone()).ok();
+                self.invoice = pending.request.clone();
+                self.pending_mint = Some(pending);
+                self.reset_polling();
+                Task::none()
+            }
+            Message::PosNoteChanged(note) => {
+                self.pos.note = note;
+                Task::none()
+            }
+            Message::PosNext => {
+                self.pos.next_sale();
+                self.receipt_qr = None;
+                text_input::focus(text_input::Id::new(POS_AMOUNT_INPUT))
+            }
+            Message::SaleLogged(()) => Task::none(),
+            Message::ShowReceipt => {
+                if let Some((sale, _)) = &self.pos.paid {
+                    self.receipt_qr = qr_code::Data::new(pos::receipt_text(sale)).ok();
+                    self.pos.show_receipt = true;
+                }
+                Task::none()
+            }
+            Message::CopyReceipt => match &self.pos.paid {
+                Some((sale, _)) => clipboard::write::<String>(pos::receipt_text(sale)),
+                None => Task::none(),
+            },
+            Message::SaveReceipt => match &self.pos.paid {
+                Some((sale, _)) => Task::perform(
+                    export_receipt(sale.clone()),
+                    or_error(Message::ReceiptSaved),
+                ),
+                None => Task::none(),
+            },
+            Message::ReceiptSaved(path) => {
+                self.pos.receipt_path = path;
+                Task::none()
+            }
+            Message::ExportSales => {
+                Task::perform(export_sales(self.db()), or_error(Message::SalesExported))
+            }
+            Message::SalesExported(path) => {
+                self.pos.sales_path = path;
+                Task::none()
+            }
+            Message::ScheduleView => {
+                self.view = View::Schedule;
+                if self.schedule_start.is_empty() {
+                    self.schedule_start = format_local_time(unix_time() + 60 * 60);
+                }
+                Task::perform(
+                    load_scheduled_payments(self.db()),
+                    or_error(Message::ScheduledPaymentsLoaded),
+                )
+            }
+            Message::ScheduledPaymentsLoaded(scheduled) => {
+                self.scheduled_payments = scheduled;
+                Task::none()
+            }
+            Message::ScheduleContactSelected(contact) => {
+                self.schedule_address = "".to_string();
+                self.schedule_contact = Some(contact);
+                Task::none()
+            }
+            Message::ScheduleAddressChanged(address) => {
+                self.schedule_contact = None;
+                self.schedule_address = address;
+                Task::none()
+            }
+            Message::ScheduleAmountChanged(amount) => {
+                self.schedule_amount = amount;
+                Task::none()
+            }
+            Message::ScheduleRecurrenceSelected(recurrence) => {
+                self.schedule_recurrence = recurrence;
+                Task::none()
+            }
+            Message::ScheduleStartChanged(start) => {
+                self.schedule_start = start;
+                Task::none()
+            }
+            Message::AddScheduledPayment => {
+                let action = match &self.schedule_contact {
+                    Some(contact) => ScheduledAction::Send {
+                        contact_id: contact.id,
+                    },
+                    None => {
+                        let address = self.schedule_address.trim();
+                        if !lnurl::is_lnurl(address) {
+                            self.error = Some(WalletError::Schedule(format!(
+                                "Not a lightning address or LNURL: {}",
+                                address
+                            )));
+                            return Task::none();
+                        }
+                        ScheduledAction::Pay {
+                            address: address.to_string(),
+                        }
+                    }
+                };
+                let Some(amount) = i18n::parse_amount(&self.schedule_amount, &CurrencyUnit::Sat)
+                    .filter(|amount| *amount > 0)
+                else {
+                    self.error = Some(WalletError::InvalidAmount(self.schedule_amount.clone()));
+                    return Task::none();
+                };
+                let Some(next_run) = parse_local_time(&self.schedule_start) else {
+                    self.error = Some(WalletError::Schedule(format!(
+                        "Not a date and time: {}",
+                        self.schedule_start
+                    )));
+                    return Task::none();
+                };
+
+                self.schedule_contact = None;
+                self.schedule_address = "".to_string();
+                self.schedule_amount = "".to_string();
+                let db = self.db();
+                let recurrence = self.schedule_recurrence;
+                Task::perform(
+                    async move {
+                        db.add_scheduled_payment(&action, amount, recurrence, next_run)
+                            .await?;
+                        load_scheduled_payments(db).await
+                    },
+                    or_error(Message::ScheduledPaymentsLoaded),
+                )
+            }
+            Message::SkipScheduledPayment(id) => {
+                let Some(scheduled) = self
+                    .scheduled_payments
+                    .iter()
+                    .find(|scheduled| scheduled.id == id)
+                    .cloned()
+                else {
+                    return Task::none();
+                };
+                let db = self.db();
+                Task::perform(
+                    async move {
+                        // Skipping a one-off payment is cancelling it
+                        match scheduled.recurrence.next(scheduled.next_run) {
+                            Some(next_run) => db.reschedule_payment(id, next_run, None).await?,
+                            None => db.delete_scheduled_payment(id).await?,
+                        }
+                        load_scheduled_payments(db).await
+                    },
+                    or_error(Message::ScheduledPaymentsLoaded),
+                )
+            }
+            Message::CancelScheduledPayment(id) => {
+                let db = self.db();
+                Task::perform(
+                    async move {
+                        db.delete_scheduled_payment(id).await?;
+                        load_scheduled_payments(db).await
+                    },
+                    or_error(Message::ScheduledPaymentsLoaded),
+                )
+            }
+            Message::ScheduleDue => {
+                let now = unix_time();
+                let due: Vec<ScheduledPayment> = self
+                    .scheduled_payments
+                    .iter()
+                    .filter(|scheduled| {
+                        scheduled.next_run <= now && !self.paying_scheduled.contains(&scheduled.id)
+                    })
+                    .cloned()
+                    .collect();
+                Task::batch(
+                    due.into_iter()
+                        .map(|scheduled| self.pay_scheduled(scheduled)),
+                )
+            }
+            Message::ScheduledPaymentMade((id, result)) => {
+                self.paying_scheduled.remove(&id);
+                let reload = Task::perform(
+                    load_scheduled_payments(self.db()),
+                    or_error(Message::ScheduledPaymentsLoaded),
+                );
+                match result {
+                    Ok(amount) => {
+                        self.notify(
+                            &t!("scheduled-payment-made"),
+                            &t!(
+                                "scheduled-payment-sent",
+                                amount = i18n::format_number(amount)
+                            ),
+                        );
+                        Task::batch([reload, self.check_balances(), self.load_history()])
+                    }
+                    Err(err) => {
+                        self.notify(&t!("scheduled-payment-failed"), &err.to_string());
+                        reload
+                    }
+                }
+            }
+            Message::ClearSendLock => {
+                self.send_lock = None;
+                Task::none()
+            }
+            Message::SendMemoChanged(memo) => {
+                self.send_memo = memo;
+                Task::none()
+            }
+            Message::RequestPaymentView => {
+                self.view = View::RequestPayment;
+                self.request_received = None;
+                Task::none()
+            }
+            Message::RequestAmountChanged(amount) => {
+                self.request_amount = amount;
+                Task::none()
+            }
+            Message::RequestDescriptionChanged(description) => {
+                self.request_description = description;
+                Task::none()
+            }
+            Message::CreatePaymentRequest => {
+                let Some(keys) = &self.nostr_keys else {
+                    return Task::none();
+                };
+                let amount = match self.request_amount.trim() {
+                    "" => None,
+                    amount => match amount.parse() {
+                        Ok(amount) => Some(amount),
+                        Err(_) => {
+                            self.error = Some(WalletError::InvalidAmount(amount.to_string()));
+                            return Task::none();
+                        }
+                    },
+                };
+                let transport =
+                    match payment_request::nostr_transport(keys, &self.settings.nostr_relays) {
+                        Ok(transport) => transport,
+                        Err(err) => {
+                            self.error = Some(WalletError::Nostr(err.to_string()));
+                            return Task::none();
+                        }
+                    };
+
+                let id = payment_request::generate_id();
+                let mints: Vec<String> = self.mints.iter().map(|mint| mint.to_string()).collect();
+                let request = PaymentRequest {
+                    id: Some(id.clone()),
+                    amount,
+                    unit: Some("sat".to_string()),
+                    single_use: Some(true),
+                    mints: mints.clone(),
+                    description: Some(self.request_description.trim().to_string())
+                        .filter(|description| !description.is_empty()),
+                    transports: vec![transport],
+                };
+                match payment_request::encode(&request) {
+                    Ok(encoded) => {
+                        self.qr_code = qr_code::Data::new(&encoded).ok();
+                        self.request_received = None;
+                        self.payment_request = Some(OpenRequest {
+                            id,
+                            encoded,
+                            amount,
+                            mints,
+                            claiming: false,
+                        });
+                    }
+                    Err(err) => self.error = Some(WalletError::PaymentRequest(err.to_string())),
+                }
+                Task::none()
+            }
+            Message::PaymentRequestEvent(PaymentEvent::Paid(payload)) => {
+                let Some(open) = &mut self.payment_request else {
+                    return Task::none();
+                };
+                if open.claiming {
+                    return Task::none();
+                }
+                if !open.mints.is_empty() && !open.mints.contains(&payload.mint) {
+                    tracing::warn!("Ignoring payment from unrequested mint {}", payload.mint);
+                    return Task::none();
+                }
+                let token = match payload.token() {
+                    Ok(token) => token,
+                    Err(err) => {
+                        tracing::warn!("Ignoring malformed payment: {}", err);
+                        return Task::none();
+                    }
+                };
+                if open
+                    .amount
+                    .is_some_and(|amount| token_amount(&token) < amount)
+                {
+                    tracing::warn!("Ignoring payment below the requested amount");
+                    return Task::none();
+                }
+
+                open.claiming = true;
+                let service = self.service();
+                let token = token.to_string();
+                Task::perform(
+                    async move { service.receive(token).await },
+                    |result| match result {
+                        Ok(amount) => Message::PaymentRequestPaid(amount),
+                        Err(err) => Message::PaymentRequestClaimFailed(err),
+                    },
+                )
+            }
+            Message::PaymentRequestEvent(PaymentEvent::Error(err)) => {
+                self.error = Some(WalletError::Nostr(err));
+                Task::none()
+            }
+            Message::PaymentRequestPaid(amount) => {
+                self.notify(
+                    &t!("payment-request-paid"),
+                    &t!("received-sats", amount = i18n::format_number(amount)),
+                );
+                self.payment_request = None;
+                self.request_received = Some(amount);
+                self.qr_code = None;
+                self.update(Message::CheckBalance(amount))
+            }
+            Message::PaymentRequestClaimFailed(err) => {
+                if let Some(open) = &mut self.payment_request {
+                    open.claiming = false;
+                }
+                self.error = Some(err);
+                Task::none()
+            }
+            Message::PaymentRequestQuoted(preview) => {
+                self.request_preview = Some(preview);
+                self.show_result(Operation::Quote, View::ConfirmPaymentRequest);
+                Task::none()
+            }
+            Message::ConfirmPaymentRequest => {
+                if self.busy(&[Operation::Send]) {
+                    return Task::none();
+                }
+                let amount = self
+                    .request_preview
+                    .as_ref()
+                    .map(|preview| preview.amount + preview.fee);
+                if self.needs_pin(amount, Message::ConfirmPaymentRequest) {
+                    return Task::none();
+                }
+                let (Some(preview), Some(keys)) =
+                    (self.request_preview.take(), self.nostr_keys.clone())
+                else {
+                    return Task::none();
+                };
+                self.view = View::Main;
+                self.data = "".to_string();
+                let split = self.settings.split_target.target(preview.amount);
+                let task = Task::perform(
+                    pay_payment_request(
+                        self.service(),
+                        keys,
+                        self.settings.nostr_relays.clone(),
+                        preview,
+                        split,
+                    ),
+                    or_error(Message::PaymentRequestSent),
+                );
+                self.track(Operation::Send, task)
+            }
+            Message::PaymentRequestSent(amount) => self.update(Message::CheckBalance(amount)),
+            Message::PinInputChanged(pin) => {
+                self.pin_input = Zeroizing::new(pin);
+                Task::none()
+            }
+            Message::Unlock => {
+                if !verify_pin(&self.pin_input) {
+                    self.pin_input = Zeroizing::default();
+                    self.error = Some(WalletError::IncorrectPin);
+                    return Task::none();
+                }
+
+                self.pin_input = Zeroizing::default();
+                self.locked = false;
+                self.last_activity = Some(Instant::now());
+                match self.pin_prompt.take() {
+                    Some(message) => {
+                        self.pin_verified = true;
+                        self.update(*message)
+                    }
+                    None => Task::none(),
+                }
+            }
+            Message::CancelPin => {
+                self.pin_prompt = None;
+                self.pin_input = Zeroizing::default();
+                Task::none()
+            }
+            Message::NewPinChanged(pin) => {
+                self.new_pin = Zeroizing::new(pin);
+                Task::none()
+            }
+            Message::SetPin => {
+                if self.new_pin.len() < MIN_PIN_LENGTH {
+                    self.error = Some(WalletError::PinTooShort(MIN_PIN_LENGTH));
+                    return Task::none();
+                }
+                if self.pin_set && self.needs_pin(None, Message::SetPin) {
+                    return Task::none();
+                }
+                match set_pin(&self.new_pin) {
+                    Ok(()) => self.pin_set = true,
+                    Err(err) => self.error = Some(WalletError::Database(err.to_string())),
+                }
+                self.new_pin = Zeroizing::default();
+                Task::none()
+            }
+            Message::RemovePin => {
+                if self.needs_pin(None, Message::RemovePin) {
+                    return Task::none();
+                }
+                match remove_pin() {
+                    Ok(()) => self.pin_set = false,
+                    Err(err) => self.error = Some(WalletError::Database(err.to_string())),
+                }
+                Task::none()
+            }
+            Message::LockTimeoutChanged(input) => {
+                self.lock_timeout_input = input;
+                Task::none()
+            }
+            Message::PinThresholdChanged(input) => {
+                self.pin_threshold_input = input;
+                Task::none()
+            }
+            Message::SaveLockSettings => {
+                let Ok(timeout) = self.lock_timeout_input.trim().parse() else {
+                    self.error = Some(WalletError::InvalidAmount(self.lock_timeout_input.clone()));
+                    return Task::none();
+                };
+                let threshold = match self.pin_threshold_input.trim() {
+                    "" => None,
+                    input => match input.parse() {
+                        Ok(threshold) => Some(threshold),
+                        Err(_) => {
+                            self.error = Some(WalletError::InvalidAmount(input.to_string()));
+                            return Task::none();
+                        }
+                    },
+                };
+                if self.pin_set && self.needs_pin(None, Message::SaveLockSettings) {
+                    return Task::none();
+                }
+
+                self.settings.lock_timeout = timeout;
+                self.settings.pin_threshold = threshold;
+                self.save_settings();
+                Task::none()
+            }
+            Message::Activity => {
+                self.last_activity = Some(Instant::now());
+                Task::none()
+            }
+            Message::CheckIdle => {
+                let idle = self.last_activity.is_some_and(|last_activity| {
+                    last_activity.elapsed() >= Duration::from_secs(self.settings.lock_timeout * 60)
+                });
+                if self.pin_set && self.settings.lock_timeout > 0 && idle && !self.locked {
+                    self.locked = true;
+                    return text_input::focus(text_input::Id::new(PIN_INPUT));
+                }
+                Task::none()
+            }
+            Message::MinimizeToTrayToggled(enabled) => {
+                self.settings.minimize_to_tray = enabled;
+                self.save_settings();
+                Task::none()
+            }
+            Message::WindowCloseRequested(id) => {
+                let close = if self.settings.minimize_to_tray && self.tray.is_some() {
+                    window::change_mode(id, window::Mode::Hidden)
+                } else {
+                    window::close(id)
+                };
+
+                // Saved before closing, as the app exits with its window
+                window::get_size(id)
+                    .then(move |size| {
+                        window::get_position(id)
+                            .map(move |position| Message::WindowStateFetched(size, position))
+                    })
+                    .chain(close)
+            }
+            Message::WindowStateFetched(size, position) => {
+                self.settings.window = WindowState::new(size, position);
+                self.settings.last_view = self.last_view();
+                self.save_settings();
+                Task::none()
+            }
+            Message::Tray(action) => {
+                match action {
+                    TrayAction::Receive | TrayAction::Pay => self.view = View::SendReceive,
+                    TrayAction::ShowWindow => (),
+                }
+                window::get_oldest().and_then(|id| {
+                    Task::batch([
+                        window::change_mode(id, window::Mode::Windowed),
+                        window::gain_focus(id),
+                    ])
+                })
+            }
+            Message::NotificationsToggled(enabled) => {
+                self.settings.notifications = enabled;
+                self.save_settings();
+                Task::none()
+            }
+            Message::ProxyInputChanged(input) => {
+                self.proxy_input = input;
+                Task::none()
+            }
+            Message::SaveProxy => {
+                let proxy = match self.proxy_input.trim() {
+                    "" => None,
+                    input => match proxy::parse_proxy(input) {
+                        Ok(proxy) => Some(proxy),
+                        Err(err) => {
+                            self.error = Some(WalletError::InvalidProxy(err.to_string()));
+                            return Task::none();
+                        }
+                    },
+                };
+
+                self.proxy_input = proxy.clone().unwrap_or_default();
+                self.settings.proxy = proxy;
+                self.save_settings();
+                Task::none()
+            }
+            Message::ProxyBypassToggled(mint_url, bypass) => {
+                self.settings.proxy_bypass.retain(|mint| mint != &mint_url);
+                if bypass {
+                    self.settings.proxy_bypass.push(mint_url);
+                }
+                self.save_settings();
+                Task::none()
+            }
+            Message::TestProxy => {
+                let proxy = match self.proxy_input.trim() {
+                    "" => None,
+                    input => match proxy::parse_proxy(input) {
+                        Ok(proxy) => Some(proxy),
+                        Err(err) => {
+                            self.error = Some(WalletError::InvalidProxy(err.to_string()));
+                            return Task::none();
+                        }
+                    },
+                };
+
+                let tests = self.mints.iter().cloned().map(|mint_url| {
+                    let proxy = match self.settings.proxy_bypass.contains(&mint_url) {
+                        true => None,
+                        false => proxy.clone(),
+                    };
+
+                    async move {
+                        let result = proxy::test_connection(proxy, mint_url.clone())
+                            .await
+                            .map(|elapsed| elapsed.as_millis() as u64)
+                            .map_err(|err| err.to_string());
+                        (mint_url, result)
+                    }
+                });
+
+                self.proxy_testing = true;
+                self.proxy_test.clear();
+                Task::perform(futures::future::join_all(tests), Message::ProxyTested)
+            }
+            Message::ProxyTested(results) => {
+                self.proxy_testing = false;
+                self.proxy_test = results;
+                Task::none()
+            }
+            Message::ProfileSelected(name) => {
+                set_profile(&name);
+                self.password = Zeroizing::default();
+                self.passphrase = Zeroizing::default();
+                self.database_encrypted = database_encrypted();
+                self.settings.profile = Some(name);
+                self.save_settings();
+                Task::none()
+            }
+            Message::NewProfileChanged(name) => {
+                self.new_profile = name;
+                Task::none()
+            }
+            Message::CreateProfile => {
+                let name = self.new_profile.trim().to_string();
+                if let Err(err) = create_profile(&name) {
+                    self.error = Some(WalletError::InvalidProfile(err.to_string()));
+                    return Task::none();
+                }
+
+                self.new_profile.clear();
+                Task::done(Message::ProfileSelected(name))
+            }
+            Message::SwitchProfile => {
+                // Close the open wallet and go back to the start screen, the
+                // tray keeps running
+                *self = Self {
+                    tray: self.tray.take(),
+                    settings: std::mem::take(&mut self.settings),
+                    ..Self::default()
+                };
+                Task::none()
+            }
+            Message::DecodeView => {
+                self.view = View::Decode;
+                Task::none()
+            }
+            Message::DecodeInputChanged(input) => {
+                self.decode_input = input;
+                Task::none()
+            }
+            Message::DebugView => {
+                self.view = View::Debug;
+                self.log_export_path = None;
+                Task::done(Message::RefreshLogs)
+            }
+            Message::RefreshLogs => {
+                Task::perform(async { logs::recent(LOG_TAIL_LINES) }, Message::LogsLoaded)
+            }
+            Message::LogsLoaded(lines) => {
+                self.log_lines = lines;
+                Task::none()
+            }
+            Message::ExportLogs => Task::perform(export_logs(), or_error(Message::LogsExported)),
+            Message::LogsExported(path) => {
+                self.log_export_path = path;
+                Task::none()
+            }
+            Message::LanguageSelected(language) => {
+                self.settings.language = language;
+                i18n::set_language(language);
+                self.save_settings();
+                Task::none()
+            }
+            Message::ThemeSelected(theme) => {
+                self.settings.theme = theme.to_string();
+                self.save_settings();
+                Task::none()
+            }
+            Message::FollowSystemThemeToggled(follow) => {
+                self.settings.follow_system_theme = follow;
+                self.save_settings();
+                if follow {
+                    return Task::done(Message::CheckSystemTheme);
+                }
+                Task::none()
+            }
+            Message::LightThemeSelected(theme) => {
+                self.settings.light_theme = theme.to_string();
+                self.save_settings();
+                Task::none()
+            }
+            Message::DarkThemeSelected(theme) => {
+                self.settings.dark_theme = theme.to_string();
+                self.save_settings();
+                Task::none()
+            }
+            Message::UiScaleChanged(scale) => {
+                self.ui_scale_input = scale;
+                Task::none()
+            }
+            Message::SaveUiScale => {
+                // Rescaling while dragging would move the slider under the cursor
+                self.settings.ui_scale = self.ui_scale_input;
+                self.save_settings();
+                Task::none()
+            }
+            Message::CheckSystemTheme => Task::perform(
+                async {
+                    // Detection may shell out or query D-Bus
+                    tokio::task::spawn_blocking(dark_light::detect)
+                        .await
+                        .map(|mode| mode == dark_light::Mode::Dark)
+                        .unwrap_or_default()
+                },
+                Message::SystemThemeChecked,
+            ),
+            Message::SystemThemeChecked(dark) => {
+                self.system_dark = dark;
+                Task::none()
+            }
+            Message::DefaultMintChanged(input) => {
+                self.default_mint_input = input;
+                Task::none()
+            }
+            Message::PollingIntervalChanged(input) => {
+                self.polling_interval_input = input;
+                Task::none()
+            }
+            Message::MaxPollingChanged(input) => {
+                self.max_polling_input = input;
+                Task::none()
+            }
+            Message::QrIntervalChanged(input) => {
+                self.qr_interval_input = input;
+                Task::none()
+            }
+            Message::NextQrFrame => {
+                self.qr_frame = (self.qr_frame + 1) % self.qr_frames.len().max(1);
+                Task::none()
+            }
+            Message::SaveGeneralSettings => {
+                let polling_interval = match self.polling_interval_input.trim().parse() {
+                    Ok(secs) if secs > 0 => secs,
+                    _ => {
+                        self.error = Some(WalletError::InvalidAmount(
+                            self.polling_interval_input.clone(),
+                        ));
+                        return Task::none();
+                    }
+                };
+                let max_polling_interval = match self.max_polling_input.trim().parse() {
+                    Ok(secs) if secs > 0 => secs,
+                    _ => {
+                        self.error =
+                            Some(WalletError::InvalidAmount(self.max_polling_input.clone()));
+                        return Task::none();
+                    }
+                };
+                let qr_frame_interval = match self.qr_interval_input.trim().parse() {
+                    Ok(millis) if millis > 0 => millis,
+                    _ => {
+                        self.error =
+                            Some(WalletError::InvalidAmount(self.qr_interval_input.clone()));
+                        return Task::none();
+                    }
+                };
+
+                self.settings.polling_interval = polling_interval;
+                self.settings.max_polling_interval = max_polling_interval;
+                self.settings.qr_frame_interval = qr_frame_interval;
+                self.settings.default_mint = match self.default_mint_input.trim() {
+                    "" => None,
+                    mint_url => Some(UncheckedUrl::from(mint_url)),
+                };
+                self.save_settings();
+                Task::none()
+            }
+            Message::NutzapsToggled(enabled) => {
+                self.settings.nutzaps_enabled = enabled;
+                self.save_settings();
+                self.publish_nutzap_info()
+            }
+            Message::AutoSwapToggled(enabled) => {
+                self.settings.auto_swap = enabled;
+                self.save_settings();
+                Task::none()
+            }
+            Message::AutoWithdrawToggled(enabled) => {
+                self.settings.auto_withdraw = enabled;
+                self.auto_withdraw_error = None;
+                self.save_settings();
+                self.check_auto_withdraw()
+            }
+            Message::AutoWithdrawAddressChanged(address) => {
+                self.auto_withdraw_address_input = address;
+                Task::none()
+            }
+            Message::AutoWithdrawThresholdChanged(threshold) => {
+                self.auto_withdraw_threshold_input = threshold;
+                Task::none()
+            }
+            Message::SaveAutoWithdraw => {
+                let address = self.auto_withdraw_address_input.trim();
+                if !address.is_empty() && !lnurl::is_lnurl(address) {
+                    self.error = Some(WalletError::Lnurl(format!(
+                        "Not a lightning address or LNURL: {}",
+                        address
+                    )));
+                    return Task::none();
+                }
+                let Ok(threshold) = self.auto_withdraw_threshold_input.trim().parse() else {
+                    self.error = Some(WalletError::InvalidAmount(
+                        self.auto_withdraw_threshold_input.clone(),
+                    ));
+                    return Task::none();
+                };
+
+                self.settings.auto_withdraw_address =
+                    (!address.is_empty()).then(|| address.to_string());
+                self.settings.auto_withdraw_threshold = threshold;
+                self.save_settings();
+                self.check_auto_withdraw()
+            }
+            Message::AutoWithdrawn(Ok(amount)) => {
+                if amount == 0 {
+                    return Task::none();
+                }
+                if let Some(address) = &self.settings.auto_withdraw_address {
+                    self.notify(
+                        &t!("auto-withdraw"),
+                        &t!(
+                            "auto-withdrew",
+                            amount = i18n::format_number(amount),
+                            address = address
+                        ),
+                    );
+                }
+                Task::batch([self.check_balances(), self.load_history()])
+            }
+            Message::AutoWithdrawn(Err(err)) => {
+                // Pause rather than fail again on every balance change
+                self.settings.auto_withdraw = false;
+                self.save_settings();
+                self.notify(&t!("auto-withdraw-paused"), &err);
+                self.auto_withdraw_error = Some(err);
+                Task::none()
+            }
+            Message::AutoRotateKeysetsToggled(enabled) => {
+                self.settings.auto_rotate_keysets = enabled;
+                self.save_settings();
+                if enabled {
+                    return self.update(Message::CheckKeysets);
+                }
+                Task::none()
+            }
+            Message::CheckKeysets => {
+                let service = self.service();
+                Task::perform(
+                    async move { service.retired_keyset_balances().await },
+                    or_error(Message::KeysetsChecked),
+                )
+            }
+            Message::KeysetsChecked(balances) => {
+                for (mint_url, amount) in &balances {
+                    tracing::warn!("{} holds {} sats on retired keysets", mint_url, amount);
+                }
+                self.retired_keysets = balances;
+                if !self.settings.auto_rotate_keysets {
+                    return Task::none();
+                }
+
+                let mints: Vec<UncheckedUrl> = self.retired_keysets.keys().cloned().collect();
+                Task::batch(
+                    mints
+                        .into_iter()
+                        .map(|mint_url| self.update(Message::RotateKeysets(mint_url))),
+                )
+            }
+            Message::RotateKeysets(mint_url) => {
+                let service = self.service();
+                Task::perform(
+                    async move {
+                        let amount = service.rotate_keysets(mint_url.clone()).await?;
+                        Ok::<_, WalletError>((mint_url, amount))
+                    },
+                    or_error(Message::KeysetsRotated),
+                )
+            }
+            Message::KeysetsRotated((mint_url, amount)) => {
+                self.retired_keysets.remove(&mint_url);
+                self.update(Message::CheckBalance(amount))
+            }
+            Message::NutzapInfoPublished(()) => Task::none(),
+            Message::Nutzap(NutzapEvent::Zap(nutzap)) => Task::perform(
+                redeem_nutzap(self.service(), nutzap),
+                or_error(Message::NutzapRedeemed),
+            ),
+            Message::Nutzap(NutzapEvent::Error(err)) => {
+                self.error = Some(WalletError::Nostr(err));
+                Task::none()
+            }
+            Message::NutzapRedeemed(amount) => match amount {
+                Some(amount) => {
+                    self.notify(
+                        &t!("nutzap-received"),
+                        &t!("received-sats", amount = i18n::format_number(amount)),
+                    );
+                    self.update(Message::CheckBalance(amount))
+                }
+                None => Task::none(),
+            },
+            Message::FiatCurrencySelected(currency) => {
+                self.settings.fiat_currency = currency;
+                self.save_settings();
+                self.update(Message::FetchRate)
+            }
+            Message::RateSourceSelected(source) => {
+                self.settings.rate_source = source;
+                self.save_settings();
+                self.update(Message::FetchRate)
+            }
+            Message::FetchRate => Task::perform(
+                exchange_rate(self.settings.rate_source, self.settings.fiat_currency),
+                Message::RateFetched,
+            ),
+            Message::RateFetched(rate) => {
+                // Keep the last known rate around when offline
+                if let Some(rate) = rate {
+                    if rate.currency == self.settings.fiat_currency {
+                        self.exchange_rate = Some(rate);
+                    }
+                }
+                Task::none()
+            }
+            Message::Scan => {
+                self.scanning = true;
+                self.camera_frame = None;
+                self.view = View::Scan;
+                Task::none()
+            }
+            Message::CancelScan => {
+                self.scanning = false;
+                self.view = View::SendReceive;
+                self.camera_frame = None;
+                self.scan_progress = None;
+                Task::none()
+            }
+            Message::Camera(event) => {
+                match event {
+                    CameraEvent::Frame(frame) => self.camera_frame = Some(frame),
+                    CameraEvent::Scanned(content) => {
+                        self.data = content;
+                        return self.update(Message::CancelScan);
+                    }
+                    CameraEvent::Progress(scanned, total) => {
+                        self.scan_progress = Some((scanned, total))
+                    }
+                    CameraEvent::Error(err) => {
+                        self.error = Some(WalletError::Camera(err));
+                        return self.update(Message::CancelScan);
+                    }
+                }
+                Task::none()
+            }
+            Message::CheckClipboard => clipboard::read().map(Message::ClipboardRead),
+            Message::ClipboardRead(contents) => {
+                let contents = contents.unwrap_or_default();
+                if contents == self.last_clipboard {
+                    return Task::none();
+                }
+
+                self.clipboard_prompt = detect(&contents);
+                self.last_clipboard = contents;
+                Task::none()
+            }
+            Message::AcceptClipboard => {
+                if let Some(detected) = self.clipboard_prompt.take() {
+                    self.data = detected.input().to_string();
+                    self.view = View::SendReceive;
+                }
+                Task::none()
+            }
+            Message::DismissClipboard => {
+                self.clipboard_prompt = None;
+                Task::none()
+            }
+            Message::SeedBackendSelected(backend) => {
+                match set_seed_backend(backend, &self.password) {
+                    Ok(()) => self.seed_backend = seed_backend(),
+                    Err(err) => self.error = Some(WalletError::Seed(err.to_string())),
+                }
+                Task::none()
+            }
+            Message::DatabaseEncryptionToggled(encrypted) => {
+                match set_database_encrypted(encrypted) {
+                    Ok(()) => self.database_encrypted = encrypted,
+                    Err(err) => self.error = Some(WalletError::Database(err.to_string())),
+                }
+                Task::none()
+            }
+            Message::ShowSeed => {
+                if self.needs_pin(None, Message::ShowSeed) {
+                    return Task::none();
+                }
+                match get_seed(&self.password) {
+                    Ok(Some(seed)) => {
+                        self.seed_words = seed.word_iter().map(|word| word.to_string()).collect();
+                        self.view = View::SeedBackup;
+                    }
+                    Ok(None) => self.error = Some(WalletError::Seed("No seed found".to_string())),
+                    Err(err) => self.error = Some(WalletError::Seed(err.to_string())),
+                }
+                Task::none()
+            }
+            Message::VerifySeed => {
+                let mut indices =
+                    rand::seq::index::sample(&mut rand::thread_rng(), self.seed_words.len(), 3)
+                        .into_vec();
+                indices.sort();
+
+                self.verify_words = vec![String::new(); indices.len()];
+                self.verify_indices = indices;
+                self.view = View::SeedVerify;
+                Task::none()
+            }
+            Message::VerifyWordChanged((position, word)) => {
+                if let Some(verify_word) = self.verify_words.get_mut(position) {
+                    *verify_word = word;
+                }
+                Task::none()
+            }
+            Message::SubmitSeedVerification => {
+                let correct =
+                    self.verify_indices
+                        .iter()
+                        .zip(&self.verify_words)
+                        .all(|(index, word)| {
+                            self.seed_words.get(*index) == Some(&word.trim().to_lowercase())
+                        });
+
+                if !correct {
+                    self.error = Some(WalletError::SeedMismatch);
+                    return Task::none();
+                }
+
+                if let Err(err) = set_backed_up() {
+                    self.error = Some(WalletError::Seed(err.to_string()));
+                    return Task::none();
+                }
+                self.backed_up = true;
+                self.error = None;
+                match self.onboarding {
+                    Some(_) => self.update(Message::Onboarding(OnboardingStep::Mint)),
+                    None => self.update(Message::Home),
+                }
+            }
+            Message::Onboarding(step) => {
+                self.onboarding = Some(step);
+                self.view = View::Onboarding;
+                match step {
+                    OnboardingStep::Backup => {
+                        match get_seed(&self.password) {
+                            Ok(Some(seed)) => {
+                                self.seed_words =
+                                    seed.word_iter().map(|word| word.to_string()).collect();
+                            }
+                            Ok(None) => {
+                                self.error = Some(WalletError::Seed("No seed found".to_string()))
+                            }
+                            Err(err) => self.error = Some(WalletError::Seed(err.to_string())),
+                        }
+                        Task::none()
+                    }
+                    OnboardingStep::Mint => self.load_mint_info(),
+                    OnboardingStep::Pin => Task::none(),
+                }
+            }
+            Message::FinishOnboarding => {
+                self.onboarding = None;
+                self.seed_words.clear();
+                // The mint picked during setup is the one to start with from now on
+                self.settings.default_mint = Some(self.active_mint.clone());
+                self.default_mint_input = self.active_mint.to_string();
+                self.save_settings();
+                self.update(Message::Home)
+            }
+            Message::Home => {
+                self.onboarding = None;
+                if self.service.is_none() {
+                    self.restore_mnemonic = "".to_string();
+                    self.view = View::Main;
+                    return Task::none();
+                }
+                if !self.leaving && self.busy(&[Operation::Quote, Operation::Send]) {
+                    self.leaving = true;
+                    return Task::none();
+                }
+                self.leaving = false;
+                self.left_running.extend(
+                    [Operation::Quote, Operation::Send]
+                        .into_iter()
+                        .filter(|operation| self.in_flight.contains_key(operation)),
+                );
+
+                // A result waiting in the pending panel keeps what its view
+                // shows
+                if !matches!(self.ready, Some(View::Token)) {
+                    self.token = "".to_string();
+                    self.qr_code = None;
+                    self.sent_token = None;
+                    self.sent_transaction = None;
+                }
+                if !matches!(self.ready, Some(View::ConfirmSend)) {
+                    self.send_preview = None;
+                }
+                if !matches!(self.ready, Some(View::ConfirmMelt)) {
+                    self.melt_preview = None;
+                    self.melt_routes.clear();
+                }
+                if !matches!(self.ready, Some(View::ConfirmMultiPath)) {
+                    self.multi_path.clear();
+                }
+                self.data = "".to_string();
+                self.backup_password = Zeroizing::default();
+                self.backup_path = None;
+                self.nostr_recipient = "".to_string();
+                self.nostr_delivery = None;
+                self.send_lock = None;
+                self.send_hash_lock = false;
+                self.hash_lock_input = "".to_string();
+                self.send_preimage = None;
+                self.refund_hours = "".to_string();
+                self.payment_request = None;
+                self.request_amount = "".to_string();
+                self.request_description = "".to_string();
+                self.nwc_name = "".to_string();
+                self.nwc_budget = "".to_string();
+                self.nwc_uri = None;
+                self.transfer_to = None;
+                self.transfer_amount = "".to_string();
+                self.mint_review = None;
+                self.transfer_preview = None;
+                self.withdraw_address = "".to_string();
+                self.withdraw_amount = "".to_string();
+                self.withdraw_preview = None;
+                self.receive_amount.clear();
+                self.send_amount.clear();
+                self.add_mint_url = "".to_string();
+                self.history = vec![];
+                self.transaction_detail = None;
+                self.history_export_path = None;
+                self.scanning = false;
+                self.lnurl_pay = None;
+                self.lnurl_amount = "".to_string();
+                self.signing_key_input = Zeroizing::default();
+                self.preimage_input = Zeroizing::default();
+                self.camera_frame = None;
+                self.seed_words = vec![];
+                self.verify_indices = vec![];
+                self.verify_words = vec![];
+
+                self.view = View::Main;
+                self.check_balances()
+            }
+        }
+    }
+
+    fn view(&self) -> Element<Message> {
+        let title = text(t!("cashu")).size(70);
+
+        let view = match self.service {
+            Some(_) => match &self.view {
+                View::Main => {
+                    Some(column![center(
+                        column![
+                            column![].push_maybe((!self.backed_up).then(|| {
+                                row![
+                                    text(t!("back-up-your-wallet")).color(ERROR_COLOR),
+                                    button(text(t!("back-up"))).on_press(Message::ShowSeed)
+                                ]
+                                .spacing(10)
+                                .align_items(Alignment::Center)
+                            })),
+                            column![].push_maybe(self.motd_banner()),
+                            self.inbox
+                                .iter()
+                                .fold(column![].spacing(5), |col, inbox_token| {
+                                    col.push(inbox_token_row(inbox_token))
+                                }),
+                            row![
+                                text(i18n::format_number(self.balance)).size(50),
+                                text(t!("sats")).size(40)
+                            ],
+                            row![].push_maybe(self.progress(&[
+                                Operation::Balance,
+                                Operation::Receive,
+                                Operation::Mint,
+                                Operation::Melt,
+                                Operation::Send
+                            ])),
+                            self.other_unit_balances(),
+                            row![].push_maybe(self.fiat_balance()),
+                            column![].push_maybe(self.pending_panel()),
+                            row![
+                                pick_list(
+                                    self.mints.as_slice(),
+                                    Some(&self.active_mint),
+                                    Message::MintSelected
+                                ),
+                                self.health_dot(&self.active_mint),
+                                button(text(t!("info"))).on_press(Message::MintInfoView),
+                                button(text(t!("add-mint"))).on_press(Message::AddMintView),
+                                button(text(t!("transfer"))).on_press_maybe(
+                                    (self.mints.len() > 1).then_some(Message::TransferView)
+                                ),
+                                button(text(t!("withdraw"))).on_press_maybe(
+                                    self.active_mint_info()
+                                        .is_some_and(
+                                            |info| info.supports_melt_method(onchain::METHOD)
+                                        )
+                                        .then_some(Message::WithdrawView)
+                                )
+                            ]
+                            .spacing(10),
+                            self.mints
+                                .iter()
+                                .fold(column![].spacing(5), |col, mint_url| {
+                                    let balance =
+                                        self.mint_balances.get(mint_url).copied().unwrap_or(0);
+                                    col.push(
+                                        row![
+                                            self.health_dot(mint_url),
+                                            button(text(mint_url.to_string()))
+                                                .on_press(Message::MintView(mint_url.clone())),
+                                            text(t!(
+                                                "balance-sats",
+                                                balance = i18n::format_number(balance)
+                                            ))
+                                        ]
+                                        .push_maybe(self.retired_keysets_warning(mint_url))
+                                        .spacing(10),
+                                    )
+                                }),
+                            row![
+                                column![
+                                    button(text(t!("send-receive"))).on_press(Message::SendReceive)
+                                ],
+                                column![button(text(t!("history"))).on_press(Message::HistoryView)],
+                                column![button(text(t!("settings"))).on_press(Message::Settings)]
+                            ]
+                        ]
+                        .spacing(10)
+                    )])
+                }
+                View::SendReceive => Some(column![
+                    row![
+                        text(i18n::format_number(self.balance)).size(50),
+                        text(t!("sats")).size(40)
+                    ],
+                    row![].push_maybe(self.progress(&[
+                        Operation::Quote,
+                        Operation::Receive,
+                        Operation::Send
+                    ])),
+                    row![].push_maybe(self.unreachable_warning(&self.active_mint)),
+                    row![
+                        {
+                            let input = text_input(&t!("data-placeholder"), &self.data)
+                                .id(text_input::Id::new(DATA_INPUT))
+                                .padding(15);
+                            // Left as is until the claim or quote is done
+                            if self.busy(&[Operation::Quote, Operation::Receive]) {
+                                input
+                            } else {
+                                input
+                                    .on_input(Message::DataChanged)
+                                    .on_submit(Message::SubmitInput)
+                            }
+                        },
+                        button(text(t!("scan"))).on_press(Message::Scan),
+                        button(text(t!("open-file"))).on_press(Message::OpenTokenFile)
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .push_maybe(self.contact_picker(
+                        |contact| {
+                            contact.lightning_address.is_some() || contact.p2pk_pubkey.is_some()
+                        },
+                        Message::PayContactSelected
+                    )),
+                    row![].push_maybe(self.input_preview()),
+                    row![].push_maybe(detect(&self.data).map(|detected| {
+                        button(text(match detected {
+                            DetectedInput::Token { .. } => t!("claim"),
+                            DetectedInput::Lnurl(_) => t!("continue"),
+                            _ => t!("pay"),
+                        }))
+                        .on_press_maybe(
+                            (!self.busy(&[Operation::Quote, Operation::Receive]))
+                                .then_some(Message::SubmitInput),
+                        )
+                    })),
+                    row![].push_maybe((self.mint_units.len() > 1).then(|| {
+                        row![
+                            text(t!("unit")),
+                            pick_list(
+                                self.mint_units.as_slice(),
+                                Some(&self.unit),
+                                Message::UnitSelected
+                            )
+                        ]
+                        .spacing(10)
+                        .align_items(Alignment::Center)
+                    })),
+                    amount_entry(
+                        &t!("amount-to-send"),
+                        SEND_AMOUNT_INPUT,
+                        &self.send_amount,
+                        &self.unit,
+                        self.current_rate(),
+                        Message::SendAmount,
+                        true
+                    ),
+                    row![].push_maybe(self.send_lock.as_ref().map(|(name, _)| {
+                        row![
+                            text(t!("token-locked-to-contact", name = name)),
+                            button(text(t!("dont-lock"))).on_press(Message::ClearSendLock)
+                        ]
+                        .spacing(10)
+                        .align_items(Alignment::Center)
+                    })),
+                    self.hash_lock_settings(),
+                    row![].push_maybe(self.refund_settings()),
+                    text_input(&t!("memo-placeholder"), &self.send_memo)
+                        .on_input(Message::SendMemoChanged)
+                        .on_submit(Message::CreateToken),
+                    row![button(text(t!("create-token"))).on_press_maybe(
+                        (!self.busy(&[Operation::Quote, Operation::Send]))
+                            .then_some(Message::CreateToken)
+                    )],
+                    amount_entry(
+                        &t!("amount-to-receive"),
+                        RECEIVE_AMOUNT_INPUT,
+                        &self.receive_amount,
+                        &self.unit,
+                        self.current_rate(),
+                        Message::ReceiveAmount,
+                        false
+                    ),
+                    row![
+                        button(text(t!("create-invoice"))).on_press_maybe(
+                            (!self.busy(&[Operation::Quote])).then_some(Message::CreateInvoice)
+                        ),
+                        button(text(t!("deposit-on-chain"))).on_press_maybe(
+                            (!self.busy(&[Operation::Quote])
+                                && self.active_mint_info().is_some_and(|info| {
+                                    info.supports_mint_method(onchain::METHOD)
+                                }))
+                            .then_some(Message::CreateDeposit)
+                        ),
+                        button(text(t!("request-payment"))).on_press(Message::RequestPaymentView)
+                    ]
+                    .spacing(10),
+                    center(row![button(text(t!("home"))).on_press(Message::Home)])
+                ]),
+                View::LnurlPay => self.lnurl_pay.as_ref().map(|request| {
+                    column![
+                        text(request.description().unwrap_or_default()),
+                        text(t!(
+                            "between-and-sats",
+                            min_sats = i18n::format_number(request.min_sats()),
+                            max_sats = i18n::format_number(request.max_sats())
+                        )),
+                        row![text_input(&t!("amount-sats"), &self.lnurl_amount)
+                            .on_input(Message::LnurlAmountChanged)
+                            .on_submit(Message::PayLnurl)],
+                        row![button(text(t!("pay"))).on_press(Message::PayLnurl)],
+                        center(row![button(text(t!("home"))).on_press(Message::Home)])
+                    ]
+                }),
+                View::ConfirmMelt => self.melt_preview.as_ref().map(|preview| {
+                    column![
+                        text(t!(
+                            "amount",
+                            amount = i18n::format_amount(preview.amount, &preview.unit)
+                        )),
+                        text(t!(
+                            "fee-reserve",
+                            fee_reserve = i18n::format_amount(preview.fee_reserve, &preview.unit)
+                        )),
+                        text(t!(
+                            "input-fee",
+                            input_fee = i18n::format_amount(preview.input_fee, &preview.unit)
+                        )),
+                        text(t!(
+                            "total",
+                            total = i18n::format_amount(preview.total(), &preview.unit)
+                        ))
+                        .size(30),
+                        text(t!("mint", mint_url = preview.mint_url)),
+                    ]
+                    .push_maybe(
+                        preview
+                            .description
+                            .as_ref()
+                            .map(|description| text(t!("description", description = description))),
+                    )
+                    .push_maybe((self.melt_routes.len() > 1).then(|| self.melt_routes(preview)))
+                    .push_maybe(self.unreachable_warning(&preview.mint_url))
+                    .push_maybe(self.progress(&[Operation::Melt]))
+                    .push(
+                        row![
+                            button(text(t!("confirm"))).on_press_maybe(
+                                (!self.busy(&[Operation::Melt])).then_some(Message::ConfirmMelt)
+                            ),
+                            button(text(t!("cancel"))).on_press(Message::Home)
+                        ]
+                        .spacing(10),
+                    )
+                    .spacing(10)
+                }),
+                View::ConfirmMultiPath => (!self.multi_path.is_empty()).then(|| {
+                    self.multi_path_legs()
+                        .push_maybe(self.progress(&[Operation::Melt]))
+                        .push(
+                            row![
+                                button(text(t!("confirm"))).on_press_maybe(
+                                    (!self.busy(&[Operation::Melt]))
+                                        .then_some(Message::ConfirmMultiPath)
+                                ),
+                                button(text(t!("cancel"))).on_press(Message::Home)
+                            ]
+                            .spacing(10),
+                        )
+                        .spacing(10)
+                }),
+                View::ConfirmSend => self.send_preview.as_ref().map(|preview| {
+                    column![
+                        text(t!(
+                            "amount",
+                            amount = i18n::format_amount(preview.amount, &preview.unit)
+                        )),
+                        text(t!(
+                            "input-fee",
+                            input_fee = i18n::format_amount(preview.fee, &preview.unit)
+                        )),
+                        text(t!(
+                            "total",
+                            total =
+                                i18n::format_amount(preview.amount + preview.fee, &preview.unit)
+                        ))
+                        .size(30),
+                        text(t!("mint", mint_url = preview.mint_url)),
+                    ]
+                    .push_maybe(
+                        preview
+                            .lock
+                            .as_ref()
+                            .map(|(name, _)| text(t!("locked-to-contact", name = name))),
+                    )
+                    .push_maybe(
+                        preview
+                            .hash_lock
+                            .as_ref()
+                            .map(|hash| text(t!("locked-to-hash", hash = hash))),
+                    )
+                    .push_maybe(preview.locktime.map(|locktime| {
+                        text(t!(
+                            "refundable-from",
+                            time = format_local_time(locktime as i64)
+                        ))
+                    }))
+                    .push_maybe(
+                        preview
+                            .memo
+                            .as_ref()
+                            .map(|memo| text(t!("message", memo = memo))),
+                    )
+                    .push_maybe(self.unreachable_warning(&preview.mint_url))
+                    .push_maybe(self.progress(&[Operation::Send]))
+                    .push(
+                        row![
+                            button(text(t!("confirm"))).on_press_maybe(
+                                (!self.busy(&[Operation::Send])).then_some(Message::ConfirmSend)
+                            ),
+                            button(text(t!("cancel"))).on_press(Message::Home)
+                        ]
+                        .spacing(10),
+                    )
+                    .spacing(10)
+                }),
+                View::Invoice => Some(column![
+                    row![].push_maybe(
+                        self.qr_code
+                            .as_ref()
+                            .map(|data| qr_code(data).cell_size(10))
+                    ),
+                    row![].push_maybe(self.pending_mint.as_ref().map(|pending| {
+                        if pending.onchain {
+                            return column![
+                                text(t!(
+                                    "deposit-to",
+                                    amount = i18n::format_amount(pending.amount, &pending.unit)
+                                )),
+                                text(&pending.request),
+                                text(t!("waiting-for-the-deposit-to-confirm"))
+                            ]
+                            .spacing(5);
+                        }
+                        let remaining = pending.expiry.saturating_sub(unix_time() as u64);
+                        column![text(t!(
+                            "expires-in",
+                            minutes = remaining / 60,
+                            seconds = format!("{:02}", remaining % 60)
+                        ))]
+                    })),
+                    row![].push_maybe(self.progress(&[Operation::Mint])),
+                    row![button(text(t!("copy"))).on_press(Message::CopyInvoice)],
+                    row![button(text(t!("cancel"))).on_press(Message::CancelMintQuote)]
+                ]),
+                View::Token => Some(column![
+                    row![
+                        text(t!("format")),
+                        pick_list(
+                            TokenFormat::ALL,
+                            Some(self.token_format),
+                            Message::TokenFormatSelected
+                        )
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    row![].push_maybe(
+                        self.qr_frames
+                            .get(self.qr_frame)
+                            .or(self.qr_code.as_ref())
+                            .map(|data| qr_code(data).cell_size(5))
+                    ),
+                    row![].push_maybe((self.qr_frames.len() > 1).then(|| text(t!(
+                        "part-of",
+                        part = self.qr_frame + 1,
+                        parts = self.qr_frames.len()
+                    )))),
+                    row![text(&self.token)],
+                    row![].push_maybe(self.sent_hash_locks()),
+                    row![
+                        button(text(t!("copy"))).on_press(Message::CopyToken),
+                        button(text(t!("save-as-file"))).on_press(Message::SaveTokenFile),
+                        button(text(t!("export-as-paper"))).on_press(Message::ExportPaper)
+                    ]
+                    .spacing(10)
+                    .push_maybe(
+                        self.token_file_path
+                            .as_ref()
+                            .map(|path| text(t!("token-saved-to", path = path)))
+                    ),
+                    row![
+                        text_input(&t!("recipient-npub"), &self.nostr_recipient)
+                            .on_input(Message::NostrRecipientChanged),
+                        pick_list(
+                            self.nostr_recipients.as_slice(),
+                            None::<String>,
+                            Message::NostrRecipientChanged
+                        )
+                        .placeholder(t!("recent")),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .push_maybe(self.contact_picker(
+                        |contact| contact.npub.is_some(),
+                        Message::NostrContactSelected
+                    ))
+                    .push(
+                        button(text(t!("send-via-nostr"))).on_press_maybe(
+                            (!self.nostr_recipient.is_empty()
+                                && !self.nostr_sending
+                                && self.nostr_delivery != Some(DeliveryStatus::Delivered))
+                            .then_some(Message::SendNostr)
+                        )
+                    )
+                    .push_maybe(self.nostr_delivery.map(|delivery| match delivery {
+                        DeliveryStatus::Delivered => text(t!("sent")),
+                        DeliveryStatus::Failed => {
+                            text(t!("delivery-failed")).color(ERROR_COLOR)
+                        }
+                    })),
+                    row![button(text(t!("home"))).on_press(Message::Home)]
+                ]),
+                View::Scan => Some(column![
+                    match &self.camera_frame {
+                        Some(frame) => Element::from(image(frame.clone()).width(640)),
+                        None => Element::from(text(t!("starting-camera"))),
+                    },
+                    row![].push_maybe(self.scan_progress.map(|(scanned, total)| text(t!(
+                        "scanned-parts",
+                        scanned = scanned,
+                        total = total
+                    )))),
+                    row![button(text(t!("cancel"))).on_press(Message::CancelScan)]
+                ]),
+                View::AddMint => Some(column![
+                    row![text_input(&t!("mint-url"), &self.add_mint_url)
+                        .id(text_input::Id::new(MINT_URL_INPUT))
+                        .on_input(Message::AddMintUrlChanged)
+                        .on_submit(Message::AddMint)
+                        .padding(15)],
+                    row![
+                        button(text(t!("add-mint"))).on_press(Message::AddMint),
+                        button(text(t!("discover-mints"))).on_press(Message::DiscoverMints)
+                    ]
+                    .spacing(10),
+                    center(row![button(text(t!("home"))).on_press(Message::Home)])
+                ]),
+                View::DiscoverMints => Some(
+                    column![
+                        text(t!("discover-mints")).size(30),
+                        match &self.discovered_mints {
+                            Some(Ok(discovered)) if discovered.is_empty() => {
+                                column![text(t!("no-mints-discovered"))]
+                            }
+                            Some(Ok(discovered)) => column![scrollable(
+                                discovered
+                                    .iter()
+                                    .fold(column![].spacing(10), |col, mint| col
+                                        .push(discovered_mint_row(mint, &self.mints)))
+                            )
+                            .height(400)],
+                            Some(Err(err)) => column![
+                                text(t!("mint-discovery-failed", err = err)).color(ERROR_COLOR)
+                            ],
+                            None => column![text(t!("discovering-mints"))],
+                        },
+                        row![
+                            button(text(t!("refresh"))).on_press_maybe(
+                                self.discovered_mints
+                                    .is_some()
+                                    .then_some(Message::DiscoverMints)
+                            ),
+                            button(text(t!("back"))).on_press(Message::AddMintView)
+                        ]
+                        .spacing(10)
+                    ]
+                    .spacing(10),
+                ),
+                View::Settings => {
+                    Some(
+                        column![
+                            row![
+                                text(t!("language")),
+                                pick_list(
+                                    Language::ALL,
+                                    Some(self.settings.language),
+                                    Message::LanguageSelected
+                                )
+                            ]
+                            .spacing(10)
+                            .align_items(Alignment::Center),
+                            if self.settings.follow_system_theme {
+                                row![
+                                    text(t!("light-theme")),
+                                    pick_list(
+                                        Theme::ALL,
+                                        Some(self.settings.system_theme(false)),
+                                        Message::LightThemeSelected
+                                    ),
+                                    text(t!("dark-theme")),
+                                    pick_list(
+                                        Theme::ALL,
+                                        Some(self.settings.system_theme(true)),
+                                        Message::DarkThemeSelected
+                                    )
+                                ]
+                            } else {
+                                row![
+                                    text(t!("theme")),
+                                    pick_list(
+                                        Theme::ALL,
+                                        Some(self.settings.theme()),
+                                        Message::ThemeSelected
+                                    )
+                                ]
+                            }
+                            .push(
+                                checkbox(t!("follow-system"), self.settings.follow_system_theme)
+                                    .on_toggle(Message::FollowSystemThemeToggled)
+                            )
+                            .spacing(10)
+                            .align_items(Alignment::Center),
+                            row![
+                                text(t!("interface-scale")),
+                                slider(
+                                    UI_SCALE_RANGE,
+                                    self.ui_scale_input,
+                                    Message::UiScaleChanged
+                                )
+                                .step(5u16)
+                                .on_release(Message::SaveUiScale)
+                                .width(200),
+                                text(format!("{}%", self.ui_scale_input))
+                            ]
+                            .spacing(10)
+                            .align_items(Alignment::Center),
+                            row![
+                                text(t!("profile", profile = profile())),
+                                button(text(t!("switch-profile"))).on_press(Message::SwitchProfile)
+                            ]
+                            .spacing(10)
+                            .align_items(Alignment::Center),
+                            row![
+                                text(t!("default-mint")),
+                                text_input(DEFAULT_MINT, &self.default_mint_input)
+                                    .on_input(Message::DefaultMintChanged)
+                                    .on_submit(Message::SaveGeneralSettings),
+                                text(t!("quote-polling-seconds")),
+                                text_input("5", &self.polling_interval_input)
+                                    .on_input(Message::PollingIntervalChanged)
+                                    .on_submit(Message::SaveGeneralSettings),
+                                text(t!("up-to")),
+                                text_input("60", &self.max_polling_input)
+                                    .on_input(Message::MaxPollingChanged)
+                                    .on_submit(Message::SaveGeneralSettings),
+                                text(t!("qr-part-ms")),
+                                text_input("250", &self.qr_interval_input)
+                                    .on_input(Message::QrIntervalChanged)
+                                    .on_submit(Message::SaveGeneralSettings),
+                                button(text(t!("save"))).on_press(Message::SaveGeneralSettings)
+                            ]
+                            .spacing(10)
+                            .align_items(Alignment::Center),
+                            row![
+                                button(text(t!("back-up-seed"))).on_press(Message::ShowSeed),
+                                button(text(t!("debug-log"))).on_press(Message::DebugView),
+                                button(text(t!("decode"))).on_press(Message::DecodeView)
+                            ]
+                            .spacing(10),
+                            row![
+                                text_input(&t!("backup-password"), &self.backup_password)
+                                    .on_input(Message::BackupPasswordChanged)
+                                    .secure(true),
+                                button(text(t!("export-backup"))).on_press_maybe(
+                                    (!self.backup_password.is_empty())
+                                        .then_some(Message::ExportBackup)
+                                )
+                            ]
+                            .spacing(10)
+                            .align_items(Alignment::Center),
+                            row![].push_maybe(
+                                self.backup_path
+                                    .as_ref()
+                                    .map(|path| text(t!("backup-saved-to", path = path)))
+                            ),
+                            row![button(text(t!("proofs"))).on_press(Message::ProofsView)],
+                            row![button(text(t!("wallet-connect"))).on_press(Message::NwcView)],
+                            row![].push_maybe(self.control_api_settings()),
+                            row![button(text(t!("contacts"))).on_press(Message::ContactsView)],
+                            row![button(text(t!("scheduled-payments")))
+                                .on_press(Message::ScheduleView)],
+                            row![button(text(t!("point-of-sale"))).on_press(Message::PosView)],
+                            row![button(text(t!("check-proof-states")))
+                                .on_press(Message::CheckProofStates)]
+                            .push_maybe(self.proof_states.map(|states| text(t!(
+                                "proof-states-result",
+                                spent = i18n::format_number(states.spent),
+                                pending = i18n::format_number(states.pending)
+                            ))))
+                            .spacing(10)
+                            .align_items(Alignment::Center),
+                            self.maintenance_settings(),
+                            row![].push_maybe(self.p2pk_pubkey.map(|pubkey| text(t!(
+                                "p2pk-public-key",
+                                key = pubkey.to_hex()
+                            )))),
+                            row![].push_maybe(self.nostr_identity_settings()),
+                            row![
+                                text(t!("seed-storage")),
+                                pick_list(
+                                    SeedBackend::ALL,
+                                    Some(self.seed_backend),
+                                    Message::SeedBackendSelected
+                                )
+                            ]
+                            .spacing(10)
+                            .align_items(Alignment::Center),
+                            row![
+                                checkbox(t!("encrypt-database"), self.database_encrypted)
+                                    .on_toggle(Message::DatabaseEncryptionToggled),
+                                text(t!("encrypt-database-hint")).size(12)
+                            ]
+                            .spacing(10)
+                            .align_items(Alignment::Center),
+                            row![
+                                text(t!("fiat-currency")),
+                                pick_list(
+                                    FiatCurrency::ALL,
+                                    Some(self.settings.fiat_currency),
+                                    Message::FiatCurrencySelected
+                                )
+                            ]
+                            .spacing(10)
+                            .align_items(Alignment::Center),
+                            row![
+                                text(t!("proof-split")),
+                                pick_list(
+                                    SplitMode::ALL,
+                                    Some(self.split_mode),
+                                    Message::SplitModeSelected
+                                )
+                            ]
+                            .push_maybe((self.split_mode != SplitMode::Default).then(|| {
+                                row![
+                                    text_input(
+                                        &match self.split_mode {
+                                            SplitMode::Denominations => t!("split-denominations"),
+                                            _ => t!("number-of-proofs"),
+                                        },
+                                        &self.split_input
+                                    )
+                                    .on_input(Message::SplitInputChanged)
+                                    .on_submit(Message::SaveSplitSetting),
+                                    button(text(t!("save"))).on_press(Message::SaveSplitSetting)
+                                ]
+                                .spacing(10)
+                                .align_items(Alignment::Center)
+                            }))
+                            .spacing(10)
+                            .align_items(Alignment::Center),
+                            row![
+                                text_input(
+                                    &if self.pin_set { t!("new-pin") } else { t!("pin") },
+                                    &self.new_pin
+                                )
+                                .secure(true)
+                                .on_input(Message::NewPinChanged)
+                                .on_submit(Message::SetPin),
+                                button(text(if self.pin_set {
+                                    t!("change-pin")
+                                } else {
+                                    t!("set-pin")
+                                }))
+                                .on_press(Message::SetPin)
+                            ]
+                            .push_maybe(
+                                self.pin_set.then(
+                                    || button(text(t!("remove-pin"))).on_press(Message::RemovePin)
+                                )
+                            )
+                            .spacing(10)
+                            .align_items(Alignment::Center),
+                            row![].push_maybe(self.pin_set.then(|| {
+                                row![
+                                    text(t!("lock-after")),
+                                    text_input("5", &self.lock_timeout_input)
+                                        .on_input(Message::LockTimeoutChanged)
+                                        .on_submit(Message::SaveLockSettings),
+                                    text(t!("pin-threshold")),
+                                    text_input(&t!("never"), &self.pin_threshold_input)
+                                        .on_input(Message::PinThresholdChanged)
+                                        .on_submit(Message::SaveLockSettings),
+                                    button(text(t!("save"))).on_press(Message::SaveLockSettings)
+                                ]
+                                .spacing(10)
+                                .align_items(Alignment::Center)
+                            })),
+                            row![
+                                checkbox(t!("desktop-notifications"), self.settings.notifications)
+                                    .on_toggle(Message::NotificationsToggled)
+                            ],
+                            row![].push_maybe(self.tray.as_ref().map(|_| {
+                                checkbox(
+                                    t!("minimize-to-tray-on-close"),
+                                    self.settings.minimize_to_tray,
+                                )
+                                .on_toggle(Message::MinimizeToTrayToggled)
+                            })),
+                            row![checkbox(
+                                t!("receive-nutzaps"),
+                                self.settings.nutzaps_enabled
+                            )
+                            .on_toggle(Message::NutzapsToggled)],
+                            row![checkbox(
+                                t!("auto-swap"),
+                                self.settings.auto_swap
+                            )
+                            .on_toggle(Message::AutoSwapToggled)],
+                            self.auto_withdraw_settings(),
+                            row![checkbox(
+                                t!("auto-rotate-keysets"),
+                                self.settings.auto_rotate_keysets
+                            )
+                            .on_toggle(Message::AutoRotateKeysetsToggled)],
+                            row![
+                                text(t!("nostr-relays")),
+                                text_input(&t!("relays-placeholder"), &self.relays_input)
+                                    .on_input(Message::RelaysInputChanged)
+                                    .on_submit(Message::SaveRelays),
+                                button(text(t!("save"))).on_press(Message::SaveRelays)
+                            ]
+                            .spacing(10)
+                            .align_items(Alignment::Center),
+                            row![
+                                text(t!("socks5-proxy")),
+                                text_input("127.0.0.1:9050", &self.proxy_input)
+                                    .on_input(Message::ProxyInputChanged)
+                                    .on_submit(Message::SaveProxy),
+                                button(text(t!("save"))).on_press(Message::SaveProxy),
+                                button(text(t!("test-connection"))).on_press_maybe(
+                                    (!self.proxy_testing).then_some(Message::TestProxy)
+                                )
+                            ]
+                            .spacing(10)
+                            .align_items(Alignment::Center),
+                            self.mints
+                                .iter()
+                                .fold(column![].spacing(5), |col, mint_url| {
+                                    let mint = mint_url.clone();
+                                    col.push(
+                                        checkbox(
+                                            t!("connect-directly-to", mint_url = mint_url),
+                                            self.settings.proxy_bypass.contains(mint_url),
+                                        )
+                                        .on_toggle(
+                                            move |bypass| {
+                                                Message::ProxyBypassToggled(mint.clone(), bypass)
+                                            },
+                                        ),
+                                    )
+                                }),
+                            self.proxy_test.iter().fold(
+                                column![].spacing(5),
+                                |col, (mint_url, result)| col.push(text(match result {
+                                    Ok(millis) =>
+                                        t!("reachable-in-ms", mint_url = mint_url, millis = millis),
+                                    Err(err) => format!("{}: {}", mint_url, err),
+                                }))
+                            ),
+                            row![].push_maybe(
+                                (ProxySettings::from(&self.settings) != *proxy::active()).then(
+                                    || { text(t!("proxy-restart-note")) }
+                                )
+                            ),
+                            row![
+                                text(t!("rate-source")),
+                                pick_list(
+                                    RateSource::ALL,
+                                    Some(self.settings.rate_source),
+                                    Message::RateSourceSelected
+                                )
+                            ]
+                            .spacing(10)
+                            .align_items(Alignment::Center),
+                            center(row![button(text(t!("home"))).on_press(Message::Home)])
+                        ],
+                    )
+                }
+                View::SeedBackup => Some(column![
+                    text(t!("seed-backup-note")),
+                    self.seed_words
+                        .iter()
+                        .enumerate()
+                        .fold(column![].spacing(5), |col, (index, word)| col
+                            .push(text(format!("{}. {}", index + 1, word)))),
+                    row![button(text(t!("verify"))).on_press(Message::VerifySeed)],
+                    center(row![button(text(t!("home"))).on_press(Message::Home)])
+                ]),
+                View::SeedVerify => Some(column![
+                    text(t!("seed-verify-note")),
+                    self.verify_indices
+                        .iter()
+                        .zip(&self.verify_words)
+                        .enumerate()
+                        .fold(column![].spacing(5), |col, (position, (index, word))| {
+                            col.push(
+                                text_input(&t!("word", number = index + 1), word).on_input(
+                                    move |word| Message::VerifyWordChanged((position, word)),
+                                ),
+                            )
+                        }),
+                    row![button(text(t!("confirm"))).on_press(Message::SubmitSeedVerification)],
+                    center(row![button(text(t!("home"))).on_press(Message::Home)])
+                ]),
+                View::History => {
+                    let last_page = self.history_count.saturating_sub(1) / HISTORY_PAGE_SIZE;
+
+                    Some(column![
+                        row![
+                            text_input(&t!("history-search"), &self.history_filter.search)
+                                .on_input(Message::HistorySearchChanged),
+                            pick_list(
+                                self.history_tags.as_slice(),
+                                self.history_filter.tag.as_ref(),
+                                Message::HistoryTagSelected
+                            )
+                            .placeholder(t!("label")),
+                            button(text(t!("clear"))).on_press_maybe(
+                                (self.history_filter != HistoryFilter::default())
+                                    .then_some(Message::ClearHistoryFilter)
+                            )
+                        ]
+                        .spacing(10)
+                        .align_items(Alignment::Center),
+                        row![
+                            pick_list(
+                                self.mints.as_slice(),
+                                self.history_filter.mint_url.as_ref(),
+                                Message::HistoryMintSelected
+                            )
+                            .placeholder(t!("mint-filter")),
+                            pick_list(
+                                Direction::ALL,
+                                self.history_filter.direction,
+                                Message::HistoryDirectionSelected
+                            )
+                            .placeholder(t!("direction")),
+                            pick_list(
+                                TransactionStatus::ALL,
+                                self.history_filter.status,
+                                Message::HistoryStatusSelected
+                            )
+                            .placeholder(t!("status")),
+                        ]
+                        .spacing(10),
+                        row![
+                            text_input(&t!("date-from"), &self.history_from)
+                                .on_input(Message::HistoryFromChanged),
+                            text_input(&t!("date-to"), &self.history_to)
+                                .on_input(Message::HistoryToChanged),
+                            text_input(&t!("min-amount"), &self.history_min)
+                                .on_input(Message::HistoryMinChanged),
+                            text_input(&t!("max-amount"), &self.history_max)
+                                .on_input(Message::HistoryMaxChanged),
+                        ]
+                        .spacing(10),
+                        scrollable(
+                            self.history
+                                .iter()
+                                .fold(column![].spacing(5), |col, transaction| col
+                                    .push(history_row(transaction)))
+                        )
+                        .height(400),
+                        row![
+                            button(text(t!("previous"))).on_press_maybe(
+                                (self.history_page > 0)
+                                    .then(|| Message::HistoryPage(self.history_page - 1))
+                            ),
+                            text(t!(
+                                "page-of",
+                                page = self.history_page + 1,
+                                pages = last_page + 1
+                            )),
+                            button(text(t!("next"))).on_press_maybe(
+                                (self.history_page < last_page)
+                                    .then(|| Message::HistoryPage(self.history_page + 1))
+                            )
+                        ]
+                        .spacing(10)
+                        .align_items(Alignment::Center),
+                        row![
+                            button(text(t!("sent-tokens"))).on_press(Message::SentTokensView),
+                            button(text(t!("stats"))).on_press(Message::StatsView),
+                            pick_list(
+                                ExportFormat::ALL,
+                                Some(self.export_format),
+                                Message::ExportFormatSelected
+                            ),
+                            button(text(t!("export"))).on_press(Message::ExportHistory)
+                        ]
+                        .spacing(10)
+                        .align_items(Alignment::Center)
+                        .push_maybe(
+                            self.history_export_path
+                                .as_ref()
+                                .map(|path| text(t!("history-saved-to", path = path)))
+                        ),
+                        center(row![button(text(t!("home"))).on_press(Message::Home)])
+                    ])
+                }
+                View::Stats => Some(
+                    column![]
+                        .push_maybe(self.stats.as_ref().map(|stats| self.stats_view(stats)))
+                        .push(center(
+                            row![
+                                button(text(t!("history"))).on_press(Message::HistoryView),
+                                button(text(t!("home"))).on_press(Message::Home)
+                            ]
+                            .spacing(10),
+                        ))
+                        .spacing(10),
+                ),
+                View::TransactionDetail => self.transaction_detail.as_ref().map(|transaction| {
+                    transaction_detail(transaction)
+                        .push(
+                            text_input(&t!("note"), &self.note_input)
+                                .on_input(Message::NoteChanged)
+                                .on_submit(Message::SaveNote),
+                        )
+                        .push(
+                            text_input(&t!("labels-comma-separated"), &self.tags_input)
+                                .on_input(Message::TagsChanged)
+                                .on_submit(Message::SaveNote),
+                        )
+                        .push(center(
+                            row![
+                                button(text(t!("save"))).on_press(Message::SaveNote),
+                                button(text(t!("back"))).on_press(Message::HistoryView)
+                            ]
+                            .spacing(10),
+                        ))
+                }),
+                View::RequestPayment => Some(match &self.payment_request {
+                    Some(open) => column![
+                        text(match open.amount {
+                            Some(amount) =>
+                                t!("waiting-for-sats", amount = i18n::format_number(amount)),
+                            None => t!("waiting-for-payment"),
+                        }),
+                        row![].push_maybe(
+                            self.qr_code.as_ref().map(|data| qr_code(data).cell_size(5))
+                        ),
+                        text(&open.encoded),
+                        center(row![button(text(t!("home"))).on_press(Message::Home)])
+                    ]
+                    .spacing(10),
+                    None => column![]
+                        .push_maybe(self.request_received.map(|amount| {
+                            text(t!("received-sats", amount = i18n::format_number(amount)))
+                        }))
+                        .push(
+                            text_input(&t!("amount-sats-optional"), &self.request_amount)
+                                .on_input(Message::RequestAmountChanged),
+                        )
+                        .push(
+                            text_input(&t!("description-optional"), &self.request_description)
+                                .on_input(Message::RequestDescriptionChanged)
+                                .on_submit(Message::CreatePaymentRequest),
+                        )
+                        .push(text(t!(
+                            "request-payment-note",
+                            relays = self.settings.nostr_relays.join(", ")
+                        )))
+                        .push(row![button(text(t!("create-request")))
+                            .on_press(Message::CreatePaymentRequest)])
+                        .push(center(row![
+                            button(text(t!("home"))).on_press(Message::Home)
+                        ]))
+                        .spacing(10),
+                }),
+                View::ConfirmPaymentRequest => self.request_preview.as_ref().map(|preview| {
+                    column![]
+                        .push_maybe(preview.request.description.as_ref().map(text))
+                        .push(text(t!(
+                            "amount-in-sats",
+                            amount = i18n::format_number(preview.amount)
+                        )))
+                        .push(text(t!(
+                            "input-fee-sats",
+                            fee = i18n::format_number(preview.fee)
+                        )))
+                        .push(
+                            text(t!(
+                                "total-sats",
+                                total = i18n::format_number(preview.amount + preview.fee)
+                            ))
+                            .size(30),
+                        )
+                        .push(text(t!("mint", mint_url = preview.mint_url)))
+                        .push_maybe(
+                            preview
+                                .request
+                                .nut10
+                                .as_ref()
+                                .map(|nut10| text(t!("locked-to", data = nut10.data))),
+                        )
+                        .push_maybe(preview.request.transport().map(|transport| {
+                            text(t!(
+                                "sent-via-to",
+                                kind = transport.kind,
+                                target = transport.target
+                            ))
+                        }))
+                        .push(
+                            row![
+                                button(text(t!("confirm"))).on_press_maybe(
+                                    (!self.busy(&[Operation::Send]))
+                                        .then_some(Message::ConfirmPaymentRequest)
+                                ),
+                                button(text(t!("cancel"))).on_press(Message::Home)
+                            ]
+                            .spacing(10),
+                        )
+                        .spacing(10)
+                }),
+                View::Contacts => Some(
+                    column![
+                        self.contacts
+                            .iter()
+                            .fold(column![].spacing(5), |col, contact| {
+                                col.push(contact_row(contact))
+                            }),
+                        text_input(&t!("name"), &self.contact_name)
+                            .on_input(Message::ContactNameChanged),
+                        text_input(&t!("lightning-address"), &self.contact_address)
+                            .on_input(Message::ContactAddressChanged),
+                        text_input(&t!("npub"), &self.contact_npub)
+                            .on_input(Message::ContactNpubChanged),
+                        text_input(&t!("p2pk-public-key-hex"), &self.contact_pubkey)
+                            .on_input(Message::ContactPubkeyChanged)
+                            .on_submit(Message::AddContact),
+                        row![button(text(t!("add-contact"))).on_press(Message::AddContact)],
+                        center(row![button(text(t!("home"))).on_press(Message::Home)])
+                    ]
+                    .spacing(10),
+                ),
+                View::Schedule => Some(self.schedule_view()),
+                View::Pos => Some(self.pos_view()),
+                View::Nwc => Some(
+                    column![
+                        self.nwc_connections
+                            .iter()
+                            .fold(column![].spacing(5), |col, connection| {
+                                col.push(nwc_connection_row(connection))
+                            }),
+                        row![
+                            text_input(&t!("name"), &self.nwc_name)
+                                .on_input(Message::NwcNameChanged),
+                            text_input(&t!("budget-sats-30-days"), &self.nwc_budget)
+                                .on_input(Message::NwcBudgetChanged),
+                        ]
+                        .spacing(10),
+                        row![
+                            text_input(&t!("relay"), &self.nwc_relay)
+                                .on_input(Message::NwcRelayChanged),
+                            button(text(t!("create-connection"))).on_press_maybe(
+                                (!self.nwc_name.is_empty() && !self.nwc_relay.is_empty())
+                                    .then_some(Message::CreateNwcConnection)
+                            )
+                        ]
+                        .spacing(10)
+                        .align_items(Alignment::Center),
+                    ]
+                    .push_maybe(self.nwc_uri.as_ref().map(|uri| {
+                        column![text(t!("nwc-paste-note")), text(uri)]
+                            .push_maybe(
+                                self.qr_code.as_ref().map(|data| qr_code(data).cell_size(5)),
+                            )
+                            .spacing(10)
+                    }))
+                    .push(center(row![
+                        button(text(t!("home"))).on_press(Message::Home)
+                    ]))
+                    .spacing(10),
+                ),
+                View::Proofs => Some(column![
+                    scrollable(
+                        self.proof_summaries
+                            .iter()
+                            .fold(column![].spacing(20), |col, summary| col
+                                .push(proof_summary(summary)))
+                    )
+                    .height(400),
+                    center(row![button(text(t!("home"))).on_press(Message::Home)])
+                ]),
+                View::Debug => Some(
+                    column![
+                        scrollable(
+                            self.log_lines
+                                .iter()
+                                .fold(column![].spacing(2), |col, line| col
+                                    .push(text(line).size(12)))
+                        )
+                        .height(400),
+                        row![
+                            button(text(t!("export-logs"))).on_press(Message::ExportLogs),
+                            button(text(t!("back"))).on_press(Message::Settings)
+                        ]
+                        .spacing(10)
+                    ]
+                    .push_maybe(
+                        self.log_export_path
+                            .as_ref()
+                            .map(|path| text(t!("logs-saved-to", path = path))),
+                    )
+                    .spacing(10),
+                ),
+                View::Decode => Some(
+                    column![text_input(&t!("decode-placeholder"), &self.decode_input)
+                        .on_input(Message::DecodeInputChanged)
+                        .padding(15)]
+                    .push_maybe(
+                        (!self.decode_input.trim().is_empty())
+                            .then(|| decoded_input(&self.decode_input)),
+                    )
+                    .push(row![button(text(t!("back"))).on_press(Message::Settings)])
+                    .spacing(10),
+                ),
+                View::Onboarding => Some(self.onboarding_view()),
+                View::MintReview => self
+                    .mint_review
+                    .as_ref()
+                    .map(|review| mint_review(review, &self.swap_target())),
+                View::MintInfo => Some(
+                    match &self.mint_info {
+                        Some(details) => mint_info_details(details),
+                        None => column![text(t!("loading-mint-info"))],
+                    }
+                    .push(center(row![
+                        button(text(t!("home"))).on_press(Message::Home)
+                    ])),
+                ),
+                View::Mint => Some(
+                    match &self.mint_overview {
+                        Some(overview) => self.mint_overview(overview),
+                        None => column![text(t!("loading-mint"))],
+                    }
+                    .push(center(row![
+                        button(text(t!("home"))).on_press(Message::Home)
+                    ])),
+                ),
+                View::RemoveMint => self
+                    .removal
+                    .as_ref()
+                    .map(|removal| self.removal_view(removal)),
+                View::Transfer => Some(
+                    column![
+                        text(t!("from", active_mint = self.active_mint)),
+                        row![
+                            text(t!("to")),
+                            pick_list(
+                                self.mints.as_slice(),
+                                self.transfer_to.as_ref(),
+                                Message::TransferToSelected
+                            )
+                        ]
+                        .spacing(10)
+                        .align_items(Alignment::Center),
+                        row![text_input(&t!("amount-sats"), &self.transfer_amount)
+                            .on_input(Message::TransferAmountChanged)],
+                        row![button(text(t!("preview"))).on_press_maybe(
+                            (self.transfer_step.is_none()
+                                && self.transfer_to.as_ref() != Some(&self.active_mint))
+                            .then_some(Message::PreviewTransfer)
+                        )],
+                    ]
+                    .push_maybe(self.transfer_preview.as_ref().map(|preview| {
+                        column![
+                            text(t!(
+                                "amount-in-sats",
+                                amount = i18n::format_number(preview.melt.amount)
+                            )),
+                            text(t!(
+                                "fee-reserve-sats",
+                                fee_reserve = i18n::format_number(preview.melt.fee_reserve)
+                            )),
+                            text(t!(
+                                "input-fee-sats",
+                                fee = i18n::format_number(preview.melt.input_fee)
+                            )),
+                            text(t!(
+                                "total-sats",
+                                total = i18n::format_number(preview.melt.total())
+                            ))
+                            .size(30),
+                            button(text(t!("confirm"))).on_press(Message::ConfirmTransfer)
+                        ]
+                        .spacing(10)
+                    }))
+                    .push_maybe(self.transfer_step.map(|step| {
+                        text(match step {
+                            TransferStep::Paying => t!("paying-from-the-source-mint"),
+                            TransferStep::Minting => t!("minting-at-the-destination-mint"),
+                        })
+                    }))
+                    .push(center(row![
+                        button(text(t!("home"))).on_press(Message::Home)
+                    ])),
+                ),
+                View::Withdraw => Some(
+                    column![
+                        text(t!("from", active_mint = self.active_mint)),
+                        row![text_input(&t!("bitcoin-address"), &self.withdraw_address)
+                            .on_input(Message::WithdrawAddressChanged)],
+                        row![text_input(&t!("amount-sats"), &self.withdraw_amount)
+                            .on_input(Message::WithdrawAmountChanged)],
+                        row![button(text(t!("preview"))).on_press_maybe(
+                            self.withdrawal
+                                .is_none()
+                                .then_some(Message::PreviewWithdrawal)
+                        )],
+                    ]
+                    .push_maybe(self.withdraw_preview.as_ref().map(|preview| {
+                        column![
+                            text(preview.description.clone().unwrap_or_default()),
+                            text(t!(
+                                "amount-in-sats",
+                                amount = i18n::format_number(preview.amount)
+                            )),
+                            text(t!(
+                                "network-fee-sats",
+                                fee_reserve = i18n::format_number(preview.fee_reserve)
+                            )),
+                            text(t!(
+                                "input-fee-sats",
+                                fee = i18n::format_number(preview.input_fee)
+                            )),
+                            text(t!(
+                                "total-sats",
+                                total = i18n::format_number(preview.total())
+                            ))
+                            .size(30),
+                            button(text(t!("confirm"))).on_press(Message::ConfirmWithdrawal)
+                        ]
+                        .spacing(10)
+                    }))
+                    .push(center(row![
+                        button(text(t!("home"))).on_press(Message::Home)
+                    ])),
+                ),
+                View::SentTokens => Some(column![
+                    scrollable(
+                        self.sent_tokens
+                            .iter()
+                            .fold(column![].spacing(5), |col, sent| col
+                                .push(sent_token_row(sent, self.reclaimable_at(sent))))
+                    )
+                    .height(400),
+                    center(row![button(text(t!("home"))).on_press(Message::Home)])
+                ]),
+            },
+            None => match &self.view {
+                View::Restore => {
+                    let invalid = invalid_words(&self.restore_mnemonic);
+                    let word_count = self.restore_mnemonic.split_whitespace().count();
+
+                    Some(
+                        column![
+                            text_input(&t!("enter-your-seed-words"), &self.restore_mnemonic)
+                                .on_input(Message::RestoreMnemonicChanged)
+                                .padding(15),
+                            text_input(&t!("new-password"), &self.password)
+                                .on_input(Message::PasswordChanged)
+                                .secure(true)
+                                .padding(15),
+                            checkbox(t!("encrypt-database"), self.database_encrypted)
+                                .on_toggle(Message::DatabaseEncryptionToggled),
+                            self.passphrase_inputs(),
+                            text(if invalid.is_empty() {
+                                t!("words", word_count = word_count)
+                            } else {
+                                t!("invalid-words", words = invalid.join(", "))
+                            }),
+                            button(text(t!("restore"))).on_press_maybe(
+                                (invalid.is_empty()
+                                    && parse_mnemonic(&self.restore_mnemonic).is_ok()
+                                    && !self.password.is_empty()
+                                    && self.passphrase_confirmed())
+                                .then_some(Message::RestoreWallet)
+                            ),
+                            text(t!("or-import-a-backup-file")),
+                            text_input(&t!("backup-password"), &self.backup_password)
+                                .on_input(Message::BackupPasswordChanged)
+                                .secure(true)
+                                .padding(15),
+                            button(text(t!("import-backup"))).on_press_maybe(
+                                (!self.backup_password.is_empty()
+                                    && !self.password.is_empty()
+                                    && self.passphrase_confirmed())
+                                .then_some(Message::ImportBackup)
+                            ),
+                            button(text(t!("back"))).on_press(Message::Home)
+                        ]
+                        .spacing(10),
+                    )
+                }
+                _ if has_seed() => Some(
+                    column![
+                        self.profile_picker(),
+                        text_input(&t!("password"), &self.password)
+                            .on_input(Message::PasswordChanged)
+                            .on_submit(Message::NewWallet)
+                            .secure(true)
+                            .padding(15),
+                    ]
+                    .push_maybe(has_passphrase().then(|| {
+                        column![
+                            text_input(&t!("passphrase"), &self.passphrase)
+                                .on_input(Message::PassphraseChanged)
+                                .on_submit(Message::NewWallet)
+                                .secure(true)
+                                .padding(15),
+                            text(t!("passphrase-unlock-hint")),
+                        ]
+                        .spacing(10)
+                    }))
+                    .push(button(text(t!("unlock"))).on_press(Message::NewWallet))
+                    .push(button(text(t!("restore-wallet"))).on_press(Message::RestoreView))
+                    .spacing(10),
+                ),
+                _ => Some(
+                    column![
+                        text(t!("welcome-to-iced-cashu")).size(30),
+                        text(t!("choose-password")),
+                        self.profile_picker(),
+                        text_input(&t!("new-password"), &self.password)
+                            .on_input(Message::PasswordChanged)
+                            .secure(true)
+                            .padding(15),
+                        checkbox(t!("encrypt-database"), self.database_encrypted)
+                            .on_toggle(Message::DatabaseEncryptionToggled),
+                        self.passphrase_inputs(),
+                        row![
+                            button(text(t!("create-a-new-wallet"))).on_press_maybe(
+                                (!self.password.is_empty() && self.passphrase_confirmed())
+                                    .then_some(Message::NewWallet)
+                            ),
+                            button(text(t!("restore-from-seed-or-backup")))
+                                .on_press(Message::RestoreView)
+                        ]
+                        .spacing(10)
+                    ]
+                    .spacing(10),
+                ),
+            },
+        };
+
+        let error = self.error.as_ref().map(|err| {
+            container(
+                row![
+                    text(err.to_string()).color(ERROR_COLOR),
+                    button(text(t!("dismiss"))).on_press(Message::DismissError)
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+            )
+            .padding(10)
+            .style(container::rounded_box)
+        });
+
+        let clipboard_prompt = self.clipboard_prompt.as_ref().map(|detected| {
+            let prompt = match detected {
+                DetectedInput::Token { amount, .. } => {
+                    t!("claim-token-for-sats", amount = i18n::format_number(amount))
+                }
+                DetectedInput::Bolt11 {
+                    amount: Some(amount),
+                    ..
+                } => t!(
+                    "pay-this-invoice-for-sats",
+                    amount = i18n::format_number(amount)
+                ),
+                DetectedInput::Bolt11 { amount: None, .. } => t!("pay-this-invoice"),
+                DetectedInput::Bolt12 {
+                    amount: Some(amount),
+                    ..
+                } => t!(
+                    "pay-this-offer-for-sats",
+                    amount = i18n::format_number(amount)
+                ),
+                DetectedInput::Bolt12 { amount: None, .. } => t!("pay-this-offer"),
+                DetectedInput::Lnurl(_) => t!("pay-this-lnurl"),
+                DetectedInput::PaymentRequest {
+                    amount: Some(amount),
+                    ..
+                } => t!(
+                    "pay-this-request-for-sats",
+                    amount = i18n::format_number(amount)
+                ),
+                DetectedInput::PaymentRequest { amount: None, .. } => {
+                    t!("pay-this-payment-request")
+                }
+            };
+
+            container(
+                row![
+                    text(prompt),
+                    button(text(t!("open"))).on_press(Message::AcceptClipboard),
+                    button(text(t!("dismiss"))).on_press(Message::DismissClipboard)
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+            )
+            .padding(10)
+            .style(container::rounded_box)
+        });
+
+        let leave_prompt = self.leaving.then(|| {
+            container(
+                row![
+                    text(t!("leave-prompt")).color(ERROR_COLOR),
+                    button(text(t!("leave"))).on_press(Message::Home),
+                    button(text(t!("stay"))).on_press(Message::StayOnView)
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+            )
+            .padding(10)
+            .style(container::rounded_box)
+        });
+
+        let command_palette = self.palette.as_ref().map(|query| {
+            let commands = palette::search(query, self.commands()).into_iter().fold(
+                column![].spacing(5),
+                |col, (label, message)| {
+                    col.push(
+                        button(text(label))
+                            .on_press(Message::RunCommand(Box::new(message)))
+                            .width(Length::Fill),
+                    )
+                },
+            );
+
+            container(
+                column![
+                    text_input(&t!("type-a-command"), query)
+                        .id(text_input::Id::new(PALETTE_INPUT))
+                        .on_input(Message::PaletteChanged)
+                        .on_submit(Message::PaletteSubmit),
+                    commands
+                ]
+                .spacing(10),
+            )
+            .padding(10)
+            .style(container::rounded_box)
+        });
+
+        let locked = self.locked || self.pin_prompt.is_some();
+        let view = match self.service.is_some() && locked {
+            true => Some(self.lock_screen()),
+            false => view,
+        };
+
+        let content = column![title]
+            .push_maybe(error)
+            .push_maybe(leave_prompt.filter(|_| !locked))
+            .push_maybe(command_palette.filter(|_| !locked))
+            .push_maybe(
+                self.service
+                    .as_ref()
+                    .filter(|_| !locked)
+                    .and(clipboard_prompt),
+            )
+            .push_maybe(view)
+            .width(700)
+            .spacing(20)
+            .align_items(Alignment::Center);
+
+        center(content).padding(20).into()
+    }
+
+    fn lock_screen(&self) -> Column<Message> {
+        column![
+            text(match self.locked {
+                true => t!("wallet-locked"),
+                false => t!("enter-your-pin-to-continue"),
+            }),
+            text_input(&t!("pin"), &self.pin_input)
+                .id(text_input::Id::new(PIN_INPUT))
+                .secure(true)
+                .on_input(Message::PinInputChanged)
+                .on_submit(Message::Unlock),
+            row![button(text(t!("unlock"))).on_press(Message::Unlock)]
+                .push_maybe(
+                    (!self.locked).then(|| button(text(t!("cancel"))).on_press(Message::CancelPin))
+                )
+                .push_maybe(
+                    self.locked.then(
+                        || button(text(t!("switch-profile"))).on_press(Message::SwitchProfile)
+                    )
+                )
+                .spacing(10)
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center)
+    }
+
+    /// Show the review of `mint_url` before `action` uses it
+    fn review_mint(&mut self, mint_url: UncheckedUrl, action: ReviewAction) -> Task<Message> {
+        self.mint_review = Some(MintReview {
+            mint_url: mint_url.clone(),
+            details: None,
+            input_fee_ppk: None,
+            action,
+            acknowledged: false,
+        });
+        self.view = View::MintReview;
+        Task::perform(review_mint(mint_url), Message::MintReviewLoaded)
+    }
+
+    fn mint_balance(&self, mint_url: &UncheckedUrl) -> u64 {
+        self.mint_balances
+            .get(mint_url)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Quotes of each mint to pay from, with `selected` marked
+    fn melt_routes(&self, selected: &MeltPreview) -> Column<Message> {
+        self.melt_routes.iter().enumerate().fold(
+            column![text(t!("quotes-from-your-mints"))].spacing(5),
+            |col, (index, route)| {
+                let balance = self.mint_balance(&route.mint_url);
+                let is_selected = route.quote_id == selected.quote_id;
+                col.push(
+                    row![
+                        text(t!(
+                            "route-quote",
+                            mint_url = route.mint_url,
+                            total = i18n::format_number(route.total()),
+                            fee_reserve = i18n::format_number(route.fee_reserve),
+                            input_fee = i18n::format_number(route.input_fee),
+                            warning = match route.total() > balance {
+                                true => t!("more-than-its-balance"),
+                                false => String::new(),
+                            }
+                        )),
+                        button(text(if is_selected {
+                            t!("selected")
+                        } else {
+                            t!("use")
+                        }))
+                        .on_press_maybe(
+                            (!is_selected).then_some(Message::MeltRouteSelected(index))
+                        )
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                )
+            },
+        )
+    }
+
+    /// Part paid from each mint of a multi-path payment and the total
+    fn multi_path_legs(&self) -> Column<Message> {
+        let amount: u64 = self.multi_path.iter().map(|leg| leg.amount).sum();
+        let total: u64 = self.multi_path.iter().map(MeltPreview::total).sum();
+        let description = self
+            .multi_path
+            .first()
+            .and_then(|leg| leg.description.clone());
+
+        self.multi_path
+            .iter()
+            .fold(column![text(t!("multi-path-note"))], |col, leg| {
+                col.push(text(t!(
+                    "multi-path-leg",
+                    mint_url = leg.mint_url,
+                    amount = i18n::format_number(leg.amount),
+                    fees = i18n::format_number(leg.fee_reserve + leg.input_fee)
+                )))
+            })
+            .push(text(t!(
+                "amount-in-sats",
+                amount = i18n::format_number(amount)
+            )))
+            .push(text(t!("total-sats", total = i18n::format_number(total))).size(30))
+            .push_maybe(
+                description.map(|description| text(t!("description", description = description))),
+            )
+    }
+
+    /// Trusted mint tokens from unknown mints are moved to, the default mint
+    /// if the wallet uses it
+    fn swap_target(&self) -> UncheckedUrl {
+        self.settings
+            .default_mint
+            .clone()
+            .filter(|mint_url| self.mints.contains(mint_url))
+            .unwrap_or_else(|| self.active_mint.clone())
+    }
+
+    /// First mint of `token` the wallet doesn't know yet
+    fn unknown_mint(&self, token: &str) -> Option<UncheckedUrl> {
+        token::parse(token)
+            .ok()?
+            .token
+            .into_iter()
+            .map(|proofs| proofs.mint)
+            .find(|mint_url| !self.mints.contains(mint_url))
+    }
+
+    /// Claim `token`, moving it on to the default mint if set to
+    fn receive(&mut self, token: String) -> Task<Message> {
+        self.view = View::Main;
+        let service = self.service();
+        let swap = self.auto_swap(&token);
+        let split = self.settings.split_target.clone();
+        let preimages = self.token_preimages(&token);
+        self.preimage_input = Zeroizing::default();
+        let task = Task::perform(
+            async move {
+                let amount = if preimages.is_empty() {
+                    let result = service.receive(token.clone()).await;
+                    queue_on_failure(&service, RetryOperation::Receive { token }, result).await?
+                } else {
+                    // Retries would claim without the preimages
+                    service.receive_with_preimages(token, preimages).await?
+                };
+                if let Some((from, to)) = swap {
+                    service
+                        .auto_swap(from, to, amount, split.target(amount))
+                        .await;
+                }
+                Ok::<_, WalletError>(amount)
+            },
+            or_error(Message::CheckBalance),
+        );
+        self.track(Operation::Receive, task)
+    }
+
+    /// Melt the balance above the threshold to the configured lightning
+    /// address, unless the rule is paused or already running
+    fn check_auto_withdraw(&mut self) -> Task<Message> {
+        let Some(address) = self.settings.auto_withdraw_address.clone() else {
+            return Task::none();
+        };
+        let excess = self
+            .balance
+            .saturating_sub(self.settings.auto_withdraw_threshold);
+        if !self.settings.auto_withdraw
+            || excess < AUTO_WITHDRAW_MIN_SATS
+            || self.busy(&[Operation::AutoWithdraw])
+        {
+            return Task::none();
+        }
+
+        let description = t!("auto-withdraw-description", address = address);
+        let service = self.service();
+        let task = Task::perform(
+            async move { service.auto_withdraw(&address, excess, description).await },
+            |result| Message::AutoWithdrawn(result.map_err(|err| err.to_string())),
+        );
+        self.track(Operation::AutoWithdraw, task)
+    }
+
+    /// Mints to move `token` between after claiming it, if the setting is on
+    /// and it is from another mint than the default one
+    fn auto_swap(&self, token: &str) -> Option<(UncheckedUrl, UncheckedUrl)> {
+        if !self.settings.auto_swap {
+            return None;
+        }
+        let to = self
+            .settings
+            .default_mint
+            .clone()
+            .filter(|mint_url| self.mints.contains(mint_url))?;
+
+        let token = token::parse(token).ok()?;
+        // How much each mint's part of a multi mint token swaps to is unknown
+        let [proofs] = token.token.as_slice() else {
+            return None;
+        };
+
+        (proofs.mint != to).then(|| (proofs.mint.clone(), to))
+    }
+
+    /// Current step of the first run setup
+    fn onboarding_view(&self) -> Column<Message> {
+        let Some(step) = self.onboarding else {
+            return column![];
+        };
+
+        let content = match step {
+            OnboardingStep::Backup => column![
+                text(t!("onboarding-backup")).size(30),
+                text(t!("onboarding-backup-note")),
+                self.seed_words
+                    .iter()
+                    .enumerate()
+                    .fold(column![].spacing(5), |col, (index, word)| col
+                        .push(text(format!("{}. {}", index + 1, word)))),
+                row![
+                    button(text(t!("i-wrote-them-down"))).on_press(Message::VerifySeed),
+                    button(text(t!("later"))).on_press(Message::Onboarding(OnboardingStep::Mint))
+                ]
+                .spacing(10)
+            ],
+            OnboardingStep::Mint => column![
+                text(t!("onboarding-mint")).size(30),
+                text(t!("onboarding-mint-note")),
+                row![
+                    pick_list(
+                        self.mints.as_slice(),
+                        Some(&self.active_mint),
+                        Message::MintSelected
+                    ),
+                    text_input(&t!("add-another-mint-url"), &self.add_mint_url)
+                        .on_input(Message::AddMintUrlChanged)
+                        .on_submit(Message::AddMint),
+                    button(text(t!("add"))).on_press_maybe(
+                        (!self.add_mint_url.trim().is_empty()).then_some(Message::AddMint)
+                    )
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                match &self.mint_info {
+                    Some(details) => mint_info_details(details),
+                    None => column![text(t!("loading-mint-info"))],
+                },
+                button(text(t!("use-this-mint")))
+                    .on_press(Message::Onboarding(OnboardingStep::Pin))
+            ],
+            OnboardingStep::Pin => column![
+                text(t!("onboarding-pin")).size(30),
+                text(t!("onboarding-pin-note")),
+                row![
+                    text_input(&t!("pin"), &self.new_pin)
+                        .secure(true)
+                        .on_input(Message::NewPinChanged)
+                        .on_submit(Message::SetPin),
+                    button(text(t!("set-pin"))).on_press(Message::SetPin)
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+            ]
+            .push_maybe(self.pin_set.then(|| text(t!("pin-set"))))
+            .push(
+                button(text(match self.pin_set {
+                    true => t!("finish"),
+                    false => t!("skip-and-finish"),
+                }))
+                .on_press(Message::FinishOnboarding),
+            ),
+        };
+
+        content.spacing(10)
+    }
+
+    /// Picker for the wallet profile to open, and an entry to create one
+    fn profile_picker(&self) -> Element<Message> {
+        row![
+            text(t!("profile-label")),
+            pick_list(profiles(), Some(profile()), Message::ProfileSelected),
+            text_input(&t!("new-profile"), &self.new_profile)
+                .on_input(Message::NewProfileChanged)
+                .on_submit(Message::CreateProfile),
+            button(text(t!("create"))).on_press_maybe(
+                (!self.new_profile.trim().is_empty()).then_some(Message::CreateProfile)
+            )
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .into()
+    }
+
+    /// Opt-in BIP39 passphrase for a new or restored wallet, entered twice
+    fn passphrase_inputs(&self) -> Element<Message> {
+        let toggle = checkbox(t!("use-passphrase"), self.use_passphrase)
+            .on_toggle(Message::UsePassphraseToggled);
+        if !self.use_passphrase {
+            return toggle.into();
+        }
+
+        let mismatch = !self.passphrase_confirm.is_empty()
+            && self.passphrase.as_str() != self.passphrase_confirm.as_str();
+        column![
+            toggle,
+            text(t!("passphrase-warning")).color(ERROR_COLOR),
+            text_input(&t!("passphrase"), &self.passphrase)
+                .on_input(Message::PassphraseChanged)
+                .secure(true)
+                .padding(15),
+            text_input(&t!("confirm-passphrase"), &self.passphrase_confirm)
+                .on_input(Message::PassphraseConfirmChanged)
+                .secure(true)
+                .padding(15),
+        ]
+        .push_maybe(mismatch.then(|| text(t!("passphrases-differ")).color(ERROR_COLOR)))
+        .spacing(10)
+        .into()
+    }
+
+    /// Whether the passphrase, if one is wanted, is entered the same twice
+    fn passphrase_confirmed(&self) -> bool {
+        !self.use_passphrase
+            || (!self.passphrase.is_empty()
+                && self.passphrase.as_str() == self.passphrase_confirm.as_str())
+    }
+
+    /// Hold `message` back until the PIN is entered
+    ///
+    /// Payments of `amount` only need the PIN above the configured threshold,
+    /// actions without an amount always do.
+    fn needs_pin(&mut self, amount: Option<u64>, message: Message) -> bool {
+        if !self.pin_set || std::mem::take(&mut self.pin_verified) {
+            return false;
+        }
+
+        let required = match amount {
+            Some(amount) => self
+                .settings
+                .pin_threshold
+                .is_some_and(|threshold| amount > threshold),
+            None => true,
+        };
+        if required {
+            self.pin_input = Zeroizing::default();
+            self.pin_prompt = Some(Box::new(message));
+        }
+
+        required
+    }
+
+    /// What the Send/Receive input was recognised as
+    fn input_preview(&self) -> Option<Element<Message>> {
+        if self.data.trim().is_empty() {
+            return None;
+        }
+
+        let preview = match detect(&self.data) {
+            Some(DetectedInput::Token { token: encoded, .. }) => match token::parse(&encoded) {
+                Ok(parsed) => token_summary(&token::summary(&parsed))
+                    .push_maybe(self.token_locks())
+                    .push_maybe(self.token_hash_locks())
+                    .into(),
+                Err(err) => text(err.to_string()).color(ERROR_COLOR).into(),
+            },
+            Some(DetectedInput::Bolt11 { .. }) => match decode_invoice(&self.data) {
+                Ok(details) => invoice_details(&details),
+                Err(err) => text(err.to_string()).color(ERROR_COLOR).into(),
+            },
+            Some(DetectedInput::Bolt12 { offer, .. }) => match bolt12::decode_offer(&offer) {
+                Ok(details) => offer_details(&details),
+                Err(err) => text(err.to_string()).color(ERROR_COLOR).into(),
+            },
+            Some(DetectedInput::Lnurl(_)) => text(t!("lnurl-or-lightning-address")).into(),
+            Some(DetectedInput::PaymentRequest { amount, .. }) => text(match amount {
+                Some(amount) => t!(
+                    "payment-request-for-sats",
+                    amount = i18n::format_number(amount)
+                ),
+                None => t!("payment-request-any-amount"),
+            })
+            .into(),
+            None => unrecognized_input(&self.data),
+        };
+
+        Some(preview)
+    }
+
+    /// The open wallet, only called once one has been created or restored
+    fn service(&self) -> WalletService {
+        self.service.clone().expect("Wallet is open")
+    }
+
+    fn wallet(&self) -> Arc<Wallet> {
+        self.service().wallet().clone()
+    }
+
+    fn db(&self) -> Database {
+        self.service().db().clone()
+    }
+
+    /// Reload the current history page with the current filter
+    fn load_history(&self) -> Task<Message> {
+        Task::perform(
+            load_history(self.db(), self.history_page, self.history_filter.clone()),
+            or_error(Message::HistoryLoaded),
+        )
+    }
+
+    /// Reload the total and per mint balances
+    fn check_balances(&mut self) -> Task<Message> {
+        let (service, mints, units) = (self.service(), self.service(), self.service());
+        let task = Task::batch([
+            Task::perform(
+                async move { service.check_balance().await },
+                or_error(Message::Balance),
+            ),
+            Task::perform(
+                async move { mints.mint_balances().await },
+                or_error(Message::MintBalances),
+            ),
+            Task::perform(
+                async move { units.unit_balances().await },
+                or_error(Message::UnitBalances),
+            ),
+        ]);
+        self.track(Operation::Balance, task)
+    }
+
+    /// Monthly totals and the balance chart of the Stats view
+    fn stats_view<'a>(&'a self, stats: &'a Stats) -> Column<'a, Message> {
+        let amount = |amount| i18n::format_amount(amount, &self.unit);
+
+        if stats.balance.is_empty() {
+            return column![text(t!("no-completed-transactions-in", unit = self.unit))];
+        }
+
+        column![
+            text(t!("balance-over-time", unit = self.unit)).size(20),
+            canvas(BalanceChart::new(&stats.balance))
+                .width(Length::Fill)
+                .height(200),
+            text(t!("monthly-totals")).size(20),
+            scrollable(
+                stats
+                    .months
+                    .iter()
+                    .rev()
+                    .fold(column![].spacing(5), |col, month| col.push(
+                        row![
+                            text(format!("{}-{:02}", month.year, month.month)),
+                            text(t!("month-in", inflow = amount(month.inflow))),
+                            text(t!("month-out", outflow = amount(month.outflow))),
+                        ]
+                        .spacing(20)
+                    ))
+            )
+            .height(200),
+        ]
+        .spacing(10)
+    }
+
+    /// Quote paying the invoice or payment request in the Send/Receive input
+    fn quote_payment(&mut self) -> Task<Message> {
+        if let Ok(request) = payment_request::decode(&self.data) {
+            let amount = match request.amount {
+                Some(amount) => amount,
+                None => match self.send_amount.sats(self.current_rate().as_ref()) {
+                    Some(amount) => amount,
+                    None => {
+                        self.error =
+                            Some(WalletError::InvalidAmount(self.send_amount.input.clone()));
+                        return Task::none();
+                    }
+                },
+            };
+            return Task::perform(
+                payment_request_preview(self.service(), self.active_mint.clone(), request, amount),
+                or_error(Message::PaymentRequestQuoted),
+            );
+        }
+
+        if let Err(err) = decode_invoice(&self.data) {
+            self.error = Some(WalletError::InvalidInvoice(err.to_string()));
+            return Task::none();
+        }
+
+        let mints: Vec<UncheckedUrl> = self
+            .mint_balances
+            .iter()
+            .filter(|(_, balance)| **balance > 0)
+            .map(|(mint_url, _)| mint_url.clone())
+            .collect();
+        let service = self.service();
+        let bolt11 = self.data.clone();
+        // Only sats are compared across mints
+        if self.unit != CurrencyUnit::Sat {
+            let (mint_url, unit) = (self.active_mint.clone(), self.unit.clone());
+            return Task::perform(
+                async move { service.melt_quote(mint_url, bolt11, unit).await },
+                or_error(Message::MeltQuote),
+            );
+        }
+        if mints.len() > 1 {
+            return Task::perform(
+                melt_quotes(service, mints, bolt11),
+                or_error(Message::MeltQuotes),
+            );
+        }
+
+        let mint_url = mints
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| self.active_mint.clone());
+        Task::perform(
+            async move {
+                service
+                    .melt_quote(mint_url, bolt11, CurrencyUnit::Sat)
+                    .await
+            },
+            or_error(Message::MeltQuote),
+        )
+    }
+
+    /// View to reopen on the next start, the main one for views only
+    /// reached in the middle of a payment or setup
+    fn last_view(&self) -> LastView {
+        match self.view {
+            View::SendReceive => LastView::SendReceive,
+            View::History => LastView::History,
+            View::SentTokens => LastView::SentTokens,
+            View::Stats => LastView::Stats,
+            View::MintInfo => LastView::MintInfo,
+            View::Proofs => LastView::Proofs,
+            View::Contacts => LastView::Contacts,
+            View::Schedule => LastView::Schedule,
+            View::Pos => LastView::Pos,
+            View::Nwc => LastView::Nwc,
+            View::Decode => LastView::Decode,
+            View::Settings => LastView::Settings,
+            _ => LastView::Main,
+        }
+    }
+
+    /// Open `view` as navigating to it does, loading what it shows
+    fn reopen(&mut self, view: LastView) -> Task<Message> {
+        let message = match view {
+            LastView::Main => return Task::none(),
+            LastView::SendReceive => Message::SendReceive,
+            LastView::History => Message::HistoryView,
+            LastView::SentTokens => Message::SentTokensView,
+            LastView::Stats => Message::StatsView,
+            LastView::MintInfo => Message::MintInfoView,
+            LastView::Proofs => Message::ProofsView,
+            LastView::Contacts => Message::ContactsView,
+            LastView::Schedule => Message::ScheduleView,
+            LastView::Pos => Message::PosView,
+            LastView::Nwc => Message::NwcView,
+            LastView::Decode => Message::DecodeView,
+            LastView::Settings => Message::Settings,
+        };
+
+        self.update(message)
+    }
+
+    /// Actions the command palette offers, with the messages running them
+    fn commands(&self) -> Vec<(String, Message)> {
+        let mut commands = vec![
+            (t!("send"), Message::Shortcut(Shortcut::Send)),
+            (t!("receive"), Message::Shortcut(Shortcut::Receive)),
+            (
+                t!("paste-from-clipboard"),
+                Message::Shortcut(Shortcut::Paste),
+            ),
+            (t!("open-history"), Message::HistoryView),
+            (t!("open-stats"), Message::StatsView),
+            (t!("add-mint-action"), Message::AddMintView),
+            (t!("mint-info"), Message::MintInfoView),
+            (t!("show-seed"), Message::ShowSeed),
+            (t!("decode"), Message::DecodeView),
+            (t!("scheduled-payments"), Message::ScheduleView),
+            (t!("point-of-sale"), Message::PosView),
+            (t!("settings"), Message::Settings),
+            (t!("home"), Message::Home),
+        ];
+        commands.extend(
+            self.mints
+                .iter()
+                .filter(|mint_url| **mint_url != self.active_mint)
+                .map(|mint_url| {
+                    (
+                        t!("switch-mint-to", mint_url = mint_url),
+                        Message::MintSelected(mint_url.clone()),
+                    )
+                }),
+        );
+
+        commands
+    }
+
+    /// Run `failed` from the retry queue again
+    /// Make the due payment `scheduled` and move it on to its next time
+    fn pay_scheduled(&mut self, scheduled: ScheduledPayment) -> Task<Message> {
+        self.paying_scheduled.insert(scheduled.id);
+        let (service, db) = (self.service(), self.db());
+        let keys = self.nostr_keys.clone();
+        let relays = self.settings.nostr_relays.clone();
+        let split = self.settings.split_target.clone();
+        let id = scheduled.id;
+        Task::perform(
+            async move {
+                let result =
+                    run_scheduled_payment(service, keys, relays, split, scheduled.clone()).await;
+                let error = result.as_ref().err().map(ToString::to_string);
+                if let Err(err) = reschedule(db, scheduled, error).await {
+                    tracing::warn!("Could not reschedule payment {}: {}", id, err);
+                }
+                (id, result)
+            },
+            Message::ScheduledPaymentMade,
+        )
+    }
+
+    fn retry(&mut self, failed: FailedOperation) -> Task<Message> {
+        self.retrying.insert(failed.id);
+        let service = self.service();
+        let split = self.settings.split_target.target(failed.operation.amount());
+        let id = failed.id;
+        Task::perform(
+            async move { (id, service.retry(failed, split).await) },
+            Message::Retried,
+        )
+    }
+
+    /// Check `pending_mint` from the polling interval on again
+    fn reset_polling(&mut self) {
+        let mut backoff = Backoff::new(
+            Duration::from_secs(self.settings.polling_interval.max(1)),
+            Duration::from_secs(self.settings.max_polling_interval),
+        );
+        self.poll_delay = backoff.next_delay();
+        self.poll_backoff = Some(backoff);
+    }
+
+    /// Run `task` as `operation`, which stays in flight until it completes
+    fn track(&mut self, operation: Operation, task: Task<Message>) -> Task<Message> {
+        *self.in_flight.entry(operation).or_default() += 1;
+        task.chain(Task::done(Message::Finished(operation)))
+    }
+
+    /// Open `view` with the result of `operation`, or offer it in the
+    /// pending panel if the user went home while it ran
+    fn show_result(&mut self, operation: Operation, view: View) {
+        if self.left_running.contains(&operation) {
+            self.notify(&t!("ready"), &ready_label(&view));
+            self.ready = Some(view);
+        } else {
+            self.view = view;
+        }
+    }
+
+    /// Whether any of `operations` is running
+    fn busy(&self, operations: &[Operation]) -> bool {
+        operations
+            .iter()
+            .any(|operation| self.in_flight.contains_key(operation))
+    }
+
+    /// Progress text of the running `operations`, in the order given
+    fn progress(&self, operations: &[Operation]) -> Option<Row<Message>> {
+        let running: Vec<String> = operations
+            .iter()
+            .filter(|operation| self.in_flight.contains_key(operation))
+            .map(ToString::to_string)
+            .collect();
+
+        (!running.is_empty()).then(|| row![text(running.join(" "))])
+    }
+
+    /// Operations still waiting on a mint or a recipient, listed by a
+    /// collapsible section of the main view
+    /// Balance, keysets with their fees and open quotes of a mint, with
+    /// what can be done with it
+    fn mint_overview<'a>(&'a self, overview: &'a MintOverview) -> Column<'a, Message> {
+        let mint_url = &overview.mint_url;
+        let balance = self.mint_balances.get(mint_url).copied().unwrap_or(0);
+        let is_default = self.settings.default_mint.as_ref() == Some(mint_url);
+        let other_mints = self.mints.iter().any(|other| other != mint_url);
+
+        let keysets: Element<Message> = match &overview.keysets {
+            Ok(keysets) => keysets
+                .iter()
+                .fold(column![].spacing(5), |col, keyset| {
+                    col.push(text(t!(
+                        "keyset-info",
+                        keyset_id = keyset.id,
+                        unit = keyset.unit,
+                        state = if keyset.active {
+                            t!("keyset-active")
+                        } else {
+                            t!("keyset-inactive")
+                        },
+                        ppk = keyset.input_fee_ppk
+                    )))
+                })
+                .into(),
+            Err(err) => text(t!("mint-unreachable-error", err = err))
+                .color(ERROR_COLOR)
+                .into(),
+        };
+        let quotes = if overview.quotes.is_empty() {
+            column![text(t!("no-pending-quotes"))]
+        } else {
+            overview
+                .quotes
+                .iter()
+                .fold(column![].spacing(5), |col, quote| {
+                    col.push(
+                        row![
+                            text(t!(
+                                "pending-quote",
+                                amount = i18n::format_amount(quote.amount, &quote.unit),
+                                quote_id = quote.quote_id
+                            )),
+                            button(text(t!("show"))).on_press(Message::MintQuote(quote.clone()))
+                        ]
+                        .spacing(10)
+                        .align_items(Alignment::Center),
+                    )
+                })
+        };
+
+        column![
+            row![
+                self.health_dot(mint_url),
+                text(mint_url.to_string()).size(30)
+            ]
+            .spacing(10)
+            .align_items(Alignment::Center),
+            text(t!("balance-sats", balance = i18n::format_number(balance))).size(24),
+            row![].push_maybe(self.retired_keysets_warning(mint_url)),
+            text(t!("keysets")).size(20),
+            keysets,
+            text(t!("pending-quotes")).size(20),
+            quotes,
+            row![
+                button(text(t!("set-default-mint"))).on_press_maybe(
+                    (!is_default).then(|| Message::SetDefaultMint(mint_url.clone()))
+                ),
+                button(text(t!("swap-away"))).on_press_maybe(
+                    (balance > 0 && other_mints).then(|| Message::SwapAwayFrom(mint_url.clone()))
+                ),
+                button(text(t!("remove-mint")))
+                    .on_press_maybe(other_mints.then(|| Message::RemoveMint(mint_url.clone())))
+            ]
+            .spacing(10)
+        ]
+        .spacing(10)
+    }
+
+    /// Ways to empty a mint before removing it, and the removal once one
+    /// was used
+    fn removal_view<'a>(&'a self, removal: &'a MintRemoval) -> Column<'a, Message> {
+        let balance = self
+            .mint_balances
+            .get(&removal.mint_url)
+            .copied()
+            .unwrap_or(0);
+        let busy = self.busy(&[Operation::Melt, Operation::Send]);
+        let targets: Vec<UncheckedUrl> = self
+            .mints
+            .iter()
+            .filter(|mint_url| **mint_url != removal.mint_url)
+            .cloned()
+            .collect();
+
+        column![
+            text(t!("remove-mint-title", mint_url = removal.mint_url)).size(30),
+            text(t!(
+                "mint-still-holds",
+                balance = i18n::format_number(balance)
+            )),
+            row![
+                text(t!("to")),
+                pick_list(
+                    targets,
+                    removal.target.as_ref(),
+                    Message::RemovalTargetSelected
+                ),
+                button(text(t!("move-funds"))).on_press_maybe(
+                    (balance > 0 && removal.target.is_some() && !busy)
+                        .then_some(Message::MigrateFunds)
+                )
+            ]
+            .spacing(10)
+            .align_items(Alignment::Center),
+            button(text(t!("export-funds-as-token")))
+                .on_press_maybe((balance > 0 && !busy).then_some(Message::ExportFunds)),
+        ]
+        .push_maybe(self.progress(&[Operation::Melt, Operation::Send]))
+        .push_maybe(removal.migrated.then(|| {
+            text(if balance > 0 {
+                t!(
+                    "remove-mint-forfeit",
+                    balance = i18n::format_number(balance)
+                )
+            } else {
+                t!("mint-emptied")
+            })
+        }))
+        .push(
+            row![
+                button(text(t!("remove-mint")))
+                    .on_press_maybe((removal.migrated && !busy).then_some(Message::ConfirmRemoval)),
+                button(text(t!("back"))).on_press(Message::MintView(removal.mint_url.clone()))
+            ]
+            .spacing(10),
+        )
+        .spacing(10)
+    }
+
+    fn pending_panel(&self) -> Option<Column<Message>> {
+        let count = usize::from(self.pending_mint.is_some())
+            + usize::from(self.ready.is_some())
+            + usize::from(self.melting.is_some())
+            + usize::from(self.withdrawal.is_some())
+            + self.failed_operations.len()
+            + self.sent_tokens.len();
+        if count == 0 {
+            return None;
+        }
+
+        let toggle = button(text(t!(
+            "pending-toggle",
+            arrow = if self.pending_expanded { "v" } else { ">" },
+            count = count
+        )))
+        .on_press(Message::TogglePending);
+        if !self.pending_expanded {
+            return Some(column![toggle]);
+        }
+
+        let mint_quote = self.pending_mint.as_ref().map(|pending| {
+            row![
+                text(t!(
+                    "receiving-at",
+                    amount = i18n::format_amount(pending.amount, &pending.unit),
+                    mint_url = pending.mint_url
+                )),
+                button(text(t!("show"))).on_press(Message::MintQuote(pending.clone())),
+                button(text(t!("check"))).on_press(Message::CheckMintQuote),
+                button(text(t!("cancel"))).on_press(Message::CancelMintQuote)
+            ]
+            .spacing(10)
+            .align_items(Alignment::Center)
+        });
+        // Melts can't be called off once the mint has the proofs
+        let melt = self.melting.as_ref().map(|melting| {
+            text(t!(
+                "paying-amount-state",
+                amount = i18n::format_amount(melting.amount, &melting.unit),
+                state = self.melt_state.as_deref().unwrap_or("PENDING")
+            ))
+        });
+        let withdrawal = self.withdrawal.as_ref().map(|withdrawal| {
+            row![
+                text(t!(
+                    "withdrawing-sats-on-chain",
+                    amount = i18n::format_number(withdrawal.amount),
+                    state = withdrawal.state
+                )),
+                button(text(t!("check"))).on_press(Message::CheckWithdrawal)
+            ]
+            .spacing(10)
+            .align_items(Alignment::Center)
+        });
+
+        let ready = self.ready.as_ref().map(|view| {
+            row![
+                text(ready_label(view)),
+                button(text(t!("show"))).on_press(Message::ShowReady),
+                button(text(t!("dismiss"))).on_press(Message::DismissReady)
+            ]
+            .spacing(10)
+            .align_items(Alignment::Center)
+        });
+
+        Some(
+            column![toggle]
+                .push_maybe(ready)
+                .push_maybe(mint_quote)
+                .push_maybe(melt)
+                .push_maybe(withdrawal)
+                .push(
+                    self.failed_operations
+                        .iter()
+                        .fold(column![].spacing(5), |col, failed| {
+                            col.push(failed_operation_row(
+                                failed,
+                                self.retrying.contains(&failed.id),
+                            ))
+                        }),
+                )
+                .push(
+                    self.sent_tokens
+                        .iter()
+                        .fold(column![].spacing(5), |col, sent| {
+                            col.push(sent_token_row(sent, self.reclaimable_at(sent)))
+                        }),
+                )
+                .spacing(5),
+        )
+    }
+
+    /// Typed amount in the selected unit, sats possibly converted from fiat
+    fn unit_amount(&self, amount: &AmountInput) -> Option<u64> {
+        match &self.unit {
+            CurrencyUnit::Sat => amount.sats(self.current_rate().as_ref()),
+            unit => i18n::parse_amount(&amount.input, unit),
+        }
+    }
+
+    /// Amount for the PIN threshold, which is in sats, any other unit always
+    /// asks for the PIN
+    fn pin_amount(amount: u64, unit: &CurrencyUnit) -> Option<u64> {
+        (*unit == CurrencyUnit::Sat).then_some(amount)
+    }
+
+    /// Balances held in units other than sats
+    fn other_unit_balances(&self) -> Row<Message> {
+        let mut balances: Vec<_> = self
+            .unit_balances
+            .iter()
+            .filter(|(unit, amount)| **unit != CurrencyUnit::Sat && **amount > 0)
+            .collect();
+        balances.sort_by_key(|(unit, _)| unit.to_string());
+
+        balances
+            .into_iter()
+            .fold(row![].spacing(20), |row, (unit, amount)| {
+                row.push(text(i18n::format_amount(*amount, unit)).size(30))
+            })
+    }
+
+    #[cfg(feature = "control-api")]
+    fn load_control_token(&mut self) {
+        match control::auth_token() {
+            Ok(token) => self.control_token = Some(token),
+            Err(err) => self.error = Some(WalletError::Control(err.to_string())),
+        }
+    }
+
+    /// Settings of the local control API, none when built without it
+    #[cfg(feature = "control-api")]
+    fn control_api_settings(&self) -> Option<Element<Message>> {
+        Some(
+            column![checkbox(t!("local-control-api"), self.settings.control_api)
+                .on_toggle(Message::ControlApiToggled)]
+            .push_maybe(self.settings.control_api.then(|| {
+                row![
+                    text(t!("port")),
+                    text_input("4448", &self.control_port_input)
+                        .on_input(Message::ControlPortChanged)
+                        .on_submit(Message::SaveControlPort),
+                    button(text(t!("save"))).on_press(Message::SaveControlPort),
+                    button(text(t!("copy-auth-token"))).on_press(Message::CopyControlToken)
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center)
+            }))
+            .spacing(10)
+            .into(),
+        )
+    }
+
+    #[cfg(not(feature = "control-api"))]
+    fn control_api_settings(&self) -> Option<Element<Message>> {
+        None
+    }
+
+    /// The wallet's npub, with where it comes from and ways to replace it
+    fn nostr_identity_settings(&self) -> Option<Element<Message>> {
+        let keys = self.nostr_keys.as_ref()?;
+        let derived = self
+            .seed_nostr_keys
+            .as_ref()
+            .is_some_and(|seed_keys| seed_keys.public_key() == keys.public_key());
+
+        let identity =
+            column![
+                row![
+                    text(t!("nostr-identity", npub = nostr::npub(&keys.public_key()))),
+                    button(text(t!("copy"))).on_press(Message::CopyNpub),
+                    button(text(if self.npub_qr.is_some() {
+                        t!("hide-qr")
+                    } else {
+                        t!("show-qr")
+                    }))
+                    .on_press(Message::ToggleNpubQr)
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                text(if derived {
+                    t!("nostr-key-derived")
+                } else {
+                    t!("nostr-key-stored")
+                })
+                .size(12),
+            ]
+            .push_maybe(self.npub_qr.as_ref().map(|data| qr_code(data).cell_size(5)))
+            .push(
+                row![
+                    text_input(&t!("nostr-secret-key"), &self.nostr_key_input)
+                        .on_input(Message::NostrKeyChanged)
+                        .on_submit(Message::ImportNostrKey)
+                        .secure(true),
+                    button(text(t!("import-key"))).on_press_maybe(
+                        (!self.nostr_key_input.is_empty()).then_some(Message::ImportNostrKey)
+                    ),
+                    button(text(t!("generate-nostr-key"))).on_press(Message::GenerateNostrKey),
+                ]
+                .push_maybe((!derived).then(|| {
+                    button(text(t!("use-seed-nostr-key"))).on_press(Message::UseSeedNostrKey)
+                }))
+                .spacing(10)
+                .align_items(Alignment::Center),
+            )
+            .spacing(10);
+
+        Some(identity.into())
+    }
+
+    /// Upcoming scheduled payments and the form to add one
+    fn schedule_view(&self) -> Column<Message> {
+        let recipients: Vec<Contact> = self
+            .contacts
+            .iter()
+            .filter(|contact| contact.npub.is_some())
+            .cloned()
+            .collect();
+        let recurrences =
+            Recurrence::ALL
+                .into_iter()
+                .fold(row![].spacing(10), |row, recurrence| {
+                    row.push(radio(
+                        recurrence_label(recurrence),
+                        recurrence,
+                        Some(self.schedule_recurrence),
+                        Message::ScheduleRecurrenceSelected,
+                    ))
+                });
+
+        column![
+            text(t!("scheduled-payments")).size(24),
+            if self.scheduled_payments.is_empty() {
+                column![text(t!("no-scheduled-payments"))]
+            } else {
+                self.scheduled_payments
+                    .iter()
+                    .fold(column![].spacing(5), |col, scheduled| {
+                        col.push(self.scheduled_payment_row(scheduled))
+                    })
+            },
+            text(t!("schedule-a-payment")).size(20),
+            row![
+                text_input(&t!("lightning-address"), &self.schedule_address)
+                    .on_input(Message::ScheduleAddressChanged),
+                pick_list(
+                    recipients,
+                    self.schedule_contact.clone(),
+                    Message::ScheduleContactSelected
+                )
+                .placeholder(t!("or-send-ecash-to-a-contact")),
+            ]
+            .spacing(10)
+            .align_items(Alignment::Center),
+            row![
+                text_input(&t!("amount-sats"), &self.schedule_amount)
+                    .on_input(Message::ScheduleAmountChanged),
+                text(t!("first-payment")),
+                text_input("YYYY-MM-DD HH:MM", &self.schedule_start)
+                    .on_input(Message::ScheduleStartChanged)
+                    .on_submit(Message::AddScheduledPayment),
+            ]
+            .spacing(10)
+            .align_items(Alignment::Center),
+            recurrences,
+            text(t!("scheduled-payments-hint")).size(12),
+            row![button(text(t!("schedule"))).on_press_maybe(
+                (!self.schedule_amount.is_empty()
+                    && (self.schedule_contact.is_some() || !self.schedule_address.is_empty()))
+                .then_some(Message::AddScheduledPayment)
+            )],
+            center(row![button(text(t!("home"))).on_press(Message::Home)])
+        ]
+        .spacing(10)
+    }
+
+    fn scheduled_payment_row<'a>(
+        &'a self,
+        scheduled: &'a ScheduledPayment,
+    ) -> Element<'a, Message> {
+        let recipient = match &scheduled.action {
+            ScheduledAction::Pay { address } => address.clone(),
+            ScheduledAction::Send { contact_id } => self
+                .contacts
+                .iter()
+                .find(|contact| contact.id == *contact_id)
+                .map(|contact| t!("ecash-to", contact = contact.name))
+                .unwrap_or_else(|| t!("deleted-contact")),
+        };
+        let paying = self.paying_scheduled.contains(&scheduled.id);
+
+        column![row![
+            text(format_local_time(scheduled.next_run)),
+            text(t!(
+                "scheduled-payment-summary",
+                amount = i18n::format_number(scheduled.amount),
+                recipient = recipient,
+                recurrence = recurrence_label(scheduled.recurrence)
+            )),
+            button(text(t!("skip")))
+                .on_press_maybe((!paying).then_some(Message::SkipScheduledPayment(scheduled.id))),
+            button(text(t!("cancel")))
+                .on_press_maybe((!paying).then_some(Message::CancelScheduledPayment(scheduled.id))),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center)]
+        .push_maybe(scheduled.last_error.as_ref().map(|err| {
+            text(t!("last-run-failed", err = err))
+                .size(12)
+                .color(ERROR_COLOR)
+        }))
+        .spacing(2)
+        .into()
+    }
+
+    /// Till of the point-of-sale mode: the amount and tip being rung up, the
+    /// invoice the customer scans or the PAID screen, and the day's takings
+    fn pos_view(&self) -> Column<Message> {
+        let amount = i18n::parse_amount(&self.pos.amount, &CurrencyUnit::Sat).unwrap_or_default();
+        let sale: Element<Message> = if let Some((sale, _)) = &self.pos.paid {
+            let receipt = if self.pos.show_receipt {
+                pos::receipt_lines(sale)
+                    .into_iter()
+                    .fold(column![].spacing(5), |col, line| col.push(text(line)))
+                    .push_maybe(
+                        self.receipt_qr
+                            .as_ref()
+                            .map(|data| qr_code(data).cell_size(4)),
+                    )
+                    .push(
+                        row![
+                            button(text(t!("copy"))).on_press(Message::CopyReceipt),
+                            button(text(t!("save-pdf"))).on_press(Message::SaveReceipt),
+                        ]
+                        .spacing(10),
+                    )
+                    .push_maybe(
+                        self.pos
+                            .receipt_path
+                            .as_ref()
+                            .map(|path| text(t!("receipt-saved-to", path = path))),
+                    )
+            } else {
+                column![button(text(t!("receipt"))).on_press(Message::ShowReceipt)]
+            };
+
+            column![
+                text(t!("paid")).size(120).color(ONLINE_COLOR),
+                text(t!("pos-amount", amount = i18n::format_number(sale.amount))).size(32),
+                receipt.align_items(Alignment::Center),
+                button(text(t!("next-customer"))).on_press(Message::PosNext),
+            ]
+            .spacing(20)
+            .align_items(Alignment::Center)
+            .into()
+        } else if let Some(pending) = self.pos.charging.as_ref().and(self.pending_mint.as_ref()) {
+            let remaining = pending.expiry.saturating_sub(unix_time() as u64);
+            column![]
+                .push_maybe(
+                    self.qr_code
+                        .as_ref()
+                        .map(|data| qr_code(data).cell_size(10)),
+                )
+                .push(
+                    text(t!(
+                        "pos-amount",
+                        amount = i18n::format_number(pending.amount)
+                    ))
+                    .size(32),
+                )
+                .push(text(t!(
+                    "expires-in",
+                    minutes = remaining / 60,
+                    seconds = format!("{:02}", remaining % 60)
+                )))
+                .push(button(text(t!("cancel"))).on_press(Message::CancelMintQuote))
+                .spacing(10)
+                .align_items(Alignment::Center)
+                .into()
+        } else {
+            let tips = TIP_PRESETS
+                .into_iter()
+                .fold(row![].spacing(10), |row, tip| {
+                    row.push(radio(
+                        if tip == 0 {
+                            t!("no-tip")
+                        } else {
+                            format!("{}%", tip)
+                        },
+                        tip,
+                        Some(self.pos.tip),
+                        Message::PosTipSelected,
+                    ))
+                });
+
+            column![
+                text_input(&t!("amount-sats"), &self.pos.amount)
+                    .id(text_input::Id::new(POS_AMOUNT_INPUT))
+                    .on_input(Message::PosAmountChanged)
+                    .on_submit(Message::PosCharge)
+                    .size(32),
+                text_input(&t!("order-note"), &self.pos.note)
+                    .on_input(Message::PosNoteChanged)
+                    .on_submit(Message::PosCharge),
+                text(t!("tip")),
+                tips,
+                text(t!(
+                    "total-sats",
+                    total = i18n::format_number(self.pos.total(amount))
+                ))
+                .size(24),
+                button(text(t!("charge"))).on_press_maybe(
+                    (amount > 0 && !self.busy(&[Operation::Quote])).then_some(Message::PosCharge)
+                ),
+            ]
+            .push_maybe(self.progress(&[Operation::Quote]))
+            .spacing(10)
+            .into()
+        };
+
+        column![
+            text(t!("point-of-sale")).size(24),
+            text(self.active_mint.to_string()).size(12),
+            sale,
+            text(t!(
+                "pos-today",
+                amount = i18n::format_number(self.pos.totals.amount),
+                sales = self.pos.totals.sales
+            )),
+            row![button(text(t!("export-sales"))).on_press(Message::ExportSales)].push_maybe(
+                self.pos
+                    .sales_path
+                    .as_ref()
+                    .map(|path| text(t!("sales-saved-to", path = path)))
+            ),
+            center(row![button(text(t!("home"))).on_press(Message::Home)])
+        ]
+        .spacing(20)
+    }
+
+    /// Ledger entry of the point-of-sale payment of `pending`
+    fn pos_sale(&self, pending: &PendingMint) -> Sale {
+        let amount =
+            i18n::parse_amount(&self.pos.amount, &CurrencyUnit::Sat).unwrap_or(pending.amount);
+        let rate = self.current_rate();
+        let note = self.pos.note.trim();
+
+        Sale {
+            id: 0,
+            amount: pending.amount,
+            tip: pending.amount.saturating_sub(amount),
+            fiat_amount: rate.map(|rate| rate.sats_to_fiat(pending.amount)),
+            fiat_currency: rate.map(|rate| rate.currency.to_string()),
+            note: (!note.is_empty()).then(|| note.to_string()),
+            payment_hash: invoice::inspect_invoice(&pending.request)
+                .map(|details| details.payment_hash)
+                .unwrap_or_default(),
+            mint_url: pending.mint_url.clone(),
+            timestamp: unix_time(),
+        }
+    }
+
+    /// Auto-withdraw rule with its address, threshold and why it was paused
+    fn auto_withdraw_settings(&self) -> Element<Message> {
+        column![
+            checkbox(t!("auto-withdraw-enabled"), self.settings.auto_withdraw)
+                .on_toggle(Message::AutoWithdrawToggled),
+            row![
+                text_input(&t!("lightning-address"), &self.auto_withdraw_address_input)
+                    .on_input(Message::AutoWithdrawAddressChanged)
+                    .on_submit(Message::SaveAutoWithdraw),
+                text(t!("auto-withdraw-above")),
+                text_input("100000", &self.auto_withdraw_threshold_input)
+                    .on_input(Message::AutoWithdrawThresholdChanged)
+                    .on_submit(Message::SaveAutoWithdraw)
+                    .width(120),
+                button(text(t!("save"))).on_press(Message::SaveAutoWithdraw)
+            ]
+            .spacing(10)
+            .align_items(Alignment::Center),
+        ]
+        .push_maybe(self.progress(&[Operation::AutoWithdraw]))
+        .push_maybe(
+            self.auto_withdraw_error
+                .as_ref()
+                .map(|err| text(t!("auto-withdraw-paused-because", err = err)).color(ERROR_COLOR)),
+        )
+        .spacing(10)
+        .into()
+    }
+
+    /// Maintenance button with the progress and results of the last run
+    fn maintenance_settings(&self) -> Element<Message> {
+        let running = self
+            .maintenance
+            .as_ref()
+            .is_some_and(|maintenance| !maintenance.finished());
+
+        column![row![button(text(t!("run-maintenance")))
+            .on_press_maybe((!running).then_some(Message::RunMaintenance))]
+        .push_maybe(running.then(|| text(t!("maintenance-running"))))
+        .spacing(10)
+        .align_items(Alignment::Center)]
+        .push_maybe(self.maintenance.as_ref().map(|maintenance| {
+            column![]
+                .push_maybe(running.then(|| {
+                    progress_bar(
+                        0.0..=Maintenance::STEPS as f32,
+                        maintenance.steps.len() as f32,
+                    )
+                    .height(10)
+                }))
+                .push(
+                    maintenance
+                        .steps
+                        .iter()
+                        .fold(column![].spacing(5), |col, step| {
+                            col.push(text(step.to_string()))
+                        }),
+                )
+                .push_maybe(
+                    maintenance
+                        .error
+                        .as_ref()
+                        .map(|err| text(err.to_string()).color(ERROR_COLOR)),
+                )
+                .push_maybe(maintenance.size_after.map(|size_after| {
+                    text(t!(
+                        "database-size-change",
+                        before = format_size(maintenance.size_before),
+                        after = format_size(size_after)
+                    ))
+                }))
+                .spacing(5)
+        }))
+        .spacing(10)
+        .into()
+    }
+
+    fn load_mint_info(&self) -> Task<Message> {
+        let (db, service) = (self.db(), self.service());
+        let mint_url = self.active_mint.clone();
+        Task::batch([
+            Task::perform(
+                load_mint_info(db, mint_url.clone()),
+                or_error(Message::MintInfoLoaded),
+            ),
+            Task::perform(
+                async move {
+                    let units = service.mint_units(mint_url.clone()).await?;
+                    Ok::<_, WalletError>((mint_url, units))
+                },
+                or_error(Message::MintUnits),
+            ),
+        ])
+    }
+
+    /// Dot colored by how `mint_url` answered its last health check
+    fn health_dot(&self, mint_url: &UncheckedUrl) -> Element<Message> {
+        let color = match self.mint_health.get(mint_url) {
+            Some(MintHealth::Online) => ONLINE_COLOR,
+            Some(MintHealth::Slow) => SLOW_COLOR,
+            Some(MintHealth::Unreachable) => ERROR_COLOR,
+            // Not checked yet
+            None => Color::from_rgb(0.5, 0.5, 0.5),
+        };
+
+        text("●").color(color).into()
+    }
+
+    /// Warning shown before starting an operation with an unreachable mint
+    fn unreachable_warning(&self, mint_url: &UncheckedUrl) -> Option<Row<Message>> {
+        (self.mint_health.get(mint_url) == Some(&MintHealth::Unreachable))
+            .then(|| row![text(t!("mint-unreachable", mint_url = mint_url)).color(ERROR_COLOR)])
+    }
+
+    /// Warning that `mint_url` holds funds on retired keysets, with the
+    /// swap moving them to its active ones
+    fn retired_keysets_warning(&self, mint_url: &UncheckedUrl) -> Option<Row<Message>> {
+        self.retired_keysets.get(mint_url).map(|amount| {
+            row![
+                text(t!(
+                    "on-retired-keysets",
+                    amount = i18n::format_number(*amount)
+                ))
+                .color(ERROR_COLOR),
+                button(text(t!("swap"))).on_press(Message::RotateKeysets(mint_url.clone()))
+            ]
+            .spacing(10)
+            .align_items(Alignment::Center)
+        })
+    }
+
+    /// Loaded info of the active mint
+    fn active_mint_info(&self) -> Option<&MintInfo> {
+        self.mint_info
+            .as_ref()
+            .filter(|details| details.mint_url == self.active_mint)
+            .map(|details| &details.info)
+    }
+
+    fn motd_banner(&self) -> Option<Element<Message>> {
+        let details = self.mint_info.as_ref()?;
+        if details.motd_dismissed {
+            return None;
+        }
+        let motd = details.info.motd.as_ref()?;
+
+        Some(
+            container(
+                row![
+                    text(motd),
+                    button(text(t!("dismiss"))).on_press(Message::DismissMotd)
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+            )
+            .padding(10)
+            .style(container::rounded_box)
+            .into(),
+        )
+    }
+
+    fn notify(&self, summary: &str, body: &str) {
+        if self.settings.notifications {
+            notify::show(summary, body);
+        }
+    }
+
+    /// Picker of the contacts matching `filter`, if there are any
+    fn contact_picker(
+        &self,
+        filter: impl Fn(&Contact) -> bool,
+        on_select: impl Fn(Contact) -> Message + 'static,
+    ) -> Option<Element<Message>> {
+        let contacts: Vec<Contact> = self
+            .contacts
+            .iter()
+            .filter(|contact| filter(contact))
+            .cloned()
+            .collect();
+        if contacts.is_empty() {
+            return None;
+        }
+
+        Some(
+            pick_list(contacts, None::<Contact>, on_select)
+                .placeholder(t!("contacts"))
+                .into(),
+        )
+    }
+
+    /// Switch to the imported or generated `keys`, kept for the next unlock
+    fn store_nostr_keys(&mut self, keys: Keys) -> Task<Message> {
+        if let Err(err) = nostr::store_keys(&keys, &self.password) {
+            self.error = Some(WalletError::Nostr(err.to_string()));
+            return Task::none();
+        }
+
+        self.set_nostr_keys(keys)
+    }
+
+    /// Use `keys` as the wallet's nostr identity
+    ///
+    /// Its secret also signs for tokens locked to the identity, and zappers
+    /// are told about the switch.
+    fn set_nostr_keys(&mut self, keys: Keys) -> Task<Message> {
+        let secret = keys
+            .secret_key()
+            .map(|secret| Zeroizing::new(secret.to_secret_hex()));
+        self.nostr_keys = Some(keys);
+        self.npub_qr = None;
+
+        let signing_key = match secret {
+            Ok(secret) => Task::perform(
+                import_signing_key(self.wallet(), secret),
+                or_error(Message::SigningKeyImported),
+            ),
+            Err(_) => Task::none(),
+        };
+
+        Task::batch([signing_key, self.publish_nutzap_info()])
+    }
+
+    /// Announce the current mints and relays to zappers, if nutzaps are on
+    fn publish_nutzap_info(&self) -> Task<Message> {
+        let (true, Some(keys), Some(p2pk)) = (
+            self.settings.nutzaps_enabled,
+            &self.nostr_keys,
+            self.p2pk_pubkey,
+        ) else {
+            return Task::none();
+        };
+        if self.mints.is_empty() {
+            return Task::none();
+        }
+
+        Task::perform(
+            publish_nutzap_info(
+                keys.clone(),
+                self.settings.nostr_relays.clone(),
+                p2pk,
+                self.mints.clone(),
+            ),
+            or_error(Message::NutzapInfoPublished),
+        )
+    }
+
+    /// Encode the last created token in the selected format, falling back to
+    /// V3 for tokens V4 can't represent
+    fn encode_token(&mut self) {
+        let Some(sent_token) = &self.sent_token else {
+            return;
+        };
+
+        self.token =
+            token::encode(sent_token, self.token_format).unwrap_or_else(|_| sent_token.to_string());
+        self.qr_code = qr_code::Data::new(&self.token).ok();
+        self.qr_frame = 0;
+        self.qr_frames = if self.token.len() > ANIMATED_QR_THRESHOLD {
+            bbqr::split(&self.token)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|part| qr_code::Data::new(part).ok())
+                .collect()
+        } else {
+            vec![]
+        };
+    }
+
+    /// Signing keys held for P2PK locks, the derived key first
+    fn signing_pubkeys(&self) -> impl Iterator<Item = &PublicKey> {
+        self.p2pk_pubkey.iter().chain(&self.imported_pubkeys)
+    }
+
+    /// Lock keys of the entered token if none of them can be signed for
+    fn missing_signing_key(&self) -> Option<String> {
+        let token = token::parse(&self.data).ok()?;
+        let locks = p2pk::token_locks(&token);
+
+        if locks.is_empty()
+            || locks
+                .iter()
+                .any(|lock| self.signing_pubkeys().any(|pubkey| pubkey == lock))
+        {
+            return None;
+        }
+
+        Some(
+            locks
+                .iter()
+                .map(PublicKey::to_hex)
+                .collect::<Vec<_>>()
+                .join(", "),
+        )
+    }
+
+    /// Preimage of `hash` held by the wallet or typed in
+    fn preimage_for(&self, hash: &str) -> Option<String> {
+        if let Some(preimage) = self.preimages.get(hash) {
+            return Some(preimage.clone());
+        }
+        let typed = htlc::parse_preimage(&self.preimage_input).ok()?;
+
+        (htlc::hash_lock(&typed).ok()? == hash).then_some(typed)
+    }
+
+    /// Preimages for the HTLC locks of `token`, none if it has none
+    fn token_preimages(&self, token: &str) -> Vec<String> {
+        let Ok(token) = token::parse(token) else {
+            return vec![];
+        };
+
+        htlc::token_hash_locks(&token)
+            .iter()
+            .filter_map(|hash| self.preimage_for(hash))
+            .collect()
+    }
+
+    /// Hashes of the token being received the wallet has no preimage of
+    fn missing_preimage(&self) -> Option<String> {
+        let token = token::parse(&self.data).ok()?;
+        let missing: Vec<String> = htlc::token_hash_locks(&token)
+            .into_iter()
+            .filter(|hash| self.preimage_for(hash).is_none())
+            .collect();
+
+        (!missing.is_empty()).then(|| missing.join(", "))
+    }
+
+    fn token_hash_locks(&self) -> Option<Element<Message>> {
+        let token = token::parse(&self.data).ok()?;
+        let hashes = htlc::token_hash_locks(&token);
+        if hashes.is_empty() {
+            return None;
+        }
+
+        let locks = hashes.iter().fold(
+            column![text(t!("token-is-hash-locked-to"))].spacing(5),
+            |col, hash| {
+                let held = self.preimage_for(hash).is_some();
+                let label = text(format!(
+                    "{} ({})",
+                    hash,
+                    if held {
+                        t!("preimage-held")
+                    } else {
+                        t!("preimage-required")
+                    }
+                ));
+                col.push(if held {
+                    label
+                } else {
+                    label.color(ERROR_COLOR)
+                })
+            },
+        );
+
+        let input = self.missing_preimage().map(|_| {
+            text_input(&t!("preimage-hex"), &self.preimage_input)
+                .on_input(Message::PreimageChanged)
+                .on_submit(Message::Receive)
+                .secure(true)
+        });
+
+        Some(locks.push_maybe(input).into())
+    }
+
+    /// Option to lock the next token to a hash, with the preimage generated
+    /// for it to keep secret until the swap completes
+    fn hash_lock_settings(&self) -> Column<Message> {
+        column![
+            checkbox(t!("lock-to-hash"), self.send_hash_lock).on_toggle(Message::HashLockToggled)
+        ]
+        .push_maybe(self.send_hash_lock.then(|| {
+            row![
+                text_input(&t!("hash-hex"), &self.hash_lock_input)
+                    .on_input(Message::HashLockChanged),
+                button(text(t!("generate-preimage"))).on_press(Message::GeneratePreimage),
+            ]
+            .spacing(10)
+            .align_items(Alignment::Center)
+        }))
+        .push_maybe(
+            self.send_preimage
+                .as_ref()
+                .filter(|_| self.send_hash_lock)
+                .map(|preimage| text(t!("preimage", preimage = preimage.as_str())).size(12)),
+        )
+        .spacing(5)
+    }
+
+    /// Delay after which the locked token being created can be refunded
+    fn refund_settings(&self) -> Option<Row<Message>> {
+        if self.send_lock.is_none() && !self.send_hash_lock {
+            return None;
+        }
+
+        Some(
+            row![
+                text(t!("refundable-after")),
+                text_input("24", &self.refund_hours)
+                    .on_input(Message::RefundHoursChanged)
+                    .width(80),
+                text(t!("hours-optional")),
+            ]
+            .spacing(10)
+            .align_items(Alignment::Center),
+        )
+    }
+
+    /// When `sent` can be taken back, see [`p2pk::reclaimable_at`]
+    fn reclaimable_at(&self, sent: &SentToken) -> Option<u64> {
+        let (Ok(token), Some(pubkey)) = (token::parse(&sent.token), &self.p2pk_pubkey) else {
+            return Some(0);
+        };
+
+        p2pk::reclaimable_at(&token, pubkey)
+    }
+
+    /// Hash locks of the token just created, with the preimages the wallet
+    /// holds for them
+    fn sent_hash_locks(&self) -> Option<Column<Message>> {
+        let hashes = htlc::token_hash_locks(self.sent_token.as_ref()?);
+        if hashes.is_empty() {
+            return None;
+        }
+
+        Some(hashes.into_iter().fold(column![].spacing(5), |col, hash| {
+            let preimage = self.preimages.get(&hash).cloned();
+            col.push(text(t!("locked-to-hash", hash = hash)))
+                .push_maybe(preimage.map(|preimage| {
+                    row![
+                        text(t!("preimage", preimage = preimage)),
+                        button(text(t!("copy"))).on_press(Message::CopyPreimage(preimage.clone())),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                }))
+        }))
+    }
+
+    fn token_locks(&self) -> Option<Element<Message>> {
+        let token = token::parse(&self.data).ok()?;
+        let locks = p2pk::token_locks(&token);
+        if locks.is_empty() {
+            return None;
+        }
+
+        let keys = locks.iter().fold(
+            column![text(t!("token-is-locked-to"))].spacing(5),
+            |col, lock| {
+                let held = self.signing_pubkeys().any(|pubkey| pubkey == lock);
+                let label = text(format!(
+                    "{} ({})",
+                    lock.to_hex(),
+                    if held {
+                        t!("key-held")
+                    } else {
+                        t!("key-required")
+                    }
+                ));
+                col.push(if held {
+                    label
+                } else {
+                    label.color(ERROR_COLOR)
+                })
+            },
+        );
+
+        let import = self.missing_signing_key().map(|_| {
+            row![
+                text_input(&t!("signing-key-hex-or-nsec"), &self.signing_key_input)
+                    .on_input(Message::SigningKeyChanged)
+                    .on_submit(Message::ImportSigningKey)
+                    .secure(true),
+                button(text(t!("import-key"))).on_press(Message::ImportSigningKey)
+            ]
+            .spacing(10)
+            .align_items(Alignment::Center)
+        });
+
+        Some(keys.push_maybe(import).into())
+    }
+
+    fn save_settings(&mut self) {
+        if let Err(err) = self.settings.save() {
+            self.error = Some(WalletError::Database(err.to_string()));
+        }
+    }
+
+    /// Exchange rate of the selected currency, if one was fetched
+    fn current_rate(&self) -> Option<ExchangeRate> {
+        self.exchange_rate
+            .filter(|rate| rate.currency == self.settings.fiat_currency)
+    }
+
+    fn fiat_balance(&self) -> Option<Element<Message>> {
+        let rate = self.current_rate()?;
+
+        let fiat = text(format!(
+            "≈ {} {}",
+            i18n::format_decimal(rate.sats_to_fiat(self.balance), 2),
+            rate.currency
+        ));
+
+        Some(
+            row![fiat]
+                .push_maybe(
+                    rate.is_stale()
+                        .then(|| text(t!("offline")).color(ERROR_COLOR)),
+                )
+                .spacing(10)
+                .into(),
+        )
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        let mut subscriptions = vec![window::close_requests().map(Message::WindowCloseRequested)];
+        // The theme applies to the lock and onboarding screens too
+        if self.settings.follow_system_theme {
+            subscriptions
+                .push(iced::time::every(SYSTEM_THEME_INTERVAL).map(|_| Message::CheckSystemTheme));
+        }
+        if self.service.is_none() {
+            return Subscription::batch(subscriptions);
+        }
+
+        subscriptions.extend([
+            iced::event::listen_with(|event, _status, _window| match event {
+                iced::Event::Window(window::Event::FileDropped(path)) => {
+                    Some(Message::FileDropped(path))
+                }
+                _ => None,
+            }),
+            iced::time::every(RATE_REFRESH_INTERVAL).map(|_| Message::FetchRate),
+            iced::time::every(CLIPBOARD_POLL_INTERVAL).map(|_| Message::CheckClipboard),
+            iced::time::every(PROOF_STATE_INTERVAL).map(|_| Message::CheckProofStates),
+            iced::time::every(MINT_HEALTH_INTERVAL).map(|_| Message::CheckMintHealth),
+            iced::time::every(KEYSET_CHECK_INTERVAL).map(|_| Message::CheckKeysets),
+            iced::time::every(SENT_TOKEN_POLL_INTERVAL).map(|_| Message::CheckSentTokens),
+        ]);
+
+        if self
+            .failed_operations
+            .iter()
+            .any(|failed| failed.next_attempt.is_some())
+        {
+            subscriptions.push(iced::time::every(RETRY_CHECK_INTERVAL).map(|_| Message::RetryDue));
+        }
+
+        // Keeps running while the window is closed to the tray
+        if !self.scheduled_payments.is_empty() {
+            subscriptions
+                .push(iced::time::every(SCHEDULE_CHECK_INTERVAL).map(|_| Message::ScheduleDue));
+        }
+
+        if let Some(pending) = &self.pending_mint {
+            // Mints only push bolt11 quote updates over websockets
+            if pending.onchain || self.poll_quotes.contains(&pending.quote_id) {
+                subscriptions.push(
+                    iced::time::every(self.poll_delay.max(Duration::from_secs(1)))
+                        .map(|_| Message::CheckMintQuote),
+                );
+            } else {
+                subscriptions.push(
+                    ws::quote_updates(
+                        pending.mint_url.clone(),
+                        QuoteKind::Mint,
+                        pending.quote_id.clone(),
+                    )
+                    .map(Message::QuoteUpdate),
+                );
+            }
+        }
+
+        if self.pending_mint.is_some() || self.pos.paid.is_some() {
+            subscriptions.push(iced::time::every(Duration::from_secs(1)).map(|_| Message::Tick));
+        }
+
+        if self.withdrawal.is_some() {
+            subscriptions.push(
+                iced::time::every(Duration::from_secs(self.settings.polling_interval.max(1)))
+                    .map(|_| Message::CheckWithdrawal),
+            );
+        }
+
+        if matches!(self.view, View::Token) && self.qr_frames.len() > 1 {
+            subscriptions.push(
+                iced::time::every(Duration::from_millis(
+                    self.settings.qr_frame_interval.max(1),
+                ))
+                .map(|_| Message::NextQrFrame),
+            );
+        }
+
+        if matches!(self.view, View::Debug) {
+            subscriptions
+                .push(iced::time::every(LOG_REFRESH_INTERVAL).map(|_| Message::RefreshLogs));
+        }
+
+        if let Some(melting) = &self.melting {
+            subscriptions.push(
+                ws::quote_updates(
+                    melting.mint_url.clone(),
+                    QuoteKind::Melt,
+                    melting.quote_id.clone(),
+                )
+                .map(Message::QuoteUpdate),
+            );
+        }
+
+        if let Some(keys) = &self.nostr_keys {
+            subscriptions.push(
+                nostr::dm_inbox(keys.clone(), self.settings.nostr_relays.clone())
+                    .map(Message::Inbox),
+            );
+        }
+
+        if let (true, Some(keys)) = (self.settings.nutzaps_enabled, &self.nostr_keys) {
+            if !self.mints.is_empty() {
+                subscriptions.push(
+                    nutzap::receiver(
+                        keys.clone(),
+                        self.settings.nostr_relays.clone(),
+                        self.mints.clone(),
+                    )
+                    .map(Message::Nutzap),
+                );
+            }
+        }
+
+        if let (Some(open), Some(keys)) = (&self.payment_request, &self.nostr_keys) {
+            subscriptions.push(
+                payment_request::listen(
+                    keys.clone(),
+                    self.settings.nostr_relays.clone(),
+                    open.id.clone(),
+                )
+                .map(Message::PaymentRequestEvent),
+            );
+        }
+
+        if !self.nwc_connections.is_empty() {
+            subscriptions.push(nwc::service(self.nwc_connections.clone()).map(Message::Nwc));
+        }
+
+        #[cfg(feature = "control-api")]
+        if let Some(token) = self
+            .control_token
+            .clone()
+            .filter(|_| self.settings.control_api)
+        {
+            subscriptions
+                .push(control::server(self.settings.control_port, token).map(Message::Control));
+        }
+
+        if self.tray.is_some() {
+            subscriptions.push(tray::actions().map(Message::Tray));
+        }
+
+        if !self.locked && self.pin_prompt.is_none() && self.onboarding.is_none() {
+            subscriptions.push(keyboard::on_key_press(shortcut));
+        }
+
+        if self.pin_set && !self.locked {
+            subscriptions.push(iced::event::listen().map(|_| Message::Activity));
+            subscriptions.push(iced::time::every(IDLE_CHECK_INTERVAL).map(|_| Message::CheckIdle));
+        }
+
+        if self.scanning {
+            subscriptions.push(camera::scan().map(Message::Camera));
+        }
+
+        Subscription::batch(subscriptions)
+    }
+
+    fn theme(&self) -> Theme {
+        if self.settings.follow_system_theme {
+            return self.settings.system_theme(self.system_dark);
+        }
+        self.settings.theme()
+    }
+
+    fn scale_factor(&self) -> f64 {
+        self.settings.scale_factor()
+    }
+}
+
+fn history_row(transaction: &Transaction) -> Element<Message> {
+    let timestamp = chrono::DateTime::from_timestamp(transaction.timestamp, 0)
+        .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default();
+
+    row![
+        text(timestamp),
+        text(transaction.kind.to_string()),
+        text(i18n::format_amount(transaction.amount, &transaction.unit)),
+    ]
+    .push_maybe((transaction.fee > 0).then(|| {
+        text(t!(
+            "fee",
+            fee = i18n::format_amount(transaction.fee, &transaction.unit)
+        ))
+    }))
+    .push(text(transaction.mint_url.to_string()))
+    .push(text(transaction.status.to_string()))
+    .push_maybe(
+        transaction
+            .memo
+            .as_ref()
+            .map(|memo| text(format!("\"{}\"", memo))),
+    )
+    .push_maybe(transaction.note.as_ref().map(text))
+    .push_maybe((!transaction.tags.is_empty()).then(|| text(transaction.tags.join(", "))))
+    .push_maybe(transaction.dleq.map(dleq_badge))
+    .push_maybe(
+        transaction
+            .nostr_recipient
+            .as_ref()
+            .zip(transaction.delivery)
+            .map(|(npub, delivery)| text(t!("nostr-delivery", delivery = delivery, npub = npub))),
+    )
+    .push(button(text(t!("details"))).on_press(Message::ShowTransaction(transaction.clone())))
+    .spacing(10)
+    .into()
+}
+
+/// Why `input` was not recognised, saying why a token doesn't decode rather
+/// than that it isn't one
+fn unrecognized_input(input: &str) -> Element<Message> {
+    match token::parse(input) {
+        Err(err) if input.trim().starts_with("cashu") => text(t!("invalid-token", err = err))
+            .color(ERROR_COLOR)
+            .into(),
+        _ => text(WalletError::UnrecognizedInput.to_string())
+            .color(ERROR_COLOR)
+            .into(),
+    }
+}
+
+/// Every field of `input` the Decode view shows, without touching the wallet
+fn decoded_input(input: &str) -> Element<Message> {
+    let error = |err: anyhow::Error| -> Element<Message> {
+        text(err.to_string()).color(ERROR_COLOR).into()
+    };
+
+    match detect(input) {
+        Some(DetectedInput::Token { token: encoded, .. }) => match token::parse(&encoded) {
+            Ok(parsed) => token_summary(&token::summary(&parsed))
+                .push(
+                    parsed
+                        .token
+                        .iter()
+                        .flat_map(|mint_proofs| &mint_proofs.proofs)
+                        .fold(
+                            column![text(t!("proofs-label"))].spacing(5),
+                            |col, proof| {
+                                col.push(text(t!(
+                                    "proof-from-keyset",
+                                    amount = i18n::format_number(proof.amount),
+                                    keyset_id = proof.keyset_id,
+                                    dleq = if proof.dleq.is_some() {
+                                        t!("with-dleq-proof")
+                                    } else {
+                                        String::new()
+                                    }
+                                )))
+                            },
+                        ),
+                )
+                .into(),
+            Err(err) => error(err),
+        },
+        Some(DetectedInput::Bolt11 { invoice, .. }) => match invoice::inspect_invoice(&invoice) {
+            Ok(details) => column![
+                invoice_details(&details),
+                text(t!("payment-hash", payment_hash = details.payment_hash))
+            ]
+            .push_maybe(
+                (details.expires_at <= unix_time() as u64)
+                    .then(|| text(t!("invoice-has-expired")).color(ERROR_COLOR)),
+            )
+            .spacing(5)
+            .into(),
+            Err(err) => error(err),
+        },
+        Some(DetectedInput::Bolt12 { offer, .. }) => match bolt12::decode_offer(&offer) {
+            Ok(details) => offer_details(&details),
+            Err(err) => error(err),
+        },
+        Some(DetectedInput::Lnurl(lnurl)) => match lnurl::decode(&lnurl) {
+            Ok(url) => text(t!("lnurl-pointing-to", url = url)).into(),
+            Err(err) => error(err),
+        },
+        Some(DetectedInput::PaymentRequest { request, .. }) => {
+            match payment_request::decode(&request) {
+                Ok(request) => payment_request_details(&request).into(),
+                Err(err) => error(err),
+            }
+        }
+        None => unrecognized_input(input),
+    }
+}
+
+/// Fields of a NUT-18 payment request
+fn payment_request_details(request: &PaymentRequest) -> Column<Message> {
+    let unit = request.unit.clone().unwrap_or_else(|| "sat".to_string());
+
+    column![text(match request.amount {
+        Some(amount) => format!("{} {}", i18n::format_number(amount), unit),
+        None => t!("any-amount-in", unit = unit),
+    })
+    .size(30)]
+    .push_maybe(request.description.clone().map(text))
+    .push_maybe(request.id.as_ref().map(|id| text(t!("id", id = id))))
+    .push(text(if request.mints.is_empty() {
+        t!("mints-any")
+    } else {
+        t!("mints", mints = request.mints.join(", "))
+    }))
+    .push_maybe(
+        request
+            .single_use
+            .filter(|single_use| *single_use)
+            .map(|_| text(t!("single-use"))),
+    )
+    .push_maybe(
+        request
+            .nut10
+            .as_ref()
+            .map(|nut10| text(t!("locked-with-to", kind = nut10.kind, data = nut10.data))),
+    )
+    .push(request.transports.iter().fold(column![], |col, transport| {
+        col.push(text(t!(
+            "pay-over-to",
+            kind = transport.kind,
+            target = transport.target
+        )))
+    }))
+    .spacing(5)
+}
+
+/// Contents of a token about to be claimed
+fn token_summary(summary: &TokenSummary) -> Column<Message> {
+    column![text(t!(
+        "token-for",
+        amount = i18n::format_amount(summary.amount, &summary.unit)
+    ))
+    .size(30)]
+    .push_maybe(
+        summary
+            .memo
+            .as_ref()
+            .map(|memo| text(format!("\"{}\"", memo)).size(24)),
+    )
+    .push(summary.mints.iter().fold(column![], |col, mint_url| {
+        col.push(text(t!("mint", mint_url = mint_url)))
+    }))
+    .push(text(t!("token-unit", unit = summary.unit)))
+    .push(text(t!("token-proofs", proofs = summary.proofs)))
+    .push(summary.conditions.iter().fold(column![], |col, condition| {
+        col.push(text(condition.to_string()))
+    }))
+    .spacing(10)
+}
+
+fn transaction_detail(transaction: &Transaction) -> Column<Message> {
+    let amount = |amount| i18n::format_amount(amount, &transaction.unit);
+    let timestamp = chrono::DateTime::from_timestamp(transaction.timestamp, 0)
+        .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_default();
+
+    column![
+        text(format!(
+            "{} {}",
+            transaction.kind,
+            amount(transaction.amount)
+        ))
+        .size(30),
+        text(t!("transaction-status", status = transaction.status)),
+        text(t!("date", timestamp = timestamp)),
+        text(t!("mint", mint_url = transaction.mint_url)),
+    ]
+    .push_maybe(
+        transaction
+            .memo
+            .as_ref()
+            .map(|memo| text(t!("memo", memo = memo))),
+    )
+    .push_maybe(
+        transaction
+            .reference
+            .as_ref()
+            .map(|reference| text(t!("quote", reference = reference))),
+    )
+    .push(match transaction.fee_reserve {
+        Some(fee_reserve) => text(t!(
+            "transaction-fees",
+            fee_reserve = amount(fee_reserve),
+            fee = amount(transaction.fee),
+            returned = amount(transaction.change.unwrap_or_default())
+        )),
+        None => text(t!("transaction-fee", fee = amount(transaction.fee))),
+    })
+    .push_maybe(transaction.dleq.map(dleq_badge))
+    .push_maybe(
+        transaction
+            .nostr_recipient
+            .as_ref()
+            .zip(transaction.delivery)
+            .map(|(npub, delivery)| {
+                text(t!("transaction-nostr", delivery = delivery, npub = npub))
+            }),
+    )
+    .spacing(10)
+}
+
+fn dleq_badge(dleq: DleqStatus) -> Element<'static, Message> {
+    match dleq {
+        DleqStatus::Verified => text(t!("signatures-verified")).into(),
+        DleqStatus::Unverified => text(t!("signatures-unverified")).into(),
+        DleqStatus::Invalid => text(t!("signatures-invalid")).color(ERROR_COLOR).into(),
+    }
+}
+
+/// Amount input with a sat/fiat toggle, presets and optionally a max button
+///
+/// Amounts in a mint unit other than sats are typed as they are, without
+/// the toggle or presets.
+fn amount_entry<'a>(
+    placeholder: &str,
+    id: &'static str,
+    amount: &'a AmountInput,
+    mint_unit: &CurrencyUnit,
+    rate: Option<ExchangeRate>,
+    on_event: fn(AmountEvent) -> Message,
+    max: bool,
+) -> Element<'a, Message> {
+    let is_sat = *mint_unit == CurrencyUnit::Sat;
+    let rate = rate.filter(|_| is_sat);
+    let unit = match (amount.unit, rate) {
+        (AmountUnit::Fiat, Some(rate)) => rate.currency.to_string(),
+        _ if !is_sat => mint_unit.to_string(),
+        _ => t!("sats"),
+    };
+
+    let presets = PRESETS
+        .iter()
+        .fold(row![].spacing(5), |row, &sats| {
+            row.push(
+                button(text(format!("{}k", sats / 1000)))
+                    .on_press(on_event(AmountEvent::Preset(Some(sats)))),
+            )
+        })
+        .push(button(text(t!("custom"))).on_press(on_event(AmountEvent::Preset(None))))
+        .push_maybe(max.then(|| button(text(t!("max"))).on_press(on_event(AmountEvent::Max))));
+
+    column![row![
+        text_input(placeholder, &amount.input)
+            .id(text_input::Id::new(id))
+            .on_input(move |input| on_event(AmountEvent::Changed(input))),
+        button(text(unit)).on_press_maybe(rate.map(|_| on_event(AmountEvent::ToggleUnit)))
+    ]
+    .push_maybe(amount.conversion(rate.as_ref()).map(text))
+    .spacing(10)
+    .align_items(Alignment::Center)]
+    .push_maybe(is_sat.then_some(presets))
+    .spacing(5)
+    .into()
+}
+
+fn inbox_token_row(inbox_token: &InboxToken) -> Element<Message> {
+    container(
+        row![
+            text(t!(
+                "received-sats-from",
+                amount = i18n::format_number(inbox_token.amount),
+                sender = inbox_token.sender
+            )),
+            button(text(t!("claim"))).on_press(Message::ClaimInboxToken(inbox_token.id)),
+            button(text(t!("dismiss"))).on_press(Message::DismissInboxToken(inbox_token.id))
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center),
+    )
+    .padding(10)
+    .style(container::rounded_box)
+    .into()
+}
+
+/// Shortcut of a key press no input took
+fn shortcut(key: Key, modifiers: Modifiers) -> Option<Message> {
+    let shortcut = match key.as_ref() {
+        Key::Named(key::Named::Escape) => Shortcut::Home,
+        Key::Named(key::Named::Enter) => Shortcut::Submit,
+        Key::Named(key::Named::Tab) if modifiers.shift() => Shortcut::FocusPrevious,
+        Key::Named(key::Named::Tab) => Shortcut::FocusNext,
+        Key::Character("v") if modifiers.command() => Shortcut::Paste,
+        Key::Character("r") if modifiers.command() => Shortcut::Receive,
+        Key::Character("s") if modifiers.command() => Shortcut::Send,
+        Key::Character("k") if modifiers.command() => Shortcut::Palette,
+        _ => return None,
+    };
+
+    Some(Message::Shortcut(shortcut))
+}
+
+fn failed_operation_row(failed: &FailedOperation, retrying: bool) -> Element<Message> {
+    let status = match failed.next_attempt {
+        _ if retrying => t!("retrying"),
+        Some(next_attempt) => t!(
+            "retrying-in-s",
+            seconds = next_attempt.saturating_sub(unix_time()).max(0)
+        ),
+        None => t!("not-retrying"),
+    };
+
+    column![
+        row![
+            text(t!(
+                "failed-of-sats",
+                operation = failed.operation,
+                amount = i18n::format_number(failed.operation.amount()),
+                status = status
+            )),
+            button(text(t!("retry-now")))
+                .on_press_maybe((!retrying).then_some(Message::RetryNow(failed.id))),
+            button(text(t!("discard"))).on_press(Message::DiscardFailed(failed.id))
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center),
+        text(&failed.error).size(12)
+    ]
+    .into()
+}
+
+/// What a result left for the pending panel is, by the view it opens
+fn ready_label(view: &View) -> String {
+    match view {
+        View::Token => t!("token-created"),
+        View::ConfirmSend => t!("token-quote-ready"),
+        _ => t!("payment-quote-ready"),
+    }
+}
+
+/// Row of a sent token, with what taking it back takes given the time it
+/// becomes `reclaimable_at`
+fn sent_token_row(sent: &SentToken, reclaimable_at: Option<u64>) -> Element<Message> {
+    let timestamp = chrono::DateTime::from_timestamp(sent.timestamp, 0)
+        .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default();
+    let action: Element<Message> = match reclaimable_at {
+        Some(0) => button(text(t!("reclaim")))
+            .on_press(Message::Reclaim(sent.id))
+            .into(),
+        Some(at) if at <= unix_time() as u64 => button(text(t!("refund-now-available")))
+            .on_press(Message::Reclaim(sent.id))
+            .into(),
+        Some(at) => text(t!("refundable-from", time = format_local_time(at as i64))).into(),
+        None => text(t!("locked-to-recipient")).into(),
+    };
+
+    row![
+        text(timestamp),
+        text(t!(
+            "balance-sats",
+            balance = i18n::format_number(sent.amount)
+        )),
+        text(sent.mint_url.to_string()),
+        action
+    ]
+    .spacing(10)
+    .align_items(Alignment::Center)
+    .into()
+}
+
+fn recurrence_label(recurrence: Recurrence) -> String {
+    match recurrence {
+        Recurrence::Once => t!("once"),
+        Recurrence::Daily => t!("daily"),
+        Recurrence::Weekly => t!("weekly"),
+        Recurrence::Monthly => t!("monthly"),
+    }
+}
+
+/// `YYYY-MM-DD HH:MM` of unix time `timestamp` in the local timezone
+fn format_local_time(timestamp: i64) -> String {
+    chrono::Local
+        .timestamp_opt(timestamp, 0)
+        .earliest()
+        .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+/// Unix time of a `YYYY-MM-DD HH:MM` local date and time
+fn parse_local_time(input: &str) -> Option<i64> {
+    let time = chrono::NaiveDateTime::parse_from_str(input.trim(), "%Y-%m-%d %H:%M").ok()?;
+
+    Some(
+        chrono::Local
+            .from_local_datetime(&time)
+            .earliest()?
+            .timestamp(),
+    )
+}
+
+fn contact_row(contact: &Contact) -> Element<Message> {
+    let details: Vec<&str> = [
+        contact.lightning_address.as_deref(),
+        contact.npub.as_deref(),
+        contact.p2pk_pubkey.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    row![
+        text(&contact.name),
+        text(details.join(" | ")),
+        button(text(t!("delete"))).on_press(Message::DeleteContact(contact.id))
+    ]
+    .spacing(10)
+    .align_items(Alignment::Center)
+    .into()
+}
+
+fn nwc_connection_row(connection: &NwcConnection) -> Element<Message> {
+    row![
+        text(&connection.name),
+        text(t!(
+            "of-sats-left",
+            remaining = i18n::format_number(connection.remaining()),
+            budget = i18n::format_number(connection.budget)
+        )),
+        text(&connection.relay),
+        button(text(t!("delete"))).on_press(Message::DeleteNwcConnection(connection.id))
+    ]
+    .spacing(10)
+    .align_items(Alignment::Center)
+    .into()
+}
+
+fn proof_summary(summary: &ProofSummary) -> Element<Message> {
+    let denominations = summary.denominations.iter().fold(
+        column![].spacing(5),
+        |col, ((keyset_id, amount), count)| {
+            col.push(text(t!(
+                "keyset-proofs",
+                keyset_id = keyset_id,
+                amount = i18n::format_number(amount),
+                count = count
+            )))
+        },
+    );
+
+    column![
+        text(summary.mint_url.to_string()).size(20),
+        denominations,
+        row![
+            text(t!("proof-count", count = summary.count)),
+            button(text(t!(
+                "consolidate-fee-sats",
+                consolidation_fee = i18n::format_number(summary.consolidation_fee)
+            )))
+            .on_press_maybe(
+                (summary.count > 1).then(|| Message::Consolidate(summary.mint_url.clone()))
+            )
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center)
+    ]
+    .spacing(10)
+    .into()
+}
+
+/// Mint found on nostr with its reviews, added through the mint review
+fn discovered_mint_row<'a>(
+    discovered: &'a DiscoveredMint,
+    mints: &[UncheckedUrl],
+) -> Element<'a, Message> {
+    let reviews = match discovered.rating {
+        Some(rating) => t!(
+            "mint-rating",
+            rating = i18n::format_decimal(f64::from(rating), 1),
+            reviews = discovered.reviews
+        ),
+        None => t!("mint-reviews", reviews = discovered.reviews),
+    };
+    let units = if discovered.units.is_empty() {
+        t!("units-unknown")
+    } else {
+        t!("mint-units", units = discovered.units.join(", "))
+    };
+    let added = mints.contains(&discovered.mint_url);
+
+    row![
+        column![
+            text(discovered.mint_url.to_string()),
+            text(reviews).size(14),
+            text(units).size(14)
+        ]
+        .spacing(2)
+        .width(Length::Fill),
+        button(text(if added { t!("added") } else { t!("add") })).on_press_maybe(
+            (!added).then(|| Message::AddDiscoveredMint(discovered.mint_url.clone()))
+        )
+    ]
+    .spacing(10)
+    .align_items(Alignment::Center)
+    .into()
+}
+
+fn mint_info_details(details: &MintDetails) -> Column<Message> {
+    let info = &details.info;
+    let nuts = info
+        .supported_nuts()
+        .iter()
+        .map(u32::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    column![]
+        .push_maybe(
+            details
+                .icon
+                .clone()
+                .map(|icon| image(icon).width(64).height(64)),
+        )
+        .push(
+            text(
+                info.name
+                    .clone()
+                    .unwrap_or_else(|| details.mint_url.to_string()),
+            )
+            .size(30),
+        )
+        .push_maybe(info.description.as_ref().map(text))
+        .push_maybe(info.description_long.as_ref().map(text))
+        .push_maybe(
+            info.version
+                .as_ref()
+                .map(|version| text(t!("version", version = version))),
+        )
+        .push(text(t!("supported-nuts", nuts = nuts)))
+        .push_maybe(
+            info.tos_url
+                .as_ref()
+                .map(|url| text(t!("terms-of-service", url = url))),
+        )
+        .push(
+            info.contact
+                .iter()
+                .fold(column![].spacing(5), |col, contact| {
+                    col.push(text(format!("{}: {}", contact.method(), contact.info())))
+                }),
+        )
+        .spacing(10)
+}
+
+fn mint_review<'a>(review: &'a MintReview, swap_target: &UncheckedUrl) -> Column<'a, Message> {
+    let details = match &review.details {
+        Some(Ok(details)) => mint_info_details(details),
+        Some(Err(err)) => column![
+            text(review.mint_url.to_string()).size(30),
+            text(t!("mint-unreachable-error", err = err)).color(ERROR_COLOR)
+        ],
+        None => column![text(t!("loading-mint-info"))],
+    };
+    let fees = match review.input_fee_ppk {
+        Some(0) => t!("no-input-fees"),
+        Some(ppk) => t!("input-fee-ppk", ppk = ppk),
+        None => t!("input-fees-unknown"),
+    };
+
+    column![
+        text(t!("review-mint")).size(30),
+        details,
+        text(fees),
+        container(text(t!("custodian-warning")))
+            .padding(10)
+            .style(container::rounded_box),
+        checkbox(t!("custodian-acknowledge"), review.acknowledged)
+            .on_toggle(Message::MintReviewAcknowledged),
+        row![button(text(match review.action {
+            ReviewAction::AddMint => t!("add-mint-action"),
+            _ => t!("add-mint-and-claim"),
+        }))
+        .on_press_maybe(review.acknowledged.then_some(Message::ConfirmMintReview))]
+        .push_maybe(matches!(review.action, ReviewAction::Receive(_)).then(|| {
+            button(text(t!("claim-and-move-to", swap_target = swap_target)))
+                .on_press_maybe(review.acknowledged.then_some(Message::ClaimAndSwap))
+        }))
+        .push(button(text(t!("cancel"))).on_press(Message::CancelMintReview))
+        .spacing(10)
+    ]
+    .spacing(10)
+}
+
+fn offer_details(details: &OfferDetails) -> Element<Message> {
+    let amount = match (details.amount_msat, &details.currency) {
+        (Some(msat), _) => t!("balance-sats", balance = i18n::format_number(msat / 1000)),
+        (None, Some(currency)) => t!("amount-in", currency = currency),
+        (None, None) => t!("offer-any-amount"),
+    };
+
+    column![text(amount).size(30)]
+        .push_maybe(details.description.clone().map(text))
+        .push_maybe(
+            details
+                .issuer
+                .as_ref()
+                .map(|issuer| text(t!("from", active_mint = issuer))),
+        )
+        .push_maybe(details.expires_at.map(|expires_at| {
+            let expires_at = chrono::DateTime::from_timestamp(expires_at as i64, 0)
+                .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default();
+            text(t!("expires", expires_at = expires_at))
+        }))
+        .spacing(5)
+        .into()
+}
+
+fn invoice_details(details: &InvoiceDetails) -> Element<Message> {
+    let amount = match details.amount {
+        Some(amount) => t!("balance-sats", balance = i18n::format_number(amount)),
+        None => t!("any-amount"),
+    };
+    let expires_at = chrono::DateTime::from_timestamp(details.expires_at as i64, 0)
+        .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default();
+
+    column![
+        text(amount).size(30),
+        text(details.description.clone().unwrap_or_default()),
+        text(t!("payee", payee = details.payee)),
+        text(t!("expires", expires_at = expires_at))
+    ]
+    .spacing(5)
+    .into()
 }
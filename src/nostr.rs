@@ -0,0 +1,239 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use bitcoin::bip32::{DerivationPath, ExtendedPrivKey};
+use bitcoin::secp256k1::Secp256k1;
+use bitcoin::Network;
+use futures::SinkExt;
+use iced::Subscription;
+use iced_cashu_core::config;
+use nostr_sdk::nips::nip04;
+use nostr_sdk::{
+    Client, Event, EventBuilder, EventId, Filter, Keys, Kind, PublicKey, RelayPoolNotification,
+    SecretKey, Timestamp, ToBech32,
+};
+
+use crate::input;
+
+/// NIP-06 derivation path of the wallet's nostr identity
+const NOSTR_DERIVATION_PATH: &str = "m/44'/1237'/0'/0/0";
+/// Imported or generated identity used instead of the derived one,
+/// encrypted with the wallet password
+const NOSTR_KEY_FILE: &str = "nostr_key.enc";
+
+/// How far back the inbox looks for DMs when it starts
+const INBOX_LOOKBACK: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Relays used until the user configures their own
+pub const DEFAULT_RELAYS: [&str; 3] = [
+    "wss://relay.damus.io",
+    "wss://nos.lol",
+    "wss://relay.primal.net",
+];
+
+/// Nostr identity of the wallet, derived from the seed
+pub fn derive_keys(seed: &[u8]) -> anyhow::Result<Keys> {
+    let secp = Secp256k1::new();
+    let xpriv = ExtendedPrivKey::new_master(Network::Bitcoin, seed)?
+        .derive_priv(&secp, &DerivationPath::from_str(NOSTR_DERIVATION_PATH)?)?;
+
+    Ok(Keys::new(SecretKey::from_slice(
+        &xpriv.private_key.secret_bytes(),
+    )?))
+}
+
+/// Identity imported or generated for the profile, if it doesn't use the one
+/// derived from the seed
+pub fn stored_keys(password: &str) -> anyhow::Result<Option<Keys>> {
+    let path = config::wallet_dir().join(NOSTR_KEY_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let secret = config::decrypt(&std::fs::read(path)?, password)?;
+
+    Ok(Some(Keys::new(SecretKey::from_slice(&secret)?)))
+}
+
+/// Use `keys` as the profile's identity instead of the derived one
+pub fn store_keys(keys: &Keys, password: &str) -> anyhow::Result<()> {
+    let secret = keys.secret_key()?.secret_bytes();
+    std::fs::create_dir_all(config::wallet_dir())?;
+    std::fs::write(
+        config::wallet_dir().join(NOSTR_KEY_FILE),
+        config::encrypt(&secret, password)?,
+    )?;
+
+    Ok(())
+}
+
+/// Go back to the identity derived from the seed
+pub fn remove_stored_keys() -> anyhow::Result<()> {
+    let path = config::wallet_dir().join(NOSTR_KEY_FILE);
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+
+    Ok(())
+}
+
+/// Parse an `nsec` or hex secret key
+pub fn parse_keys(input: &str) -> anyhow::Result<Keys> {
+    Ok(Keys::parse(input.trim())?)
+}
+
+pub fn default_relays() -> Vec<String> {
+    DEFAULT_RELAYS
+        .iter()
+        .map(|relay| relay.to_string())
+        .collect()
+}
+
+/// Relay urls from a comma or whitespace separated list
+pub fn parse_relays(input: &str) -> Vec<String> {
+    input
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|relay| !relay.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Parse an `npub` or hex public key
+pub fn parse_pubkey(input: &str) -> anyhow::Result<PublicKey> {
+    Ok(PublicKey::parse(input.trim())?)
+}
+
+/// `npub` of `pubkey`, falling back to hex
+pub fn npub(pubkey: &PublicKey) -> String {
+    pubkey.to_bech32().unwrap_or_else(|_| pubkey.to_hex())
+}
+
+/// Client connected to `relays`
+///
+/// Events are signed by the caller, so the client holds no keys.
+pub async fn connect<I, S>(relays: I) -> anyhow::Result<Client>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let client = Client::default();
+    for relay in relays {
+        client.add_relay(relay.as_ref()).await?;
+    }
+    client.connect().await;
+
+    Ok(client)
+}
+
+/// Send `content` to `receiver` as a NIP-04 encrypted direct message
+///
+/// Fails if no relay accepted the event.
+pub async fn send_dm(
+    keys: &Keys,
+    relays: &[String],
+    receiver: PublicKey,
+    content: &str,
+) -> anyhow::Result<EventId> {
+    let client = connect(relays).await?;
+
+    let event =
+        EventBuilder::encrypted_direct_msg(keys, receiver, content, None)?.to_event(keys)?;
+    let result = client.send_event(event).await;
+    client.disconnect().await?;
+
+    Ok(result?)
+}
+
+/// Cashu token found in a direct message to the wallet
+#[derive(Debug, Clone)]
+pub struct IncomingToken {
+    pub event_id: String,
+    /// `npub` of the sender
+    pub sender: String,
+    pub token: String,
+    /// Unix timestamp of the DM in seconds
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone)]
+pub enum InboxEvent {
+    Token(IncomingToken),
+    Error(String),
+}
+
+/// Watch the wallet's direct messages for cashu tokens
+///
+/// DMs from the last week are replayed on start, callers dedupe by event id.
+pub fn dm_inbox(keys: Keys, relays: Vec<String>) -> Subscription<InboxEvent> {
+    let id = ("nostr-inbox", keys.public_key(), relays.clone());
+
+    iced::subscription::channel(id, 100, move |mut output| async move {
+        if let Err(err) = run_inbox(&keys, &relays, &mut output).await {
+            tracing::warn!("Nostr inbox stopped: {}", err);
+            let _ = output.send(InboxEvent::Error(err.to_string())).await;
+        }
+
+        std::future::pending().await
+    })
+}
+
+async fn run_inbox(
+    keys: &Keys,
+    relays: &[String],
+    output: &mut futures::channel::mpsc::Sender<InboxEvent>,
+) -> anyhow::Result<()> {
+    let client = connect(relays).await?;
+
+    let filter = Filter::new()
+        .kind(Kind::EncryptedDirectMessage)
+        .pubkey(keys.public_key())
+        .since(Timestamp::now() - INBOX_LOOKBACK);
+    client.subscribe(vec![filter], None).await;
+
+    let mut notifications = client.notifications();
+    loop {
+        let RelayPoolNotification::Event { event, .. } = notifications.recv().await? else {
+            continue;
+        };
+
+        match incoming_token(keys, &event) {
+            Ok(Some(token)) => output.send(InboxEvent::Token(token)).await?,
+            Ok(None) => (),
+            Err(err) => tracing::debug!("Could not read DM {}: {}", event.id, err),
+        }
+    }
+}
+
+/// Decrypt a DM to the wallet, `None` if it holds no token
+fn incoming_token(keys: &Keys, event: &Event) -> anyhow::Result<Option<IncomingToken>> {
+    if event.kind != Kind::EncryptedDirectMessage {
+        return Ok(None);
+    }
+
+    let content = nip04::decrypt(keys.secret_key()?, &event.pubkey, &event.content)?;
+
+    Ok(input::find_token(&content).map(|token| IncomingToken {
+        event_id: event.id.to_hex(),
+        sender: npub(&event.pubkey),
+        token,
+        timestamp: event.created_at.as_u64() as i64,
+    }))
+}
+
+/// Send `content` to `receiver` as a NIP-17 gift wrapped direct message
+pub async fn send_private_msg(
+    keys: &Keys,
+    relays: &[String],
+    receiver: PublicKey,
+    content: &str,
+) -> anyhow::Result<EventId> {
+    let client = Client::new(keys);
+    for relay in relays {
+        client.add_relay(relay.as_str()).await?;
+    }
+    client.connect().await;
+
+    let result = client.send_private_msg(receiver, content, None).await;
+    client.disconnect().await?;
+
+    Ok(result?)
+}
@@ -0,0 +1,13 @@
+const APP_NAME: &str = "Cashu Wallet";
+
+/// Show a native desktop notification, logging if the system refuses it
+pub fn show(summary: &str, body: &str) {
+    if let Err(err) = notify_rust::Notification::new()
+        .appname(APP_NAME)
+        .summary(summary)
+        .body(body)
+        .show()
+    {
+        tracing::warn!("Could not show notification: {}", err);
+    }
+}
@@ -0,0 +1,151 @@
+use anyhow::{anyhow, bail};
+use cdk::nuts::{PublicKey, Token};
+use cdk::UncheckedUrl;
+use futures::SinkExt;
+use iced::Subscription;
+use nostr_sdk::{
+    Alphabet, Event, EventBuilder, Filter, Keys, Kind, RelayPoolNotification, SingleLetterTag, Tag,
+};
+use serde_json::{json, Value};
+
+use crate::nostr;
+
+/// NIP-61 nutzap info event, replaceable
+const NUTZAP_INFO_KIND: u16 = 10019;
+/// NIP-61 nutzap event
+const NUTZAP_KIND: u16 = 9321;
+
+/// Nutzap locked to the wallet's P2PK key
+#[derive(Debug, Clone)]
+pub struct Nutzap {
+    pub event_id: String,
+    /// `npub` of the zapper
+    pub sender: String,
+    pub mint_url: UncheckedUrl,
+    /// V3 token of the zapped proofs
+    pub token: String,
+    /// Unix timestamp of the zap in seconds
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone)]
+pub enum NutzapEvent {
+    Zap(Nutzap),
+    Error(String),
+}
+
+/// Announce which mints, relays and P2PK key zappers should use
+pub async fn publish_info(
+    keys: Keys,
+    relays: Vec<String>,
+    p2pk: PublicKey,
+    mints: Vec<UncheckedUrl>,
+) -> anyhow::Result<()> {
+    let mut tags = vec![];
+    for relay in &relays {
+        tags.push(Tag::parse(&["relay", relay.as_str()])?);
+    }
+    for mint_url in &mints {
+        tags.push(Tag::parse(&["mint", &mint_url.to_string(), "sat"])?);
+    }
+    tags.push(Tag::parse(&["pubkey", &p2pk.to_hex()])?);
+
+    let event = EventBuilder::new(Kind::from(NUTZAP_INFO_KIND), "", tags).to_event(&keys)?;
+
+    let client = nostr::connect(&relays).await?;
+    let result = client.send_event(event).await;
+    client.disconnect().await?;
+    result?;
+
+    Ok(())
+}
+
+/// Watch relays for nutzaps to the wallet from any of `mints`
+///
+/// Every matching zap on the relays is replayed on start, callers dedupe by
+/// event id.
+pub fn receiver(
+    keys: Keys,
+    relays: Vec<String>,
+    mints: Vec<UncheckedUrl>,
+) -> Subscription<NutzapEvent> {
+    let id = ("nutzaps", keys.public_key(), relays.clone(), mints.clone());
+
+    iced::subscription::channel(id, 100, move |mut output| async move {
+        if let Err(err) = run(&keys, &relays, &mints, &mut output).await {
+            tracing::warn!("Nutzap receiver stopped: {}", err);
+            let _ = output.send(NutzapEvent::Error(err.to_string())).await;
+        }
+
+        std::future::pending().await
+    })
+}
+
+async fn run(
+    keys: &Keys,
+    relays: &[String],
+    mints: &[UncheckedUrl],
+    output: &mut futures::channel::mpsc::Sender<NutzapEvent>,
+) -> anyhow::Result<()> {
+    let client = nostr::connect(relays).await?;
+
+    let filter = Filter::new()
+        .kind(Kind::from(NUTZAP_KIND))
+        .pubkey(keys.public_key())
+        .custom_tag(
+            SingleLetterTag::lowercase(Alphabet::U),
+            mints.iter().map(|mint_url| mint_url.to_string()),
+        );
+    client.subscribe(vec![filter], None).await;
+
+    let mut notifications = client.notifications();
+    loop {
+        let RelayPoolNotification::Event { event, .. } = notifications.recv().await? else {
+            continue;
+        };
+        if event.kind != Kind::from(NUTZAP_KIND) {
+            continue;
+        }
+
+        match parse_nutzap(&event) {
+            // Relays don't all honour the mint filter
+            Ok(nutzap) if !mints.contains(&nutzap.mint_url) => (),
+            Ok(nutzap) => output.send(NutzapEvent::Zap(nutzap)).await?,
+            Err(err) => tracing::debug!("Ignoring nutzap {}: {}", event.id, err),
+        }
+    }
+}
+
+/// Collect the `proof` tags of a nutzap into a token for its `u` mint
+fn parse_nutzap(event: &Event) -> anyhow::Result<Nutzap> {
+    let mut mint_url = None;
+    let mut proofs = vec![];
+
+    for tag in event.tags.iter() {
+        match tag.as_vec() {
+            [kind, mint, ..] if kind == "u" => mint_url = Some(mint.clone()),
+            [kind, proof, ..] if kind == "proof" => {
+                proofs.push(serde_json::from_str::<Value>(proof)?)
+            }
+            _ => (),
+        }
+    }
+
+    let mint_url = mint_url.ok_or_else(|| anyhow!("Nutzap has no mint"))?;
+    if proofs.is_empty() {
+        bail!("Nutzap has no proofs");
+    }
+
+    let token: Token = serde_json::from_value(json!({
+        "token": [{ "mint": mint_url, "proofs": proofs }],
+        "unit": "sat",
+    }))?;
+
+    Ok(Nutzap {
+        event_id: event.id.to_hex(),
+        sender: nostr::npub(&event.pubkey),
+        mint_url: UncheckedUrl::from(mint_url),
+        token: token.to_string(),
+        timestamp: event.created_at.as_u64() as i64,
+    })
+}
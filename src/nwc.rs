@@ -0,0 +1,284 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::anyhow;
+use futures::channel::mpsc;
+use futures::{SinkExt, StreamExt};
+use iced::Subscription;
+use nostr_sdk::nips::nip04;
+use nostr_sdk::nips::nip47::{
+    ErrorCode, GetBalanceResponseResult, Method, NIP47Error, PayInvoiceResponseResult, Request,
+    RequestParams, Response, ResponseResult,
+};
+use nostr_sdk::{
+    Event, EventBuilder, EventId, Filter, JsonUtil, Keys, Kind, PublicKey, RelayPoolNotification,
+    SecretKey, Tag, Timestamp, Url,
+};
+
+use crate::db::NwcConnection;
+use crate::nostr;
+
+/// Methods advertised in the wallet service info event
+const SUPPORTED_METHODS: &str = "pay_invoice get_balance";
+
+/// Request from a connected app that the wallet has to act on
+#[derive(Debug, Clone)]
+pub struct NwcRequest {
+    pub connection_id: i64,
+    event_id: EventId,
+    client: PublicKey,
+    service: PublicKey,
+    pub method: NwcMethod,
+}
+
+#[derive(Debug, Clone)]
+pub enum NwcMethod {
+    GetBalance,
+    PayInvoice { invoice: String },
+}
+
+#[derive(Debug, Clone)]
+pub enum NwcResult {
+    /// Spendable balance in sats
+    Balance(u64),
+    Paid {
+        preimage: String,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub enum NwcError {
+    /// Payment would exceed the connection's budget
+    QuotaExceeded,
+    InsufficientBalance,
+    Other(String),
+}
+
+impl NwcError {
+    fn code(&self) -> ErrorCode {
+        match self {
+            NwcError::QuotaExceeded => ErrorCode::QuotaExceeded,
+            NwcError::InsufficientBalance => ErrorCode::InsufficientBalance,
+            NwcError::Other(_) => ErrorCode::Other,
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            NwcError::QuotaExceeded => "Budget exceeded".to_string(),
+            NwcError::InsufficientBalance => "Insufficient balance".to_string(),
+            NwcError::Other(message) => message.clone(),
+        }
+    }
+}
+
+/// Result of a request, sent back to the service to publish
+#[derive(Debug, Clone)]
+pub struct NwcReply {
+    pub request: NwcRequest,
+    pub result: Result<NwcResult, NwcError>,
+}
+
+#[derive(Debug, Clone)]
+pub enum NwcEvent {
+    /// Service is listening, replies go through the sender
+    Ready(mpsc::Sender<NwcReply>),
+    Request(NwcRequest),
+    Error(String),
+}
+
+/// Fresh service and client secrets for a new connection, hex encoded
+pub fn generate_secrets() -> anyhow::Result<(String, String)> {
+    let service = Keys::generate();
+    let client = Keys::generate();
+
+    Ok((
+        service.secret_key()?.to_secret_hex(),
+        client.secret_key()?.to_secret_hex(),
+    ))
+}
+
+/// `nostr+walletconnect://` URI to paste into the connecting app
+pub fn connection_uri(connection: &NwcConnection) -> anyhow::Result<String> {
+    let service = Keys::new(SecretKey::from_hex(&connection.service_secret)?);
+
+    let uri = Url::parse_with_params(
+        &format!("nostr+walletconnect://{}", service.public_key().to_hex()),
+        &[
+            ("relay", connection.relay.as_str()),
+            ("secret", connection.client_secret.as_str()),
+        ],
+    )?;
+
+    Ok(uri.to_string())
+}
+
+/// Listen for NIP-47 requests to any of `connections`
+pub fn service(connections: Vec<NwcConnection>) -> Subscription<NwcEvent> {
+    let id: Vec<i64> = connections.iter().map(|connection| connection.id).collect();
+
+    iced::subscription::channel(("nwc", id), 100, move |mut output| async move {
+        if let Err(err) = run(connections, &mut output).await {
+            tracing::warn!("NWC service stopped: {}", err);
+            let _ = output.send(NwcEvent::Error(err.to_string())).await;
+        }
+
+        std::future::pending().await
+    })
+}
+
+struct ServiceKeys {
+    connection_id: i64,
+    keys: Keys,
+    client: PublicKey,
+}
+
+async fn run(
+    connections: Vec<NwcConnection>,
+    output: &mut mpsc::Sender<NwcEvent>,
+) -> anyhow::Result<()> {
+    let mut services = HashMap::new();
+    for connection in &connections {
+        let keys = Keys::new(SecretKey::from_hex(&connection.service_secret)?);
+        let client = Keys::new(SecretKey::from_hex(&connection.client_secret)?).public_key();
+
+        services.insert(
+            keys.public_key(),
+            ServiceKeys {
+                connection_id: connection.id,
+                keys,
+                client,
+            },
+        );
+    }
+
+    let relays: HashSet<&str> = connections
+        .iter()
+        .map(|connection| connection.relay.as_str())
+        .collect();
+    let client = nostr::connect(relays).await?;
+
+    for service in services.values() {
+        let info = EventBuilder::new(Kind::WalletConnectInfo, SUPPORTED_METHODS, [])
+            .to_event(&service.keys)?;
+        client.send_event(info).await?;
+    }
+
+    let filter = Filter::new()
+        .kind(Kind::WalletConnectRequest)
+        .pubkeys(services.keys().copied())
+        .since(Timestamp::now());
+    client.subscribe(vec![filter], None).await;
+
+    let (sender, mut replies) = mpsc::channel(100);
+    output.send(NwcEvent::Ready(sender)).await?;
+
+    let mut notifications = client.notifications();
+    loop {
+        tokio::select! {
+            notification = notifications.recv() => {
+                let RelayPoolNotification::Event { event, .. } = notification? else {
+                    continue;
+                };
+
+                match parse_request(&services, &event) {
+                    Ok(Some(request)) => output.send(NwcEvent::Request(request)).await?,
+                    Ok(None) => (),
+                    Err(err) => tracing::debug!("Ignoring NWC event {}: {}", event.id, err),
+                }
+            }
+            Some(reply) = replies.next() => {
+                let Some(service) = services.get(&reply.request.service) else {
+                    continue;
+                };
+                client.send_event(response_event(service, reply)?).await?;
+            }
+        }
+    }
+}
+
+/// Decrypt a request event, `None` if it isn't meant for one of our connections
+fn parse_request(
+    services: &HashMap<PublicKey, ServiceKeys>,
+    event: &Event,
+) -> anyhow::Result<Option<NwcRequest>> {
+    if event.kind != Kind::WalletConnectRequest {
+        return Ok(None);
+    }
+
+    let Some((service_pubkey, service)) = event
+        .public_keys()
+        .find_map(|pubkey| services.get_key_value(pubkey))
+    else {
+        return Ok(None);
+    };
+    if event.pubkey != service.client {
+        return Err(anyhow!("Request not signed by the connection's client key"));
+    }
+
+    let content = nip04::decrypt(service.keys.secret_key()?, &event.pubkey, &event.content)?;
+    let request = Request::from_json(content)?;
+
+    let method = match request.params {
+        RequestParams::GetBalance => NwcMethod::GetBalance,
+        RequestParams::PayInvoice(params) => NwcMethod::PayInvoice {
+            invoice: params.invoice,
+        },
+        _ => return Err(anyhow!("Unsupported method {}", request.method)),
+    };
+
+    Ok(Some(NwcRequest {
+        connection_id: service.connection_id,
+        event_id: event.id,
+        client: event.pubkey,
+        service: *service_pubkey,
+        method,
+    }))
+}
+
+fn response_event(service: &ServiceKeys, reply: NwcReply) -> anyhow::Result<Event> {
+    let result_type = match reply.request.method {
+        NwcMethod::GetBalance => Method::GetBalance,
+        NwcMethod::PayInvoice { .. } => Method::PayInvoice,
+    };
+
+    let response = match reply.result {
+        Ok(NwcResult::Balance(sats)) => Response {
+            result_type,
+            error: None,
+            result: Some(ResponseResult::GetBalance(GetBalanceResponseResult {
+                balance: sats * 1000,
+            })),
+        },
+        Ok(NwcResult::Paid { preimage }) => Response {
+            result_type,
+            error: None,
+            result: Some(ResponseResult::PayInvoice(PayInvoiceResponseResult {
+                preimage,
+            })),
+        },
+        Err(err) => Response {
+            result_type,
+            error: Some(NIP47Error {
+                code: err.code(),
+                message: err.message(),
+            }),
+            result: None,
+        },
+    };
+
+    let content = nip04::encrypt(
+        service.keys.secret_key()?,
+        &reply.request.client,
+        response.as_json(),
+    )?;
+
+    Ok(EventBuilder::new(
+        Kind::WalletConnectResponse,
+        content,
+        [
+            Tag::public_key(reply.request.client),
+            Tag::event(reply.request.event_id),
+        ],
+    )
+    .to_event(&service.keys)?)
+}
@@ -0,0 +1,48 @@
+/// Most commands the palette lists at once
+const MAX_RESULTS: usize = 8;
+
+/// How well `query` matches `label`, `None` unless all of its characters
+/// appear in order
+///
+/// Matches score higher the more of them run together or start a word.
+pub fn fuzzy_score(query: &str, label: &str) -> Option<i64> {
+    let label: Vec<char> = label.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut last_match = None;
+
+    for c in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = position + label[position..].iter().position(|&l| l == c)?;
+
+        score += 1;
+        if last_match.is_some_and(|last| last + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || label[found - 1] == ' ' {
+            score += 3;
+        }
+        last_match = Some(found);
+        position = found + 1;
+    }
+
+    // Shorter labels are closer matches
+    Some(score * 100 - label.len() as i64)
+}
+
+/// `commands` matching `query`, best first, all of them in order if it is
+/// empty
+pub fn search<T>(query: &str, commands: Vec<(String, T)>) -> Vec<(String, T)> {
+    let mut matches: Vec<(i64, (String, T))> = commands
+        .into_iter()
+        .filter_map(|command| Some((fuzzy_score(query, &command.0)?, command)))
+        .collect();
+    if !query.trim().is_empty() {
+        matches.sort_by_key(|(score, _)| -score);
+    }
+
+    matches
+        .into_iter()
+        .take(MAX_RESULTS)
+        .map(|(_, command)| command)
+        .collect()
+}
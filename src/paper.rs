@@ -0,0 +1,163 @@
+use std::fmt::Write;
+
+use anyhow::anyhow;
+use iced_cashu_core::token::TokenSummary;
+use iced_cashu_core::unit;
+use qrcode::{Color, QrCode};
+
+/// A4 in points
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 72.0;
+const QR_SIZE: f32 = 360.0;
+const RECEIPT_QR_SIZE: f32 = 200.0;
+/// Light modules around the code so scanners find its edges
+const QUIET_ZONE: usize = 4;
+
+/// One page PDF with `encoded` as a QR code and its amount, mint and memo
+/// printed around it, for gifting ecash on paper
+///
+/// Text is set in Helvetica, which PDF readers ship, so no font is embedded.
+pub fn render_pdf(encoded: &str, summary: &TokenSummary) -> anyhow::Result<Vec<u8>> {
+    let code = QrCode::new(encoded.as_bytes())
+        .map_err(|err| anyhow!("Token does not fit in a QR code: {}", err))?;
+
+    let mut content = String::new();
+    let mut y = PAGE_HEIGHT - MARGIN;
+    text_line(&mut content, 16.0, y, "Cashu ecash");
+    y -= 40.0;
+    text_line(
+        &mut content,
+        32.0,
+        y,
+        &unit::format_amount(summary.amount, &summary.unit),
+    );
+    if let Some(memo) = &summary.memo {
+        y -= 28.0;
+        text_line(&mut content, 14.0, y, &format!("\"{}\"", memo));
+    }
+
+    let top = y - 24.0;
+    qr_square(&mut content, &code, top, QR_SIZE);
+
+    y = top - QR_SIZE - 24.0;
+    for mint_url in &summary.mints {
+        text_line(&mut content, 11.0, y, &format!("Mint: {}", mint_url));
+        y -= 16.0;
+    }
+    text_line(
+        &mut content,
+        11.0,
+        y - 8.0,
+        "Scan with a cashu wallet to claim. Whoever holds this paper can spend it.",
+    );
+
+    Ok(pdf(&content))
+}
+
+/// One page PDF receipt of a point-of-sale payment, `lines` printed above
+/// `receipt` as a QR code the customer can scan to keep a copy
+pub fn render_receipt_pdf(lines: &[String], receipt: &str) -> anyhow::Result<Vec<u8>> {
+    let code = QrCode::new(receipt.as_bytes())
+        .map_err(|err| anyhow!("Receipt does not fit in a QR code: {}", err))?;
+
+    let mut content = String::new();
+    let mut y = PAGE_HEIGHT - MARGIN;
+    for (index, line) in lines.iter().enumerate() {
+        // The first line is the title
+        let size = if index == 0 { 20.0 } else { 11.0 };
+        text_line(&mut content, size, y, line);
+        y -= if index == 0 { 32.0 } else { 16.0 };
+    }
+    qr_square(&mut content, &code, y - 16.0, RECEIPT_QR_SIZE);
+
+    Ok(pdf(&content))
+}
+
+/// `code` drawn `size` points wide, centered, with its top edge at `top`
+fn qr_square(content: &mut String, code: &QrCode, top: f32, size: f32) {
+    let modules = code.width() + 2 * QUIET_ZONE;
+    let module_size = size / modules as f32;
+    let left = (PAGE_WIDTH - size) / 2.0;
+    for (index, color) in code.to_colors().iter().enumerate() {
+        if *color != Color::Dark {
+            continue;
+        }
+        let column = index % code.width() + QUIET_ZONE;
+        let row = index / code.width() + QUIET_ZONE;
+        let _ = writeln!(
+            content,
+            "{:.2} {:.2} {:.2} {:.2} re",
+            left + column as f32 * module_size,
+            top - (row + 1) as f32 * module_size,
+            module_size,
+            module_size
+        );
+    }
+    content.push_str("f\n");
+}
+
+/// Left aligned line of text with its baseline at `y`
+fn text_line(content: &mut String, size: f32, y: f32, line: &str) {
+    let _ = writeln!(
+        content,
+        "BT /F1 {} Tf {} {} Td ({}) Tj ET",
+        size,
+        MARGIN,
+        y,
+        escape(line)
+    );
+}
+
+/// `line` as a PDF string in WinAnsi, characters outside Latin-1 as `?`
+fn escape(line: &str) -> String {
+    line.chars()
+        .map(|c| match c {
+            '(' | ')' | '\\' => format!("\\{}", c),
+            ' '..='~' => c.to_string(),
+            '\u{a0}'..='\u{ff}' => format!("\\{:03o}", c as u32),
+            _ => "?".to_string(),
+        })
+        .collect()
+}
+
+/// PDF file of a single page drawn by `content`
+fn pdf(content: &str) -> Vec<u8> {
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+             /Resources << /Font << /F1 4 0 R >> >> /Contents 5 0 R >>",
+            PAGE_WIDTH, PAGE_HEIGHT
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+            .to_string(),
+        format!(
+            "<< /Length {} >>\nstream\n{}endstream",
+            content.len(),
+            content
+        ),
+    ];
+
+    let mut file = String::from("%PDF-1.4\n");
+    let mut offsets = vec![];
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(file.len());
+        let _ = write!(file, "{} 0 obj\n{}\nendobj\n", index + 1, object);
+    }
+
+    let xref = file.len();
+    let _ = write!(file, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        let _ = write!(file, "{:010} 00000 n \n", offset);
+    }
+    let _ = write!(
+        file,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref
+    );
+
+    file.into_bytes()
+}
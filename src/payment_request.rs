@@ -0,0 +1,286 @@
+use std::time::Duration;
+
+use anyhow::{anyhow, bail};
+use base64::engine::general_purpose::{URL_SAFE, URL_SAFE_NO_PAD};
+use base64::Engine;
+use cdk::nuts::nut11::Conditions;
+use cdk::nuts::{PublicKey, SpendingConditions, Token};
+use futures::SinkExt;
+use iced::Subscription;
+use nostr_sdk::nips::nip19::Nip19Profile;
+use nostr_sdk::nips::{nip04, nip59};
+use nostr_sdk::{
+    Event, Filter, FromBech32, Keys, Kind, RelayPoolNotification, Timestamp, ToBech32,
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::nostr;
+
+const PREFIX: &str = "creqA";
+
+/// Gift wraps are backdated by up to two days, see NIP-59
+const GIFT_WRAP_LOOKBACK: Duration = Duration::from_secs(2 * 24 * 60 * 60);
+
+/// NUT-18 payment request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaymentRequest {
+    #[serde(rename = "i", default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Requested amount in `unit`, any amount if unset
+    #[serde(rename = "a", default, skip_serializing_if = "Option::is_none")]
+    pub amount: Option<u64>,
+    #[serde(rename = "u", default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    #[serde(rename = "s", default, skip_serializing_if = "Option::is_none")]
+    pub single_use: Option<bool>,
+    /// Mints the payment is accepted from, any mint if empty
+    #[serde(rename = "m", default, skip_serializing_if = "Vec::is_empty")]
+    pub mints: Vec<String>,
+    #[serde(rename = "d", default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(rename = "t", default)]
+    pub transports: Vec<Transport>,
+    /// Spending condition the payment must be locked with
+    #[serde(rename = "nut10", default, skip_serializing_if = "Option::is_none")]
+    pub nut10: Option<Nut10Option>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Nut10Option {
+    /// Secret kind, only `P2PK` is supported
+    #[serde(rename = "k")]
+    pub kind: String,
+    #[serde(rename = "d")]
+    pub data: String,
+    #[serde(rename = "t", default)]
+    pub tags: Vec<Vec<String>>,
+}
+
+impl PaymentRequest {
+    /// Conditions to lock the payment's proofs with
+    pub fn conditions(&self) -> anyhow::Result<Option<SpendingConditions>> {
+        let Some(nut10) = &self.nut10 else {
+            return Ok(None);
+        };
+        if nut10.kind != "P2PK" {
+            bail!("Unsupported spending condition {}", nut10.kind);
+        }
+
+        let pubkey = PublicKey::from_hex(&nut10.data)?;
+        let conditions = match nut10.tags.is_empty() {
+            true => None,
+            false => Some(Conditions::try_from(nut10.tags.clone())?),
+        };
+
+        Ok(Some(SpendingConditions::new_p2pk(pubkey, conditions)))
+    }
+
+    /// First transport this wallet can deliver over
+    pub fn transport(&self) -> Option<&Transport> {
+        self.transports
+            .iter()
+            .find(|transport| transport.kind == "nostr" || transport.kind == "post")
+    }
+}
+
+/// Where the payer should send the payment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transport {
+    /// `nostr` or `post`
+    #[serde(rename = "t")]
+    pub kind: String,
+    /// nprofile or URL
+    #[serde(rename = "a")]
+    pub target: String,
+    #[serde(rename = "g", default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<Vec<String>>>,
+}
+
+/// Proofs sent in response to a payment request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentPayload {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+    pub mint: String,
+    pub unit: String,
+    pub proofs: Vec<Value>,
+}
+
+impl PaymentPayload {
+    /// Payload answering `request` with the proofs of `token`
+    pub fn new(request: &PaymentRequest, token: &Token) -> anyhow::Result<Self> {
+        let value = serde_json::to_value(token)?;
+        let [mint_proofs] = value["token"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+        else {
+            bail!("Payment must come from a single mint");
+        };
+
+        Ok(PaymentPayload {
+            id: request.id.clone(),
+            memo: None,
+            mint: mint_proofs["mint"].as_str().unwrap_or_default().to_string(),
+            unit: request.unit.clone().unwrap_or_else(|| "sat".to_string()),
+            proofs: mint_proofs["proofs"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default(),
+        })
+    }
+
+    /// V3 token of the payload's proofs
+    pub fn token(&self) -> anyhow::Result<Token> {
+        Ok(serde_json::from_value(json!({
+            "token": [{ "mint": self.mint, "proofs": self.proofs }],
+            "unit": self.unit,
+            "memo": self.memo,
+        }))?)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum PaymentEvent {
+    Paid(PaymentPayload),
+    Error(String),
+}
+
+/// Random id to match payments to a request
+pub fn generate_id() -> String {
+    hex::encode(rand::thread_rng().gen::<[u8; 4]>())
+}
+
+/// Transport asking for payments as NIP-17 DMs to `keys` on `relays`
+pub fn nostr_transport(keys: &Keys, relays: &[String]) -> anyhow::Result<Transport> {
+    let nprofile = Nip19Profile::new(keys.public_key(), relays.iter().cloned())?.to_bech32()?;
+
+    Ok(Transport {
+        kind: "nostr".to_string(),
+        target: nprofile,
+        tags: Some(vec![vec!["n".to_string(), "17".to_string()]]),
+    })
+}
+
+pub fn encode(request: &PaymentRequest) -> anyhow::Result<String> {
+    let mut bytes = vec![];
+    ciborium::into_writer(request, &mut bytes)?;
+
+    Ok(format!("{}{}", PREFIX, URL_SAFE_NO_PAD.encode(bytes)))
+}
+
+pub fn decode(input: &str) -> anyhow::Result<PaymentRequest> {
+    let Some(encoded) = input.trim().strip_prefix(PREFIX) else {
+        bail!("Not a payment request");
+    };
+
+    let bytes = URL_SAFE_NO_PAD
+        .decode(encoded.trim_end_matches('='))
+        .or_else(|_| URL_SAFE.decode(encoded))?;
+
+    Ok(ciborium::from_reader(bytes.as_slice())?)
+}
+
+/// Send `payload` over `transport`, returning the `npub` for nostr transports
+///
+/// `relays` are used when the request's nprofile names none.
+pub async fn deliver(
+    keys: &Keys,
+    relays: &[String],
+    transport: &Transport,
+    payload: &PaymentPayload,
+) -> anyhow::Result<Option<String>> {
+    match transport.kind.as_str() {
+        "nostr" => {
+            let profile = Nip19Profile::from_bech32(&transport.target)?;
+            let relays = match profile.relays.is_empty() {
+                true => relays.to_vec(),
+                false => profile.relays.clone(),
+            };
+
+            nostr::send_private_msg(
+                keys,
+                &relays,
+                profile.public_key,
+                &serde_json::to_string(payload)?,
+            )
+            .await?;
+
+            Ok(Some(nostr::npub(&profile.public_key)))
+        }
+        "post" => {
+            reqwest::Client::new()
+                .post(&transport.target)
+                .json(payload)
+                .send()
+                .await?
+                .error_for_status()?;
+
+            Ok(None)
+        }
+        kind => Err(anyhow!("Unsupported transport {}", kind)),
+    }
+}
+
+/// Listen for payments to the request `id` sent to `keys` over nostr
+pub fn listen(keys: Keys, relays: Vec<String>, id: String) -> Subscription<PaymentEvent> {
+    iced::subscription::channel(
+        ("payment-request", id.clone()),
+        100,
+        move |mut output| async move {
+            if let Err(err) = run(&keys, &relays, &id, &mut output).await {
+                tracing::warn!("Payment request listener stopped: {}", err);
+                let _ = output.send(PaymentEvent::Error(err.to_string())).await;
+            }
+
+            std::future::pending().await
+        },
+    )
+}
+
+async fn run(
+    keys: &Keys,
+    relays: &[String],
+    id: &str,
+    output: &mut futures::channel::mpsc::Sender<PaymentEvent>,
+) -> anyhow::Result<()> {
+    let client = nostr::connect(relays).await?;
+
+    let filter = Filter::new()
+        .kinds([Kind::GiftWrap, Kind::EncryptedDirectMessage])
+        .pubkey(keys.public_key())
+        .since(Timestamp::now() - GIFT_WRAP_LOOKBACK);
+    client.subscribe(vec![filter], None).await;
+
+    let mut notifications = client.notifications();
+    loop {
+        let RelayPoolNotification::Event { event, .. } = notifications.recv().await? else {
+            continue;
+        };
+
+        match decrypt_payload(keys, &event) {
+            Ok(payload) if payload.id.as_deref() == Some(id) => {
+                output.send(PaymentEvent::Paid(payload)).await?
+            }
+            Ok(_) => (),
+            Err(err) => tracing::debug!("Ignoring event {}: {}", event.id, err),
+        }
+    }
+}
+
+/// Decrypt a NIP-17 or NIP-04 DM holding a payment payload
+fn decrypt_payload(keys: &Keys, event: &Event) -> anyhow::Result<PaymentPayload> {
+    let content = match event.kind {
+        Kind::GiftWrap => nip59::extract_rumor(keys, event)?.content,
+        Kind::EncryptedDirectMessage => {
+            nip04::decrypt(keys.secret_key()?, &event.pubkey, &event.content)?
+        }
+        kind => return Err(anyhow!("Unexpected event kind {}", kind)),
+    };
+
+    Ok(serde_json::from_str(&content)?)
+}
@@ -0,0 +1,174 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use anyhow::anyhow;
+use chrono::{Local, NaiveDate};
+use iced_cashu_core::config::{get_config_value, set_config_value};
+use iced_cashu_core::db::Sale;
+
+use crate::i18n;
+
+/// Tips a customer can add, in percent of the amount
+pub const TIP_PRESETS: [u64; 4] = [0, 10, 15, 20];
+/// How long the PAID screen stays up before the next sale
+pub const PAID_SCREEN_DURATION: Duration = Duration::from_secs(5);
+/// Profile config value today's takings are kept in
+const TOTALS_CONFIG: &str = "pos_totals";
+
+/// Point-of-sale mode: the sale being rung up or charged, and the day's
+/// takings
+#[derive(Debug, Default)]
+pub struct Pos {
+    pub amount: String,
+    /// Tip in percent, one of [`TIP_PRESETS`]
+    pub tip: u64,
+    /// Order note printed on the receipt
+    pub note: String,
+    /// Mint quote of the sale waiting to be paid
+    pub charging: Option<String>,
+    /// Sale just paid and when, shown until the next customer
+    pub paid: Option<(Sale, Instant)>,
+    /// The receipt of the paid sale is open, which keeps the PAID screen up
+    pub show_receipt: bool,
+    pub receipt_path: Option<String>,
+    pub sales_path: Option<String>,
+    pub totals: DailyTotals,
+}
+
+impl Pos {
+    /// Sats to charge for `amount` with the selected tip, rounded to the
+    /// nearest sat
+    pub fn total(&self, amount: u64) -> u64 {
+        amount + (amount * self.tip + 50) / 100
+    }
+
+    /// Whether `quote_id` is the sale being charged
+    pub fn is_charging(&self, quote_id: &str) -> bool {
+        self.charging.as_deref() == Some(quote_id)
+    }
+
+    /// Show the PAID screen for `sale` and count it in the day's takings
+    pub fn paid(&mut self, sale: Sale) {
+        self.charging = None;
+        if let Err(err) = self.totals.add(sale.amount) {
+            tracing::warn!("Could not save the day's takings: {}", err);
+        }
+        self.paid = Some((sale, Instant::now()));
+    }
+
+    /// Clear the till for the next customer
+    pub fn next_sale(&mut self) {
+        self.amount = "".to_string();
+        self.tip = 0;
+        self.note = "".to_string();
+        self.paid = None;
+        self.show_receipt = false;
+        self.receipt_path = None;
+    }
+
+    /// Whether the PAID screen has been up long enough
+    pub fn paid_screen_done(&self) -> bool {
+        !self.show_receipt
+            && self
+                .paid
+                .as_ref()
+                .is_some_and(|(_, at)| at.elapsed() >= PAID_SCREEN_DURATION)
+    }
+}
+
+/// Lines of the receipt of `sale`, its title first
+pub fn receipt_lines(sale: &Sale) -> Vec<String> {
+    let mut lines = vec![
+        t!("receipt"),
+        t!("receipt-amount", amount = i18n::format_number(sale.amount)),
+    ];
+    if sale.tip > 0 {
+        lines.push(t!("receipt-tip", amount = i18n::format_number(sale.tip)));
+    }
+    if let (Some(amount), Some(currency)) = (sale.fiat_amount, &sale.fiat_currency) {
+        lines.push(t!(
+            "receipt-fiat",
+            amount = i18n::format_decimal(amount, 2),
+            currency = currency
+        ));
+    }
+    lines.push(t!(
+        "receipt-time",
+        time = crate::format_local_time(sale.timestamp)
+    ));
+    if let Some(note) = &sale.note {
+        lines.push(t!("receipt-note", note = note));
+    }
+    lines.push(t!("payment-hash", payment_hash = sale.payment_hash));
+
+    lines
+}
+
+/// Receipt of `sale` as plain text, for the clipboard and its QR code
+pub fn receipt_text(sale: &Sale) -> String {
+    receipt_lines(sale).join("\n")
+}
+
+/// Sats taken and sales made in POS mode on one local day
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DailyTotals {
+    pub day: NaiveDate,
+    pub amount: u64,
+    pub sales: u32,
+}
+
+impl Default for DailyTotals {
+    fn default() -> Self {
+        Self {
+            day: Local::now().date_naive(),
+            amount: 0,
+            sales: 0,
+        }
+    }
+}
+
+impl DailyTotals {
+    /// Today's totals of the current profile
+    pub fn load() -> Self {
+        get_config_value::<DailyTotals>(TOTALS_CONFIG)
+            .filter(|totals| totals.day == Local::now().date_naive())
+            .unwrap_or_default()
+    }
+
+    /// Count a sale of `amount`, starting over on a new day
+    fn add(&mut self, amount: u64) -> anyhow::Result<()> {
+        let today = Local::now().date_naive();
+        if self.day != today {
+            *self = Self::default();
+        }
+        self.amount += amount;
+        self.sales += 1;
+
+        set_config_value(TOTALS_CONFIG, *self)
+    }
+}
+
+impl fmt::Display for DailyTotals {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.day, self.amount, self.sales)
+    }
+}
+
+impl FromStr for DailyTotals {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split_whitespace();
+        let (Some(day), Some(amount), Some(sales)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(anyhow!("Invalid POS totals: {}", s));
+        };
+
+        Ok(DailyTotals {
+            day: day.parse()?,
+            amount: amount.parse()?,
+            sales: sales.parse()?,
+        })
+    }
+}
@@ -0,0 +1,156 @@
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail};
+use cdk::UncheckedUrl;
+use reqwest::Url;
+use tokio::net::TcpStream;
+use tokio_socks::tcp::Socks5Stream;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use crate::cli;
+use crate::settings::Settings;
+
+const TEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Settings applied when the wallet started
+static ACTIVE: OnceLock<ProxySettings> = OnceLock::new();
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProxySettings {
+    /// `socks5h://` url of the proxy, none to connect directly
+    pub proxy: Option<String>,
+    /// Mints connected to directly even when a proxy is set
+    pub bypass: Vec<UncheckedUrl>,
+}
+
+impl From<&Settings> for ProxySettings {
+    fn from(settings: &Settings) -> Self {
+        Self {
+            proxy: settings.proxy.clone(),
+            bypass: settings.proxy_bypass.clone(),
+        }
+    }
+}
+
+impl ProxySettings {
+    /// Proxy for traffic to `mint_url`, honouring the mint's override
+    pub fn for_mint(&self, mint_url: &UncheckedUrl) -> Option<&str> {
+        match self.bypass.contains(mint_url) {
+            true => None,
+            false => self.proxy.as_deref(),
+        }
+    }
+}
+
+/// Settings in effect for this run of the wallet
+pub fn active() -> &'static ProxySettings {
+    ACTIVE.get_or_init(ProxySettings::default)
+}
+
+/// Route HTTP traffic through the saved proxy, or the one passed with
+/// `--proxy`
+///
+/// reqwest reads proxies from the environment once per process, including
+/// the client cdk builds for the wallet, so this has to run before any
+/// request is made and changes only apply after a restart.
+pub fn apply() {
+    let mut settings = ProxySettings::from(&Settings::load());
+    if let Some(proxy) = &cli::args().proxy {
+        settings.proxy = Some(proxy.clone());
+    }
+
+    if let Some(proxy) = &settings.proxy {
+        for var in ["HTTP_PROXY", "HTTPS_PROXY", "ALL_PROXY"] {
+            std::env::set_var(var, proxy);
+        }
+
+        let hosts: Vec<String> = settings.bypass.iter().filter_map(host).collect();
+        if !hosts.is_empty() {
+            std::env::set_var("NO_PROXY", hosts.join(","));
+        }
+    }
+
+    let _ = ACTIVE.set(settings);
+}
+
+/// Parse `host:port` or a `socks5://` url into a `socks5h://` url
+///
+/// `socks5h` leaves name resolution to the proxy, which onion mints need.
+pub fn parse_proxy(input: &str) -> anyhow::Result<String> {
+    let input = input.trim();
+    let address = input
+        .strip_prefix("socks5h://")
+        .or_else(|| input.strip_prefix("socks5://"))
+        .unwrap_or(input);
+    if address.contains("://") {
+        bail!("Only SOCKS5 proxies are supported");
+    }
+
+    let url = format!("socks5h://{}", address.trim_end_matches('/'));
+    if Url::parse(&url)?.port().is_none() {
+        bail!("Proxy needs a port, e.g. 127.0.0.1:9050");
+    }
+
+    Ok(url)
+}
+
+/// Fetch the info of `mint_url` through `proxy`, returning the round trip time
+pub async fn test_connection(
+    proxy: Option<String>,
+    mint_url: UncheckedUrl,
+) -> anyhow::Result<Duration> {
+    let builder = reqwest::Client::builder().timeout(TEST_TIMEOUT);
+    let client = match proxy {
+        Some(proxy) => builder.proxy(reqwest::Proxy::all(proxy)?),
+        None => builder.no_proxy(),
+    }
+    .build()?;
+
+    let started = Instant::now();
+    client
+        .get(format!(
+            "{}/v1/info",
+            mint_url.to_string().trim_end_matches('/')
+        ))
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(started.elapsed())
+}
+
+/// Open a websocket to `mint_url`, through the active proxy if it applies
+pub async fn connect_websocket(
+    mint_url: &UncheckedUrl,
+    url: &str,
+) -> anyhow::Result<WebSocketStream<MaybeTlsStream<TcpStream>>> {
+    let Some(proxy) = active().for_mint(mint_url) else {
+        let (socket, _) = tokio_tungstenite::connect_async(url).await?;
+        return Ok(socket);
+    };
+
+    let target = Url::parse(url)?;
+    let host = target
+        .host_str()
+        .ok_or_else(|| anyhow!("Websocket url has no host"))?;
+    let port = target
+        .port_or_known_default()
+        .ok_or_else(|| anyhow!("Websocket url has no port"))?;
+
+    let stream = Socks5Stream::connect(proxy.trim_start_matches("socks5h://"), (host, port))
+        .await?
+        .into_inner();
+    let (socket, _) = tokio_tungstenite::client_async_tls(url, stream).await?;
+
+    Ok(socket)
+}
+
+fn host(mint_url: &UncheckedUrl) -> Option<String> {
+    Some(
+        Url::parse(&mint_url.to_string())
+            .ok()?
+            .host_str()?
+            .to_string(),
+    )
+}
@@ -0,0 +1,309 @@
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::{fmt, fs};
+
+use cdk::UncheckedUrl;
+use iced::{window, Point, Size, Theme};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{data_dir, get_config_value};
+use crate::fiat::{FiatCurrency, RateSource};
+use crate::i18n::Language;
+use crate::split::SplitSetting;
+use crate::{cli, nostr};
+
+const SETTINGS_FILE: &str = "config.toml";
+/// Interface scales offered, in percent
+pub const UI_SCALE_RANGE: RangeInclusive<u16> = 75..=200;
+
+/// User preferences, persisted to `config.toml` in the data dir
+///
+/// Settings are shared by all profiles. Secrets and wallet state such as the
+/// seed, the PIN hash and whether the seed was backed up stay in their own
+/// files in each profile's directory.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub language: Language,
+    /// Name of the iced theme, see [`Theme::ALL`]
+    pub theme: String,
+    /// Use `light_theme` or `dark_theme` to match the OS instead of `theme`
+    pub follow_system_theme: bool,
+    pub light_theme: String,
+    pub dark_theme: String,
+    /// Size of text and spacing in percent, see [`UI_SCALE_RANGE`]
+    pub ui_scale: u16,
+    /// Mint a new wallet starts with and that is selected on start
+    pub default_mint: Option<UncheckedUrl>,
+    #[serde(with = "display_fromstr")]
+    pub fiat_currency: FiatCurrency,
+    #[serde(with = "display_fromstr")]
+    pub rate_source: RateSource,
+    /// Seconds between checks of pending mint quotes, doubled after each
+    /// check up to `max_polling_interval`
+    pub polling_interval: u64,
+    pub max_polling_interval: u64,
+    /// Milliseconds each part of an animated QR code is shown for
+    pub qr_frame_interval: u64,
+    /// `socks5h://` url of the proxy for mint traffic
+    pub proxy: Option<String>,
+    /// Mints connected to directly even when a proxy is set
+    pub proxy_bypass: Vec<UncheckedUrl>,
+    #[serde(with = "display_fromstr")]
+    pub split_target: SplitSetting,
+    pub nostr_relays: Vec<String>,
+    pub nutzaps_enabled: bool,
+    /// Move tokens received at other mints to the default mint
+    pub auto_swap: bool,
+    /// Swap proofs of keysets a mint retired as soon as they are found
+    pub auto_rotate_keysets: bool,
+    /// Melt the balance above `auto_withdraw_threshold` to
+    /// `auto_withdraw_address`, pausing keeps both
+    pub auto_withdraw: bool,
+    /// Lightning address the auto-withdraw rule pays to
+    pub auto_withdraw_address: Option<String>,
+    /// Sats the auto-withdraw rule leaves in the wallet
+    pub auto_withdraw_threshold: u64,
+    pub notifications: bool,
+    pub minimize_to_tray: bool,
+    /// Minutes of inactivity before locking, 0 to never lock
+    pub lock_timeout: u64,
+    /// Payments above this many sats need the PIN, none if unset
+    pub pin_threshold: Option<u64>,
+    /// Wallet profile opened last, see [`crate::config::profile`]
+    pub profile: Option<String>,
+    /// Serve the local control API, only with the `control-api` feature
+    pub control_api: bool,
+    /// Port the control API listens on, on localhost only
+    pub control_port: u16,
+    /// Size and position of the window when it was last closed
+    pub window: WindowState,
+    /// View open when the window was last closed, reopened on start
+    pub last_view: LastView,
+}
+
+/// Window geometry restored on start, in logical pixels
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowState {
+    pub width: f32,
+    pub height: f32,
+    /// Top left corner, placed by the OS if unset
+    pub position: Option<(f32, f32)>,
+}
+
+impl Default for WindowState {
+    fn default() -> Self {
+        let size = window::Settings::default().size;
+
+        Self {
+            width: size.width,
+            height: size.height,
+            position: None,
+        }
+    }
+}
+
+impl WindowState {
+    pub fn new(size: Size, position: Option<Point>) -> Self {
+        Self {
+            width: size.width,
+            height: size.height,
+            position: position.map(|position| (position.x, position.y)),
+        }
+    }
+
+    pub fn size(&self) -> Size {
+        Size::new(self.width, self.height)
+    }
+
+    pub fn position(&self) -> window::Position {
+        match self.position {
+            Some((x, y)) => window::Position::Specific(Point::new(x, y)),
+            None => window::Position::Default,
+        }
+    }
+}
+
+/// Views that can be reopened on start without the flow that led to them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LastView {
+    #[default]
+    Main,
+    SendReceive,
+    History,
+    SentTokens,
+    Stats,
+    MintInfo,
+    Proofs,
+    Contacts,
+    Schedule,
+    Pos,
+    Nwc,
+    Decode,
+    Settings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            language: Language::system(),
+            theme: Theme::Dracula.to_string(),
+            follow_system_theme: false,
+            light_theme: Theme::Light.to_string(),
+            dark_theme: Theme::Dracula.to_string(),
+            ui_scale: 100,
+            default_mint: None,
+            fiat_currency: FiatCurrency::default(),
+            rate_source: RateSource::default(),
+            polling_interval: 5,
+            max_polling_interval: 60,
+            qr_frame_interval: 250,
+            proxy: None,
+            proxy_bypass: vec![],
+            split_target: SplitSetting::default(),
+            nostr_relays: nostr::default_relays(),
+            nutzaps_enabled: false,
+            auto_swap: false,
+            auto_rotate_keysets: false,
+            auto_withdraw: false,
+            auto_withdraw_address: None,
+            auto_withdraw_threshold: 100_000,
+            notifications: true,
+            minimize_to_tray: false,
+            lock_timeout: 5,
+            pin_threshold: None,
+            profile: None,
+            control_api: false,
+            control_port: 4448,
+            window: WindowState::default(),
+            last_view: LastView::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Settings from `config.toml`, falling back to defaults
+    ///
+    /// Settings saved one file per value by earlier versions are picked up
+    /// the first time.
+    pub fn load() -> Self {
+        let path = settings_path();
+        if !path.exists() {
+            let settings = Self::from_config_files();
+            if let Err(err) = settings.save() {
+                tracing::warn!("Could not save settings: {}", err);
+            }
+            return settings;
+        }
+
+        match fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|contents| Ok(toml::from_str(&contents)?))
+        {
+            Ok(settings) => settings,
+            Err(err) => {
+                tracing::warn!("Could not read {}: {}", path.display(), err);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        fs::create_dir_all(data_dir())?;
+        fs::write(settings_path(), toml::to_string_pretty(self)?)?;
+
+        Ok(())
+    }
+
+    /// Mint to select on start, `--mint` taking precedence over the setting
+    pub fn start_mint(&self) -> Option<UncheckedUrl> {
+        cli::args()
+            .mint
+            .clone()
+            .or_else(|| self.default_mint.clone())
+    }
+
+    pub fn theme(&self) -> Theme {
+        named_theme(&self.theme).unwrap_or(Theme::Dracula)
+    }
+
+    /// Theme to use when following the OS, `dark` if it prefers dark mode
+    pub fn system_theme(&self, dark: bool) -> Theme {
+        if dark {
+            named_theme(&self.dark_theme).unwrap_or(Theme::Dracula)
+        } else {
+            named_theme(&self.light_theme).unwrap_or(Theme::Light)
+        }
+    }
+
+    /// Factor the whole interface is scaled by
+    pub fn scale_factor(&self) -> f64 {
+        let scale = self
+            .ui_scale
+            .clamp(*UI_SCALE_RANGE.start(), *UI_SCALE_RANGE.end());
+
+        f64::from(scale) / 100.0
+    }
+
+    fn from_config_files() -> Self {
+        let defaults = Self::default();
+
+        Self {
+            fiat_currency: get_config_value("fiat_currency").unwrap_or_default(),
+            rate_source: get_config_value("rate_source").unwrap_or_default(),
+            proxy: get_config_value::<String>("proxy").filter(|proxy| !proxy.is_empty()),
+            proxy_bypass: get_config_value::<String>("proxy_bypass")
+                .map(|mints| mints.split_whitespace().map(UncheckedUrl::from).collect())
+                .unwrap_or_default(),
+            split_target: get_config_value("split_target").unwrap_or_default(),
+            nostr_relays: get_config_value::<String>("nostr_relays")
+                .map(|relays| nostr::parse_relays(&relays))
+                .filter(|relays| !relays.is_empty())
+                .unwrap_or(defaults.nostr_relays),
+            nutzaps_enabled: get_config_value("nutzaps_enabled").unwrap_or_default(),
+            notifications: get_config_value("notifications").unwrap_or(defaults.notifications),
+            minimize_to_tray: get_config_value("minimize_to_tray").unwrap_or_default(),
+            lock_timeout: get_config_value("lock_timeout").unwrap_or(defaults.lock_timeout),
+            pin_threshold: get_config_value("pin_threshold"),
+            ..defaults
+        }
+    }
+}
+
+fn named_theme(name: &str) -> Option<Theme> {
+    Theme::ALL
+        .iter()
+        .find(|theme| theme.to_string() == name)
+        .cloned()
+}
+
+fn settings_path() -> PathBuf {
+    data_dir().join(SETTINGS_FILE)
+}
+
+/// Store a value by its `Display` string, as the config files did
+mod display_fromstr {
+    use super::*;
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: fmt::Display,
+        S: serde::Serializer,
+    {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+        D: serde::Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
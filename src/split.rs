@@ -0,0 +1,134 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail};
+use cdk::amount::{Amount, SplitTarget};
+
+/// How proofs are split when minting and sending
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum SplitSetting {
+    /// Fewest proofs for the amount
+    #[default]
+    Default,
+    /// Split into these denominations, in sats
+    Denominations(Vec<u64>),
+    /// Split the amount into roughly this many groups of proofs
+    TargetCount(u64),
+}
+
+/// Kind of [`SplitSetting`], for the settings picker
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SplitMode {
+    #[default]
+    Default,
+    Denominations,
+    TargetCount,
+}
+
+impl SplitMode {
+    pub const ALL: [SplitMode; 3] = [
+        SplitMode::Default,
+        SplitMode::Denominations,
+        SplitMode::TargetCount,
+    ];
+}
+
+impl fmt::Display for SplitMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SplitMode::Default => write!(f, "{}", t!("split-default")),
+            SplitMode::Denominations => write!(f, "{}", t!("split-denominations-mode")),
+            SplitMode::TargetCount => write!(f, "{}", t!("split-target-count")),
+        }
+    }
+}
+
+impl SplitSetting {
+    pub fn mode(&self) -> SplitMode {
+        match self {
+            SplitSetting::Default => SplitMode::Default,
+            SplitSetting::Denominations(_) => SplitMode::Denominations,
+            SplitSetting::TargetCount(_) => SplitMode::TargetCount,
+        }
+    }
+
+    /// Setting of `mode` parsed from the settings input
+    pub fn from_input(mode: SplitMode, input: &str) -> anyhow::Result<Self> {
+        match mode {
+            SplitMode::Default => Ok(SplitSetting::Default),
+            SplitMode::Denominations => {
+                let denominations = input
+                    .split(',')
+                    .map(|amount| amount.trim())
+                    .filter(|amount| !amount.is_empty())
+                    .map(|amount| {
+                        amount
+                            .parse()
+                            .map_err(|_| anyhow!("Invalid denomination: {}", amount))
+                    })
+                    .collect::<anyhow::Result<Vec<u64>>>()?;
+
+                if denominations.is_empty() || denominations.contains(&0) {
+                    bail!("Enter denominations as comma separated sats, e.g. 1,2,4");
+                }
+
+                Ok(SplitSetting::Denominations(denominations))
+            }
+            SplitMode::TargetCount => match input.trim().parse() {
+                Ok(count) if count > 0 => Ok(SplitSetting::TargetCount(count)),
+                _ => bail!("Enter the number of proofs to aim for"),
+            },
+        }
+    }
+
+    /// Value shown in the settings input
+    pub fn input(&self) -> String {
+        match self {
+            SplitSetting::Default => String::new(),
+            SplitSetting::Denominations(denominations) => denominations
+                .iter()
+                .map(u64::to_string)
+                .collect::<Vec<_>>()
+                .join(","),
+            SplitSetting::TargetCount(count) => count.to_string(),
+        }
+    }
+
+    /// cdk split target for an operation of `amount` sats
+    pub fn target(&self, amount: u64) -> SplitTarget {
+        match self {
+            SplitSetting::Default => SplitTarget::default(),
+            SplitSetting::Denominations(denominations) => {
+                SplitTarget::Values(denominations.iter().copied().map(Amount::from).collect())
+            }
+            SplitSetting::TargetCount(count) => {
+                SplitTarget::Value(Amount::from((amount / count).max(1)))
+            }
+        }
+    }
+}
+
+impl fmt::Display for SplitSetting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SplitSetting::Default => write!(f, "default"),
+            SplitSetting::Denominations(_) => write!(f, "denominations:{}", self.input()),
+            SplitSetting::TargetCount(count) => write!(f, "count:{}", count),
+        }
+    }
+}
+
+impl FromStr for SplitSetting {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "default" => Ok(SplitSetting::Default),
+            Some(("denominations", input)) => {
+                SplitSetting::from_input(SplitMode::Denominations, input)
+            }
+            Some(("count", input)) => SplitSetting::from_input(SplitMode::TargetCount, input),
+            _ => Err(anyhow!("Unknown split setting: {}", s)),
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use std::sync::mpsc;
+use std::thread;
+
+use futures::SinkExt;
+use iced::Subscription;
+use tray_icon::menu::{Menu, MenuEvent, MenuItem};
+use tray_icon::{Icon, TrayIcon, TrayIconBuilder};
+
+use crate::i18n;
+
+const ICON_SIZE: u32 = 32;
+/// Cashu orange
+const ICON_COLOR: [u8; 3] = [0xf7, 0x93, 0x1a];
+
+/// Menu entry picked in the tray
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrayAction {
+    Receive,
+    Pay,
+    ShowWindow,
+}
+
+/// Handle to the tray icon, which lives on its own thread
+#[derive(Debug)]
+pub struct Tray {
+    balance: mpsc::Sender<u64>,
+}
+
+impl Tray {
+    /// Show `balance` in the tooltip
+    pub fn set_balance(&self, balance: u64) {
+        let _ = self.balance.send(balance);
+    }
+}
+
+/// Start the tray icon on a gtk thread
+#[cfg(target_os = "linux")]
+pub fn spawn() -> anyhow::Result<Tray> {
+    let (balance, balances) = mpsc::channel::<u64>();
+    let (ready_sender, ready) = mpsc::channel();
+
+    thread::spawn(move || {
+        let tray = gtk::init()
+            .map_err(anyhow::Error::from)
+            .and_then(|_| build());
+        let tray = match tray {
+            Ok(tray) => {
+                let _ = ready_sender.send(Ok(()));
+                tray
+            }
+            Err(err) => {
+                let _ = ready_sender.send(Err(err));
+                return;
+            }
+        };
+
+        gtk::glib::timeout_add_local(std::time::Duration::from_millis(250), move || {
+            while let Ok(balance) = balances.try_recv() {
+                if let Err(err) = tray.set_tooltip(Some(tooltip(balance))) {
+                    tracing::warn!("Could not update tray tooltip: {}", err);
+                }
+            }
+            gtk::glib::ControlFlow::Continue
+        });
+        gtk::main();
+    });
+
+    ready.recv()??;
+
+    Ok(Tray { balance })
+}
+
+/// The tray needs the platform event loop on the main thread, which iced owns
+#[cfg(not(target_os = "linux"))]
+pub fn spawn() -> anyhow::Result<Tray> {
+    anyhow::bail!("The tray icon is only supported on Linux")
+}
+
+/// Menu entries picked in the tray
+pub fn actions() -> Subscription<TrayAction> {
+    iced::subscription::channel("tray-actions", 10, |mut output| async move {
+        loop {
+            let event = tokio::task::spawn_blocking(|| MenuEvent::receiver().recv()).await;
+            let Ok(Ok(event)) = event else {
+                return std::future::pending().await;
+            };
+
+            let action = match event.id.as_ref() {
+                "receive" => TrayAction::Receive,
+                "pay" => TrayAction::Pay,
+                "show" => TrayAction::ShowWindow,
+                _ => continue,
+            };
+            let _ = output.send(action).await;
+        }
+    })
+}
+
+fn build() -> anyhow::Result<TrayIcon> {
+    let menu = Menu::new();
+    menu.append_items(&[
+        &MenuItem::with_id("receive", t!("tray-receive"), true, None),
+        &MenuItem::with_id("pay", t!("tray-pay"), true, None),
+        &MenuItem::with_id("show", t!("tray-show"), true, None),
+    ])?;
+
+    Ok(TrayIconBuilder::new()
+        .with_menu(Box::new(menu))
+        .with_tooltip(tooltip(0))
+        .with_icon(icon()?)
+        .build()?)
+}
+
+fn tooltip(balance: u64) -> String {
+    t!("tray-tooltip", balance = i18n::format_number(balance))
+}
+
+/// Filled circle, so no image asset has to be shipped
+fn icon() -> anyhow::Result<Icon> {
+    let center = ICON_SIZE as f32 / 2.0;
+    let mut rgba = Vec::with_capacity((ICON_SIZE * ICON_SIZE * 4) as usize);
+
+    for y in 0..ICON_SIZE {
+        for x in 0..ICON_SIZE {
+            let dx = x as f32 + 0.5 - center;
+            let dy = y as f32 + 0.5 - center;
+            let alpha = if (dx * dx + dy * dy).sqrt() <= center {
+                0xff
+            } else {
+                0
+            };
+            rgba.extend_from_slice(&ICON_COLOR);
+            rgba.push(alpha);
+        }
+    }
+
+    Ok(Icon::from_rgba(rgba, ICON_SIZE, ICON_SIZE)?)
+}
@@ -0,0 +1,140 @@
+use anyhow::{anyhow, bail};
+use cdk::UncheckedUrl;
+use futures::channel::mpsc::Sender;
+use futures::{SinkExt, StreamExt};
+use iced::Subscription;
+use serde_json::{json, Value};
+use tokio_tungstenite::tungstenite;
+
+use crate::{mint_info, proxy};
+
+/// Quote kinds that can be subscribed to over NUT-17
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QuoteKind {
+    Mint,
+    Melt,
+}
+
+impl QuoteKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            QuoteKind::Mint => "bolt11_mint_quote",
+            QuoteKind::Melt => "bolt11_melt_quote",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum QuoteUpdate {
+    /// Quote state reported by the mint, e.g. `PAID`
+    State { quote_id: String, state: String },
+    /// Mint has no NUT-17 support or the connection failed, poll instead
+    Unsupported { quote_id: String },
+}
+
+/// Stream state changes of a quote from the mint's websocket
+pub fn quote_updates(
+    mint_url: UncheckedUrl,
+    kind: QuoteKind,
+    quote_id: String,
+) -> Subscription<QuoteUpdate> {
+    iced::subscription::channel((kind, quote_id.clone()), 10, move |mut output| async move {
+        if let Err(err) = run(&mint_url, kind, &quote_id, &mut output).await {
+            tracing::debug!("Websocket unavailable for {}: {}", mint_url, err);
+            let _ = output.send(QuoteUpdate::Unsupported { quote_id }).await;
+        }
+
+        std::future::pending().await
+    })
+}
+
+async fn supports_nut17(mint_url: &UncheckedUrl, kind: QuoteKind) -> anyhow::Result<bool> {
+    let info = mint_info::fetch_info(mint_url).await?;
+    let Some(nut17) = info.nuts.get("17") else {
+        return Ok(false);
+    };
+
+    let supported = nut17["supported"]
+        .as_array()
+        .map(|methods| {
+            methods.iter().any(|method| {
+                method["commands"]
+                    .as_array()
+                    .is_some_and(|commands| commands.iter().any(|c| c == kind.as_str()))
+            })
+        })
+        .unwrap_or(false);
+
+    Ok(supported)
+}
+
+fn websocket_url(mint_url: &UncheckedUrl) -> anyhow::Result<String> {
+    let url = mint_url.to_string();
+    let url = url.trim_end_matches('/');
+
+    if let Some(rest) = url.strip_prefix("https://") {
+        Ok(format!("wss://{}/v1/ws", rest))
+    } else if let Some(rest) = url.strip_prefix("http://") {
+        Ok(format!("ws://{}/v1/ws", rest))
+    } else {
+        Err(anyhow!("Unsupported mint url: {}", url))
+    }
+}
+
+async fn run(
+    mint_url: &UncheckedUrl,
+    kind: QuoteKind,
+    quote_id: &str,
+    output: &mut Sender<QuoteUpdate>,
+) -> anyhow::Result<()> {
+    if !supports_nut17(mint_url, kind).await? {
+        bail!("Mint does not support NUT-17");
+    }
+
+    let mut socket = proxy::connect_websocket(mint_url, &websocket_url(mint_url)?).await?;
+
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 0,
+        "method": "subscribe",
+        "params": {
+            "kind": kind.as_str(),
+            "subId": quote_id,
+            "filters": [quote_id],
+        },
+    });
+    socket
+        .send(tungstenite::Message::Text(request.to_string()))
+        .await?;
+
+    while let Some(message) = socket.next().await {
+        let tungstenite::Message::Text(message) = message? else {
+            continue;
+        };
+
+        let message: Value = serde_json::from_str(&message)?;
+        if let Some(error) = message.get("error") {
+            bail!("Subscription failed: {}", error);
+        }
+
+        let payload = &message["params"]["payload"];
+        if payload["quote"] != quote_id {
+            continue;
+        }
+
+        let state = match payload["state"].as_str() {
+            Some(state) => state.to_string(),
+            None if payload["paid"] == true => "PAID".to_string(),
+            None => "UNPAID".to_string(),
+        };
+
+        let _ = output
+            .send(QuoteUpdate::State {
+                quote_id: quote_id.to_string(),
+                state,
+            })
+            .await;
+    }
+
+    bail!("Websocket closed")
+}